@@ -230,6 +230,7 @@ fn benchmark_kaos() -> Result<(f64, bool, u64, u64), Box<dyn std::error::Error>>
     let config = RingBufferConfig {
         size: RING_SIZE,
         num_consumers: 1,
+        ..Default::default()
     };
 
     let ring_buffer = Arc::new(RingBuffer::new(config)?);
@@ -343,6 +344,7 @@ fn benchmark_kaos_small() -> Result<(f64, bool, u64, u64), Box<dyn std::error::E
     let config = RingBufferConfig {
         size: RING_SIZE,
         num_consumers: 1,
+        ..Default::default()
     };
 
     let ring_buffer = Arc::new(RingBuffer::new(config)?);
@@ -1,9 +1,12 @@
 //! Fast archive with SPSC ring buffer + background writer (30-34 M/s).
 
-use crate::{ArchiveError, MmapArchive};
-use std::path::Path;
+use crate::{
+    ArchiveError, ArchiveOptions, ArchiveReader, CompactReport, MmapArchive, RetentionPolicy,
+    SegmentedArchive, Tail,
+};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
 
 const RING_SIZE: usize = 65536;
@@ -16,6 +19,8 @@ struct PaddedU64(AtomicU64);
 #[repr(C, align(64))]
 struct Slot {
     len: u16,
+    /// Written with `append_compressed`
+    compress: bool,
     data: [u8; MAX_MSG_SIZE],
 }
 
@@ -23,11 +28,18 @@ impl Default for Slot {
     fn default() -> Self {
         Self {
             len: 0,
+            compress: false,
             data: [0u8; MAX_MSG_SIZE],
         }
     }
 }
 
+/// Requests the writer thread serves between batches
+enum Control {
+    MarkDeleted(u64, mpsc::Sender<Result<(), ArchiveError>>),
+    Compact(mpsc::Sender<Result<CompactReport, ArchiveError>>),
+}
+
 struct SharedState {
     ring: Vec<Slot>,
     producer_cursor: PaddedU64,
@@ -41,13 +53,49 @@ pub struct Archive {
     local_cursor: u64,
     cached_consumer: u64,
     writer_handle: Option<JoinHandle<()>>,
+    control: mpsc::Sender<Control>,
+    /// Single-file archives, for `tail`
+    base_path: Option<PathBuf>,
 }
 
 impl Archive {
     /// Create a new archive with background persistence.
     pub fn create<P: AsRef<Path>>(base_path: P, capacity: usize) -> Result<Self, ArchiveError> {
-        let path = base_path.as_ref().to_path_buf();
+        let archive = MmapArchive::create(&base_path, capacity)?;
+        let base_path = base_path.as_ref().to_path_buf();
+        Ok(Self::start(
+            Sink::Single(Box::new(archive)),
+            Some(base_path),
+        ))
+    }
+
+    /// Like `create`, with compression (`append_compressed`) and
+    /// durability options for the writer thread.
+    pub fn create_with_options<P: AsRef<Path>>(
+        base_path: P,
+        capacity: usize,
+        options: ArchiveOptions,
+    ) -> Result<Self, ArchiveError> {
+        let archive = MmapArchive::create(&base_path, capacity)?.with_options(options);
+        let base_path = base_path.as_ref().to_path_buf();
+        Ok(Self::start(
+            Sink::Single(Box::new(archive)),
+            Some(base_path),
+        ))
+    }
+
+    /// Background persistence into a `SegmentedArchive` in `dir`: rolls over
+    /// instead of dropping writes once a segment is full.
+    pub fn create_segmented<P: AsRef<Path>>(
+        dir: P,
+        segment_capacity: usize,
+        retention: RetentionPolicy,
+    ) -> Result<Self, ArchiveError> {
+        let archive = SegmentedArchive::open(dir, segment_capacity)?.with_retention(retention);
+        Ok(Self::start(Sink::Segmented(archive), None))
+    }
 
+    fn start(mut archive: Sink, base_path: Option<PathBuf>) -> Self {
         let mut slots = Vec::with_capacity(RING_SIZE);
         slots.resize_with(RING_SIZE, Slot::default);
 
@@ -58,14 +106,16 @@ impl Archive {
             running: AtomicBool::new(true),
         });
 
+        let (control, requests) = mpsc::channel();
         let state_clone = state.clone();
         let handle = thread::spawn(move || {
-            let mut archive =
-                MmapArchive::create(&path, capacity).expect("Failed to create archive");
             let mut consumer = 0u64;
             let mut batch_buf: Vec<&[u8]> = Vec::with_capacity(64);
 
             while state_clone.running.load(Ordering::Relaxed) {
+                if let Ok(request) = requests.try_recv() {
+                    archive.serve(request);
+                }
                 let producer = state_clone.producer_cursor.0.load(Ordering::Acquire);
                 if producer == consumer {
                     std::hint::spin_loop();
@@ -82,19 +132,34 @@ impl Archive {
                             .as_ptr()
                             .add(((consumer + (i as u64)) as usize) & RING_MASK)
                     };
-                    if slot.len > 0 {
-                        batch_buf.push(unsafe {
-                            std::slice::from_raw_parts(slot.data.as_ptr(), slot.len as usize)
-                        });
+                    if slot.len == 0 {
+                        continue;
+                    }
+                    let msg = unsafe {
+                        std::slice::from_raw_parts(slot.data.as_ptr(), slot.len as usize)
+                    };
+                    if slot.compress {
+                        // Keep order: plain messages collected so far go first
+                        if !batch_buf.is_empty() {
+                            archive.write_batch(&batch_buf);
+                            batch_buf.clear();
+                        }
+                        archive.write_compressed(msg);
+                    } else {
+                        batch_buf.push(msg);
                     }
                 }
 
                 // Batch write
                 if !batch_buf.is_empty() {
-                    let _ = archive.append_batch(&batch_buf);
+                    archive.write_batch(&batch_buf);
                 }
 
                 consumer += batch_size as u64;
+                // Caught up: don't leave a partial block behind `flush()`
+                if consumer == state_clone.producer_cursor.0.load(Ordering::Acquire) {
+                    archive.flush_block();
+                }
                 state_clone
                     .consumer_cursor
                     .0
@@ -110,27 +175,57 @@ impl Archive {
                         .as_ptr()
                         .add((consumer as usize) & RING_MASK)
                 };
-                if slot.len > 0 {
-                    let _ = archive.append_no_index(&slot.data[..slot.len as usize]);
+                if slot.len > 0 && slot.compress {
+                    archive.write_compressed(&slot.data[..slot.len as usize]);
+                } else if slot.len > 0 {
+                    archive.write(&slot.data[..slot.len as usize]);
                 }
                 consumer += 1;
             }
+            archive.flush_block();
             state_clone
                 .consumer_cursor
                 .0
                 .store(consumer, Ordering::Release);
         });
 
-        Ok(Self {
+        Self {
             state,
             local_cursor: 0,
             cached_consumer: 0,
             writer_handle: Some(handle),
-        })
+            control,
+            base_path,
+        }
+    }
+
+    /// Follow what the writer thread persists, from `from_seq` on (see
+    /// `Tail`). Appends reach the writer every 64 messages or on `flush`.
+    /// Single-file archives only.
+    pub fn tail(&self, from_seq: u64) -> Result<Tail, ArchiveError> {
+        let path = self.base_path.as_ref().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "tail: use ArchiveSet for segmented archives",
+            )
+        })?;
+        Ok(ArchiveReader::open(path)?.tail(from_seq))
     }
 
     #[inline(always)]
     pub fn append(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
+        self.push(data, false)
+    }
+
+    /// Append, compressed by the writer thread per `ArchiveOptions` (see
+    /// `MmapArchive::append_compressed`). Segmented archives store it as is.
+    #[inline]
+    pub fn append_compressed(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
+        self.push(data, true)
+    }
+
+    #[inline(always)]
+    fn push(&mut self, data: &[u8], compress: bool) -> Result<u64, ArchiveError> {
         if data.len() > MAX_MSG_SIZE {
             return Err(ArchiveError::Full);
         }
@@ -151,6 +246,7 @@ impl Archive {
                 .add((self.local_cursor as usize) & RING_MASK) as *mut Slot)
         };
         slot.len = data.len() as u16;
+        slot.compress = compress;
         unsafe {
             std::ptr::copy_nonoverlapping(data.as_ptr(), slot.data.as_mut_ptr(), data.len());
         }
@@ -184,6 +280,29 @@ impl Archive {
             std::hint::spin_loop();
         }
     }
+
+    /// Tombstone `seq` (see `MmapArchive::mark_deleted`). Flushes first.
+    pub fn mark_deleted(&mut self, seq: u64) -> Result<(), ArchiveError> {
+        self.request(|reply| Control::MarkDeleted(seq, reply))
+    }
+
+    /// Drop tombstoned messages from the log (see `MmapArchive::compact`).
+    /// Flushes first; appends wait in the ring meanwhile.
+    pub fn compact(&mut self) -> Result<CompactReport, ArchiveError> {
+        self.request(Control::Compact)
+    }
+
+    /// Have the writer thread run `control` once it has everything appended
+    fn request<T>(
+        &mut self,
+        control: impl FnOnce(mpsc::Sender<Result<T, ArchiveError>>) -> Control,
+    ) -> Result<T, ArchiveError> {
+        self.flush();
+        let stopped = || ArchiveError::Io(std::io::Error::other("writer thread stopped"));
+        let (reply, result) = mpsc::channel();
+        self.control.send(control(reply)).map_err(|_| stopped())?;
+        result.recv().map_err(|_| stopped())?
+    }
 }
 
 impl Drop for Archive {
@@ -196,6 +315,72 @@ impl Drop for Archive {
     }
 }
 
+/// What the writer thread persists to
+enum Sink {
+    Single(Box<MmapArchive>),
+    Segmented(SegmentedArchive),
+}
+
+impl Sink {
+    fn write_batch(&mut self, batch: &[&[u8]]) {
+        match self {
+            Sink::Single(archive) => {
+                let _ = archive.append_batch(batch);
+            }
+            Sink::Segmented(archive) => {
+                for msg in batch {
+                    let _ = archive.append(msg);
+                }
+            }
+        }
+    }
+
+    fn write_compressed(&mut self, msg: &[u8]) {
+        match self {
+            Sink::Single(archive) => {
+                let _ = archive.append_compressed(msg);
+            }
+            Sink::Segmented(archive) => {
+                let _ = archive.append(msg);
+            }
+        }
+    }
+
+    fn flush_block(&mut self) {
+        if let Sink::Single(archive) = self {
+            let _ = archive.flush_block();
+        }
+    }
+
+    fn serve(&mut self, request: Control) {
+        match (self, request) {
+            (Sink::Single(archive), Control::MarkDeleted(seq, reply)) => {
+                let _ = reply.send(archive.mark_deleted(seq));
+            }
+            (Sink::Segmented(archive), Control::MarkDeleted(seq, reply)) => {
+                let _ = reply.send(archive.mark_deleted(seq));
+            }
+            (Sink::Single(archive), Control::Compact(reply)) => {
+                let _ = reply.send(archive.compact());
+            }
+            (Sink::Segmented(archive), Control::Compact(reply)) => {
+                let _ = reply.send(archive.compact());
+            }
+        }
+    }
+
+    fn write(&mut self, msg: &[u8]) {
+        match self {
+            Sink::Single(archive) => {
+                let _ = archive.append_no_index(msg);
+            }
+            Sink::Segmented(archive) => {
+                let _ = archive.append(msg);
+            }
+        }
+    }
+}
+
 unsafe impl Send for SharedState {}
 unsafe impl Sync for SharedState {}
 
@@ -213,4 +398,92 @@ mod tests {
         }
         archive.flush();
     }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_append_compressed_in_background() {
+        use crate::{ArchiveReader, Compression};
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("packed");
+        let options = ArchiveOptions::default()
+            .with_compression(Compression::Lz4)
+            .with_block_messages(16);
+        let mut archive = Archive::create_with_options(&path, 1024 * 1024, options).unwrap();
+        let mut reader = ArchiveReader::open(&path).unwrap();
+        for i in 0..100u64 {
+            archive.append_compressed(&[i as u8; 256]).unwrap();
+            if i % 10 == 0 {
+                archive.append(&i.to_le_bytes()).unwrap();
+            }
+        }
+        archive.flush();
+
+        let mut seen = Vec::new();
+        assert_eq!(reader.poll(|_, data| seen.push(data.to_vec())), 110);
+        assert_eq!(seen[0], [0u8; 256]);
+        assert_eq!(seen[1], 0u64.to_le_bytes());
+        assert_eq!(seen[109], [99u8; 256]);
+        assert_eq!(reader.read(109).unwrap(), [99u8; 256]);
+    }
+
+    #[test]
+    fn test_tail() {
+        let dir = tempdir().unwrap();
+        let mut archive = Archive::create(dir.path().join("tail"), 1024 * 1024).unwrap();
+        let mut tail = archive.tail(5).unwrap();
+        for i in 0..10u64 {
+            archive.append(&i.to_le_bytes()).unwrap();
+        }
+        archive.flush();
+        let seqs: Vec<u64> = tail.by_ref().take(5).map(|(seq, _)| seq).collect();
+        assert_eq!(seqs, [5, 6, 7, 8, 9]);
+        assert_eq!(tail.next_timeout(std::time::Duration::from_millis(1)), None);
+
+        let segmented =
+            Archive::create_segmented(dir.path().join("seg"), 4096, RetentionPolicy::default())
+                .unwrap();
+        assert!(segmented.tail(0).is_err());
+    }
+
+    #[test]
+    fn test_compact_in_background() {
+        use crate::ArchiveSet;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("room");
+        let mut archive = Archive::create(&path, 1024 * 1024).unwrap();
+        for i in 0..100u64 {
+            archive.append(&i.to_le_bytes()).unwrap();
+        }
+        for seq in 0..50 {
+            archive.mark_deleted(seq).unwrap();
+        }
+        assert!(archive.mark_deleted(100).is_err());
+        assert_eq!(archive.compact().unwrap().removed, 50);
+        archive.append(&100u64.to_le_bytes()).unwrap();
+        drop(archive);
+
+        let archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 101);
+        // Compaction indexed the writer thread's frames (written without CRC)
+        assert!(archive.read_no_verify(49).is_err());
+        assert_eq!(archive.read_no_verify(50).unwrap(), 50u64.to_le_bytes());
+        drop(archive);
+        assert_eq!(ArchiveSet::open(&path).unwrap().iter().count(), 51);
+    }
+
+    #[test]
+    fn test_segmented_archive_rolls_over() {
+        let dir = tempdir().unwrap();
+        {
+            let mut archive =
+                Archive::create_segmented(dir.path(), 4096, RetentionPolicy::default()).unwrap();
+            for i in 0..1000u64 {
+                archive.append(&i.to_le_bytes()).unwrap();
+            }
+            archive.flush();
+        }
+        let archive = SegmentedArchive::open(dir.path(), 4096).unwrap();
+        assert_eq!(archive.len(), 1000);
+        assert_eq!(archive.read(999).unwrap(), 999u64.to_le_bytes());
+    }
 }
@@ -0,0 +1,221 @@
+//! Inspect archive logs: a single archive (base path) or a directory of segments.
+//!
+//! Stat:   kaos-archive stat <path>
+//! Dump:   kaos-archive dump <path> [--from N] [--to N] [--hex]
+//! Grep:   kaos-archive grep <path> <text> [--hex] [--from N] [--to N]
+//! Verify: kaos-archive verify <path>
+//! Export: kaos-archive export <path> [--from N] [--to N]   (JSONL on stdout)
+
+use kaos_archive::{ArchiveSet, Record};
+use std::io::{self, BufWriter, Write};
+
+fn usage() -> ! {
+    eprintln!("Kaos Archive Tool");
+    eprintln!("=================");
+    eprintln!("Stat:   kaos-archive stat <path>");
+    eprintln!("Dump:   kaos-archive dump <path> [--from N] [--to N] [--hex]");
+    eprintln!("Grep:   kaos-archive grep <path> <text> [--hex] [--from N] [--to N]");
+    eprintln!("        (--hex: <text> is hex bytes, e.g. deadbeef)");
+    eprintln!("Verify: kaos-archive verify <path>");
+    eprintln!("Export: kaos-archive export <path> [--from N] [--to N]   (JSONL)");
+    eprintln!();
+    eprintln!("<path>: archive base path, or a directory of *.log segments");
+    std::process::exit(1);
+}
+
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == name).map(|i| {
+        args.get(i + 1)
+            .map(|s| s.as_str())
+            .unwrap_or_else(|| usage())
+    })
+}
+
+fn parse_seq(value: Option<&str>) -> Option<u64> {
+    value.map(|v| v.parse().unwrap_or_else(|_| usage()))
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Printable ASCII as-is, everything else escaped
+fn escaped(data: &[u8]) -> String {
+    data.iter()
+        .flat_map(|&b| std::ascii::escape_default(b))
+        .map(char::from)
+        .collect()
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn crc_status(r: &Record) -> &'static str {
+    match r.verify() {
+        Some(true) => "ok",
+        Some(false) => "bad",
+        None => "none",
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        usage();
+    }
+    let (command, path) = (args[1].as_str(), &args[2]);
+    let set = ArchiveSet::open(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        std::process::exit(1);
+    });
+
+    let from = parse_seq(flag_value(&args, "--from")).unwrap_or(0);
+    let to = parse_seq(flag_value(&args, "--to")).unwrap_or(u64::MAX);
+    let as_hex = args.iter().any(|a| a == "--hex");
+
+    let out = io::stdout();
+    let mut out = BufWriter::new(out.lock());
+
+    // Write errors (e.g. piped into `head`) end the output quietly
+    let result = match command {
+        "stat" => stat(&mut out, &set),
+        "dump" => set.range(from, to).try_for_each(|r| {
+            let payload = if as_hex { hex(r.data) } else { escaped(r.data) };
+            writeln!(
+                out,
+                "{:>10}  {:>6}B  crc={:<4}  {}",
+                r.seq,
+                r.data.len(),
+                crc_status(&r),
+                payload
+            )
+        }),
+        "grep" => {
+            let pattern = args
+                .get(3)
+                .filter(|a| !a.starts_with("--"))
+                .unwrap_or_else(|| usage());
+            let needle = if as_hex {
+                parse_hex(pattern).unwrap_or_else(|| usage())
+            } else {
+                pattern.as_bytes().to_vec()
+            };
+            let hits = set
+                .grep(&needle)
+                .filter(|r| r.seq >= from && r.seq < to)
+                .try_for_each(|r| {
+                    writeln!(
+                        out,
+                        "{:>10}  {:>6}B  {}",
+                        r.seq,
+                        r.data.len(),
+                        escaped(r.data)
+                    )
+                });
+            hits
+        }
+        "verify" => {
+            let report = set.verify();
+            let _ = writeln!(
+                out,
+                "{} messages: {} ok, {} without crc, {} corrupted",
+                set.len(),
+                report.ok,
+                report.unchecked,
+                report.corrupted.len()
+            );
+            for seq in &report.corrupted {
+                let _ = writeln!(out, "corrupted seq={}", seq);
+            }
+            let _ = out.flush();
+            std::process::exit(if report.is_ok() { 0 } else { 2 });
+        }
+        "export" => {
+            let names: Vec<String> = set
+                .segments()
+                .iter()
+                .map(|s| {
+                    s.path
+                        .file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect();
+            set.range(from, to).try_for_each(|r| {
+                write!(
+                    out,
+                    "{{\"seq\":{},\"segment\":{},\"len\":{},\"crc\":\"{}\",\"hex\":\"{}\"",
+                    r.seq,
+                    json_string(&names[r.segment]),
+                    r.data.len(),
+                    crc_status(&r),
+                    hex(r.data)
+                )?;
+                if let Some(ts) = r.timestamp {
+                    write!(out, ",\"ts\":{}", ts)?;
+                }
+                if let Ok(text) = std::str::from_utf8(r.data) {
+                    write!(out, ",\"text\":{}", json_string(text))?;
+                }
+                writeln!(out, "}}")
+            })
+        }
+        _ => usage(),
+    };
+    let _ = result.and_then(|_| out.flush());
+}
+
+fn stat(out: &mut impl Write, set: &ArchiveSet) -> io::Result<()> {
+    let segments = set.segments();
+    writeln!(
+        out,
+        "{:<32} {:>12} {:>10} {:>14} {:>14} {:>6}",
+        "segment", "first_seq", "messages", "bytes", "capacity", "used"
+    )?;
+    for s in &segments {
+        writeln!(
+            out,
+            "{:<32} {:>12} {:>10} {:>14} {:>14} {:>5.1}%",
+            s.path.file_name().unwrap_or_default().to_string_lossy(),
+            s.first_seq,
+            s.count,
+            s.bytes,
+            s.capacity,
+            s.bytes as f64 * 100.0 / s.capacity.max(1) as f64
+        )?;
+    }
+    let bytes: u64 = segments.iter().map(|s| s.bytes).sum();
+    writeln!(
+        out,
+        "total: {} segments, {} messages, {} bytes",
+        segments.len(),
+        set.len(),
+        bytes
+    )
+}
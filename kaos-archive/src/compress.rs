@@ -0,0 +1,324 @@
+//! Compressed frames.
+//!
+//! A frame with `FRAME_COMPRESSED` set in its length word holds one message,
+//! or a block of several, behind an 8-byte header:
+//!
+//! ```text
+//! [codec u8][flags u8][count u16][raw_len u32][compressed body]
+//! ```
+//!
+//! For `count > 1` the raw bytes are `[len u32][data]` per message. The frame
+//! CRC covers the stored (compressed) bytes. With `FLAG_SEALED` the body is
+//! encrypted (see `encryption`); the header stays readable so frames can be
+//! counted and indexed without the key.
+
+use crate::ArchiveError;
+use kaos::checksum::Checksum;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub(crate) const COMPRESSION_HEADER_SIZE: usize = 8;
+
+const CODEC_NONE: u8 = 0;
+#[cfg(feature = "lz4")]
+const CODEC_LZ4: u8 = 1;
+#[cfg(feature = "zstd")]
+const CODEC_ZSTD: u8 = 2;
+
+/// Header `flags`: body sealed with AES-256-GCM
+const FLAG_SEALED: u8 = 1;
+
+#[cfg(feature = "encryption")]
+pub(crate) use crate::encryption::Keyring;
+/// Without the `encryption` feature there are never keys to pass
+#[cfg(not(feature = "encryption"))]
+#[derive(Clone)]
+pub(crate) enum Keyring {}
+
+/// Where a sealed frame belongs: its archive's id and the sequence of its
+/// first message. Sealing authenticates it, so a frame moved to another
+/// slot or archive fails to open. `None` for logs sealed before frames
+/// were bound (see `LOG_BOUND`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FramePosition {
+    pub(crate) archive_id: u64,
+    pub(crate) seq: u64,
+}
+
+impl FramePosition {
+    /// Position of a block's first message, from one at `idx` in it
+    pub(crate) fn block_start(at: Option<Self>, idx: usize) -> Option<Self> {
+        at.map(|at| Self {
+            seq: at.seq - idx as u64,
+            ..at
+        })
+    }
+}
+
+/// Codec for `append_compressed`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Stored as is (block mode still groups messages)
+    #[default]
+    None,
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Zstd at the given level (1-22, 3 is zstd's default)
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+}
+
+impl Compression {
+    fn codec(self) -> u8 {
+        match self {
+            Compression::None => CODEC_NONE,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => CODEC_LZ4,
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(_) => CODEC_ZSTD,
+        }
+    }
+}
+
+/// Messages not yet written as a block
+#[derive(Default)]
+pub(crate) struct PendingBlock {
+    raw: Vec<u8>,
+    count: usize,
+}
+
+impl PendingBlock {
+    /// Returns what `truncate` needs to undo the push
+    pub(crate) fn push(&mut self, data: &[u8]) -> (usize, usize) {
+        let undo = (self.raw.len(), self.count);
+        self.raw
+            .extend_from_slice(&(data.len() as u32).to_le_bytes());
+        self.raw.extend_from_slice(data);
+        self.count += 1;
+        undo
+    }
+
+    pub(crate) fn truncate(&mut self, (len, count): (usize, usize)) {
+        self.raw.truncate(len);
+        self.count = count;
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Raw frame bytes: a lone message is stored without its length prefix
+    pub(crate) fn raw(&self) -> &[u8] {
+        if self.count == 1 {
+            &self.raw[4..]
+        } else {
+            &self.raw
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.raw.clear();
+        self.count = 0;
+    }
+}
+
+/// Stored payload for `count` messages whose raw bytes are `raw`, sealed
+/// with the active key when `keys` is given (and bound to `at`)
+pub(crate) fn encode(
+    compression: Compression,
+    raw: &[u8],
+    count: usize,
+    keys: Option<&Keyring>,
+    at: Option<FramePosition>,
+) -> Result<Vec<u8>, ArchiveError> {
+    let body = match compression {
+        Compression::None => raw.to_vec(),
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => lz4_flex::block::compress(raw),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd(level) => zstd::bulk::compress(raw, level)?,
+    };
+    let mut header = [0u8; COMPRESSION_HEADER_SIZE];
+    header[0] = compression.codec();
+    header[1] = if keys.is_some() { FLAG_SEALED } else { 0 };
+    header[2..4].copy_from_slice(&(count as u16).to_le_bytes());
+    header[4..8].copy_from_slice(&(raw.len() as u32).to_le_bytes());
+    let body = match keys {
+        Some(keys) => seal(keys, &header, &body, at)?,
+        None => body,
+    };
+    let mut stored = Vec::with_capacity(COMPRESSION_HEADER_SIZE + body.len());
+    stored.extend_from_slice(&header);
+    stored.extend_from_slice(&body);
+    Ok(stored)
+}
+
+#[cfg(feature = "encryption")]
+fn seal(
+    keys: &Keyring,
+    header: &[u8],
+    body: &[u8],
+    at: Option<FramePosition>,
+) -> Result<Vec<u8>, ArchiveError> {
+    keys.seal(header, body, at)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn seal(
+    keys: &Keyring,
+    _: &[u8],
+    _: &[u8],
+    _: Option<FramePosition>,
+) -> Result<Vec<u8>, ArchiveError> {
+    match *keys {}
+}
+
+/// Decrypted body of a sealed payload
+#[cfg(feature = "encryption")]
+fn open(
+    stored: &[u8],
+    keys: Option<&Keyring>,
+    at: Option<FramePosition>,
+) -> Result<Vec<u8>, ArchiveError> {
+    match keys {
+        Some(keys) => keys.open(stored, at),
+        None => Err(ArchiveError::UnknownKey(sealed_key_id(stored)?)),
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+fn open(
+    stored: &[u8],
+    _: Option<&Keyring>,
+    _: Option<FramePosition>,
+) -> Result<Vec<u8>, ArchiveError> {
+    Err(ArchiveError::UnknownKey(sealed_key_id(stored)?))
+}
+
+/// Key a sealed payload was encrypted with
+pub(crate) fn sealed_key_id(stored: &[u8]) -> Result<u32, ArchiveError> {
+    let id = stored
+        .get(COMPRESSION_HEADER_SIZE..COMPRESSION_HEADER_SIZE + 4)
+        .ok_or(ArchiveError::Corrupted)?;
+    Ok(u32::from_le_bytes(id.try_into().unwrap()))
+}
+
+/// Messages in a stored payload (0 if too short to have a header)
+pub(crate) fn message_count(stored: &[u8]) -> usize {
+    if stored.len() < COMPRESSION_HEADER_SIZE {
+        return 0;
+    }
+    u16::from_le_bytes([stored[2], stored[3]]) as usize
+}
+
+/// Raw bytes of a stored payload (sealed ones need their key in `keys`
+/// and the position they were sealed at)
+pub(crate) fn decode(
+    stored: &[u8],
+    keys: Option<&Keyring>,
+    at: Option<FramePosition>,
+) -> Result<Vec<u8>, ArchiveError> {
+    if stored.len() < COMPRESSION_HEADER_SIZE {
+        return Err(ArchiveError::Corrupted);
+    }
+    let raw_len = u32::from_le_bytes(stored[4..8].try_into().unwrap()) as usize;
+    let opened;
+    let body = if stored[1] & FLAG_SEALED != 0 {
+        opened = open(stored, keys, at)?;
+        &opened[..]
+    } else {
+        &stored[COMPRESSION_HEADER_SIZE..]
+    };
+    let raw = match stored[0] {
+        CODEC_NONE => body.to_vec(),
+        #[cfg(feature = "lz4")]
+        CODEC_LZ4 => {
+            lz4_flex::block::decompress(body, raw_len).map_err(|_| ArchiveError::Corrupted)?
+        }
+        #[cfg(feature = "zstd")]
+        CODEC_ZSTD => zstd::bulk::decompress(body, raw_len).map_err(|_| ArchiveError::Corrupted)?,
+        codec => return Err(ArchiveError::UnsupportedCodec(codec)),
+    };
+    if raw.len() != raw_len {
+        return Err(ArchiveError::Corrupted);
+    }
+    Ok(raw)
+}
+
+/// Message `idx` of a decoded frame holding `count` messages
+pub(crate) fn message(raw: &[u8], count: usize, idx: usize) -> Result<&[u8], ArchiveError> {
+    messages(raw, count).nth(idx).ok_or(ArchiveError::Corrupted)
+}
+
+/// Every message of a decoded frame holding `count` messages
+pub(crate) fn messages(raw: &[u8], count: usize) -> Messages<'_> {
+    Messages {
+        raw,
+        left: count,
+        single: count == 1,
+    }
+}
+
+pub(crate) struct Messages<'a> {
+    raw: &'a [u8],
+    left: usize,
+    /// One message: the raw bytes are the message, no length prefix
+    single: bool,
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        if self.single {
+            return Some(self.raw);
+        }
+        let len = u32::from_le_bytes(self.raw.get(..4)?.try_into().unwrap()) as usize;
+        let data = self.raw.get(4..4 + len)?;
+        self.raw = &self.raw[4 + len..];
+        Some(data)
+    }
+}
+
+/// Decoded compressed frames, keyed by frame offset, so `&self` reads can
+/// hand out slices. Entries live until `clear` (which needs `&mut`).
+#[derive(Default)]
+pub(crate) struct DecodeCache {
+    frames: Mutex<HashMap<usize, Box<[u8]>>>,
+}
+
+impl DecodeCache {
+    /// Raw bytes of the frame at `pos` with stored payload `stored` and
+    /// frame checksum `checksum` (computed with `kind`)
+    pub(crate) fn get(
+        &self,
+        pos: usize,
+        stored: &[u8],
+        kind: Checksum,
+        checksum: u32,
+        keys: Option<&Keyring>,
+        at: Option<FramePosition>,
+    ) -> Result<&[u8], ArchiveError> {
+        let mut frames = self.frames.lock().unwrap();
+        let raw: *const [u8] = match frames.entry(pos) {
+            Entry::Occupied(entry) => &**entry.into_mut(),
+            Entry::Vacant(entry) => {
+                if kind.compute(stored) != checksum {
+                    return Err(ArchiveError::Corrupted);
+                }
+                &**entry.insert(decode(stored, keys, at)?.into_boxed_slice())
+            }
+        };
+        // Safety: boxed contents never move and are only dropped by `clear(&mut self)`
+        Ok(unsafe { &*raw })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.frames.get_mut().unwrap().clear();
+    }
+}
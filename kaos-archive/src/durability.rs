@@ -0,0 +1,87 @@
+//! When appends reach the disk.
+//!
+//! Appends land in the page cache and become visible to readers right away;
+//! a `DurabilityPolicy` decides when they are also synced, trading append
+//! latency for how much a power loss can take with it.
+
+use std::fs::File;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// When `MmapArchive` syncs appended data to disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DurabilityPolicy {
+    /// Leave it to the OS (and explicit `flush`)
+    #[default]
+    Never,
+    /// Sync once `n` messages were appended since the last sync
+    EveryNMessages(u64),
+    /// Sync when this long has passed since the last sync
+    EveryDuration(Duration),
+    /// Sync every append before it returns
+    Always,
+}
+
+#[derive(Default)]
+struct FlusherState {
+    stop: AtomicBool,
+    errors: AtomicU64,
+}
+
+/// Background thread running `sync_data` on an archive's files
+pub(crate) struct Flusher {
+    state: Arc<FlusherState>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Flusher {
+    /// Sync `files` every `interval` (if any) and on each `request`, and
+    /// once more when dropped
+    pub(crate) fn spawn(files: Vec<File>, interval: Option<Duration>) -> Self {
+        let state = Arc::new(FlusherState::default());
+        let state_clone = state.clone();
+        let handle = thread::spawn(move || loop {
+            match interval {
+                Some(interval) => thread::park_timeout(interval),
+                None => thread::park(),
+            }
+            let stop = state_clone.stop.load(Ordering::Acquire);
+            for file in &files {
+                if file.sync_data().is_err() {
+                    state_clone.errors.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            if stop {
+                break;
+            }
+        });
+        Self {
+            state,
+            handle: Some(handle),
+        }
+    }
+
+    /// Sync soon, without waiting for it
+    pub(crate) fn request(&self) {
+        if let Some(handle) = &self.handle {
+            handle.thread().unpark();
+        }
+    }
+
+    /// Failed syncs so far
+    pub(crate) fn errors(&self) -> u64 {
+        self.state.errors.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Flusher {
+    fn drop(&mut self) {
+        self.state.stop.store(true, Ordering::Release);
+        self.request();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Encryption at rest: AES-256-GCM sealed frames (feature `encryption`).
+//!
+//! A sealed frame is a compressed frame (see `compress`) with `FLAG_SEALED`
+//! set in its header; the (compressed) body is encrypted:
+//!
+//! ```text
+//! [codec u8][flags u8][count u16][raw_len u32][key_id u32][nonce 12][ciphertext][tag 16]
+//! ```
+//!
+//! Everything before the nonce is authenticated, and so is the frame's
+//! position: the archive's random id and the frame's first sequence (see
+//! `FramePosition`), so frames can't be swapped, duplicated or moved between
+//! archives sharing a key. Each frame names its key, so a `Keyring` holding
+//! retired keys reads across rotations; the log header records the active
+//! key and the sequence it took over at.
+
+use crate::compress::{sealed_key_id, FramePosition, COMPRESSION_HEADER_SIZE};
+use crate::ArchiveError;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::collections::HashMap;
+
+/// AES-256 key length
+pub const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+/// Authenticated prefix: compression header + key id
+const AAD_SIZE: usize = COMPRESSION_HEADER_SIZE + 4;
+
+/// Random id for a newly encrypted archive
+pub(crate) fn archive_id() -> u64 {
+    OsRng.next_u64()
+}
+
+/// Authenticated data: the stored prefix, then the frame's position
+fn aad(prefix: &[u8], at: Option<FramePosition>) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(AAD_SIZE + 16);
+    aad.extend_from_slice(prefix);
+    if let Some(at) = at {
+        aad.extend_from_slice(&at.archive_id.to_le_bytes());
+        aad.extend_from_slice(&at.seq.to_le_bytes());
+    }
+    aad
+}
+
+/// Active key plus retired ones still needed to read older frames
+#[derive(Clone)]
+pub struct Keyring {
+    keys: HashMap<u32, Aes256Gcm>,
+    active: u32,
+}
+
+impl Keyring {
+    /// Keyring sealing new frames with `key`
+    pub fn new(key_id: u32, key: &[u8; KEY_SIZE]) -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(key_id, cipher(key));
+        Self {
+            keys,
+            active: key_id,
+        }
+    }
+
+    /// Add a key for reading only (e.g. one rotated out)
+    pub fn with_key(mut self, key_id: u32, key: &[u8; KEY_SIZE]) -> Self {
+        self.keys.insert(key_id, cipher(key));
+        self
+    }
+
+    /// Seal new frames with `key`; the previous key stays for reading
+    pub fn rotate(&mut self, key_id: u32, key: &[u8; KEY_SIZE]) {
+        self.keys.insert(key_id, cipher(key));
+        self.active = key_id;
+    }
+
+    /// Key id new frames are sealed with
+    pub fn active(&self) -> u32 {
+        self.active
+    }
+
+    pub fn contains(&self, key_id: u32) -> bool {
+        self.keys.contains_key(&key_id)
+    }
+
+    /// `[key_id][nonce][ciphertext][tag]` for `body` behind `header`,
+    /// bound to position `at`
+    pub(crate) fn seal(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        at: Option<FramePosition>,
+    ) -> Result<Vec<u8>, ArchiveError> {
+        let mut prefix = [0u8; AAD_SIZE];
+        prefix[..COMPRESSION_HEADER_SIZE].copy_from_slice(header);
+        prefix[COMPRESSION_HEADER_SIZE..].copy_from_slice(&self.active.to_le_bytes());
+        let aad = aad(&prefix, at);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let sealed = self.keys[&self.active]
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: body,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| ArchiveError::Corrupted)?;
+
+        let mut out = Vec::with_capacity(4 + NONCE_SIZE + sealed.len());
+        out.extend_from_slice(&self.active.to_le_bytes());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// Decrypted body of a stored sealed frame (header included), which
+    /// must have been sealed at `at`
+    pub(crate) fn open(
+        &self,
+        stored: &[u8],
+        at: Option<FramePosition>,
+    ) -> Result<Vec<u8>, ArchiveError> {
+        if stored.len() < AAD_SIZE + NONCE_SIZE {
+            return Err(ArchiveError::Corrupted);
+        }
+        let key_id = sealed_key_id(stored)?;
+        let cipher = self
+            .keys
+            .get(&key_id)
+            .ok_or(ArchiveError::UnknownKey(key_id))?;
+        let nonce = Nonce::from_slice(&stored[AAD_SIZE..AAD_SIZE + NONCE_SIZE]);
+        let aad = aad(&stored[..AAD_SIZE], at);
+        let payload = Payload {
+            msg: &stored[AAD_SIZE + NONCE_SIZE..],
+            aad: &aad,
+        };
+        cipher
+            .decrypt(nonce, payload)
+            .map_err(|_| ArchiveError::Corrupted)
+    }
+}
+
+impl std::fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut ids: Vec<_> = self.keys.keys().collect();
+        ids.sort();
+        f.debug_struct("Keyring")
+            .field("active", &self.active)
+            .field("keys", &ids)
+            .finish()
+    }
+}
+
+fn cipher(key: &[u8; KEY_SIZE]) -> Aes256Gcm {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+}
+
+/// Active key and the first sequence sealed with it, from the log header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyMarker {
+    pub key_id: u32,
+    pub since: u64,
+}
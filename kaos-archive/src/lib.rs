@@ -2,12 +2,32 @@
 //!
 //! - `Archive` - background writer, call `flush()`
 //! - `MmapArchive` - faster but crash-safe per write
+//! - `SegmentedArchive` - numbered segments with rollover and retention
+//! - `ArchiveReader` - read-only handle while another thread/process appends
+//! - `ArchiveSet` - read-only view of many segments as one log (see `kaos-archive` CLI)
 
 mod archive;
+mod compress;
+mod durability;
+#[cfg(feature = "encryption")]
+mod encryption;
 mod mmap_archive;
+mod options;
+mod reader;
+mod segmented;
+mod set;
 
 pub use archive::Archive;
-pub use mmap_archive::MmapArchive;
+pub use compress::Compression;
+pub use durability::DurabilityPolicy;
+#[cfg(feature = "encryption")]
+pub use encryption::{KeyMarker, Keyring, KEY_SIZE};
+pub use kaos::checksum::Checksum;
+pub use mmap_archive::{CompactReport, MmapArchive, RecoveryReport};
+pub use options::ArchiveOptions;
+pub use reader::{ArchiveReader, Tail};
+pub use segmented::{RetentionPolicy, SegmentedArchive};
+pub use set::{ArchiveSet, Record, SegmentInfo, VerifyReport};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ArchiveError {
@@ -21,4 +41,12 @@ pub enum ArchiveError {
     Corrupted,
     #[error("invalid magic")]
     InvalidMagic,
+    #[error("unsupported codec: {0} (feature not enabled?)")]
+    UnsupportedCodec(u8),
+    #[error("unsupported checksum: {0}")]
+    UnsupportedChecksum(u32),
+    #[error("no key {0} (encryption feature not enabled?)")]
+    UnknownKey(u32),
+    #[error("not supported on encrypted archives")]
+    Encrypted,
 }
@@ -1,40 +1,299 @@
 //! Synchronous archive - crash-safe per write.
 
-use crate::ArchiveError;
-use kaos::crc32::crc32_simd;
+use crate::compress::{self, DecodeCache, FramePosition, Keyring, PendingBlock};
+use crate::durability::Flusher;
+use crate::{ArchiveError, ArchiveOptions, ArchiveReader, DurabilityPolicy};
+use kaos::checksum::Checksum;
 use memmap2::{MmapMut, MmapOptions};
+use std::collections::HashSet;
 use std::fs::{File, OpenOptions};
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
 #[repr(C, align(64))]
-struct LogHeader {
+pub(crate) struct LogHeader {
     magic: u64,
     version: u32,
-    _reserved: u32,
-    write_pos: AtomicU64,
-    msg_count: AtomicU64,
-    _pad: [u8; 32],
+    /// `Checksum::id` of the frame checksums (0, in older logs too: CRC32)
+    pub(crate) checksum: u32,
+    /// Published with Release after the frames before it are written
+    pub(crate) write_pos: AtomicU64,
+    pub(crate) msg_count: AtomicU64,
+    /// Sequence of the first message when part of a `SegmentedArchive`
+    base_seq: AtomicU64,
+    /// `LOG_ENCRYPTED` once frames are sealed, `LOG_UNCHECKED` once a frame
+    /// skipped its checksum
+    pub(crate) flags: u32,
+    /// Key rotation marker: active key and the first sequence sealed with it
+    pub(crate) key_id: u32,
+    pub(crate) key_since: u64,
+    /// Random id sealed frames are bound to (`LOG_BOUND`)
+    pub(crate) archive_id: u64,
 }
 
-const MAGIC: u64 = 0x004b414f534c4f47; // "KAOSLOG\0"
-const HEADER_SIZE: usize = 64;
-const FRAME_HEADER_SIZE: usize = 8;
+impl LogHeader {
+    /// Position sealed frames starting at `seq` were bound to, if they are
+    pub(crate) fn position(&self, seq: u64) -> Option<FramePosition> {
+        (self.flags & LOG_BOUND != 0).then_some(FramePosition {
+            archive_id: self.archive_id,
+            seq,
+        })
+    }
+}
+
+/// `LogHeader::flags`: frames are sealed (see `encryption`)
+pub(crate) const LOG_ENCRYPTED: u32 = 1;
+/// `LogHeader::flags`: sealed frames authenticate `archive_id` and their
+/// first sequence (set for archives encrypted since frames were bound)
+pub(crate) const LOG_BOUND: u32 = 2;
+/// `LogHeader::flags`: some frames were written without a checksum, so
+/// recovery has to accept a zero checksum field
+pub(crate) const LOG_UNCHECKED: u32 = 4;
+pub(crate) const MAGIC: u64 = 0x004b414f534c4f47; // "KAOSLOG\0"
+pub(crate) const HEADER_SIZE: usize = 64;
+pub(crate) const FRAME_HEADER_SIZE: usize = 8;
+/// Set in a frame's length word when a timestamp precedes the payload
+pub(crate) const FRAME_TIMESTAMPED: u32 = 1 << 31;
+pub(crate) const TIMESTAMP_SIZE: usize = 8;
+/// Set in a frame's length word when the payload is compressed (see `compress`)
+pub(crate) const FRAME_COMPRESSED: u32 = 1 << 30;
+/// Set in a frame's length word for messages `compact` dropped: no payload,
+/// the checksum field holds how many sequences it stands for
+pub(crate) const FRAME_SKIP: u32 = 1 << 29;
+pub(crate) const FRAME_LEN_MASK: u32 = !(FRAME_TIMESTAMPED | FRAME_COMPRESSED | FRAME_SKIP);
+
+/// `IndexEntry::flags`: frame carries a timestamp
+pub(crate) const INDEX_TIMESTAMPED: u32 = 1;
+/// `IndexEntry::flags`: compressed frame; bits 16.. hold the message's
+/// position in the block
+pub(crate) const INDEX_COMPRESSED: u32 = 2;
+/// Timestamped frames per time index entry
+const TIME_INDEX_STRIDE: u64 = 64;
+
+/// Frame checksum algorithm recorded in a log header
+pub(crate) fn header_checksum(id: u32) -> Result<Checksum, ArchiveError> {
+    u8::try_from(id)
+        .ok()
+        .and_then(Checksum::from_id)
+        .ok_or(ArchiveError::UnsupportedChecksum(id))
+}
 
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
-struct IndexEntry {
-    offset: u64,
+pub(crate) struct IndexEntry {
+    pub(crate) offset: u64,
     length: u32,
-    _pad: u32,
+    pub(crate) flags: u32,
+}
+
+impl IndexEntry {
+    #[inline(always)]
+    pub(crate) fn payload(&self) -> std::ops::Range<usize> {
+        let mut start = self.offset as usize + FRAME_HEADER_SIZE;
+        if self.flags & INDEX_TIMESTAMPED != 0 {
+            start += TIMESTAMP_SIZE;
+        }
+        start..start + self.length as usize
+    }
+
+    /// Position in its block, for messages in compressed frames
+    #[inline(always)]
+    pub(crate) fn block_index(&self) -> Option<usize> {
+        (self.flags & INDEX_COMPRESSED != 0).then_some((self.flags >> 16) as usize)
+    }
+
+    fn checksum(&self, log: &[u8]) -> u32 {
+        let at = self.offset as usize + 4;
+        u32::from_ne_bytes(log[at..at + 4].try_into().unwrap())
+    }
+
+    /// The message `self` points at (position `at`), decoding compressed
+    /// frames through `cache`
+    pub(crate) fn message<'a>(
+        &self,
+        log: &'a [u8],
+        cache: &'a DecodeCache,
+        kind: Checksum,
+        keys: Option<&Keyring>,
+        at: Option<FramePosition>,
+        verify: bool,
+    ) -> Result<&'a [u8], ArchiveError> {
+        let stored = &log[self.payload()];
+        match self.block_index() {
+            Some(idx) => {
+                let checksum = self.checksum(log);
+                let at = FramePosition::block_start(at, idx);
+                let raw = cache.get(self.offset as usize, stored, kind, checksum, keys, at)?;
+                compress::message(raw, compress::message_count(stored), idx)
+            }
+            None if verify && kind.compute(stored) != self.checksum(log) => {
+                Err(ArchiveError::Corrupted)
+            }
+            None => Ok(stored),
+        }
+    }
+
+    /// Decoded (checksum-verified) frame of a compressed entry at `at` and
+    /// its message count
+    pub(crate) fn decode_block(
+        &self,
+        log: &[u8],
+        kind: Checksum,
+        keys: Option<&Keyring>,
+        at: Option<FramePosition>,
+    ) -> Result<(Vec<u8>, usize), ArchiveError> {
+        let stored = &log[self.payload()];
+        if kind.compute(stored) != self.checksum(log) {
+            return Err(ArchiveError::Corrupted);
+        }
+        let at = FramePosition::block_start(at, self.block_index().unwrap_or(0));
+        Ok((
+            compress::decode(stored, keys, at)?,
+            compress::message_count(stored),
+        ))
+    }
+}
+
+/// Sparse `(timestamp, seq)` pairs in `<base>.tix`, one per
+/// `TIME_INDEX_STRIDE` timestamped frames. Created on first use.
+struct TimeIndex {
+    mmap: MmapMut,
+    _file: File,
+    len: usize,
+}
+
+impl TimeIndex {
+    fn create(path: &Path, capacity: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        // Smallest timestamped frame is 16 bytes
+        file.set_len(((capacity / 64) & !15).max(16) as u64)?;
+        let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+        Ok(Self {
+            mmap,
+            _file: file,
+            len: 0,
+        })
+    }
+
+    fn open(path: &Path) -> std::io::Result<Option<Self>> {
+        let file = match OpenOptions::new().read(true).write(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+        let mut index = Self {
+            mmap,
+            _file: file,
+            len: 0,
+        };
+        // Zeroed slots are unused (seqs are stored +1)
+        while index.len < index.capacity() && index.raw(index.len).1 != 0 {
+            index.len += 1;
+        }
+        Ok(Some(index))
+    }
+
+    fn capacity(&self) -> usize {
+        self.mmap.len() / 16
+    }
+
+    fn raw(&self, i: usize) -> (u64, u64) {
+        let at = i * 16;
+        (
+            u64::from_ne_bytes(self.mmap[at..at + 8].try_into().unwrap()),
+            u64::from_ne_bytes(self.mmap[at + 8..at + 16].try_into().unwrap()),
+        )
+    }
+
+    fn entry(&self, i: usize) -> (u64, u64) {
+        let (ts, seq) = self.raw(i);
+        (ts, seq - 1)
+    }
+
+    fn record(&mut self, timestamp: u64, seq: u64) {
+        let last = self.len.checked_sub(1).map(|i| self.entry(i));
+        if last.is_some_and(|(_, s)| seq < s + TIME_INDEX_STRIDE) || self.len == self.capacity() {
+            return;
+        }
+        // Kept non-decreasing so lookups can binary search
+        let timestamp = last.map_or(timestamp, |(t, _)| timestamp.max(t));
+        let at = self.len * 16;
+        self.mmap[at..at + 8].copy_from_slice(&timestamp.to_ne_bytes());
+        self.mmap[at + 8..at + 16].copy_from_slice(&(seq + 1).to_ne_bytes());
+        self.len += 1;
+    }
+
+    /// Sequence to scan from for frames at or after `timestamp`
+    fn start_seq(&self, timestamp: u64) -> u64 {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.entry(mid).0 < timestamp {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        match lo.checked_sub(1).or((self.len > 0).then_some(0)) {
+            Some(i) => self.entry(i).1,
+            None => 0,
+        }
+    }
+}
+
+/// What `MmapArchive::open_with_recovery` repaired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Complete frames found past the synced header, now readable again
+    pub recovered: u64,
+    /// Bytes of a torn (or unindexable) frame that were zeroed
+    pub discarded_bytes: u64,
+}
+
+/// What `MmapArchive::compact` dropped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Deleted messages no longer in the log
+    pub removed: u64,
+    /// Log bytes freed for new appends
+    pub reclaimed_bytes: u64,
 }
 
 /// Synchronous mmap archive. Crash-safe but slower than `Archive`.
 pub struct MmapArchive {
     log_mmap: MmapMut,
     index_mmap: MmapMut,
-    _log_file: File,
+    log_file: File,
     _index_file: File,
+    log_path: PathBuf,
+    time_index: Option<TimeIndex>,
+    /// Sequences `mark_deleted` tombstoned, until `compact` drops them
+    deleted: HashSet<u64>,
+    /// `<base>.del`: the tombstones, one u64 each. Opened on first use.
+    tombstones: Option<File>,
+    options: ArchiveOptions,
+    /// Frame checksum, from the header
+    checksum: Checksum,
+    /// Seals new frames and opens sealed ones (`with_keyring`)
+    keyring: Option<Keyring>,
+    /// Header says frames are sealed: appends need `keyring`
+    encrypted: bool,
+    /// `append_compressed` messages not yet written as a block
+    pending: PendingBlock,
+    decoded: DecodeCache,
+    /// Log bytes / messages covered by the last sync (or its request)
+    synced_pos: usize,
+    synced_count: u64,
+    last_sync: Instant,
+    flusher: Option<Flusher>,
     capacity: usize,
     write_pos: usize,
     msg_count: u64,
@@ -66,14 +325,20 @@ impl MmapArchive {
 
         let mut log_mmap = unsafe { MmapOptions::new().map_mut(&log_file)? };
         let mut index_mmap = unsafe { MmapOptions::new().map_mut(&index_file)? };
+        // A stale time index would point into the old log, stale
+        // tombstones at its messages
+        remove_if_exists(&base.with_extension("tix"))?;
+        remove_if_exists(&base.with_extension("del"))?;
 
         let header = unsafe { &mut *(log_mmap.as_mut_ptr() as *mut LogHeader) };
         header.magic = MAGIC;
         header.version = 1;
+        header.checksum = Checksum::default().id() as u32;
         header
             .write_pos
             .store(HEADER_SIZE as u64, Ordering::Release);
         header.msg_count.store(0, Ordering::Release);
+        header.base_seq.store(0, Ordering::Release);
 
         let log_base = log_mmap.as_mut_ptr();
         let idx_base = index_mmap.as_mut_ptr();
@@ -85,8 +350,22 @@ impl MmapArchive {
         Ok(Self {
             log_mmap,
             index_mmap,
-            _log_file: log_file,
+            log_file,
             _index_file: index_file,
+            log_path: base.with_extension("log"),
+            time_index: None,
+            deleted: HashSet::new(),
+            tombstones: None,
+            options: ArchiveOptions::default(),
+            checksum: Checksum::default(),
+            keyring: None,
+            encrypted: false,
+            pending: PendingBlock::default(),
+            decoded: DecodeCache::default(),
+            synced_pos: HEADER_SIZE,
+            synced_count: 0,
+            last_sync: Instant::now(),
+            flusher: None,
             capacity,
             write_pos: HEADER_SIZE,
             msg_count: 0,
@@ -96,8 +375,20 @@ impl MmapArchive {
         })
     }
 
+    /// Open an existing archive, repairing its tail (see `open_with_recovery`).
     pub fn open<P: AsRef<Path>>(base_path: P) -> Result<Self, ArchiveError> {
+        Self::open_with_recovery(base_path).map(|(archive, _)| archive)
+    }
+
+    /// Open and scan past the last synced header: complete frames are indexed
+    /// and counted again, a torn frame is zeroed out, as is a frame the index
+    /// has no room for. Once frames were written without a checksum, a zero
+    /// checksum passes and those can only be checked for length.
+    pub fn open_with_recovery<P: AsRef<Path>>(
+        base_path: P,
+    ) -> Result<(Self, RecoveryReport), ArchiveError> {
         let base = base_path.as_ref();
+        finish_compaction(&base.with_extension("log"))?;
 
         let log_file = OpenOptions::new()
             .read(true)
@@ -116,8 +407,11 @@ impl MmapArchive {
         if header.magic != MAGIC {
             return Err(ArchiveError::InvalidMagic);
         }
+        let checksum = header_checksum(header.checksum)?;
+        let encrypted = header.flags & LOG_ENCRYPTED != 0;
+        let time_index = TimeIndex::open(&base.with_extension("tix"))?;
 
-        Ok(Self {
+        let mut archive = Self {
             write_pos: header.write_pos.load(Ordering::Relaxed) as usize,
             msg_count: header.msg_count.load(Ordering::Relaxed),
             log_base: log_mmap.as_mut_ptr(),
@@ -125,52 +419,322 @@ impl MmapArchive {
             idx_len: index_mmap.len(),
             log_mmap,
             index_mmap,
-            _log_file: log_file,
+            log_file,
             _index_file: index_file,
+            log_path: base.with_extension("log"),
+            time_index,
+            deleted: load_tombstones(&base.with_extension("del"))?,
+            tombstones: None,
+            options: ArchiveOptions::default(),
+            checksum,
+            keyring: None,
+            encrypted,
+            pending: PendingBlock::default(),
+            decoded: DecodeCache::default(),
+            synced_pos: 0,
+            synced_count: 0,
+            last_sync: Instant::now(),
+            flusher: None,
             capacity,
-        })
+        };
+        let report = archive.recover();
+        archive.synced_pos = archive.write_pos;
+        archive.synced_count = archive.msg_count;
+        Ok((archive, report))
+    }
+
+    fn recover(&mut self) -> RecoveryReport {
+        let mut report = RecoveryReport::default();
+        let unchecked = self.header().flags & LOG_UNCHECKED != 0;
+        while self.write_pos + FRAME_HEADER_SIZE <= self.capacity {
+            let pos = self.write_pos;
+            let word = u32::from_ne_bytes(self.log_mmap[pos..pos + 4].try_into().unwrap());
+            let checksum = u32::from_ne_bytes(self.log_mmap[pos + 4..pos + 8].try_into().unwrap());
+            if word == 0 && checksum == 0 {
+                // Never written
+                break;
+            }
+            if word & FRAME_SKIP != 0 {
+                self.write_pos = pos + FRAME_HEADER_SIZE;
+                self.msg_count += checksum as u64;
+                continue;
+            }
+            let timestamped = word & FRAME_TIMESTAMPED != 0;
+            let compressed = word & FRAME_COMPRESSED != 0;
+            let len = (word & FRAME_LEN_MASK) as usize;
+            let end = pos + frame_size(len, timestamped);
+            let payload = end - len;
+            let intact = end <= self.capacity
+                && ((unchecked && checksum == 0)
+                    || self.checksum.compute(&self.log_mmap[payload..end]) == checksum);
+            let count = if compressed && intact {
+                compress::message_count(&self.log_mmap[payload..end])
+            } else {
+                1
+            };
+            let indexable = (self.msg_count as usize + count) * 16 <= self.idx_len;
+            if !intact || !indexable {
+                let torn = end.min(self.capacity);
+                self.log_mmap[pos..torn].fill(0);
+                report.discarded_bytes = (torn - pos) as u64;
+                break;
+            }
+
+            for i in 0..count {
+                let flags = match (compressed, timestamped) {
+                    (true, _) => INDEX_COMPRESSED | ((i as u32) << 16),
+                    (false, true) => INDEX_TIMESTAMPED,
+                    (false, false) => 0,
+                };
+                self.write_index(self.msg_count + i as u64, pos, len, flags);
+            }
+            self.write_pos = end;
+            self.msg_count += count as u64;
+            report.recovered += count as u64;
+        }
+        if report != RecoveryReport::default() {
+            self.sync_header();
+        }
+        report
     }
 
     // ─── Append (safe) ───────────────────────────────────────────────────────
 
+    /// Compression, checksum and durability options. The checksum is
+    /// recorded in the header and only changes while the archive is empty.
+    pub fn with_options(mut self, options: ArchiveOptions) -> Self {
+        if self.msg_count == 0 && self.pending.count() == 0 {
+            self.checksum = options.checksum();
+            let header = unsafe { &mut *(self.log_mmap.as_mut_ptr() as *mut LogHeader) };
+            header.checksum = self.checksum.id() as u32;
+        }
+        self.options = options;
+        self
+    }
+
+    /// Algorithm frames are checksummed with
+    pub fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    /// Seal every frame written from now on with the keyring's active key
+    /// (AES-256-GCM) and open sealed frames on read. Sealed frames go
+    /// through the `append_compressed` block format, one message per frame
+    /// for plain appends; timestamps are not supported.
+    #[cfg(feature = "encryption")]
+    pub fn with_keyring(mut self, keyring: Keyring) -> Self {
+        self.mark_key(keyring.active());
+        self.keyring = Some(keyring);
+        self
+    }
+
+    /// Seal frames from the next sequence on with `key` (older keys stay
+    /// in the keyring for reads) and record the rotation in the header.
+    /// Starts encrypting if the archive wasn't yet.
+    #[cfg(feature = "encryption")]
+    pub fn rotate_key(
+        &mut self,
+        key_id: u32,
+        key: &[u8; crate::encryption::KEY_SIZE],
+    ) -> Result<(), ArchiveError> {
+        // Buffered messages belong to the old key
+        self.flush_block()?;
+        match self.keyring.as_mut() {
+            Some(keyring) => keyring.rotate(key_id, key),
+            None => self.keyring = Some(Keyring::new(key_id, key)),
+        }
+        self.mark_key(key_id);
+        Ok(())
+    }
+
+    /// Active key and the sequence it took over at, for encrypted archives
+    #[cfg(feature = "encryption")]
+    pub fn key_marker(&self) -> Option<crate::KeyMarker> {
+        let header = self.header();
+        (header.flags & LOG_ENCRYPTED != 0).then_some(crate::KeyMarker {
+            key_id: header.key_id,
+            since: header.key_since,
+        })
+    }
+
+    #[cfg(feature = "encryption")]
+    fn mark_key(&mut self, key_id: u32) {
+        let msg_count = self.msg_count;
+        let header = unsafe { &mut *(self.log_mmap.as_mut_ptr() as *mut LogHeader) };
+        if header.flags & LOG_ENCRYPTED == 0 {
+            // No sealed frames yet: bind them all to this archive
+            header.archive_id = crate::encryption::archive_id();
+            header.flags |= LOG_BOUND;
+        }
+        if header.flags & LOG_ENCRYPTED == 0 || header.key_id != key_id {
+            header.key_id = key_id;
+            header.key_since = msg_count;
+            header.flags |= LOG_ENCRYPTED;
+        }
+        self.encrypted = true;
+    }
+
+    /// Whether frames are sealed (see `with_keyring`)
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// Keys for new frames; an encrypted archive opened without its
+    /// keyring refuses to write plaintext
+    fn sealing_keys(&self) -> Result<Option<&Keyring>, ArchiveError> {
+        match &self.keyring {
+            None if self.encrypted => Err(ArchiveError::UnknownKey(self.header().key_id)),
+            keys => Ok(keys.as_ref()),
+        }
+    }
+
+    /// One message as its own sealed frame
+    fn append_sealed(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
+        self.flush_block()?;
+        let seq = self.msg_count;
+        let undo = self.pending.push(data);
+        if let Err(e) = self.flush_block() {
+            self.pending.truncate(undo);
+            return Err(e);
+        }
+        Ok(seq)
+    }
+
+    /// Append compressed per `ArchiveOptions`. In block mode the message is
+    /// buffered and becomes readable once its block is written: when full,
+    /// on `flush_block`, on any other append, or on drop.
+    pub fn append_compressed(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
+        let seq = self.msg_count + self.pending.count() as u64;
+        let undo = self.pending.push(data);
+        if self.pending.count() >= self.options.block_messages() {
+            if let Err(e) = self.flush_block() {
+                self.pending.truncate(undo);
+                return Err(e);
+            }
+        }
+        Ok(seq)
+    }
+
+    /// Write buffered `append_compressed` messages as one frame
+    pub fn flush_block(&mut self) -> Result<(), ArchiveError> {
+        let count = self.pending.count();
+        if count == 0 {
+            return Ok(());
+        }
+        let stored = compress::encode(
+            self.options.compression(),
+            self.pending.raw(),
+            count,
+            self.sealing_keys()?,
+            self.header().position(self.msg_count),
+        )?;
+
+        let pos = self.write_pos;
+        let new_pos = pos + FRAME_HEADER_SIZE + stored.len();
+        if new_pos > self.capacity || (self.msg_count as usize + count) * 16 > self.idx_len {
+            return Err(ArchiveError::Full);
+        }
+        unsafe {
+            let base = self.log_base.add(pos);
+            std::ptr::write_unaligned(base as *mut u32, stored.len() as u32 | FRAME_COMPRESSED);
+            std::ptr::write_unaligned(base.add(4) as *mut u32, self.checksum.compute(&stored));
+            std::ptr::copy_nonoverlapping(
+                stored.as_ptr(),
+                base.add(FRAME_HEADER_SIZE),
+                stored.len(),
+            );
+        }
+        for i in 0..count {
+            let flags = INDEX_COMPRESSED | ((i as u32) << 16);
+            self.write_index(self.msg_count + i as u64, pos, stored.len(), flags);
+        }
+
+        self.pending.clear();
+        self.write_pos = new_pos;
+        self.msg_count += count as u64;
+        self.sync_header();
+        self.apply_durability()
+    }
+
+    fn write_index(&mut self, seq: u64, pos: usize, length: usize, flags: u32) {
+        let idx_pos = (seq as usize) << 4;
+        if idx_pos + 16 <= self.idx_len {
+            let entry = IndexEntry {
+                offset: pos as u64,
+                length: length as u32,
+                flags,
+            };
+            unsafe {
+                std::ptr::write_unaligned(self.idx_base.add(idx_pos) as *mut IndexEntry, entry)
+            };
+        }
+    }
+
     /// Append with CRC32 + index (safe, ~10 M/s).
     #[inline]
     pub fn append(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
-        self.append_inner(data, true, true)
+        self.append_inner(data, true, true, None)
     }
 
     /// Append without CRC32 (faster, still indexed).
     #[inline]
     pub fn append_no_crc(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
-        self.append_inner(data, false, true)
+        self.append_inner(data, false, true, None)
     }
 
     /// Append without CRC32 or index (fastest safe).
     #[inline]
     pub fn append_no_index(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
-        self.append_inner(data, false, false)
+        self.append_inner(data, false, false, None)
+    }
+
+    /// Append with CRC32 + index and a timestamp (e.g. unix nanos) for
+    /// `replay_between`. Timestamps should not go backwards.
+    pub fn append_timestamped(&mut self, data: &[u8], timestamp: u64) -> Result<u64, ArchiveError> {
+        if self.encrypted {
+            return Err(ArchiveError::Encrypted);
+        }
+        if self.time_index.is_none() {
+            let path = self.log_path.with_extension("tix");
+            self.time_index = Some(TimeIndex::create(&path, self.capacity)?);
+        }
+        let seq = self.append_inner(data, true, true, Some(timestamp))?;
+        if let Some(index) = self.time_index.as_mut() {
+            index.record(timestamp, seq);
+        }
+        Ok(seq)
     }
 
     #[inline(always)]
-    fn append_inner(&mut self, data: &[u8], crc: bool, index: bool) -> Result<u64, ArchiveError> {
+    fn append_inner(
+        &mut self,
+        data: &[u8],
+        crc: bool,
+        index: bool,
+        timestamp: Option<u64>,
+    ) -> Result<u64, ArchiveError> {
+        if self.encrypted {
+            return self.append_sealed(data);
+        }
+        // Keep sequences in order behind buffered compressed messages
+        self.flush_block()?;
         let seq = self.msg_count;
         let pos = self.write_pos;
-        let new_pos = pos + FRAME_HEADER_SIZE + data.len();
+        let new_pos = pos + frame_size(data.len(), timestamp.is_some());
 
         if new_pos > self.capacity {
             return Err(ArchiveError::Full);
         }
 
         unsafe {
-            self.write_frame(pos, seq, data, crc, index);
+            self.write_frame(pos, seq, data, crc, index, timestamp);
         }
 
         self.write_pos = new_pos;
         self.msg_count = seq + 1;
-
-        if (seq & 0x3ff) == 0 {
-            self.sync_header();
-        }
+        // Publish to `ArchiveReader`s (and crash recovery)
+        self.sync_header();
+        self.apply_durability()?;
 
         Ok(seq)
     }
@@ -178,11 +742,20 @@ impl MmapArchive {
     // ─── Batch append ─────────────────────────────────────────────────────────
 
     /// Batch append same-size messages (fastest - single memcpy per message).
+    /// Encrypted archives seal each message as `append` does.
     #[inline]
     pub fn append_batch(&mut self, messages: &[&[u8]]) -> Result<u64, ArchiveError> {
+        self.flush_block()?;
         if messages.is_empty() {
             return Ok(self.msg_count);
         }
+        if self.encrypted {
+            let start_seq = self.msg_count;
+            for data in messages {
+                self.append_sealed(data)?;
+            }
+            return Ok(start_seq);
+        }
 
         let msg_size = messages[0].len();
         let frame_size = FRAME_HEADER_SIZE + msg_size;
@@ -196,11 +769,13 @@ impl MmapArchive {
         unsafe {
             self.write_batch_raw(messages, msg_size);
         }
+        self.apply_durability()?;
         Ok(start_seq)
     }
 
     #[inline(always)]
     unsafe fn write_batch_raw(&mut self, messages: &[&[u8]], msg_size: usize) {
+        self.mark_unchecked();
         let frame_size = FRAME_HEADER_SIZE + msg_size;
         let mut ptr = self.log_base.add(self.write_pos);
 
@@ -215,32 +790,68 @@ impl MmapArchive {
 
         self.write_pos += frame_size * messages.len();
         self.msg_count += messages.len() as u64;
+        self.sync_header();
     }
 
     // ─── Append (unsafe) ─────────────────────────────────────────────────────
 
     /// Append without bounds check. Caller must ensure capacity.
+    /// Not visible to `ArchiveReader`s until the next checked append or drop.
+    /// Writes plaintext, so not for encrypted archives.
     /// # Safety
     /// - `write_pos + 8 + data.len()` must not exceed capacity
     #[inline(always)]
     pub unsafe fn append_unchecked(&mut self, data: &[u8]) -> u64 {
+        debug_assert_eq!(self.pending.count(), 0, "flush_block() first");
+        debug_assert!(!self.encrypted, "append() seals, append_unchecked doesn't");
         let seq = self.msg_count;
         let pos = self.write_pos;
 
-        self.write_frame(pos, seq, data, false, false);
+        self.write_frame(pos, seq, data, false, false, None);
 
         self.write_pos = pos + FRAME_HEADER_SIZE + data.len();
         self.msg_count = seq + 1;
         seq
     }
 
+    /// Set `LOG_UNCHECKED` before the first frame without a checksum
     #[inline(always)]
-    unsafe fn write_frame(&mut self, pos: usize, seq: u64, data: &[u8], crc: bool, index: bool) {
+    fn mark_unchecked(&mut self) {
+        // Safety: log_base points at the mapped header
+        let header = unsafe { &mut *(self.log_base as *mut LogHeader) };
+        header.flags |= LOG_UNCHECKED;
+    }
+
+    #[inline(always)]
+    unsafe fn write_frame(
+        &mut self,
+        pos: usize,
+        seq: u64,
+        data: &[u8],
+        crc: bool,
+        index: bool,
+        timestamp: Option<u64>,
+    ) {
         let base = self.log_base.add(pos);
-        let checksum = if crc { crc32_simd(data) } else { 0 };
-        std::ptr::write_unaligned(base as *mut u32, data.len() as u32);
+        let checksum = if crc {
+            self.checksum.compute(data)
+        } else {
+            self.mark_unchecked();
+            0
+        };
+        let (len_word, payload) = match timestamp {
+            Some(ts) => {
+                std::ptr::write_unaligned(base.add(FRAME_HEADER_SIZE) as *mut u64, ts);
+                (
+                    data.len() as u32 | FRAME_TIMESTAMPED,
+                    FRAME_HEADER_SIZE + TIMESTAMP_SIZE,
+                )
+            }
+            None => (data.len() as u32, FRAME_HEADER_SIZE),
+        };
+        std::ptr::write_unaligned(base as *mut u32, len_word);
         std::ptr::write_unaligned(base.add(4) as *mut u32, checksum);
-        std::ptr::copy_nonoverlapping(data.as_ptr(), base.add(FRAME_HEADER_SIZE), data.len());
+        std::ptr::copy_nonoverlapping(data.as_ptr(), base.add(payload), data.len());
 
         if index {
             let idx_pos = (seq as usize) << 4;
@@ -248,57 +859,311 @@ impl MmapArchive {
                 let idx_ptr = self.idx_base.add(idx_pos);
                 std::ptr::write_unaligned(idx_ptr as *mut u64, pos as u64);
                 std::ptr::write_unaligned(idx_ptr.add(8) as *mut u32, data.len() as u32);
+                let flags = if timestamp.is_some() {
+                    INDEX_TIMESTAMPED
+                } else {
+                    0
+                };
+                std::ptr::write_unaligned(idx_ptr.add(12) as *mut u32, flags);
             }
         }
     }
 
     // ─── Read (safe) ─────────────────────────────────────────────────────────
 
-    /// Read with CRC32 verification.
-    pub fn read(&self, seq: u64) -> Result<&[u8], ArchiveError> {
-        if seq >= self.msg_count {
+    /// Index entry of `seq`, unless it was never indexed or is deleted
+    fn entry(&self, seq: u64) -> Result<&IndexEntry, ArchiveError> {
+        let deleted = !self.deleted.is_empty() && self.deleted.contains(&seq);
+        if seq >= self.msg_count || ((seq as usize) + 1) * 16 > self.idx_len || deleted {
             return Err(ArchiveError::InvalidSequence(seq));
         }
-
         let entry =
             unsafe { &*(self.index_mmap.as_ptr().add((seq as usize) * 16) as *const IndexEntry) };
-        let offset = entry.offset as usize;
-        let length = entry.length as usize;
-        let data = &self.log_mmap[offset + FRAME_HEADER_SIZE..offset + FRAME_HEADER_SIZE + length];
-
-        let checksum =
-            u32::from_ne_bytes(self.log_mmap[offset + 4..offset + 8].try_into().unwrap());
-        if crc32_simd(data) != checksum {
-            return Err(ArchiveError::Corrupted);
+        // Zeroed: `append_no_index`, or dropped by `compact`
+        if entry.offset == 0 {
+            return Err(ArchiveError::InvalidSequence(seq));
         }
+        Ok(entry)
+    }
 
-        Ok(data)
+    /// Read with checksum verification.
+    pub fn read(&self, seq: u64) -> Result<&[u8], ArchiveError> {
+        self.entry(seq)?.message(
+            &self.log_mmap,
+            &self.decoded,
+            self.checksum,
+            self.keyring.as_ref(),
+            self.header().position(seq),
+            true,
+        )
     }
 
-    /// Read without CRC32 verification (faster).
+    /// Read without checksum verification (faster).
     pub fn read_no_verify(&self, seq: u64) -> Result<&[u8], ArchiveError> {
-        if seq >= self.msg_count {
-            return Err(ArchiveError::InvalidSequence(seq));
-        }
+        self.entry(seq)?.message(
+            &self.log_mmap,
+            &self.decoded,
+            self.checksum,
+            self.keyring.as_ref(),
+            self.header().position(seq),
+            false,
+        )
+    }
 
-        let entry =
-            unsafe { &*(self.index_mmap.as_ptr().add((seq as usize) * 16) as *const IndexEntry) };
-        let offset = entry.offset as usize;
-        Ok(&self.log_mmap
-            [offset + FRAME_HEADER_SIZE..offset + FRAME_HEADER_SIZE + (entry.length as usize)])
+    /// Drop blocks `read` decompressed (replay doesn't keep them)
+    pub fn clear_decoded(&mut self) {
+        self.decoded.clear();
     }
 
     // ─── Read (unsafe) ───────────────────────────────────────────────────────
 
     /// Read without bounds check. Caller must ensure seq < msg_count.
+    /// Uncompressed messages only.
     /// # Safety
     /// - `seq` must be a valid sequence number
     #[inline(always)]
     pub unsafe fn read_unchecked(&self, seq: u64) -> &[u8] {
         let entry = &*(self.index_mmap.as_ptr().add((seq as usize) * 16) as *const IndexEntry);
-        let offset = entry.offset as usize;
-        &self.log_mmap
-            [offset + FRAME_HEADER_SIZE..offset + FRAME_HEADER_SIZE + (entry.length as usize)]
+        &self.log_mmap[entry.payload()]
+    }
+
+    // ─── Time ────────────────────────────────────────────────────────────────
+
+    /// Timestamp of `seq`, if it was written with `append_timestamped`
+    pub fn timestamp(&self, seq: u64) -> Option<u64> {
+        let entry = self.entry(seq).ok()?;
+        if entry.flags & INDEX_TIMESTAMPED == 0 {
+            return None;
+        }
+        let at = entry.offset as usize + FRAME_HEADER_SIZE;
+        Some(u64::from_ne_bytes(
+            self.log_mmap[at..at + TIMESTAMP_SIZE].try_into().unwrap(),
+        ))
+    }
+
+    /// Replay timestamped messages with `from <= timestamp < to`, starting
+    /// from the time index instead of scanning the whole log. Returns number
+    /// of messages replayed.
+    pub fn replay_between<F>(&self, from: u64, to: u64, mut handler: F) -> Result<u64, ArchiveError>
+    where
+        F: FnMut(u64, &[u8]),
+    {
+        let start = self.time_index.as_ref().map_or(0, |t| t.start_seq(from));
+        let mut count = 0;
+        for seq in start..self.msg_count {
+            let Some(ts) = self.timestamp(seq) else {
+                continue;
+            };
+            if ts >= to {
+                break;
+            }
+            if ts >= from {
+                handler(seq, self.read(seq)?);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    // ─── Compaction ──────────────────────────────────────────────────────────
+
+    /// Tombstone `seq`: it reads as `InvalidSequence` and is skipped by
+    /// `replay` from now on, and leaves the log on the next `compact`.
+    /// Recorded in `<base>.del`, synced by `flush`.
+    pub fn mark_deleted(&mut self, seq: u64) -> Result<(), ArchiveError> {
+        if seq >= self.msg_count {
+            return Err(ArchiveError::InvalidSequence(seq));
+        }
+        if self.deleted.contains(&seq) {
+            return Ok(());
+        }
+        if self.tombstones.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.log_path.with_extension("del"))?;
+            self.tombstones = Some(file);
+        }
+        self.tombstones
+            .as_mut()
+            .unwrap()
+            .write_all(&seq.to_le_bytes())?;
+        self.deleted.insert(seq);
+        Ok(())
+    }
+
+    /// Tombstones waiting for `compact`
+    pub fn deleted(&self) -> usize {
+        self.deleted.len()
+    }
+
+    /// Rewrite the log without the messages marked deleted and rebuild the
+    /// index; every other message keeps its sequence. The new files are
+    /// built next to the old ones and renamed over them, so a crash leaves
+    /// one or the other (`open` finishes an interrupted swap). Readers
+    /// opened before keep the old files.
+    pub fn compact(&mut self) -> Result<CompactReport, ArchiveError> {
+        self.flush_block()?;
+        if self.deleted.is_empty() {
+            return Ok(CompactReport::default());
+        }
+        let log_tmp = self.log_path.with_extension("compacting.log");
+        let idx_tmp = self.log_path.with_extension("compacting.idx");
+        let (log_file, index_file, mut out) = match self.rewrite(&log_tmp, &idx_tmp) {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                let _ = std::fs::remove_file(&log_tmp);
+                let _ = std::fs::remove_file(&idx_tmp);
+                return Err(e);
+            }
+        };
+
+        // Renamed into place, the new log commits the swap
+        let committed = self.log_path.with_extension("compacted.log");
+        std::fs::rename(&log_tmp, &committed)?;
+        std::fs::rename(&idx_tmp, self.log_path.with_extension("idx"))?;
+        std::fs::rename(&committed, &self.log_path)?;
+        self.tombstones = None;
+        remove_if_exists(&self.log_path.with_extension("del"))?;
+
+        let report = CompactReport {
+            removed: out.removed,
+            reclaimed_bytes: (self.write_pos - out.pos) as u64,
+        };
+        // Its file handles are the old files'
+        self.flusher = None;
+        self.log_base = out.log.as_mut_ptr();
+        self.idx_base = out.idx.as_mut_ptr();
+        self.write_pos = out.pos;
+        self.synced_pos = out.pos;
+        self.synced_count = self.msg_count;
+        self.log_mmap = out.log;
+        self.index_mmap = out.idx;
+        self.log_file = log_file;
+        self._index_file = index_file;
+        self.deleted.clear();
+        self.decoded.clear();
+        Ok(report)
+    }
+
+    /// Write the compacted log and index to `log_path` / `idx_path`, synced
+    fn rewrite(
+        &self,
+        log_path: &Path,
+        idx_path: &Path,
+    ) -> Result<(File, File, Rewrite), ArchiveError> {
+        let create = |path: &Path, len: usize| -> std::io::Result<(File, MmapMut)> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?;
+            file.set_len(len as u64)?;
+            let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+            Ok((file, mmap))
+        };
+        let (log_file, log) = create(log_path, self.capacity)?;
+        let (index_file, idx) = create(idx_path, self.idx_len)?;
+        let mut out = Rewrite {
+            log,
+            idx,
+            pos: HEADER_SIZE,
+            seq: 0,
+            skipped: 0,
+            removed: 0,
+        };
+        out.log[..HEADER_SIZE].copy_from_slice(&self.log_mmap[..HEADER_SIZE]);
+
+        let mut pos = HEADER_SIZE;
+        while pos + FRAME_HEADER_SIZE <= self.write_pos {
+            let word = u32::from_ne_bytes(self.log_mmap[pos..pos + 4].try_into().unwrap());
+            let checksum = u32::from_ne_bytes(self.log_mmap[pos + 4..pos + 8].try_into().unwrap());
+            if word & FRAME_SKIP != 0 {
+                out.skip(checksum as u64);
+                pos += FRAME_HEADER_SIZE;
+                continue;
+            }
+            let len = (word & FRAME_LEN_MASK) as usize;
+            let end = pos + frame_size(len, word & FRAME_TIMESTAMPED != 0);
+            let payload = &self.log_mmap[end - len..end];
+            let count = if word & FRAME_COMPRESSED != 0 {
+                compress::message_count(payload) as u64
+            } else {
+                1
+            };
+            let first = out.seq;
+            let gone = (first..first + count)
+                .filter(|seq| self.deleted.contains(seq))
+                .count() as u64;
+            if gone == 0 {
+                out.copy(
+                    word,
+                    checksum,
+                    &self.log_mmap[pos + FRAME_HEADER_SIZE..end],
+                    count,
+                )?;
+            } else if gone == count {
+                out.skip(count);
+            } else {
+                self.split_block(&mut out, payload, checksum, count)?;
+            }
+            out.removed += gone;
+            pos = end;
+        }
+        out.write_skips()?;
+
+        let header = unsafe { &mut *(out.log.as_mut_ptr() as *mut LogHeader) };
+        header.write_pos.store(out.pos as u64, Ordering::Release);
+        out.log.flush()?;
+        out.idx.flush()?;
+        Ok((log_file, index_file, out))
+    }
+
+    /// Re-encode the surviving messages of a partly deleted block, one
+    /// block per run between deleted ones
+    fn split_block(
+        &self,
+        out: &mut Rewrite,
+        stored: &[u8],
+        checksum: u32,
+        count: u64,
+    ) -> Result<(), ArchiveError> {
+        if self.checksum.compute(stored) != checksum {
+            return Err(ArchiveError::Corrupted);
+        }
+        let first = out.seq;
+        let raw = compress::decode(stored, self.keyring.as_ref(), self.header().position(first))?;
+        let mut run = PendingBlock::default();
+        for i in 0..count {
+            let msg = compress::message(&raw, count as usize, i as usize)?;
+            if self.deleted.contains(&(first + i)) {
+                self.write_run(out, &mut run)?;
+                out.skip(1);
+            } else {
+                run.push(msg);
+            }
+        }
+        self.write_run(out, &mut run)
+    }
+
+    fn write_run(&self, out: &mut Rewrite, run: &mut PendingBlock) -> Result<(), ArchiveError> {
+        let count = run.count();
+        if count == 0 {
+            return Ok(());
+        }
+        // Surviving messages keep their sequences: `out.seq` is the run's first
+        let stored = compress::encode(
+            self.options.compression(),
+            run.raw(),
+            count,
+            self.sealing_keys()?,
+            self.header().position(out.seq),
+        )?;
+        let word = stored.len() as u32 | FRAME_COMPRESSED;
+        out.copy(word, self.checksum.compute(&stored), &stored, count as u64)?;
+        run.clear();
+        Ok(())
     }
 
     // ─── Utility ─────────────────────────────────────────────────────────────
@@ -313,19 +1178,111 @@ impl MmapArchive {
     pub fn flush(&self) -> Result<(), ArchiveError> {
         self.log_mmap.flush()?;
         self.index_mmap.flush()?;
+        if let Some(tombstones) = &self.tombstones {
+            tombstones.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Sync whatever the `DurabilityPolicy` says is due
+    fn apply_durability(&mut self) -> Result<(), ArchiveError> {
+        let background = self.options.background_flush();
+        match self.options.durability() {
+            DurabilityPolicy::Never => Ok(()),
+            DurabilityPolicy::Always => self.sync_appended(),
+            DurabilityPolicy::EveryNMessages(n) if self.msg_count - self.synced_count < n => Ok(()),
+            DurabilityPolicy::EveryNMessages(_) if background => {
+                self.synced_count = self.msg_count;
+                self.flusher(None)?.request();
+                Ok(())
+            }
+            DurabilityPolicy::EveryDuration(interval) if background => {
+                self.flusher(Some(interval))?;
+                Ok(())
+            }
+            DurabilityPolicy::EveryDuration(interval) if self.last_sync.elapsed() < interval => {
+                Ok(())
+            }
+            _ => self.sync_appended(),
+        }
+    }
+
+    /// Sync what was appended since the last sync: data and index, then header
+    fn sync_appended(&mut self) -> Result<(), ArchiveError> {
+        self.log_mmap
+            .flush_range(self.synced_pos, self.write_pos - self.synced_pos)?;
+        let idx_from = (self.synced_count as usize * 16).min(self.idx_len);
+        let idx_to = (self.msg_count as usize * 16).min(self.idx_len);
+        self.index_mmap.flush_range(idx_from, idx_to - idx_from)?;
+        if let Some(index) = &self.time_index {
+            index.mmap.flush()?;
+        }
+        self.log_mmap.flush_range(0, HEADER_SIZE)?;
+        self.synced_pos = self.write_pos;
+        self.synced_count = self.msg_count;
+        self.last_sync = Instant::now();
         Ok(())
     }
 
+    /// The background flusher, started on first use
+    fn flusher(&mut self, interval: Option<Duration>) -> Result<&Flusher, ArchiveError> {
+        if self.flusher.is_none() {
+            let files = vec![self.log_file.try_clone()?, self._index_file.try_clone()?];
+            self.flusher = Some(Flusher::spawn(files, interval));
+        }
+        Ok(self.flusher.as_ref().unwrap())
+    }
+
+    /// Background syncs that failed (`with_background_flush`)
+    pub fn flush_errors(&self) -> u64 {
+        self.flusher.as_ref().map_or(0, Flusher::errors)
+    }
+
+    /// Room for one more indexed message of `len` bytes
+    pub(crate) fn has_room(&self, len: usize, timestamped: bool) -> bool {
+        self.write_pos + frame_size(len, timestamped) <= self.capacity
+            && ((self.msg_count as usize) + 1) * 16 <= self.idx_len
+    }
+
+    pub(crate) fn base_seq(&self) -> u64 {
+        self.header().base_seq.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_base_seq(&mut self, seq: u64) {
+        self.header().base_seq.store(seq, Ordering::Release);
+    }
+
+    /// Persist header and data, and stamp the log's mtime (segment age)
+    pub(crate) fn seal(&mut self) -> Result<(), ArchiveError> {
+        self.sync_header();
+        self.flush()?;
+        self.log_file.set_modified(SystemTime::now())?;
+        Ok(())
+    }
+
+    fn header(&self) -> &LogHeader {
+        unsafe { &*(self.log_mmap.as_ptr() as *const LogHeader) }
+    }
+
     fn sync_header(&mut self) {
         let header = unsafe { &mut *(self.log_mmap.as_mut_ptr() as *mut LogHeader) };
         header
             .write_pos
-            .store(self.write_pos as u64, Ordering::Relaxed);
+            .store(self.write_pos as u64, Ordering::Release);
         header.msg_count.store(self.msg_count, Ordering::Release);
     }
 
-    /// Replay messages in range [from, to) calling handler for each.
-    /// Returns number of messages replayed.
+    /// Read-only handle another thread can use while this one appends
+    /// (sharing this archive's keyring)
+    pub fn reader(&self) -> Result<ArchiveReader, ArchiveError> {
+        let mut reader = ArchiveReader::open(&self.log_path)?;
+        reader.keyring = self.keyring.clone();
+        Ok(reader)
+    }
+
+    /// Replay messages in range [from, to) calling handler for each,
+    /// skipping deleted (and unindexed) ones. Returns number of messages
+    /// replayed.
     pub fn replay<F>(&self, from: u64, to: u64, mut handler: F) -> Result<u64, ArchiveError>
     where
         F: FnMut(u64, &[u8]),
@@ -334,17 +1291,164 @@ impl MmapArchive {
         if from >= end {
             return Ok(0);
         }
+        let mut replayed = 0;
+        // Decode each compressed block once, without growing the read cache
+        let mut block: Option<(u64, Vec<u8>, usize)> = None;
         for seq in from..end {
-            let data = self.read(seq)?;
-            handler(seq, data);
+            let Ok(entry) = self.entry(seq) else {
+                continue;
+            };
+            replayed += 1;
+            let Some(idx) = entry.block_index() else {
+                handler(seq, self.read(seq)?);
+                continue;
+            };
+            if block
+                .as_ref()
+                .is_none_or(|(offset, ..)| *offset != entry.offset)
+            {
+                let (raw, count) = entry.decode_block(
+                    &self.log_mmap,
+                    self.checksum,
+                    self.keyring.as_ref(),
+                    self.header().position(seq),
+                )?;
+                block = Some((entry.offset, raw, count));
+            }
+            let (_, raw, count) = block.as_ref().unwrap();
+            handler(seq, compress::message(raw, *count, idx)?);
         }
-        Ok(end - from)
+        Ok(replayed)
+    }
+}
+
+#[inline(always)]
+fn frame_size(len: usize, timestamped: bool) -> usize {
+    FRAME_HEADER_SIZE + if timestamped { TIMESTAMP_SIZE } else { 0 } + len
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn load_tombstones(path: &Path) -> std::io::Result<HashSet<u64>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(bytes
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Finish a `compact` that crashed after committing (`<base>.compacted.log`
+/// exists), or drop the files of one that didn't
+fn finish_compaction(log_path: &Path) -> std::io::Result<()> {
+    let committed = log_path.with_extension("compacted.log");
+    if committed.exists() {
+        let idx = log_path.with_extension("compacting.idx");
+        if idx.exists() {
+            std::fs::rename(&idx, log_path.with_extension("idx"))?;
+        }
+        std::fs::rename(&committed, log_path)?;
+        remove_if_exists(&log_path.with_extension("del"))?;
+    }
+    remove_if_exists(&log_path.with_extension("compacting.log"))?;
+    remove_if_exists(&log_path.with_extension("compacting.idx"))
+}
+
+/// Log and index `compact` is writing
+struct Rewrite {
+    log: MmapMut,
+    idx: MmapMut,
+    pos: usize,
+    /// Sequence of the next message copied
+    seq: u64,
+    /// Dropped sequences not yet written as a skip frame
+    skipped: u64,
+    removed: u64,
+}
+
+impl Rewrite {
+    fn skip(&mut self, count: u64) {
+        self.skipped += count;
+        self.seq += count;
+    }
+
+    fn write_skips(&mut self) -> Result<(), ArchiveError> {
+        while self.skipped > 0 {
+            let count = self.skipped.min(u32::MAX as u64);
+            self.frame(FRAME_SKIP, count as u32, &[])?;
+            self.skipped -= count;
+        }
+        Ok(())
+    }
+
+    fn frame(&mut self, word: u32, checksum: u32, body: &[u8]) -> Result<usize, ArchiveError> {
+        let pos = self.pos;
+        let end = pos + FRAME_HEADER_SIZE + body.len();
+        if end > self.log.len() {
+            return Err(ArchiveError::Full);
+        }
+        self.log[pos..pos + 4].copy_from_slice(&word.to_ne_bytes());
+        self.log[pos + 4..pos + 8].copy_from_slice(&checksum.to_ne_bytes());
+        self.log[pos + FRAME_HEADER_SIZE..end].copy_from_slice(body);
+        self.pos = end;
+        Ok(pos)
+    }
+
+    /// Write a frame holding the next `count` messages and index them;
+    /// `body` is everything after the frame header
+    fn copy(
+        &mut self,
+        word: u32,
+        checksum: u32,
+        body: &[u8],
+        count: u64,
+    ) -> Result<(), ArchiveError> {
+        self.write_skips()?;
+        let pos = self.frame(word, checksum, body)?;
+        let length = word & FRAME_LEN_MASK;
+        for i in 0..count {
+            let flags = if word & FRAME_COMPRESSED != 0 {
+                INDEX_COMPRESSED | ((i as u32) << 16)
+            } else if word & FRAME_TIMESTAMPED != 0 {
+                INDEX_TIMESTAMPED
+            } else {
+                0
+            };
+            let at = ((self.seq + i) as usize) * 16;
+            if at + 16 <= self.idx.len() {
+                let entry = IndexEntry {
+                    offset: pos as u64,
+                    length,
+                    flags,
+                };
+                unsafe {
+                    std::ptr::write_unaligned(
+                        self.idx.as_mut_ptr().add(at) as *mut IndexEntry,
+                        entry,
+                    )
+                };
+            }
+        }
+        self.seq += count;
+        Ok(())
     }
 }
 
 impl Drop for MmapArchive {
     fn drop(&mut self) {
+        let _ = self.flush_block();
         self.sync_header();
+        if self.flusher.is_none() && self.options.durability() != DurabilityPolicy::Never {
+            let _ = self.sync_appended();
+        }
+        // A running flusher syncs once more as it stops
     }
 }
 
@@ -375,6 +1479,354 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_replay_between() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("timed");
+        {
+            let mut archive = MmapArchive::create(&path, 1024 * 1024).unwrap();
+            archive.append(b"untimed").unwrap();
+            for i in 1..=1000u64 {
+                let seq = archive
+                    .append_timestamped(format!("t{}", i).as_bytes(), i * 10)
+                    .unwrap();
+                assert_eq!(archive.timestamp(seq), Some(i * 10));
+            }
+            assert_eq!(archive.timestamp(0), None);
+            assert_eq!(archive.read(500).unwrap(), b"t500");
+        }
+
+        // The time index survives reopen
+        let archive = MmapArchive::open(&path).unwrap();
+        let mut seen = Vec::new();
+        let count = archive
+            .replay_between(5000, 5050, |seq, data| {
+                seen.push((seq, String::from_utf8_lossy(data).to_string()));
+            })
+            .unwrap();
+        assert_eq!(count, 5);
+        assert_eq!(seen[0], (500, "t500".to_string()));
+        assert_eq!(seen[4], (504, "t504".to_string()));
+        assert_eq!(
+            archive.replay_between(0, u64::MAX, |_, _| {}).unwrap(),
+            1000
+        );
+        assert_eq!(
+            archive.replay_between(20_000, 30_000, |_, _| {}).unwrap(),
+            0
+        );
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_compressed_blocks() {
+        use crate::Compression;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("packed");
+        let snapshot = |i: usize| format!("state:{}:{}", i, "x".repeat(200)).into_bytes();
+        {
+            let mut archive = MmapArchive::create(&path, 1024 * 1024)
+                .unwrap()
+                .with_options(
+                    ArchiveOptions::default()
+                        .with_compression(Compression::Lz4)
+                        .with_block_messages(8),
+                );
+            archive.append(b"plain").unwrap();
+            for i in 1..=20 {
+                assert_eq!(archive.append_compressed(&snapshot(i)).unwrap(), i as u64);
+            }
+            // 16 written as two blocks, 4 still buffered
+            assert_eq!(archive.len(), 17);
+            assert_eq!(archive.read(9).unwrap(), snapshot(9));
+            // A plain append writes the partial block first
+            assert_eq!(archive.append(b"tail").unwrap(), 21);
+            assert_eq!(archive.read(20).unwrap(), snapshot(20));
+            assert!(archive.write_pos < 22 * 200);
+        }
+
+        let archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 22);
+        let mut seen = Vec::new();
+        archive
+            .replay(0, 22, |seq, data| seen.push((seq, data.to_vec())))
+            .unwrap();
+        assert_eq!(seen[0].1, b"plain");
+        assert_eq!(seen[5], (5, snapshot(5)));
+        assert_eq!(seen[21].1, b"tail");
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_per_message() {
+        use crate::Compression;
+        let dir = tempdir().unwrap();
+        let mut archive = MmapArchive::create(dir.path().join("zstd"), 64 * 1024)
+            .unwrap()
+            .with_options(ArchiveOptions::default().with_compression(Compression::Zstd(3)));
+        let msg = vec![7u8; 4096];
+        archive.append_compressed(&msg).unwrap();
+        assert_eq!(archive.read(0).unwrap(), msg);
+        assert!(archive.write_pos < 1024);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_frames() {
+        use crate::{ArchiveSet, KeyMarker, Keyring};
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("sealed");
+        let keyring = Keyring::new(1, &[7u8; 32]);
+        {
+            let mut archive = MmapArchive::create(&base, 64 * 1024)
+                .unwrap()
+                .with_options(ArchiveOptions::default().with_block_messages(2))
+                .with_keyring(keyring.clone());
+            archive.append(b"player one: gg").unwrap();
+            archive.append_compressed(b"player two: wp").unwrap();
+            archive.append_compressed(b"player one: rematch?").unwrap();
+            archive.append_batch(&[b"batch-a", b"batch-b"]).unwrap();
+            assert!(matches!(
+                archive.append_timestamped(b"late", 1),
+                Err(ArchiveError::Encrypted)
+            ));
+            assert_eq!(archive.len(), 5);
+            assert_eq!(archive.read(1).unwrap(), b"player two: wp");
+            assert_eq!(
+                archive.key_marker(),
+                Some(KeyMarker {
+                    key_id: 1,
+                    since: 0
+                })
+            );
+        }
+
+        let log = std::fs::read(base.with_extension("log")).unwrap();
+        for plain in [&b"player"[..], b"rematch", b"batch-"] {
+            assert!(!log.windows(plain.len()).any(|w| w == plain));
+        }
+
+        // Without the keyring: frames are counted but neither readable nor appendable
+        let mut archive = MmapArchive::open(&base).unwrap();
+        assert!(archive.is_encrypted());
+        assert_eq!(archive.len(), 5);
+        assert!(matches!(archive.read(0), Err(ArchiveError::UnknownKey(1))));
+        assert!(matches!(
+            archive.append(b"plaintext"),
+            Err(ArchiveError::UnknownKey(1))
+        ));
+        drop(archive);
+        assert_eq!(ArchiveSet::open(&base).unwrap().verify().corrupted.len(), 5);
+
+        let archive = MmapArchive::open(&base)
+            .unwrap()
+            .with_keyring(keyring.clone());
+        let mut seen = Vec::new();
+        archive
+            .replay(0, 5, |_, msg| seen.push(msg.to_vec()))
+            .unwrap();
+        assert_eq!(seen[2], b"player one: rematch?");
+        assert_eq!(archive.reader().unwrap().read(4).unwrap(), b"batch-b");
+        let set = ArchiveSet::open_with_keyring(&base, &keyring).unwrap();
+        assert_eq!(set.verify().ok, 5);
+        assert_eq!(set.read(0).unwrap(), b"player one: gg");
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_sealed_frames_bound_to_position() {
+        use crate::{ArchiveSet, Keyring};
+        let dir = tempdir().unwrap();
+        let keyring = Keyring::new(1, &[7u8; 32]);
+        let create = |name: &str, messages: &[&[u8]]| {
+            let base = dir.path().join(name);
+            let mut archive = MmapArchive::create(&base, 64 * 1024)
+                .unwrap()
+                .with_keyring(keyring.clone());
+            let mut frames = Vec::new();
+            for msg in messages {
+                let start = archive.write_pos;
+                archive.append(msg).unwrap();
+                frames.push(start..archive.write_pos);
+            }
+            (base, frames)
+        };
+        let (a, a_frames) = create("a", &[b"pay 100", b"pay 999"]);
+        let (b, b_frames) = create("b", &[b"pay 555"]);
+
+        // Duplicate frame 0 over frame 1 (same length, intact CRC), then put
+        // another archive's frame 0 (same key) in frame 0's slot
+        let mut log = std::fs::read(a.with_extension("log")).unwrap();
+        let b_log = std::fs::read(b.with_extension("log")).unwrap();
+        log.copy_within(a_frames[0].clone(), a_frames[1].start);
+        std::fs::write(a.with_extension("log"), &log).unwrap();
+        let archive = MmapArchive::open(&a).unwrap().with_keyring(keyring.clone());
+        assert_eq!(archive.read(0).unwrap(), b"pay 100");
+        assert!(matches!(archive.read(1), Err(ArchiveError::Corrupted)));
+        drop(archive);
+
+        log[a_frames[0].clone()].copy_from_slice(&b_log[b_frames[0].clone()]);
+        std::fs::write(a.with_extension("log"), &log).unwrap();
+        let archive = MmapArchive::open(&a).unwrap().with_keyring(keyring.clone());
+        assert!(matches!(archive.read(0), Err(ArchiveError::Corrupted)));
+        drop(archive);
+        let set = ArchiveSet::open_with_keyring(&a, &keyring).unwrap();
+        assert_eq!(set.verify().corrupted, [0, 1]);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_key_rotation() {
+        use crate::{KeyMarker, Keyring};
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("rotate");
+        {
+            let mut archive = MmapArchive::create(&base, 64 * 1024)
+                .unwrap()
+                .with_keyring(Keyring::new(1, &[1u8; 32]));
+            archive.append(b"old key").unwrap();
+            archive.rotate_key(2, &[2u8; 32]).unwrap();
+            archive.append(b"new key").unwrap();
+            assert_eq!(archive.read(0).unwrap(), b"old key");
+            assert_eq!(
+                archive.key_marker(),
+                Some(KeyMarker {
+                    key_id: 2,
+                    since: 1
+                })
+            );
+        }
+
+        // Only the new key: older frames stay sealed
+        let reader = ArchiveReader::open(&base)
+            .unwrap()
+            .with_keyring(Keyring::new(2, &[2u8; 32]));
+        assert!(matches!(reader.read(0), Err(ArchiveError::UnknownKey(1))));
+        assert_eq!(reader.read(1).unwrap(), b"new key");
+
+        let mut reader = ArchiveReader::open(&base)
+            .unwrap()
+            .with_keyring(Keyring::new(2, &[2u8; 32]).with_key(1, &[1u8; 32]));
+        let mut seen = Vec::new();
+        assert_eq!(reader.poll(|_, msg| seen.push(msg.to_vec())), 2);
+        assert_eq!(seen, [b"old key".to_vec(), b"new key".to_vec()]);
+
+        // A wrong key fails authentication
+        let wrong = ArchiveReader::open(&base)
+            .unwrap()
+            .with_keyring(Keyring::new(2, &[9u8; 32]));
+        assert!(matches!(wrong.read(1), Err(ArchiveError::Corrupted)));
+    }
+
+    #[test]
+    fn test_durability_policies() {
+        let dir = tempdir().unwrap();
+        let open = |name: &str, policy| {
+            MmapArchive::create(dir.path().join(name), 1024 * 1024)
+                .unwrap()
+                .with_options(ArchiveOptions::default().with_durability(policy))
+        };
+
+        let mut never = open("never", DurabilityPolicy::Never);
+        let mut always = open("always", DurabilityPolicy::Always);
+        let mut every = open("every", DurabilityPolicy::EveryNMessages(10));
+        for i in 0..25u64 {
+            never.append(&i.to_le_bytes()).unwrap();
+            always.append(&i.to_le_bytes()).unwrap();
+            every.append(&i.to_le_bytes()).unwrap();
+            assert_eq!(always.synced_pos, always.write_pos);
+        }
+        assert_eq!(never.synced_count, 0);
+        assert_eq!(always.synced_count, 25);
+        assert_eq!(every.synced_count, 20);
+        every.append_batch(&[&[0u8; 8][..]; 5]).unwrap();
+        assert_eq!(every.synced_count, 30);
+
+        let mut timed = open(
+            "timed",
+            DurabilityPolicy::EveryDuration(Duration::from_millis(5)),
+        );
+        timed.append(b"a").unwrap();
+        assert_eq!(timed.synced_count, 0);
+        std::thread::sleep(Duration::from_millis(10));
+        timed.append(b"b").unwrap();
+        assert_eq!(timed.synced_count, 2);
+    }
+
+    #[test]
+    fn test_background_flush() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bg");
+        {
+            let mut archive = MmapArchive::create(&path, 1024 * 1024)
+                .unwrap()
+                .with_options(
+                    ArchiveOptions::default()
+                        .with_durability(DurabilityPolicy::EveryDuration(Duration::from_millis(1)))
+                        .with_background_flush(true),
+                );
+            for i in 0..1000u64 {
+                archive.append(&i.to_le_bytes()).unwrap();
+            }
+            assert!(archive.flusher.is_some());
+            // Off the append path
+            assert_eq!(archive.synced_count, 0);
+            std::thread::sleep(Duration::from_millis(10));
+            assert_eq!(archive.flush_errors(), 0);
+        }
+        let archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.read(999).unwrap(), 999u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_checksum_recorded_in_header() {
+        use crate::{ArchiveSet, Checksum};
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("xxh");
+        {
+            let mut archive = MmapArchive::create(&base, 64 * 1024).unwrap().with_options(
+                ArchiveOptions::default()
+                    .with_checksum(Checksum::XxHash64)
+                    .with_block_messages(2),
+            );
+            assert_eq!(archive.checksum(), Checksum::XxHash64);
+            archive.append(b"plain").unwrap();
+            archive.append_compressed(b"a").unwrap();
+            archive.append_compressed(b"b").unwrap();
+            archive.append(b"tail").unwrap();
+            // Not empty any more: the recorded checksum stays
+            let archive = archive.with_options(ArchiveOptions::default());
+            assert_eq!(archive.checksum(), Checksum::XxHash64);
+        }
+
+        let archive = MmapArchive::open(&base).unwrap();
+        assert_eq!(archive.checksum(), Checksum::XxHash64);
+        assert_eq!(archive.read(0).unwrap(), b"plain");
+        assert_eq!(archive.read(2).unwrap(), b"b");
+        drop(archive);
+        let reader = ArchiveReader::open(&base).unwrap();
+        assert_eq!(reader.read(3).unwrap(), b"tail");
+        assert_eq!(ArchiveSet::open(&base).unwrap().verify().ok, 4);
+
+        // Flip a payload byte of the first frame
+        let log = base.with_extension("log");
+        let mut bytes = std::fs::read(&log).unwrap();
+        bytes[HEADER_SIZE + FRAME_HEADER_SIZE] ^= 0xff;
+        std::fs::write(&log, &bytes).unwrap();
+        assert!(matches!(
+            MmapArchive::open(&base).unwrap().read(0),
+            Err(ArchiveError::Corrupted)
+        ));
+        assert_eq!(ArchiveSet::open(&base).unwrap().verify().corrupted, vec![0]);
+
+        bytes[12] = 99;
+        std::fs::write(&log, &bytes).unwrap();
+        assert!(matches!(
+            MmapArchive::open(&base),
+            Err(ArchiveError::UnsupportedChecksum(99))
+        ));
+    }
+
     #[test]
     fn test_crash_recovery() {
         let dir = tempdir().unwrap();
@@ -404,6 +1856,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_torn_tail_repaired_on_open() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("torn");
+        let torn_at = {
+            let mut archive = MmapArchive::create(&path, 64 * 1024).unwrap();
+            for i in 0..10 {
+                archive.append(format!("msg-{}", i).as_bytes()).unwrap();
+            }
+            // Written but never published in the header
+            for i in 10..15 {
+                unsafe { archive.append_unchecked(format!("msg-{}", i).as_bytes()) };
+            }
+            let torn_at = archive.write_pos;
+            // Crash: no Drop, so the header still says 10 messages
+            std::mem::forget(archive);
+            torn_at
+        };
+
+        // Half-written frame: header and CRC made it, payload didn't
+        let log = path.with_extension("log");
+        let mut bytes = std::fs::read(&log).unwrap();
+        bytes[torn_at..torn_at + 4].copy_from_slice(&100u32.to_ne_bytes());
+        bytes[torn_at + 4..torn_at + 8].copy_from_slice(&0xdead_beefu32.to_ne_bytes());
+        bytes[torn_at + 8..torn_at + 20].fill(b'x');
+        std::fs::write(&log, bytes).unwrap();
+
+        let (mut archive, report) = MmapArchive::open_with_recovery(&path).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                recovered: 5,
+                discarded_bytes: 108
+            }
+        );
+        assert_eq!(archive.len(), 15);
+        assert_eq!(archive.read_no_verify(14).unwrap(), b"msg-14");
+        assert_eq!(archive.append(b"after").unwrap(), 15);
+        assert_eq!(archive.read(15).unwrap(), b"after");
+        drop(archive);
+
+        let (archive, report) = MmapArchive::open_with_recovery(&path).unwrap();
+        assert_eq!(report, RecoveryReport::default());
+        assert_eq!(archive.len(), 16);
+    }
+
+    #[test]
+    fn test_torn_length_word_discarded() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("torn_word");
+        let torn_at = {
+            let mut archive = MmapArchive::create(&path, 64 * 1024).unwrap();
+            for i in 0..10 {
+                archive.append(format!("msg-{}", i).as_bytes()).unwrap();
+            }
+            archive.write_pos
+        };
+
+        // Crash right after the length word: checksum and payload never written
+        let log = path.with_extension("log");
+        let mut bytes = std::fs::read(&log).unwrap();
+        bytes[torn_at..torn_at + 4].copy_from_slice(&5u32.to_ne_bytes());
+        std::fs::write(&log, bytes).unwrap();
+
+        let (mut archive, report) = MmapArchive::open_with_recovery(&path).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                recovered: 0,
+                discarded_bytes: 13
+            }
+        );
+        assert_eq!(archive.len(), 10);
+        assert_eq!(archive.append(b"after").unwrap(), 10);
+        assert_eq!(archive.read(10).unwrap(), b"after");
+    }
+
+    #[test]
+    fn test_recovery_stops_when_index_full() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("idx_full");
+        {
+            // 4 KB log: index room for 64 messages
+            let mut archive = MmapArchive::create(&path, 4096).unwrap();
+            for i in 0..63 {
+                archive.append(&[i as u8]).unwrap();
+            }
+            for i in 63..66 {
+                unsafe { archive.append_unchecked(&[i as u8]) };
+            }
+            std::mem::forget(archive);
+        }
+
+        let (archive, report) = MmapArchive::open_with_recovery(&path).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                recovered: 1,
+                discarded_bytes: 9
+            }
+        );
+        assert_eq!(archive.len(), 64);
+        assert_eq!(archive.read_no_verify(63).unwrap(), &[63]);
+    }
+
     #[test]
     fn test_crash_recovery_with_replay() {
         let dir = tempdir().unwrap();
@@ -433,4 +1990,148 @@ mod tests {
             assert_eq!(replayed[9], (19, "event-19".to_string()));
         }
     }
+
+    #[test]
+    fn test_compact_drops_deleted() {
+        use crate::ArchiveSet;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("room");
+        let mut archive = MmapArchive::create(&path, 1024 * 1024).unwrap();
+        for i in 0..10 {
+            archive.append(format!("msg-{}", i).as_bytes()).unwrap();
+        }
+        let mut reader = archive.reader().unwrap();
+        for seq in [2, 3, 7] {
+            archive.mark_deleted(seq).unwrap();
+        }
+        archive.mark_deleted(3).unwrap();
+        assert!(matches!(
+            archive.mark_deleted(10),
+            Err(ArchiveError::InvalidSequence(10))
+        ));
+        assert_eq!(archive.deleted(), 3);
+        assert!(archive.read(3).is_err());
+        assert_eq!(archive.replay(0, 10, |_, _| {}).unwrap(), 7);
+
+        // Tombstones survive reopen
+        drop(archive);
+        let mut archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.deleted(), 3);
+        let before = archive.write_pos;
+
+        let report = archive.compact().unwrap();
+        assert_eq!(report.removed, 3);
+        assert_eq!(report.reclaimed_bytes, (before - archive.write_pos) as u64);
+        assert!(report.reclaimed_bytes > 0);
+        assert!(!path.with_extension("del").exists());
+        assert_eq!(archive.len(), 10);
+        assert!(archive.read(7).is_err());
+        assert_eq!(archive.read(8).unwrap(), b"msg-8");
+        assert_eq!(archive.append(b"msg-10").unwrap(), 10);
+        assert_eq!(archive.compact().unwrap(), CompactReport::default());
+
+        // A reader opened before keeps the old files
+        assert_eq!(reader.poll(|_, _| {}), 10);
+        drop(archive);
+
+        let archive = MmapArchive::open(&path).unwrap();
+        let mut seqs = Vec::new();
+        archive.replay(0, 100, |seq, _| seqs.push(seq)).unwrap();
+        assert_eq!(seqs, [0, 1, 4, 5, 6, 8, 9, 10]);
+        let mut polled = Vec::new();
+        let mut reader = archive.reader().unwrap();
+        assert_eq!(reader.poll(|seq, _| polled.push(seq)), 8);
+        assert_eq!(polled, seqs);
+        assert_eq!(reader.read(5).unwrap(), b"msg-5");
+
+        let set = ArchiveSet::open(&path).unwrap();
+        assert_eq!(set.len(), 11);
+        assert!(set.get(2).is_none());
+        assert_eq!(set.read(4).unwrap(), b"msg-4");
+        assert_eq!(set.iter().count(), 8);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_compact_splits_blocks() {
+        use crate::Compression;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("packed");
+        let options = ArchiveOptions::default()
+            .with_compression(Compression::Lz4)
+            .with_block_messages(8);
+        let mut archive = MmapArchive::create(&path, 1024 * 1024)
+            .unwrap()
+            .with_options(options);
+        for i in 0..24 {
+            archive
+                .append_compressed(format!("snap-{}", i).as_bytes())
+                .unwrap();
+        }
+        archive.flush_block().unwrap();
+        // Partly deleted first block, whole second block, untouched third
+        for seq in [0, 3, 4].into_iter().chain(8..16) {
+            archive.mark_deleted(seq).unwrap();
+        }
+        assert_eq!(archive.compact().unwrap().removed, 11);
+
+        let mut seen = Vec::new();
+        archive
+            .replay(0, 24, |seq, data| {
+                assert_eq!(data, format!("snap-{}", seq).as_bytes());
+                seen.push(seq);
+            })
+            .unwrap();
+        let kept: Vec<u64> = [1, 2, 5, 6, 7].into_iter().chain(16..24).collect();
+        assert_eq!(seen, kept);
+        drop(archive);
+        let archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 24);
+        assert_eq!(archive.read(6).unwrap(), b"snap-6");
+        assert!(archive.read(12).is_err());
+    }
+
+    #[test]
+    fn test_interrupted_compaction() {
+        let dir = tempdir().unwrap();
+        let fill = |name: &str| {
+            let path = dir.path().join(name);
+            let mut archive = MmapArchive::create(&path, 64 * 1024).unwrap();
+            for i in 0..20 {
+                archive.append(format!("msg-{}", i).as_bytes()).unwrap();
+            }
+            archive.mark_deleted(5).unwrap();
+            (path, archive)
+        };
+        let (done, mut compacted) = fill("done");
+        compacted.compact().unwrap();
+        drop(compacted);
+
+        // Crashed after committing: open finishes the swap
+        let (path, archive) = fill("committed");
+        drop(archive);
+        std::fs::copy(
+            done.with_extension("log"),
+            path.with_extension("compacted.log"),
+        )
+        .unwrap();
+        std::fs::copy(
+            done.with_extension("idx"),
+            path.with_extension("compacting.idx"),
+        )
+        .unwrap();
+        let archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.deleted(), 0);
+        assert!(archive.read(5).is_err());
+        assert_eq!(archive.read(6).unwrap(), b"msg-6");
+        assert!(!path.with_extension("compacted.log").exists());
+
+        // Crashed before: the half-written files are dropped
+        let (path, archive) = fill("uncommitted");
+        drop(archive);
+        std::fs::write(path.with_extension("compacting.log"), b"partial").unwrap();
+        let archive = MmapArchive::open(&path).unwrap();
+        assert_eq!(archive.deleted(), 1);
+        assert!(!path.with_extension("compacting.log").exists());
+    }
 }
@@ -0,0 +1,79 @@
+//! Per-archive write options.
+
+use crate::{Checksum, Compression, DurabilityPolicy};
+
+/// How `MmapArchive` (and `Archive`'s writer) compresses, checksums and syncs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveOptions {
+    compression: Compression,
+    block_messages: usize,
+    durability: DurabilityPolicy,
+    background_flush: bool,
+    checksum: Checksum,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        Self {
+            compression: Compression::default(),
+            block_messages: 1,
+            durability: DurabilityPolicy::default(),
+            background_flush: false,
+            checksum: Checksum::default(),
+        }
+    }
+}
+
+impl ArchiveOptions {
+    /// Codec for `append_compressed`
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Compress every `messages` messages into one frame (default 1: per message)
+    pub fn with_block_messages(mut self, messages: usize) -> Self {
+        self.block_messages = messages.clamp(1, u16::MAX as usize);
+        self
+    }
+
+    /// When appends are synced to disk (default `Never`: left to the OS)
+    pub fn with_durability(mut self, policy: DurabilityPolicy) -> Self {
+        self.durability = policy;
+        self
+    }
+
+    /// Run `EveryNMessages` / `EveryDuration` syncs on a background thread
+    /// instead of the appending one (`Always` stays inline)
+    pub fn with_background_flush(mut self, enable: bool) -> Self {
+        self.background_flush = enable;
+        self
+    }
+
+    /// Frame checksum (default CRC32). Recorded in the log header, so
+    /// readers pick it up; an archive that already has messages keeps its own.
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    pub fn block_messages(&self) -> usize {
+        self.block_messages
+    }
+
+    pub fn durability(&self) -> DurabilityPolicy {
+        self.durability
+    }
+
+    pub fn background_flush(&self) -> bool {
+        self.background_flush
+    }
+
+    pub fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+}
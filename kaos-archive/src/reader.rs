@@ -0,0 +1,457 @@
+//! Read-only view of an archive that is still being written.
+//!
+//! The writer (`MmapArchive`, or `Archive`'s background thread) publishes
+//! `write_pos` / `msg_count` in the log header with Release after each frame;
+//! `ArchiveReader` loads them with Acquire, so everything below them is fully
+//! written. Works across threads and, since both sides map the same file,
+//! across processes.
+
+use crate::compress::{self, DecodeCache, Keyring};
+use crate::mmap_archive::{
+    header_checksum, IndexEntry, LogHeader, FRAME_COMPRESSED, FRAME_HEADER_SIZE, FRAME_LEN_MASK,
+    FRAME_SKIP, FRAME_TIMESTAMPED, HEADER_SIZE, INDEX_TIMESTAMPED, MAGIC, TIMESTAMP_SIZE,
+};
+use crate::ArchiveError;
+use kaos::checksum::Checksum;
+use memmap2::Mmap;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default sleep between polls while a `Tail` waits for the writer
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Concurrent reader for an archive another thread or process appends to.
+pub struct ArchiveReader {
+    log_mmap: Mmap,
+    index_mmap: Mmap,
+    decoded: DecodeCache,
+    /// Frame checksum, from the header
+    checksum: Checksum,
+    /// Opens sealed frames (`with_keyring`)
+    pub(crate) keyring: Option<Keyring>,
+    /// Byte offset of the next frame `poll` hands out
+    cursor: usize,
+    /// Sequence of that frame
+    next_seq: u64,
+}
+
+impl ArchiveReader {
+    /// Open the archive at `base_path` (same base as `MmapArchive::create`)
+    pub fn open<P: AsRef<Path>>(base_path: P) -> Result<Self, ArchiveError> {
+        let base = base_path.as_ref();
+        let log_file = File::open(base.with_extension("log"))?;
+        let index_file = File::open(base.with_extension("idx"))?;
+        // Safety: read-only mappings; the writer only appends past the published position
+        let log_mmap = unsafe { Mmap::map(&log_file)? };
+        let index_mmap = unsafe { Mmap::map(&index_file)? };
+        if log_mmap.len() < HEADER_SIZE
+            || u64::from_ne_bytes(log_mmap[..8].try_into().unwrap()) != MAGIC
+        {
+            return Err(ArchiveError::InvalidMagic);
+        }
+        let header = unsafe { &*(log_mmap.as_ptr() as *const LogHeader) };
+        let checksum = header_checksum(header.checksum)?;
+        Ok(Self {
+            log_mmap,
+            index_mmap,
+            decoded: DecodeCache::default(),
+            checksum,
+            keyring: None,
+            cursor: HEADER_SIZE,
+            next_seq: 0,
+        })
+    }
+
+    /// Keys for sealed frames (see `MmapArchive::with_keyring`); without
+    /// them those frames fail to read and `poll` skips them
+    #[cfg(feature = "encryption")]
+    pub fn with_keyring(mut self, keyring: Keyring) -> Self {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    fn header(&self) -> &LogHeader {
+        unsafe { &*(self.log_mmap.as_ptr() as *const LogHeader) }
+    }
+
+    /// Messages published so far
+    pub fn len(&self) -> u64 {
+        self.header().msg_count.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entry(&self, seq: u64) -> Result<&IndexEntry, ArchiveError> {
+        let at = (seq as usize) * 16;
+        if seq >= self.len() || at + 16 > self.index_mmap.len() {
+            return Err(ArchiveError::InvalidSequence(seq));
+        }
+        let entry = unsafe { &*(self.index_mmap.as_ptr().add(at) as *const IndexEntry) };
+        // Offset 0 = appended without index
+        if entry.offset == 0 {
+            return Err(ArchiveError::InvalidSequence(seq));
+        }
+        Ok(entry)
+    }
+
+    /// Read with checksum verification (indexed messages only).
+    pub fn read(&self, seq: u64) -> Result<&[u8], ArchiveError> {
+        self.entry(seq)?.message(
+            &self.log_mmap,
+            &self.decoded,
+            self.checksum,
+            self.keyring.as_ref(),
+            self.header().position(seq),
+            true,
+        )
+    }
+
+    /// Read without checksum verification (faster).
+    pub fn read_no_verify(&self, seq: u64) -> Result<&[u8], ArchiveError> {
+        self.entry(seq)?.message(
+            &self.log_mmap,
+            &self.decoded,
+            self.checksum,
+            self.keyring.as_ref(),
+            self.header().position(seq),
+            false,
+        )
+    }
+
+    /// Drop blocks `read` decompressed
+    pub fn clear_decoded(&mut self) {
+        self.decoded.clear();
+    }
+
+    /// Timestamp of `seq`, if it was written with `append_timestamped`
+    pub fn timestamp(&self, seq: u64) -> Option<u64> {
+        let entry = self.entry(seq).ok()?;
+        if entry.flags & INDEX_TIMESTAMPED == 0 {
+            return None;
+        }
+        let at = entry.offset as usize + FRAME_HEADER_SIZE;
+        Some(u64::from_ne_bytes(
+            self.log_mmap[at..at + TIMESTAMP_SIZE].try_into().unwrap(),
+        ))
+    }
+
+    /// Replay messages in range [from, to) published so far, skipping
+    /// unindexed and compacted-away ones. Returns number of messages replayed.
+    pub fn replay<F>(&self, from: u64, to: u64, mut handler: F) -> Result<u64, ArchiveError>
+    where
+        F: FnMut(u64, &[u8]),
+    {
+        let end = to.min(self.len());
+        let mut replayed = 0;
+        for seq in from..end {
+            match self.read(seq) {
+                Ok(data) => handler(seq, data),
+                Err(ArchiveError::InvalidSequence(_)) => continue,
+                Err(e) => return Err(e),
+            }
+            replayed += 1;
+        }
+        Ok(replayed)
+    }
+
+    /// Hand every message published since the last poll to `handler`, in
+    /// order (tail-follow). Scans frames, so unindexed appends are included;
+    /// compressed blocks that fail their checksum are skipped.
+    /// Returns number of messages handled.
+    pub fn poll<F>(&mut self, mut handler: F) -> u64
+    where
+        F: FnMut(u64, &[u8]),
+    {
+        let limit =
+            (self.header().write_pos.load(Ordering::Acquire) as usize).min(self.log_mmap.len());
+        let start = self.next_seq;
+        let mut skipped = 0;
+        while self.cursor + FRAME_HEADER_SIZE <= limit {
+            let pos = self.cursor;
+            let word = u32::from_ne_bytes(self.log_mmap[pos..pos + 4].try_into().unwrap());
+            if word & FRAME_SKIP != 0 {
+                // Messages `compact` dropped
+                let count = u32::from_ne_bytes(self.log_mmap[pos + 4..pos + 8].try_into().unwrap());
+                skipped += count as u64;
+                self.next_seq += count as u64;
+                self.cursor = pos + FRAME_HEADER_SIZE;
+                continue;
+            }
+            let mut data = pos + FRAME_HEADER_SIZE;
+            if word & FRAME_TIMESTAMPED != 0 {
+                data += TIMESTAMP_SIZE;
+            }
+            let end = data + (word & FRAME_LEN_MASK) as usize;
+            if end > limit {
+                break;
+            }
+            let stored = &self.log_mmap[data..end];
+            if word & FRAME_COMPRESSED == 0 {
+                handler(self.next_seq, stored);
+                self.next_seq += 1;
+            } else {
+                let count = compress::message_count(stored);
+                let checksum =
+                    u32::from_ne_bytes(self.log_mmap[pos + 4..pos + 8].try_into().unwrap());
+                let at = self.header().position(self.next_seq);
+                let raw = (self.checksum.compute(stored) == checksum)
+                    .then(|| compress::decode(stored, self.keyring.as_ref(), at).ok())
+                    .flatten();
+                if let Some(raw) = raw {
+                    for (i, msg) in compress::messages(&raw, count).enumerate() {
+                        handler(self.next_seq + i as u64, msg);
+                    }
+                }
+                self.next_seq += count as u64;
+            }
+            self.cursor = end;
+        }
+        self.next_seq - start - skipped
+    }
+
+    /// Position `poll` at `seq`, or at an earlier message when `seq` sits
+    /// inside a compressed block or past the published end
+    fn seek(&mut self, seq: u64) {
+        let len = self.len();
+        let at = seq.min(len.saturating_sub(1));
+        (self.cursor, self.next_seq) = match self.entry(at) {
+            Ok(entry) => (
+                entry.offset as usize,
+                at - entry.block_index().unwrap_or(0) as u64,
+            ),
+            // Empty, or appended without index: scan from the start
+            Err(_) => (HEADER_SIZE, 0),
+        };
+    }
+
+    /// Follow the archive from `from_seq` on, like `tail -f`
+    pub fn tail(mut self, from_seq: u64) -> Tail {
+        self.seek(from_seq);
+        Tail {
+            reader: self,
+            from: from_seq,
+            buffered: VecDeque::new(),
+            poll_interval: TAIL_POLL_INTERVAL,
+        }
+    }
+}
+
+/// Live stream of an archive's messages from a given sequence on.
+///
+/// `poll` hands out what the writer published so far; `next_timeout` and
+/// the `Iterator` impl block until the next message (the iterator never
+/// ends; the writer may always append more).
+pub struct Tail {
+    reader: ArchiveReader,
+    /// First sequence to hand out
+    from: u64,
+    /// Polled but not yet returned by `next`
+    buffered: VecDeque<(u64, Vec<u8>)>,
+    poll_interval: Duration,
+}
+
+impl Tail {
+    /// Sleep between polls while waiting (default 1ms)
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Next sequence this tail will hand out
+    pub fn position(&self) -> u64 {
+        self.buffered
+            .front()
+            .map_or(self.from.max(self.reader.next_seq), |(seq, _)| *seq)
+    }
+
+    /// Hand every message published since the last call to `handler`,
+    /// without blocking. Returns number of messages handled.
+    pub fn poll<F>(&mut self, mut handler: F) -> u64
+    where
+        F: FnMut(u64, &[u8]),
+    {
+        let mut count = 0;
+        for (seq, data) in self.buffered.drain(..) {
+            handler(seq, &data);
+            count += 1;
+        }
+        let from = self.from;
+        self.reader.poll(|seq, data| {
+            if seq >= from {
+                handler(seq, data);
+                count += 1;
+            }
+        });
+        count
+    }
+
+    /// Wait up to `timeout` for the next message
+    pub fn next_timeout(&mut self, timeout: Duration) -> Option<(u64, Vec<u8>)> {
+        let start = Instant::now();
+        loop {
+            if let Some(next) = self.buffered.pop_front() {
+                return Some(next);
+            }
+            let (from, buffered) = (self.from, &mut self.buffered);
+            self.reader.poll(|seq, data| {
+                if seq >= from {
+                    buffered.push_back((seq, data.to_vec()));
+                }
+            });
+            if !self.buffered.is_empty() {
+                continue;
+            }
+            if start.elapsed() >= timeout {
+                return None;
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+impl Iterator for Tail {
+    type Item = (u64, Vec<u8>);
+
+    /// Blocks until the writer appends the next message
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(next) = self.next_timeout(Duration::from_secs(3600)) {
+                return Some(next);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Archive, MmapArchive};
+    use std::thread;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_reader_follows_writer_thread() {
+        const N: u64 = 20_000;
+        let dir = tempdir().unwrap();
+        let mut archive = MmapArchive::create(dir.path().join("live"), 4 * 1024 * 1024).unwrap();
+        let mut reader = archive.reader().unwrap();
+
+        let writer = thread::spawn(move || {
+            for i in 0..N {
+                archive.append(&i.to_le_bytes()).unwrap();
+            }
+            archive
+        });
+
+        let mut expected = 0;
+        while expected < N {
+            reader.poll(|seq, data| {
+                assert_eq!(seq, expected);
+                assert_eq!(data, expected.to_le_bytes());
+                expected += 1;
+            });
+            let len = reader.len();
+            if len > 0 {
+                assert_eq!(reader.read(len - 1).unwrap(), (len - 1).to_le_bytes());
+            }
+        }
+        let archive = writer.join().unwrap();
+        assert_eq!(reader.len(), archive.len());
+        assert_eq!(reader.replay(10, 20, |_, _| {}).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_reader_on_background_archive() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bg");
+        let mut archive = Archive::create(&path, 1024 * 1024).unwrap();
+        let mut reader = ArchiveReader::open(&path).unwrap();
+        for i in 0..500u64 {
+            archive.append(&i.to_le_bytes()).unwrap();
+        }
+        archive.flush();
+
+        let mut sum = 0;
+        assert_eq!(
+            reader.poll(|_, data| sum += u64::from_le_bytes(data.try_into().unwrap())),
+            500
+        );
+        assert_eq!(sum, (0..500).sum::<u64>());
+        assert_eq!(reader.poll(|_, _| {}), 0);
+    }
+
+    #[test]
+    fn test_tail_follows_from_seq() {
+        const N: u64 = 5_000;
+        let dir = tempdir().unwrap();
+        let mut archive = MmapArchive::create(dir.path().join("tail"), 1024 * 1024).unwrap();
+        for i in 0..100u64 {
+            archive.append(&i.to_le_bytes()).unwrap();
+        }
+        let tail = archive.reader().unwrap().tail(40);
+        let writer = thread::spawn(move || {
+            for i in 100..N {
+                archive.append(&i.to_le_bytes()).unwrap();
+            }
+        });
+
+        let mut expected = 40;
+        for (seq, data) in tail.take((N - 40) as usize) {
+            assert_eq!(seq, expected);
+            assert_eq!(data, expected.to_le_bytes());
+            expected += 1;
+        }
+        assert_eq!(expected, N);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_tail_inside_block_and_past_end() {
+        use crate::ArchiveOptions;
+        let dir = tempdir().unwrap();
+        let mut archive = MmapArchive::create(dir.path().join("blocks"), 64 * 1024)
+            .unwrap()
+            .with_options(ArchiveOptions::default().with_block_messages(8));
+        for i in 0..20u64 {
+            archive.append_compressed(&i.to_le_bytes()).unwrap();
+        }
+        archive.flush_block().unwrap();
+
+        let mut tail = archive.reader().unwrap().tail(11);
+        let mut seen = Vec::new();
+        assert_eq!(tail.poll(|seq, _| seen.push(seq)), 9);
+        assert_eq!(seen, (11..20).collect::<Vec<_>>());
+        assert_eq!(tail.position(), 20);
+
+        let mut ahead = archive.reader().unwrap().tail(25);
+        assert_eq!(ahead.next_timeout(Duration::from_millis(5)), None);
+        for i in 20..30u64 {
+            archive.append(&i.to_le_bytes()).unwrap();
+        }
+        assert_eq!(
+            ahead.next_timeout(Duration::from_millis(5)),
+            Some((25, 25u64.to_le_bytes().to_vec()))
+        );
+        assert_eq!(ahead.poll(|_, _| {}), 4);
+    }
+
+    #[test]
+    fn test_reader_sees_timestamps() {
+        let dir = tempdir().unwrap();
+        let mut archive = MmapArchive::create(dir.path().join("ts"), 64 * 1024).unwrap();
+        archive.append_timestamped(b"tick", 7).unwrap();
+        let reader = archive.reader().unwrap();
+        assert_eq!(reader.timestamp(0), Some(7));
+        assert_eq!(reader.read(0).unwrap(), b"tick");
+        assert!(matches!(
+            reader.read(1),
+            Err(ArchiveError::InvalidSequence(1))
+        ));
+    }
+}
@@ -0,0 +1,504 @@
+//! Archive split into numbered segments with rollover and retention.
+//!
+//! `SegmentedArchive` keeps `archive.0001.log`, `archive.0002.log`, ... (each
+//! an `MmapArchive` with its `.idx`) in one directory and starts a new segment
+//! when the active one fills. Sequences run across segments, so `read()` and
+//! `replay()` don't care where a message landed. A `RetentionPolicy` deletes
+//! the oldest sealed segments by count, total size or age.
+
+use crate::{ArchiveError, CompactReport, MmapArchive};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const SEGMENT_PREFIX: &str = "archive";
+
+/// Which sealed segments a `SegmentedArchive` deletes. The active segment is
+/// always kept. Default: keep everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    max_segments: Option<usize>,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Keep at most `count` segments (including the active one)
+    pub fn with_max_segments(mut self, count: usize) -> Self {
+        self.max_segments = Some(count);
+        self
+    }
+
+    /// Keep at most `bytes` on disk (log + index files)
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Delete segments sealed longer than `age` ago
+    pub fn with_max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+}
+
+struct Segment {
+    path: PathBuf,
+    archive: MmapArchive,
+    first_seq: u64,
+    /// Log + index file sizes
+    bytes: u64,
+    /// None while active
+    sealed_at: Option<SystemTime>,
+}
+
+impl Segment {
+    fn create(dir: &Path, id: u32, capacity: usize, first_seq: u64) -> Result<Self, ArchiveError> {
+        let path = segment_path(dir, id);
+        let mut archive = MmapArchive::create(&path, capacity)?;
+        archive.set_base_seq(first_seq);
+        Ok(Self {
+            bytes: file_bytes(&path)?,
+            path,
+            archive,
+            first_seq,
+            sealed_at: None,
+        })
+    }
+
+    fn open(path: PathBuf) -> Result<Self, ArchiveError> {
+        let archive = MmapArchive::open(&path)?;
+        Ok(Self {
+            bytes: file_bytes(&path)?,
+            first_seq: archive.base_seq(),
+            sealed_at: Some(fs::metadata(&path)?.modified()?),
+            path,
+            archive,
+        })
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.first_seq + self.archive.len()
+    }
+
+    fn remove(self) -> Result<(), ArchiveError> {
+        let Self { path, archive, .. } = self;
+        drop(archive);
+        fs::remove_file(&path)?;
+        fs::remove_file(path.with_extension("idx"))?;
+        for ext in ["tix", "del"] {
+            match fs::remove_file(path.with_extension(ext)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn compact(&mut self) -> Result<CompactReport, ArchiveError> {
+        let report = self.archive.compact()?;
+        // The rewritten log would look freshly sealed to `max_age`
+        if let (Some(at), true) = (self.sealed_at, report.removed > 0) {
+            fs::File::options()
+                .write(true)
+                .open(&self.path)?
+                .set_modified(at)?;
+        }
+        Ok(report)
+    }
+}
+
+/// `MmapArchive` that rolls over to a new segment instead of failing with `Full`.
+pub struct SegmentedArchive {
+    dir: PathBuf,
+    segment_capacity: usize,
+    retention: RetentionPolicy,
+    /// Oldest first; the last one is active
+    segments: Vec<Segment>,
+}
+
+impl SegmentedArchive {
+    /// Open the segments in `dir` (created if missing), continuing after the
+    /// last one. New segments are `segment_capacity` bytes.
+    pub fn open<P: AsRef<Path>>(dir: P, segment_capacity: usize) -> Result<Self, ArchiveError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut ids: Vec<u32> = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| segment_id(&e.file_name().to_string_lossy()))
+            .collect();
+        ids.sort_unstable();
+
+        let mut segments = ids
+            .into_iter()
+            .map(|id| Segment::open(segment_path(&dir, id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        match segments.last_mut() {
+            Some(active) => active.sealed_at = None,
+            None => segments.push(Segment::create(&dir, 1, segment_capacity, 0)?),
+        }
+
+        Ok(Self {
+            dir,
+            segment_capacity,
+            retention: RetentionPolicy::default(),
+            segments,
+        })
+    }
+
+    /// Applied on every rollover and by `apply_retention`
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = policy;
+        self
+    }
+
+    /// Append with CRC32, rolling over when the active segment is full.
+    /// `Full` only if the message doesn't fit an empty segment.
+    pub fn append(&mut self, data: &[u8]) -> Result<u64, ArchiveError> {
+        self.make_room(data.len(), false)?;
+        let active = self.active_mut();
+        Ok(active.first_seq + active.archive.append(data)?)
+    }
+
+    /// `append` with a timestamp for `replay_between`
+    pub fn append_timestamped(&mut self, data: &[u8], timestamp: u64) -> Result<u64, ArchiveError> {
+        self.make_room(data.len(), true)?;
+        let active = self.active_mut();
+        Ok(active.first_seq + active.archive.append_timestamped(data, timestamp)?)
+    }
+
+    fn make_room(&mut self, len: usize, timestamped: bool) -> Result<(), ArchiveError> {
+        if !self.active().archive.has_room(len, timestamped) {
+            if self.active().archive.is_empty() {
+                return Err(ArchiveError::Full);
+            }
+            self.roll()?;
+            if !self.active().archive.has_room(len, timestamped) {
+                return Err(ArchiveError::Full);
+            }
+        }
+        Ok(())
+    }
+
+    /// Seal the active segment and start a new one
+    pub fn roll(&mut self) -> Result<(), ArchiveError> {
+        let active = self.active_mut();
+        active.archive.seal()?;
+        active.sealed_at = Some(SystemTime::now());
+        let id = segment_id(&active.path.file_name().unwrap().to_string_lossy()).unwrap_or(0) + 1;
+        let next_seq = active.next_seq();
+
+        let segment = Segment::create(&self.dir, id, self.segment_capacity, next_seq)?;
+        self.segments.push(segment);
+        self.apply_retention()?;
+        Ok(())
+    }
+
+    /// Delete sealed segments the policy no longer keeps (returns how many)
+    pub fn apply_retention(&mut self) -> Result<usize, ArchiveError> {
+        let policy = self.retention;
+        let now = SystemTime::now();
+        let mut removed = 0;
+        while self.segments.len() > 1 {
+            let total: u64 = self.segments.iter().map(|s| s.bytes).sum();
+            let expired = match (policy.max_age, self.segments[0].sealed_at) {
+                (Some(age), Some(at)) => now.duration_since(at).is_ok_and(|d| d > age),
+                _ => false,
+            };
+            let over = policy.max_segments.is_some_and(|m| self.segments.len() > m)
+                || policy.max_bytes.is_some_and(|m| total > m);
+            if !over && !expired {
+                break;
+            }
+            self.segments.remove(0).remove()?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    fn segment_index(&self, seq: u64) -> Result<usize, ArchiveError> {
+        self.segments
+            .partition_point(|s| s.first_seq <= seq)
+            .checked_sub(1)
+            .ok_or(ArchiveError::InvalidSequence(seq))
+    }
+
+    /// Message at `seq` (CRC-verified when it has one)
+    pub fn read(&self, seq: u64) -> Result<&[u8], ArchiveError> {
+        let segment = &self.segments[self.segment_index(seq)?];
+        match segment.archive.read(seq - segment.first_seq) {
+            Err(ArchiveError::InvalidSequence(_)) => Err(ArchiveError::InvalidSequence(seq)),
+            result => result,
+        }
+    }
+
+    /// Tombstone `seq` in its segment (see `MmapArchive::mark_deleted`)
+    pub fn mark_deleted(&mut self, seq: u64) -> Result<(), ArchiveError> {
+        let idx = self.segment_index(seq)?;
+        let segment = &mut self.segments[idx];
+        match segment.archive.mark_deleted(seq - segment.first_seq) {
+            Err(ArchiveError::InvalidSequence(_)) => Err(ArchiveError::InvalidSequence(seq)),
+            result => result,
+        }
+    }
+
+    /// Compact every segment with tombstones (see `MmapArchive::compact`)
+    pub fn compact(&mut self) -> Result<CompactReport, ArchiveError> {
+        let mut total = CompactReport::default();
+        for segment in &mut self.segments {
+            let report = segment.compact()?;
+            total.removed += report.removed;
+            total.reclaimed_bytes += report.reclaimed_bytes;
+        }
+        Ok(total)
+    }
+
+    /// Replay messages in range [from, to) across segments; sequences already
+    /// deleted by retention or `mark_deleted` are skipped. Returns number of
+    /// messages replayed.
+    pub fn replay<F>(&self, from: u64, to: u64, mut handler: F) -> Result<u64, ArchiveError>
+    where
+        F: FnMut(u64, &[u8]),
+    {
+        let from = from.max(self.first_seq());
+        let end = to.min(self.next_seq());
+        let mut replayed = 0;
+        for seq in from..end {
+            match self.read(seq) {
+                Ok(data) => handler(seq, data),
+                Err(ArchiveError::InvalidSequence(_)) => continue,
+                Err(e) => return Err(e),
+            }
+            replayed += 1;
+        }
+        Ok(replayed)
+    }
+
+    /// Replay timestamped messages with `from <= timestamp < to` across
+    /// segments. Returns number of messages replayed.
+    pub fn replay_between<F>(&self, from: u64, to: u64, mut handler: F) -> Result<u64, ArchiveError>
+    where
+        F: FnMut(u64, &[u8]),
+    {
+        let mut count = 0;
+        for segment in &self.segments {
+            count += segment
+                .archive
+                .replay_between(from, to, |seq, data| handler(segment.first_seq + seq, data))?;
+        }
+        Ok(count)
+    }
+
+    /// Oldest sequence still on disk
+    pub fn first_seq(&self) -> u64 {
+        self.segments[0].first_seq
+    }
+
+    /// Sequence the next append gets
+    pub fn next_seq(&self) -> u64 {
+        self.active().next_seq()
+    }
+
+    /// Messages still on disk
+    pub fn len(&self) -> u64 {
+        self.next_seq() - self.first_seq()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Log file of each segment still on disk, oldest first
+    pub fn segment_paths(&self) -> Vec<PathBuf> {
+        self.segments.iter().map(|s| s.path.clone()).collect()
+    }
+
+    pub fn flush(&self) -> Result<(), ArchiveError> {
+        self.active().archive.flush()
+    }
+
+    fn active(&self) -> &Segment {
+        self.segments.last().unwrap()
+    }
+
+    fn active_mut(&mut self) -> &mut Segment {
+        self.segments.last_mut().unwrap()
+    }
+}
+
+fn segment_path(dir: &Path, id: u32) -> PathBuf {
+    // MmapArchive swaps the extension for .log / .idx
+    dir.join(format!("{}.{:04}.log", SEGMENT_PREFIX, id))
+}
+
+/// `archive.0001.log` -> 1
+fn segment_id(name: &str) -> Option<u32> {
+    let id = name
+        .strip_prefix(SEGMENT_PREFIX)?
+        .strip_prefix('.')?
+        .strip_suffix(".log")?;
+    id.bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| id.parse().ok())
+        .flatten()
+}
+
+fn file_bytes(log: &Path) -> Result<u64, ArchiveError> {
+    Ok(fs::metadata(log)?.len() + fs::metadata(log.with_extension("idx"))?.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArchiveSet;
+    use tempfile::tempdir;
+
+    const SEGMENT: usize = 4096;
+
+    fn fill(archive: &mut SegmentedArchive, from: u64, to: u64) {
+        for i in from..to {
+            let seq = archive.append(format!("msg-{:04}", i).as_bytes()).unwrap();
+            assert_eq!(seq, i);
+        }
+    }
+
+    #[test]
+    fn test_rollover_reads_across_segments() {
+        let dir = tempdir().unwrap();
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT).unwrap();
+        fill(&mut archive, 0, 1000);
+
+        let paths = archive.segment_paths();
+        assert!(paths.len() > 1);
+        assert!(paths[0].ends_with("archive.0001.log"));
+        assert_eq!(archive.read(0).unwrap(), b"msg-0000");
+        assert_eq!(archive.read(999).unwrap(), b"msg-0999");
+        assert!(matches!(
+            archive.read(1000),
+            Err(ArchiveError::InvalidSequence(1000))
+        ));
+
+        let mut seen = 0;
+        let replayed = archive
+            .replay(100, 900, |seq, data| {
+                assert_eq!(data, format!("msg-{:04}", seq).as_bytes());
+                seen += 1;
+            })
+            .unwrap();
+        assert_eq!((replayed, seen), (800, 800));
+
+        // The directory reads as one log too
+        drop(archive);
+        assert_eq!(ArchiveSet::open(dir.path()).unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn test_compact_segments() {
+        let dir = tempdir().unwrap();
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT).unwrap();
+        fill(&mut archive, 0, 1000);
+        assert!(archive.segment_paths().len() > 2);
+        for seq in (0..1000).step_by(3) {
+            archive.mark_deleted(seq).unwrap();
+        }
+        assert!(archive.mark_deleted(1000).is_err());
+        let report = archive.compact().unwrap();
+        assert_eq!(report.removed, 334);
+        assert!(report.reclaimed_bytes > 0);
+
+        assert!(archive.read(999).is_err());
+        assert_eq!(archive.read(998).unwrap(), b"msg-0998");
+        assert_eq!(archive.replay(0, 1000, |_, _| {}).unwrap(), 666);
+        drop(archive);
+        let set = ArchiveSet::open(dir.path()).unwrap();
+        assert_eq!(set.len(), 1000);
+        assert_eq!(set.iter().count(), 666);
+    }
+
+    #[test]
+    fn test_retention_keeps_sequences() {
+        let dir = tempdir().unwrap();
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT)
+            .unwrap()
+            .with_retention(RetentionPolicy::default().with_max_segments(2));
+        fill(&mut archive, 0, 1000);
+
+        assert_eq!(archive.segment_paths().len(), 2);
+        let first = archive.first_seq();
+        assert!(first > 0);
+        assert!(matches!(
+            archive.read(first - 1),
+            Err(ArchiveError::InvalidSequence(_))
+        ));
+        assert_eq!(
+            archive.read(first).unwrap(),
+            format!("msg-{:04}", first).as_bytes()
+        );
+        assert_eq!(archive.replay(0, 1000, |_, _| {}).unwrap(), 1000 - first);
+
+        // Reopen continues the numbering
+        drop(archive);
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT).unwrap();
+        assert_eq!((archive.first_seq(), archive.next_seq()), (first, 1000));
+        fill(&mut archive, 1000, 1010);
+        assert_eq!(archive.read(1005).unwrap(), b"msg-1005");
+    }
+
+    #[test]
+    fn test_retention_by_age_and_size() {
+        let dir = tempdir().unwrap();
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT).unwrap();
+        fill(&mut archive, 0, 10);
+        archive.roll().unwrap();
+        fill(&mut archive, 10, 20);
+        archive.roll().unwrap();
+        assert_eq!(archive.segment_paths().len(), 3);
+
+        // Size: one segment's worth of bytes keeps only the active one
+        let mut sized = archive.with_retention(RetentionPolicy::default().with_max_bytes(1));
+        assert_eq!(sized.apply_retention().unwrap(), 2);
+        assert_eq!(sized.first_seq(), 20);
+        assert!(!dir.path().join("archive.0001.idx").exists());
+
+        let mut aged =
+            sized.with_retention(RetentionPolicy::default().with_max_age(Duration::from_millis(1)));
+        aged.roll().unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(aged.apply_retention().unwrap(), 1);
+        assert_eq!(aged.segment_paths().len(), 1);
+    }
+
+    #[test]
+    fn test_replay_between_across_segments() {
+        let dir = tempdir().unwrap();
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT).unwrap();
+        for i in 0..1000u64 {
+            archive
+                .append_timestamped(format!("msg-{:04}", i).as_bytes(), 1_000 + i)
+                .unwrap();
+        }
+        assert!(archive.segment_paths().len() > 1);
+
+        let mut seqs = Vec::new();
+        let count = archive
+            .replay_between(1_100, 1_400, |seq, data| {
+                assert_eq!(data, format!("msg-{:04}", seq).as_bytes());
+                seqs.push(seq);
+            })
+            .unwrap();
+        assert_eq!(count, 300);
+        assert_eq!(seqs, (100..400).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_oversized_message_is_full() {
+        let dir = tempdir().unwrap();
+        let mut archive = SegmentedArchive::open(dir.path(), SEGMENT).unwrap();
+        assert!(matches!(
+            archive.append(&[0u8; SEGMENT]),
+            Err(ArchiveError::Full)
+        ));
+        assert_eq!(archive.append(b"fits").unwrap(), 0);
+    }
+}
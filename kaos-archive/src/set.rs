@@ -0,0 +1,512 @@
+//! Read-only view over a directory of archive segments as one logical log.
+//!
+//! Segments are `MmapArchive`/`Archive` log files (`*.log`), ordered by file
+//! name (use zero-padded names when rotating, e.g. `seg-000001`). Frames are
+//! scanned from the log itself, so logs written without an index (`Archive`,
+//! `append_no_index`) are readable too.
+
+use crate::compress::{self, FramePosition, Keyring};
+use crate::mmap_archive::{
+    header_checksum, FRAME_COMPRESSED, FRAME_HEADER_SIZE, FRAME_LEN_MASK, FRAME_SKIP,
+    FRAME_TIMESTAMPED, HEADER_SIZE, LOG_BOUND, MAGIC, TIMESTAMP_SIZE,
+};
+use crate::ArchiveError;
+use kaos::checksum::Checksum;
+use memmap2::Mmap;
+use std::fs::File;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Header field offsets (see `LogHeader`)
+const CHECKSUM_OFFSET: usize = 12;
+const WRITE_POS_OFFSET: usize = 16;
+const FLAGS_OFFSET: usize = 40;
+const ARCHIVE_ID_OFFSET: usize = 56;
+
+/// One message in the set
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    /// Sequence across the whole set
+    pub seq: u64,
+    /// Segment index (into `ArchiveSet::segments()`)
+    pub segment: usize,
+    pub data: &'a [u8],
+    /// Stored checksum (0 = written without one). For messages from compressed
+    /// frames, the checksum of the decompressed message if its frame was intact.
+    pub checksum: u32,
+    /// Algorithm of `checksum`, from the segment's header
+    pub algorithm: Checksum,
+    /// Set when written with `append_timestamped`
+    pub timestamp: Option<u64>,
+}
+
+impl Record<'_> {
+    /// Checksum check: `None` if written without one
+    pub fn verify(&self) -> Option<bool> {
+        (self.checksum != 0).then(|| self.algorithm.compute(self.data) == self.checksum)
+    }
+}
+
+/// Segment summary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    /// Log file path
+    pub path: PathBuf,
+    /// Sequence of the segment's first message in the set
+    pub first_seq: u64,
+    /// Messages in the segment
+    pub count: u64,
+    /// Bytes used (header + frames)
+    pub bytes: u64,
+    /// Log file size
+    pub capacity: u64,
+}
+
+/// `ArchiveSet::verify` result
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Messages with a checksum that matched
+    pub ok: u64,
+    /// Messages written without a checksum
+    pub unchecked: u64,
+    /// Sequences whose checksum didn't match
+    pub corrupted: Vec<u64>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.corrupted.is_empty()
+    }
+}
+
+/// Where a message's bytes are
+enum Loc {
+    /// Frame offset in the log
+    Frame(usize),
+    /// Range in `Segment::decoded` and the checksum `Record::verify` checks
+    Decoded(Range<usize>, u32),
+    /// Dropped by `MmapArchive::compact`
+    Deleted,
+}
+
+struct Segment {
+    path: PathBuf,
+    mmap: Mmap,
+    checksum: Checksum,
+    first_seq: u64,
+    /// One per message
+    messages: Vec<Loc>,
+    /// Contents of compressed frames
+    decoded: Vec<u8>,
+    end: usize,
+}
+
+impl Segment {
+    fn open(path: PathBuf, first_seq: u64, keys: Option<&Keyring>) -> Result<Self, ArchiveError> {
+        let file = File::open(&path)?;
+        // Safety: read-only mapping; concurrent writers only append past `end`
+        let mmap = unsafe { Mmap::map(&file)? };
+        if mmap.len() < HEADER_SIZE || u64::from_ne_bytes(mmap[..8].try_into().unwrap()) != MAGIC {
+            return Err(ArchiveError::InvalidMagic);
+        }
+        let checksum = header_checksum(u32::from_ne_bytes(
+            mmap[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4]
+                .try_into()
+                .unwrap(),
+        ))?;
+        let write_pos = u64::from_ne_bytes(
+            mmap[WRITE_POS_OFFSET..WRITE_POS_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let limit = write_pos.min(mmap.len());
+        let flags = u32::from_ne_bytes(mmap[FLAGS_OFFSET..FLAGS_OFFSET + 4].try_into().unwrap());
+        let archive_id = (flags & LOG_BOUND != 0).then(|| {
+            u64::from_ne_bytes(
+                mmap[ARCHIVE_ID_OFFSET..ARCHIVE_ID_OFFSET + 8]
+                    .try_into()
+                    .unwrap(),
+            )
+        });
+
+        // Walk frames up to write_pos; a frame running past it is a torn tail
+        let mut messages = Vec::new();
+        let mut decoded = Vec::new();
+        let mut pos = HEADER_SIZE;
+        while pos + FRAME_HEADER_SIZE <= limit {
+            let word = u32::from_ne_bytes(mmap[pos..pos + 4].try_into().unwrap());
+            if word & FRAME_SKIP != 0 {
+                let count = u32::from_ne_bytes(mmap[pos + 4..pos + 8].try_into().unwrap());
+                messages.extend((0..count).map(|_| Loc::Deleted));
+                pos += FRAME_HEADER_SIZE;
+                continue;
+            }
+            let mut next = pos + FRAME_HEADER_SIZE + (word & FRAME_LEN_MASK) as usize;
+            if word & FRAME_TIMESTAMPED != 0 {
+                next += TIMESTAMP_SIZE;
+            }
+            if next > limit {
+                break;
+            }
+            if word & FRAME_COMPRESSED != 0 {
+                let stored = u32::from_ne_bytes(mmap[pos + 4..pos + 8].try_into().unwrap());
+                let at = archive_id.map(|archive_id| FramePosition {
+                    archive_id,
+                    seq: messages.len() as u64,
+                });
+                Self::unpack(
+                    &mmap[pos + FRAME_HEADER_SIZE..next],
+                    checksum,
+                    stored,
+                    keys,
+                    at,
+                    &mut messages,
+                    &mut decoded,
+                );
+            } else {
+                messages.push(Loc::Frame(pos));
+            }
+            pos = next;
+        }
+
+        Ok(Self {
+            path,
+            mmap,
+            checksum,
+            first_seq,
+            messages,
+            decoded,
+            end: pos,
+        })
+    }
+
+    /// Decode a compressed frame; if it's damaged (or sealed with a key not
+    /// in `keys`) its messages read as empty and fail `verify`
+    fn unpack(
+        stored: &[u8],
+        kind: Checksum,
+        checksum: u32,
+        keys: Option<&Keyring>,
+        at: Option<FramePosition>,
+        messages: &mut Vec<Loc>,
+        decoded: &mut Vec<u8>,
+    ) {
+        let count = compress::message_count(stored).max(1);
+        let raw = (kind.compute(stored) == checksum)
+            .then(|| compress::decode(stored, keys, at).ok())
+            .flatten();
+        let before = messages.len();
+        if let Some(raw) = raw {
+            for msg in compress::messages(&raw, count) {
+                let start = decoded.len();
+                decoded.extend_from_slice(msg);
+                messages.push(Loc::Decoded(start..decoded.len(), kind.compute(msg)));
+            }
+        }
+        // Damaged (or short) block: keep the sequence numbering intact
+        let bad = count.saturating_sub(messages.len() - before);
+        let at = decoded.len();
+        messages.extend((0..bad).map(|_| Loc::Decoded(at..at, 1)));
+    }
+
+    fn frame(&self, idx: usize) -> Option<(&[u8], u32, Option<u64>)> {
+        let pos = match &self.messages[idx] {
+            Loc::Frame(pos) => *pos,
+            Loc::Decoded(range, checksum) => {
+                return Some((&self.decoded[range.clone()], *checksum, None))
+            }
+            Loc::Deleted => return None,
+        };
+        let word = u32::from_ne_bytes(self.mmap[pos..pos + 4].try_into().unwrap());
+        let len = (word & FRAME_LEN_MASK) as usize;
+        let checksum = u32::from_ne_bytes(self.mmap[pos + 4..pos + 8].try_into().unwrap());
+        let mut start = pos + FRAME_HEADER_SIZE;
+        let mut timestamp = None;
+        if word & FRAME_TIMESTAMPED != 0 {
+            let ts = &self.mmap[start..start + TIMESTAMP_SIZE];
+            timestamp = Some(u64::from_ne_bytes(ts.try_into().unwrap()));
+            start += TIMESTAMP_SIZE;
+        }
+        Some((&self.mmap[start..start + len], checksum, timestamp))
+    }
+}
+
+/// Several archive segments read as one log.
+pub struct ArchiveSet {
+    segments: Vec<Segment>,
+    len: u64,
+}
+
+impl ArchiveSet {
+    /// Open every `*.log` segment in `path` (a directory), or the single
+    /// archive at base path `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        Self::open_with(path.as_ref(), None)
+    }
+
+    /// `open`, decrypting sealed frames with `keyring`
+    #[cfg(feature = "encryption")]
+    pub fn open_with_keyring<P: AsRef<Path>>(
+        path: P,
+        keyring: &Keyring,
+    ) -> Result<Self, ArchiveError> {
+        Self::open_with(path.as_ref(), Some(keyring))
+    }
+
+    fn open_with(path: &Path, keys: Option<&Keyring>) -> Result<Self, ArchiveError> {
+        if !path.is_dir() {
+            return Self::from_segments_with([path.with_extension("log")], keys);
+        }
+        let mut logs: Vec<PathBuf> = std::fs::read_dir(path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "log"))
+            .collect();
+        logs.sort();
+        Self::from_segments_with(logs, keys)
+    }
+
+    /// Open the given log files, in order.
+    pub fn from_segments<I, P>(logs: I) -> Result<Self, ArchiveError>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self::from_segments_with(logs, None)
+    }
+
+    fn from_segments_with<I, P>(logs: I, keys: Option<&Keyring>) -> Result<Self, ArchiveError>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let mut segments = Vec::new();
+        let mut len = 0;
+        for log in logs {
+            let segment = Segment::open(log.into(), len, keys)?;
+            len += segment.messages.len() as u64;
+            segments.push(segment);
+        }
+        Ok(Self { segments, len })
+    }
+
+    /// Messages across all segments
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn segments(&self) -> Vec<SegmentInfo> {
+        self.segments
+            .iter()
+            .map(|s| SegmentInfo {
+                path: s.path.clone(),
+                first_seq: s.first_seq,
+                count: s.messages.len() as u64,
+                bytes: s.end as u64,
+                capacity: s.mmap.len() as u64,
+            })
+            .collect()
+    }
+
+    /// Message at `seq` (no checksum check); `None` once compacted away
+    pub fn get(&self, seq: u64) -> Option<Record<'_>> {
+        if seq >= self.len {
+            return None;
+        }
+        // Last segment starting at or before seq
+        let segment = self.segments.partition_point(|s| s.first_seq <= seq) - 1;
+        let s = &self.segments[segment];
+        let (data, checksum, timestamp) = s.frame((seq - s.first_seq) as usize)?;
+        Some(Record {
+            seq,
+            segment,
+            data,
+            checksum,
+            algorithm: s.checksum,
+            timestamp,
+        })
+    }
+
+    /// Message at `seq`, verified when it has a checksum.
+    pub fn read(&self, seq: u64) -> Result<&[u8], ArchiveError> {
+        let record = self.get(seq).ok_or(ArchiveError::InvalidSequence(seq))?;
+        match record.verify() {
+            Some(false) => Err(ArchiveError::Corrupted),
+            _ => Ok(record.data),
+        }
+    }
+
+    /// Messages in `[from, to)`
+    pub fn range(&self, from: u64, to: u64) -> impl Iterator<Item = Record<'_>> {
+        (from..to.min(self.len)).filter_map(move |seq| self.get(seq))
+    }
+
+    /// All messages in order
+    pub fn iter(&self) -> impl Iterator<Item = Record<'_>> {
+        self.range(0, self.len)
+    }
+
+    /// Messages whose payload contains `needle`
+    pub fn grep<'a>(&'a self, needle: &'a [u8]) -> impl Iterator<Item = Record<'a>> {
+        self.iter()
+            .filter(move |r| needle.is_empty() || r.data.windows(needle.len()).any(|w| w == needle))
+    }
+
+    /// Check every checksum
+    pub fn verify(&self) -> VerifyReport {
+        let mut report = VerifyReport::default();
+        for record in self.iter() {
+            match record.verify() {
+                Some(true) => report.ok += 1,
+                Some(false) => report.corrupted.push(record.seq),
+                None => report.unchecked += 1,
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Archive, MmapArchive};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_segments_form_one_log() {
+        let dir = tempdir().unwrap();
+        for (i, name) in ["seg-000001", "seg-000002", "seg-000003"]
+            .iter()
+            .enumerate()
+        {
+            let mut archive = MmapArchive::create(dir.path().join(name), 64 * 1024).unwrap();
+            for j in 0..10 {
+                archive.append(format!("s{}-m{}", i, j).as_bytes()).unwrap();
+            }
+        }
+
+        let set = ArchiveSet::open(dir.path()).unwrap();
+        assert_eq!(set.len(), 30);
+        let segments = set.segments();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[1].first_seq, 10);
+        assert_eq!(segments[2].count, 10);
+
+        assert_eq!(set.read(0).unwrap(), b"s0-m0");
+        assert_eq!(set.read(10).unwrap(), b"s1-m0");
+        assert_eq!(set.read(29).unwrap(), b"s2-m9");
+        assert!(set.get(30).is_none());
+
+        let range: Vec<u64> = set.range(8, 12).map(|r| r.seq).collect();
+        assert_eq!(range, vec![8, 9, 10, 11]);
+        let hits: Vec<u64> = set.grep(b"-m7").map(|r| r.seq).collect();
+        assert_eq!(hits, vec![7, 17, 27]);
+        assert_eq!(set.verify().ok, 30);
+    }
+
+    #[test]
+    fn test_unindexed_archive_readable() {
+        // Archive's background writer doesn't write the index or CRCs
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("async");
+        {
+            let mut archive = Archive::create(&base, 1024 * 1024).unwrap();
+            for i in 0..100u64 {
+                archive.append(&i.to_le_bytes()).unwrap();
+            }
+        }
+        let set = ArchiveSet::open(&base).unwrap();
+        assert_eq!(set.len(), 100);
+        assert_eq!(set.get(42).unwrap().data, 42u64.to_le_bytes());
+        let report = set.verify();
+        assert_eq!(report.unchecked, 100);
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_verify_reports_corruption() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("bad");
+        {
+            let mut archive = MmapArchive::create(&base, 64 * 1024).unwrap();
+            for i in 0..5 {
+                archive.append(format!("payload-{}", i).as_bytes()).unwrap();
+            }
+        }
+        // Flip a payload byte of message 3
+        let log = base.with_extension("log");
+        let mut bytes = std::fs::read(&log).unwrap();
+        let set = ArchiveSet::open(&base).unwrap();
+        let Loc::Frame(pos) = set.segments[0].messages[3] else {
+            unreachable!()
+        };
+        let offset = pos + FRAME_HEADER_SIZE;
+        drop(set);
+        bytes[offset] ^= 0xff;
+        std::fs::write(&log, bytes).unwrap();
+
+        let set = ArchiveSet::open(&base).unwrap();
+        let report = set.verify();
+        assert_eq!(report.corrupted, vec![3]);
+        assert_eq!(report.ok, 4);
+        assert!(matches!(set.read(3), Err(ArchiveError::Corrupted)));
+    }
+
+    #[test]
+    fn test_timestamped_frames_readable() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("timed");
+        {
+            let mut archive = MmapArchive::create(&base, 64 * 1024).unwrap();
+            archive.append(b"plain").unwrap();
+            archive.append_timestamped(b"timed", 42).unwrap();
+            archive.append(b"after").unwrap();
+        }
+        let set = ArchiveSet::open(&base).unwrap();
+        assert_eq!(set.len(), 3);
+        let timed = set.get(1).unwrap();
+        assert_eq!((timed.data, timed.timestamp), (&b"timed"[..], Some(42)));
+        assert_eq!(set.get(2).unwrap().timestamp, None);
+        assert_eq!(set.read(2).unwrap(), b"after");
+        assert!(set.verify().is_ok());
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_compressed_blocks_readable() {
+        use crate::{ArchiveOptions, Compression};
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("packed");
+        {
+            let mut archive = MmapArchive::create(&base, 64 * 1024).unwrap().with_options(
+                ArchiveOptions::default()
+                    .with_compression(Compression::Lz4)
+                    .with_block_messages(4),
+            );
+            for i in 0..10 {
+                archive
+                    .append_compressed(format!("packed-{}", i).as_bytes())
+                    .unwrap();
+            }
+            archive.append(b"plain").unwrap();
+        }
+        let set = ArchiveSet::open(&base).unwrap();
+        assert_eq!(set.len(), 11);
+        assert_eq!(set.read(6).unwrap(), b"packed-6");
+        assert_eq!(set.read(10).unwrap(), b"plain");
+        assert_eq!(set.verify().ok, 11);
+    }
+
+    #[test]
+    fn test_invalid_segment_rejected() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("junk.log"), b"not an archive").unwrap();
+        assert!(matches!(
+            ArchiveSet::open(dir.path()),
+            Err(ArchiveError::InvalidMagic)
+        ));
+    }
+}
@@ -46,7 +46,6 @@ fn main() {
     thread::sleep(Duration::from_millis(200));
 
     // Start receiver thread
-    let r = running.clone();
     let rc = recv_count.clone();
     let receiver_handle = thread::spawn(move || {
         let mut sub = loop {
@@ -63,13 +62,13 @@ fn main() {
         let timeout = Instant::now();
 
         while local_recv < N && timeout.elapsed() < Duration::from_secs(30) {
-            if let Some(_) = sub.try_receive() {
+            if sub.try_receive().is_some() {
                 if start.is_none() {
                     start = Some(Instant::now());
                 }
                 local_recv += 1;
                 rc.store(local_recv, Ordering::Relaxed);
-                if local_recv % 100000 == 0 {
+                if local_recv.is_multiple_of(100000) {
                     println!("  recv: {}", local_recv);
                 }
             } else {
@@ -100,7 +99,7 @@ fn main() {
         if app_tx.send(local_sent).is_ok() {
             local_sent += 1;
             sent_count.store(local_sent, Ordering::Relaxed);
-            if local_sent % 100000 == 0 {
+            if local_sent.is_multiple_of(100000) {
                 println!("  sent: {}", local_sent);
             }
         } else {
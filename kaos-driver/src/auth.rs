@@ -0,0 +1,452 @@
+//! IPC endpoint protection: ring file permissions, owner checks and an
+//! optional pre-shared-key challenge before the driver forwards traffic.
+//!
+//! The challenge runs over the rings themselves. The driver publishes a
+//! random nonce as the first value on its driver -> app ring; the app must
+//! answer with `IpcKey::response(nonce)` as the first value on its
+//! app -> driver ring, before any traffic.
+//!
+//! ```text
+//! kaos-driver 0.0.0.0:9000 10.0.0.2:9000 --psk /etc/kaos/ipc.key --ipc-mode 600 --ipc-owner 1000
+//! ```
+//!
+//! ```rust,no_run
+//! use kaos_driver::auth::{self, IpcKey};
+//! use kaos_ipc::{Publisher, Subscriber};
+//! use std::time::Duration;
+//!
+//! // App side, before sending traffic
+//! let key = IpcKey::from_file("/etc/kaos/ipc.key").unwrap();
+//! let mut to_driver = Publisher::create("/tmp/kaos-send", 64 * 1024).unwrap();
+//! auth::restrict("/tmp/kaos-send", 0o600).unwrap();
+//! let mut from_driver = Subscriber::open("/tmp/kaos-recv").unwrap();
+//! auth::answer(&key, &mut from_driver, &mut to_driver, Duration::from_secs(5)).unwrap();
+//! ```
+
+use kaos_ipc::{Publisher, Subscriber};
+use std::fmt;
+use std::io::{self, Read};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Mixed into every response so the key answers nothing but this challenge
+const RESPONSE_DOMAIN: u64 = 0x6b616f732d697063; // "kaos-ipc"
+
+/// Fixed SipHash keys that turn a secret of any length into an `IpcKey`
+const DERIVE_K0: u64 = 0x6b616f732d70736b; // "kaos-psk"
+const DERIVE_K1: u64 = 0x6472697665722d31; // "driver-1"
+
+/// Poll granularity while waiting for the other side
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// SipHash-2-4 of `data` under the 128-bit key `(k0, k1)`
+fn siphash24(k0: u64, k1: u64, data: &[u8]) -> u64 {
+    let mut v = [
+        k0 ^ 0x736f6d6570736575,
+        k1 ^ 0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        k1 ^ 0x7465646279746573,
+    ];
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+    let mut compress = |m: u64| {
+        v[3] ^= m;
+        round(&mut v);
+        round(&mut v);
+        v[0] ^= m;
+    };
+
+    let chunks = data.chunks_exact(8);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        compress(u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    let mut last = (data.len() as u64) << 56;
+    for (i, &byte) in tail.iter().enumerate() {
+        last |= (byte as u64) << (8 * i);
+    }
+    compress(last);
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// Random challenge nonce
+fn nonce() -> u64 {
+    let mut buf = [0u8; 8];
+    if std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut buf))
+        .is_ok()
+    {
+        return u64::from_le_bytes(buf);
+    }
+    // No urandom: std's randomly keyed hasher
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0),
+    );
+    hasher.finish()
+}
+
+/// Pre-shared key for the IPC challenge
+#[derive(Clone, PartialEq, Eq)]
+pub struct IpcKey {
+    k0: u64,
+    k1: u64,
+}
+
+impl IpcKey {
+    /// Derive from a secret (hashed, not stretched: use a long random one)
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            k0: siphash24(DERIVE_K0, DERIVE_K1, secret),
+            k1: siphash24(DERIVE_K1, DERIVE_K0, secret),
+        }
+    }
+
+    /// Read the secret from a file (trailing whitespace ignored)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let secret = std::fs::read(path)?;
+        let end = secret
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(0, |i| i + 1);
+        if end == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty PSK file"));
+        }
+        Ok(Self::new(&secret[..end]))
+    }
+
+    /// The answer to challenge `nonce`
+    pub fn response(&self, nonce: u64) -> u64 {
+        let mut msg = [0u8; 16];
+        msg[..8].copy_from_slice(&RESPONSE_DOMAIN.to_le_bytes());
+        msg[8..].copy_from_slice(&nonce.to_le_bytes());
+        siphash24(self.k0, self.k1, &msg)
+    }
+}
+
+impl fmt::Debug for IpcKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IpcKey(..)")
+    }
+}
+
+/// Owner and permissions of a ring file, for logging and checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingIdentity {
+    pub uid: u32,
+    pub gid: u32,
+    /// Permission bits (e.g. `0o600`)
+    pub mode: u32,
+}
+
+impl RingIdentity {
+    /// Any local user may write into the ring
+    pub fn world_writable(&self) -> bool {
+        self.mode & 0o002 != 0
+    }
+}
+
+impl fmt::Display for RingIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "uid={} gid={} mode={:o}", self.uid, self.gid, self.mode)
+    }
+}
+
+/// Owner and permissions of the ring at `path`
+#[cfg(unix)]
+pub fn identity<P: AsRef<Path>>(path: P) -> io::Result<RingIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(path)?;
+    Ok(RingIdentity {
+        uid: meta.uid(),
+        gid: meta.gid(),
+        mode: meta.mode() & 0o7777,
+    })
+}
+
+/// File ownership needs unix metadata.
+#[cfg(not(unix))]
+pub fn identity<P: AsRef<Path>>(_path: P) -> io::Result<RingIdentity> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "ring identity: unix only",
+    ))
+}
+
+/// Set the ring file's permission bits (e.g. `0o600`: owner only)
+#[cfg(unix)]
+pub fn restrict<P: AsRef<Path>>(path: P, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+/// Unix permission bits don't apply elsewhere.
+#[cfg(not(unix))]
+pub fn restrict<P: AsRef<Path>>(_path: P, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+/// App side of the challenge: wait for the driver's nonce on `from_driver`
+/// and answer on `to_driver`. Call before sending any traffic.
+pub fn answer(
+    key: &IpcKey,
+    from_driver: &mut Subscriber,
+    to_driver: &mut Publisher,
+    timeout: Duration,
+) -> io::Result<()> {
+    let nonce = wait_for(from_driver, timeout)?;
+    to_driver.send(key.response(nonce))?;
+    Ok(())
+}
+
+fn wait_for(subscriber: &mut Subscriber, timeout: Duration) -> io::Result<u64> {
+    let start = Instant::now();
+    loop {
+        if let Some(value) = subscriber.try_receive() {
+            return Ok(value);
+        }
+        if start.elapsed() >= timeout {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "no IPC challenge traffic",
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Driver-side IPC protection
+#[derive(Debug, Clone)]
+pub struct IpcAuth {
+    /// Permission bits for rings the driver creates (`None` = umask default)
+    pub mode: Option<u32>,
+    /// Accept app rings owned by these uids only (empty = any owner)
+    pub owners: Vec<u32>,
+    /// Require the PSK challenge before forwarding
+    pub key: Option<IpcKey>,
+    /// How long the app has to answer
+    pub timeout: Duration,
+}
+
+impl Default for IpcAuth {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            owners: Vec::new(),
+            key: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl IpcAuth {
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Allow app rings owned by `uid`
+    pub fn with_owner(mut self, uid: u32) -> Self {
+        self.owners.push(uid);
+        self
+    }
+
+    pub fn with_key(mut self, key: IpcKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Vet the app's ring before opening it: allowed owner, and not
+    /// world-writable when owners are restricted. Returns who owns it.
+    pub fn check_app_ring<P: AsRef<Path>>(&self, path: P) -> io::Result<RingIdentity> {
+        let id = match identity(&path) {
+            Err(e) if e.kind() == io::ErrorKind::Unsupported && self.owners.is_empty() => {
+                return Ok(RingIdentity {
+                    uid: 0,
+                    gid: 0,
+                    mode: 0,
+                })
+            }
+            result => result?,
+        };
+        if !self.owners.is_empty() {
+            if !self.owners.contains(&id.uid) {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("ring owned by uid {}, not an allowed owner", id.uid),
+                ));
+            }
+            if id.world_writable() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "ring is world-writable",
+                ));
+            }
+        }
+        Ok(id)
+    }
+
+    /// Apply `mode` to a ring the driver created
+    pub fn protect<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        match self.mode {
+            Some(mode) => restrict(path, mode),
+            None => Ok(()),
+        }
+    }
+
+    /// Driver side of the challenge (no-op without a key): send a nonce on
+    /// `to_app`, expect its response as the first value on `from_app`.
+    pub fn challenge(&self, to_app: &mut Publisher, from_app: &mut Subscriber) -> io::Result<()> {
+        let Some(key) = &self.key else {
+            return Ok(());
+        };
+        let nonce = nonce();
+        to_app.send(nonce)?;
+        if wait_for(from_app, self.timeout)? != key.response(nonce) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "PSK challenge failed",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_siphash_reference_vectors() {
+        let k0 = u64::from_le_bytes([0, 1, 2, 3, 4, 5, 6, 7]);
+        let k1 = u64::from_le_bytes([8, 9, 10, 11, 12, 13, 14, 15]);
+        let msg: Vec<u8> = (0..15).collect();
+        assert_eq!(siphash24(k0, k1, &[]), 0x726fdb47dd0e0e31);
+        assert_eq!(siphash24(k0, k1, &msg), 0xa129ca6149be45e5);
+    }
+
+    fn rings(name: &str) -> (String, String) {
+        let base = format!("/tmp/kaos-auth-{}-{}", name, std::process::id());
+        let paths = (format!("{}-send", base), format!("{}-recv", base));
+        let _ = fs::remove_file(&paths.0);
+        let _ = fs::remove_file(&paths.1);
+        paths
+    }
+
+    #[test]
+    fn test_challenge() {
+        let (send, recv) = rings("psk");
+        let key = IpcKey::new(b"correct horse battery staple");
+        let auth = IpcAuth::default()
+            .with_key(key.clone())
+            .with_timeout(Duration::from_secs(2));
+
+        // Right key
+        let mut to_driver = Publisher::create(&send, 64).unwrap();
+        let mut from_app = Subscriber::open(&send).unwrap();
+        let mut to_app = Publisher::create(&recv, 64).unwrap();
+        let mut from_driver = Subscriber::open(&recv).unwrap();
+        let app = thread::spawn(move || {
+            answer(
+                &key,
+                &mut from_driver,
+                &mut to_driver,
+                Duration::from_secs(2),
+            )
+            .unwrap();
+            to_driver.send(42).unwrap();
+            (from_driver, to_driver)
+        });
+        auth.challenge(&mut to_app, &mut from_app).unwrap();
+        let _rings = app.join().unwrap();
+        assert_eq!(from_app.try_receive(), Some(42));
+
+        // Wrong key
+        let (send, recv) = rings("psk-wrong");
+        let mut to_driver = Publisher::create(&send, 64).unwrap();
+        let mut from_app = Subscriber::open(&send).unwrap();
+        let mut to_app = Publisher::create(&recv, 64).unwrap();
+        let mut from_driver = Subscriber::open(&recv).unwrap();
+        let rogue = IpcKey::new(b"guess");
+        let app = thread::spawn(move || {
+            answer(
+                &rogue,
+                &mut from_driver,
+                &mut to_driver,
+                Duration::from_secs(2),
+            )
+            .unwrap();
+            (from_driver, to_driver)
+        });
+        let err = auth.challenge(&mut to_app, &mut from_app).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let _rings = app.join().unwrap();
+
+        // No answer at all
+        let auth = auth.with_timeout(Duration::from_millis(20));
+        let err = auth.challenge(&mut to_app, &mut from_app).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        for path in [send, recv] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions_and_owner() {
+        let (send, _) = rings("perm");
+        let _ring = Publisher::create(&send, 64).unwrap();
+        let auth = IpcAuth::default().with_mode(0o600);
+        auth.protect(&send).unwrap();
+        let id = auth.check_app_ring(&send).unwrap();
+        assert_eq!(id.mode, 0o600);
+
+        let owned = IpcAuth::default().with_owner(id.uid);
+        assert!(owned.check_app_ring(&send).is_ok());
+        restrict(&send, 0o666).unwrap();
+        assert!(owned.check_app_ring(&send).is_err());
+        let other = IpcAuth::default().with_owner(id.uid.wrapping_add(1));
+        let err = other.check_app_ring(&send).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let _ = fs::remove_file(send);
+    }
+
+    #[test]
+    fn test_key_from_file() {
+        let path = format!("/tmp/kaos-auth-key-{}", std::process::id());
+        fs::write(&path, "s3cret\n").unwrap();
+        assert_eq!(IpcKey::from_file(&path).unwrap(), IpcKey::new(b"s3cret"));
+        fs::write(&path, "\n").unwrap();
+        assert!(IpcKey::from_file(&path).is_err());
+        assert_ne!(IpcKey::new(b"a").response(1), IpcKey::new(b"b").response(1));
+        let _ = fs::remove_file(path);
+    }
+}
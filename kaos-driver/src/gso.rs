@@ -0,0 +1,211 @@
+//! UDP GSO/GRO driver (Linux 4.18+ / 5.0+)
+//!
+//! One sendmsg per batch (`UDP_SEGMENT`) and coalesced receives (`UDP_GRO`).
+//! Falls back to sendmmsg / single-datagram recv when the kernel says no.
+#![cfg(target_os = "linux")]
+
+use std::io;
+use std::net::UdpSocket;
+use std::os::fd::AsRawFd;
+
+/// SOL_UDP socket options (linux/udp.h)
+const UDP_SEGMENT: i32 = 103;
+const UDP_GRO: i32 = 104;
+
+/// Message size (u64 values)
+const MSG_SIZE: usize = 8;
+
+/// Max segments per GSO send (UDP_MAX_SEGMENTS on older kernels)
+const MAX_SEGMENTS: usize = 64;
+
+/// GRO receive buffer (one coalesced super-packet)
+const RECV_BUF_SIZE: usize = 65535;
+
+pub struct GsoDriver {
+    fd: i32,
+    send_buf: Vec<u8>,
+    recv_buf: Vec<u8>,
+    gso: bool,
+    gro: bool,
+}
+
+impl GsoDriver {
+    /// Socket must be connected (sends use the connected peer).
+    pub fn new(socket: &UdpSocket) -> Self {
+        let fd = socket.as_raw_fd();
+        let on: i32 = 1;
+        // Safety: fd is a socket, option value is a valid i32
+        let gro = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_UDP,
+                UDP_GRO,
+                &on as *const i32 as *const libc::c_void,
+                std::mem::size_of::<i32>() as u32,
+            )
+        } == 0;
+
+        Self {
+            fd,
+            send_buf: Vec::with_capacity(MAX_SEGMENTS * MSG_SIZE),
+            recv_buf: vec![0u8; RECV_BUF_SIZE],
+            gso: true,
+            gro,
+        }
+    }
+
+    /// UDP_SEGMENT still in use (false after the kernel rejected it)
+    pub fn gso_enabled(&self) -> bool {
+        self.gso
+    }
+
+    /// UDP_GRO was accepted by the kernel
+    pub fn gro_enabled(&self) -> bool {
+        self.gro
+    }
+
+    /// Send up to 64 values as one GSO super-packet (sendmmsg fallback).
+    pub fn submit_sends(&mut self, data: &[[u8; MSG_SIZE]]) -> io::Result<usize> {
+        let data = &data[..data.len().min(MAX_SEGMENTS)];
+        if data.is_empty() {
+            return Ok(0);
+        }
+        if self.gso {
+            match self.send_gso(data) {
+                Ok(()) => return Ok(data.len()),
+                Err(e) if is_unsupported(&e) => self.gso = false,
+                Err(e) => return Err(e),
+            }
+        }
+        self.send_mmsg(data)
+    }
+
+    fn send_gso(&mut self, data: &[[u8; MSG_SIZE]]) -> io::Result<()> {
+        self.send_buf.clear();
+        for v in data {
+            self.send_buf.extend_from_slice(v);
+        }
+
+        let mut iov = libc::iovec {
+            iov_base: self.send_buf.as_mut_ptr() as *mut _,
+            iov_len: self.send_buf.len(),
+        };
+        let mut control = [0u64; 4];
+        // Safety: msghdr is valid when zeroed; cmsg fits in `control`
+        unsafe {
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr() as *mut _;
+            msg.msg_controllen = libc::CMSG_SPACE(std::mem::size_of::<u16>() as u32) as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_UDP;
+            (*cmsg).cmsg_type = UDP_SEGMENT;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(std::mem::size_of::<u16>() as u32) as _;
+            std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut u16, MSG_SIZE as u16);
+
+            if libc::sendmsg(self.fd, &msg, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    fn send_mmsg(&mut self, data: &[[u8; MSG_SIZE]]) -> io::Result<usize> {
+        // Safety: libc iovec/mmsghdr are valid when zeroed; buffers outlive the call
+        unsafe {
+            let mut iovecs: [libc::iovec; MAX_SEGMENTS] = std::mem::zeroed();
+            let mut msgs: [libc::mmsghdr; MAX_SEGMENTS] = std::mem::zeroed();
+            for (i, v) in data.iter().enumerate() {
+                iovecs[i].iov_base = v.as_ptr() as *mut _;
+                iovecs[i].iov_len = MSG_SIZE;
+                msgs[i].msg_hdr.msg_iov = &mut iovecs[i];
+                msgs[i].msg_hdr.msg_iovlen = 1;
+            }
+            let n = libc::sendmmsg(self.fd, msgs.as_mut_ptr(), data.len() as u32, 0);
+            if n < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(n as usize)
+            }
+        }
+    }
+
+    /// Non-blocking receive; calls `on_recv` per value. Returns value count.
+    pub fn poll_recv<F: FnMut(u64)>(&mut self, mut on_recv: F) -> usize {
+        let mut iov = libc::iovec {
+            iov_base: self.recv_buf.as_mut_ptr() as *mut _,
+            iov_len: self.recv_buf.len(),
+        };
+        let mut control = [0u64; 8];
+        // Safety: msghdr is valid when zeroed; buffers outlive the call
+        let (len, segment) = unsafe {
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr() as *mut _;
+            msg.msg_controllen = std::mem::size_of_val(&control) as _;
+
+            let r = libc::recvmsg(self.fd, &mut msg, libc::MSG_DONTWAIT);
+            if r <= 0 {
+                return 0;
+            }
+
+            // Segment size from UDP_GRO cmsg; absent = single datagram
+            let mut segment = r as usize;
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_UDP && (*cmsg).cmsg_type == UDP_GRO {
+                    let size = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const i32);
+                    if size > 0 {
+                        segment = size as usize;
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+            (r as usize, segment)
+        };
+
+        let mut count = 0;
+        for datagram in self.recv_buf[..len].chunks(segment) {
+            if datagram.len() >= MSG_SIZE {
+                on_recv(u64::from_le_bytes(datagram[..MSG_SIZE].try_into().unwrap()));
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+/// Errors meaning the kernel/device can't do GSO (switch to sendmmsg)
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EIO) | Some(libc::EINVAL) | Some(libc::ENOPROTOOPT) | Some(libc::EOPNOTSUPP)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gso_driver_roundtrip() {
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.connect(receiver.local_addr().unwrap()).unwrap();
+
+        let mut tx = GsoDriver::new(&sender);
+        let mut rx = GsoDriver::new(&receiver);
+        println!("gso={} gro={}", tx.gso_enabled(), rx.gro_enabled());
+
+        let data: Vec<[u8; 8]> = (0..32u64).map(|v| v.to_le_bytes()).collect();
+        assert_eq!(tx.submit_sends(&data).unwrap(), 32);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let mut got = Vec::new();
+        while rx.poll_recv(|v| got.push(v)) > 0 {}
+        assert_eq!(got, (0..32u64).collect::<Vec<_>>());
+    }
+}
@@ -2,7 +2,13 @@
 //!
 //! Provides high-performance I/O backends for kaos.
 
+pub mod auth;
+pub mod streams;
+pub mod supervisor;
 pub mod xdp;
 
+#[cfg(target_os = "linux")]
+pub mod gso;
+
 #[cfg(all(target_os = "linux", feature = "uring"))]
 pub mod uring;
@@ -3,10 +3,20 @@
 //! Unicast:   kaos-driver <bind> <peer> [send_path] [recv_path]
 //! Multicast: kaos-driver <bind> <multicast_group> --multicast [send_path] [recv_path]
 //! Echo:      kaos-driver <bind> --echo
+//! Streams:   kaos-driver <bind> <peer> --stream <id>:<send_path>:<recv_path> [--stream ...]
 //!
 //! Features: --features reliable (kaos-rudp), --features uring (io_uring)
-
-use kaos_ipc::{Publisher, Subscriber};
+//! Flags:    --gso (Linux UDP GSO/GRO, falls back to sendmmsg)
+//!           --heartbeat <path> (liveness ring for `kaos_driver::supervisor`)
+//!           --hugepages (2MB pages for the driver → app ring, falls back to base pages)
+//!           --ipc-mode <octal> (permissions of the rings the driver creates, e.g. 600)
+//!           --ipc-owner <uid> (only accept app rings owned by uid; repeatable)
+//!           --psk <file> (app must answer a pre-shared-key challenge, see `kaos_driver::auth`)
+
+use kaos_driver::auth::{IpcAuth, IpcKey};
+use kaos_driver::streams::{StreamDriver, StreamSpec};
+use kaos_driver::supervisor::Heartbeat;
+use kaos_ipc::{HugePageSize, Publisher, Subscriber};
 use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
 #[cfg(target_os = "linux")]
 use std::os::fd::AsRawFd;
@@ -15,6 +25,8 @@ use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
+#[cfg(all(target_os = "linux", not(feature = "reliable")))]
+mod gso;
 #[cfg(all(target_os = "linux", feature = "uring"))]
 mod uring;
 #[cfg(all(target_os = "linux", feature = "xdp"))]
@@ -29,10 +41,28 @@ const BATCH_SIZE: usize = 64;
 /// Message size for multicast
 const MSG_SIZE: usize = 64;
 
+/// Heartbeat period (supervisor default timeout is 3s)
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Flags that take a value (not positional args)
+const VALUE_FLAGS: [&str; 5] = [
+    "--stream",
+    "--heartbeat",
+    "--ipc-mode",
+    "--ipc-owner",
+    "--psk",
+];
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let echo = args.iter().any(|a| a == "--echo" || a == "-e");
     let multicast = args.iter().any(|a| a == "--multicast" || a == "-m");
+    #[cfg_attr(
+        any(not(target_os = "linux"), feature = "reliable"),
+        allow(unused_variables)
+    )]
+    let gso = args.iter().any(|a| a == "--gso");
+    let hugepages = args.iter().any(|a| a == "--hugepages");
 
     if args.len() < 2 {
         eprintln!("Kaos Media Driver");
@@ -40,19 +70,43 @@ fn main() {
         eprintln!("Unicast:   kaos-driver <bind> <peer> [send_path] [recv_path]");
         eprintln!("Multicast: kaos-driver <bind> <group:port> --multicast [send_path] [recv_path]");
         eprintln!("Echo:      kaos-driver <bind> --echo");
+        eprintln!(
+            "Streams:   kaos-driver <bind> <peer> --stream <id>:<send>:<recv> [--stream ...]"
+        );
         eprintln!();
         eprintln!("Features: --features reliable, --features uring");
+        eprintln!("Flags:    --gso (Linux UDP GSO/GRO)");
+        eprintln!("          --heartbeat <path> (liveness ring for supervisors)");
+        eprintln!("          --hugepages (huge page backed IPC ring)");
+        eprintln!("          --ipc-mode <octal> --ipc-owner <uid> --psk <file> (IPC auth)");
         std::process::exit(1);
     }
 
     let bind: SocketAddr = args[1].parse().expect("invalid bind address");
 
-    // Parse peer/group (skip flags)
-    let peer_arg = args
+    // --stream <id>:<send>:<recv> (repeatable); its values aren't positional args
+    let streams: Vec<StreamSpec> = args
+        .windows(2)
+        .filter(|w| w[0] == "--stream")
+        .map(|w| StreamSpec::parse(&w[1]).expect("invalid --stream (id:send_path:recv_path)"))
+        .collect();
+    let positional: Vec<&String> = args
         .iter()
+        .enumerate()
         .skip(2)
-        .find(|a| !a.starts_with('-'))
-        .map(|s| s.as_str());
+        .filter(|(i, a)| !a.starts_with('-') && !VALUE_FLAGS.contains(&args[i - 1].as_str()))
+        .map(|(_, a)| a)
+        .collect();
+    let auth = parse_auth(&args);
+
+    // --heartbeat <path>: beat from startup so supervisors see us before any app connects
+    if let Some(w) = args.windows(2).find(|w| w[0] == "--heartbeat") {
+        let heartbeat = Heartbeat::create(&w[1]).expect("create heartbeat failed");
+        heartbeat.spawn(HEARTBEAT_INTERVAL, Arc::new(AtomicBool::new(true)));
+    }
+
+    // Parse peer/group (skip flags)
+    let peer_arg = positional.first().map(|s| s.as_str());
 
     let peer: SocketAddr = if echo {
         bind
@@ -63,11 +117,14 @@ fn main() {
             .expect("invalid peer address")
     };
 
+    if !streams.is_empty() {
+        return run_streams(bind, peer, &streams, &auth);
+    }
+
     // Get IPC paths (skip flags)
-    let paths: Vec<&str> = args
+    let paths: Vec<&str> = positional
         .iter()
-        .skip(2)
-        .filter(|a| !a.starts_with('-') && a.parse::<SocketAddr>().is_err())
+        .filter(|a| a.parse::<SocketAddr>().is_err())
         .map(|s| s.as_str())
         .collect();
     let (send_path, recv_path) = (
@@ -85,8 +142,25 @@ fn main() {
     println!("kaos-driver{} {} → {}", mode, bind, peer);
     println!("IPC: {} / {}", send_path, recv_path);
 
-    let mut from_app = wait_for_ipc(send_path);
-    let mut to_app = Publisher::create(recv_path, RING_SIZE).expect("create Publisher failed");
+    let mut from_app = wait_for_ipc(send_path, &auth);
+    let mut to_app = if hugepages {
+        let publisher =
+            Publisher::create_with_hugepages(recv_path, RING_SIZE, HugePageSize::Size2M)
+                .expect("create Publisher failed");
+        println!("IPC pages: {:?}", publisher.page_backing());
+        publisher
+    } else {
+        Publisher::create(recv_path, RING_SIZE).expect("create Publisher failed")
+    };
+    auth.protect(recv_path).expect("set IPC permissions failed");
+    if auth.key.is_some() {
+        println!("Waiting for app to answer the PSK challenge...");
+        if let Err(e) = auth.challenge(&mut to_app, &mut from_app) {
+            eprintln!("IPC auth failed on {}: {}", send_path, e);
+            std::process::exit(1);
+        }
+        println!("IPC auth: PSK ok");
+    }
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
     ctrlc::set_handler(move || r.store(false, Ordering::SeqCst)).ok();
@@ -118,6 +192,10 @@ fn main() {
         socket2.connect(&peer.into()).unwrap();
         let socket: UdpSocket = socket2.into();
 
+        #[cfg(target_os = "linux")]
+        if gso {
+            return run_gso(&socket, &mut from_app, &mut to_app, &running);
+        }
         #[cfg(all(target_os = "linux", feature = "uring"))]
         return run_uring(&socket, &mut from_app, &mut to_app, &running);
         #[cfg(all(target_os = "linux", not(feature = "uring")))]
@@ -127,17 +205,92 @@ fn main() {
     }
 }
 
-fn wait_for_ipc(path: &str) -> Subscriber {
+fn parse_auth(args: &[String]) -> IpcAuth {
+    let mut auth = IpcAuth::default();
+    for w in args.windows(2) {
+        auth = match w[0].as_str() {
+            "--ipc-mode" => auth.with_mode(
+                u32::from_str_radix(&w[1], 8).expect("invalid --ipc-mode (octal, e.g. 600)"),
+            ),
+            "--ipc-owner" => auth.with_owner(w[1].parse().expect("invalid --ipc-owner (uid)")),
+            "--psk" => auth.with_key(IpcKey::from_file(&w[1]).expect("read --psk file failed")),
+            _ => auth,
+        };
+    }
+    auth
+}
+
+fn wait_for_ipc(path: &str, auth: &IpcAuth) -> Subscriber {
     println!("Waiting for app to create {}...", path);
     loop {
-        if let Ok(s) = Subscriber::open(path) {
-            println!("Connected to {}", path);
-            return s;
+        if std::path::Path::new(path).exists() {
+            // Vet the ring before reading a single value from it
+            let id = match auth.check_app_ring(path) {
+                Ok(id) => id,
+                Err(e) => {
+                    eprintln!("Rejected {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            };
+            if let Ok(s) = Subscriber::open(path) {
+                println!("Connected to {} ({})", path, id);
+                if id.world_writable() {
+                    eprintln!("warning: {} is world-writable (see --ipc-owner)", path);
+                }
+                return s;
+            }
         }
         thread::sleep(Duration::from_millis(100));
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// STREAMS - several IPC streams multiplexed over one socket
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn run_streams(bind: SocketAddr, peer: SocketAddr, specs: &[StreamSpec], auth: &IpcAuth) {
+    println!("kaos-driver[STREAMS] {} → {}", bind, peer);
+    for spec in specs {
+        println!(
+            "  stream {}: {} / {}",
+            spec.id, spec.send_path, spec.recv_path
+        );
+    }
+    if auth.key.is_some() || !auth.owners.is_empty() {
+        eprintln!("warning: --psk / --ipc-owner are not applied to --stream rings");
+    }
+    let mut driver =
+        StreamDriver::new(bind, peer, specs, RING_SIZE).expect("stream driver setup failed");
+    for spec in specs {
+        auth.protect(&spec.recv_path)
+            .expect("set IPC permissions failed");
+    }
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || r.store(false, Ordering::SeqCst)).ok();
+
+    let mut last = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        let moved = driver.poll();
+        if last.elapsed() > Duration::from_secs(5) {
+            for (id, s) in driver.all_stats() {
+                println!(
+                    "  [{}] tx={} rx={} rx_dropped={} tx_errors={}",
+                    id, s.tx, s.rx, s.rx_dropped, s.tx_errors
+                );
+            }
+            last = Instant::now();
+        }
+        // Busy-spin like Aeron's BusySpinIdleStrategy for max throughput
+        if moved == 0 {
+            std::hint::spin_loop();
+        }
+    }
+    for (id, s) in driver.all_stats() {
+        println!("done [{}] tx={} rx={}", id, s.tx, s.rx);
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // MULTICAST - batched sends to multicast group
 // ═══════════════════════════════════════════════════════════════════════════
@@ -448,6 +601,57 @@ fn run_uring(
     println!("done tx={} rx={}", sent, recvd);
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// UNICAST - UDP GSO/GRO (Linux)
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(all(target_os = "linux", not(feature = "reliable")))]
+fn run_gso(
+    socket: &UdpSocket,
+    from_app: &mut Subscriber,
+    to_app: &mut Publisher,
+    running: &Arc<AtomicBool>,
+) {
+    use crate::gso::GsoDriver;
+    let mut driver = GsoDriver::new(socket);
+    println!(
+        "Running GSO driver (gso={}, gro={}, batch={})",
+        driver.gso_enabled(),
+        driver.gro_enabled(),
+        BATCH_SIZE
+    );
+    let (mut sent, mut recvd, mut last) = (0u64, 0u64, Instant::now());
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    while running.load(Ordering::Relaxed) {
+        batch.clear();
+        while batch.len() < BATCH_SIZE {
+            if let Some(v) = from_app.try_receive() {
+                batch.push(v.to_le_bytes());
+            } else {
+                break;
+            }
+        }
+        if !batch.is_empty() {
+            if let Ok(n) = driver.submit_sends(&batch) {
+                sent += n as u64;
+            }
+        }
+        while driver.poll_recv(|v| {
+            let _ = to_app.send(v);
+            recvd += 1;
+        }) > 0
+        {}
+        if last.elapsed() > Duration::from_secs(5) {
+            println!("  tx={} rx={}", sent, recvd);
+            last = Instant::now();
+        }
+        // Busy-spin like Aeron's BusySpinIdleStrategy for max throughput
+        std::hint::spin_loop();
+    }
+    println!("done tx={} rx={}", sent, recvd);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // UNICAST - sendmmsg/recvmmsg (Linux)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -0,0 +1,300 @@
+//! Multiplexed streams over one socket.
+//!
+//! Each stream is a Publisher/Subscriber pair identified by a stream id. On
+//! the wire every datagram is `[stream id: MUX_KEY_SIZE bytes LE][u64 LE]`,
+//! so one driver instance can serve several app components.
+//!
+//! ```text
+//! kaos-driver 0.0.0.0:9000 10.0.0.2:9000 \
+//!     --stream 1:/tmp/game-send:/tmp/game-recv \
+//!     --stream 2:/tmp/chat-send:/tmp/chat-recv
+//! ```
+
+use kaos_ipc::{Publisher, Subscriber};
+use kaos_shared::MUX_KEY_SIZE;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+/// Stream datagram size: key + u64 value
+pub const STREAM_MSG_SIZE: usize = MUX_KEY_SIZE + 8;
+
+/// Values drained per stream per poll (keeps one busy stream from starving others)
+const BATCH_PER_STREAM: usize = 64;
+
+/// One stream's IPC paths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamSpec {
+    pub id: u32,
+    /// App -> driver ring (created by the app)
+    pub send_path: String,
+    /// Driver -> app ring (created by the driver)
+    pub recv_path: String,
+}
+
+impl StreamSpec {
+    pub fn new(id: u32, send_path: impl Into<String>, recv_path: impl Into<String>) -> Self {
+        Self {
+            id,
+            send_path: send_path.into(),
+            recv_path: recv_path.into(),
+        }
+    }
+
+    /// Parse `id:send_path:recv_path`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut parts = spec.splitn(3, ':');
+        let id = parts.next()?.parse().ok()?;
+        let send_path = parts.next().filter(|p| !p.is_empty())?;
+        let recv_path = parts.next().filter(|p| !p.is_empty())?;
+        Some(Self::new(id, send_path, recv_path))
+    }
+}
+
+/// Per-stream counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Values sent to the network
+    pub tx: u64,
+    /// Values delivered to the app
+    pub rx: u64,
+    /// Received values dropped (app ring full)
+    pub rx_dropped: u64,
+    /// Send errors (e.g. socket buffer full)
+    pub tx_errors: u64,
+}
+
+struct Stream {
+    spec: StreamSpec,
+    /// Opened once the app has created its ring
+    from_app: Option<Subscriber>,
+    to_app: Publisher,
+    stats: StreamStats,
+}
+
+/// Forwards several IPC streams over one UDP socket.
+pub struct StreamDriver {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    streams: Vec<Stream>,
+    unknown_stream: u64,
+}
+
+impl StreamDriver {
+    /// Bind to `bind` and send to `peer`. Creates each stream's recv ring.
+    pub fn new(
+        bind: SocketAddr,
+        peer: SocketAddr,
+        specs: &[StreamSpec],
+        ring_size: usize,
+    ) -> io::Result<Self> {
+        Self::with_socket(UdpSocket::bind(bind)?, peer, specs, ring_size)
+    }
+
+    /// Use an existing socket (made non-blocking).
+    pub fn with_socket(
+        socket: UdpSocket,
+        peer: SocketAddr,
+        specs: &[StreamSpec],
+        ring_size: usize,
+    ) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        let mut streams: Vec<Stream> = Vec::with_capacity(specs.len());
+        for spec in specs {
+            if streams.iter().any(|s| s.spec.id == spec.id) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("duplicate stream id {}", spec.id),
+                ));
+            }
+            streams.push(Stream {
+                to_app: Publisher::create(&spec.recv_path, ring_size)?,
+                from_app: None,
+                spec: spec.clone(),
+                stats: StreamStats::default(),
+            });
+        }
+        Ok(Self {
+            socket,
+            peer,
+            streams,
+            unknown_stream: 0,
+        })
+    }
+
+    /// One pass: drain app rings to the network, route received datagrams
+    /// to their stream. Returns values moved (0 = idle).
+    pub fn poll(&mut self) -> usize {
+        let mut moved = 0;
+        let mut buf = [0u8; STREAM_MSG_SIZE];
+
+        for stream in &mut self.streams {
+            if stream.from_app.is_none() {
+                stream.from_app = Subscriber::open(&stream.spec.send_path).ok();
+            }
+            let Some(from_app) = stream.from_app.as_mut() else {
+                continue;
+            };
+            buf[..MUX_KEY_SIZE].copy_from_slice(&stream.spec.id.to_le_bytes());
+            for _ in 0..BATCH_PER_STREAM {
+                let Some(v) = from_app.try_receive() else {
+                    break;
+                };
+                buf[MUX_KEY_SIZE..].copy_from_slice(&v.to_le_bytes());
+                match self.socket.send_to(&buf, self.peer) {
+                    Ok(_) => stream.stats.tx += 1,
+                    Err(_) => stream.stats.tx_errors += 1,
+                }
+                moved += 1;
+            }
+        }
+
+        let mut recv_buf = [0u8; 64];
+        loop {
+            match self.socket.recv_from(&mut recv_buf) {
+                Ok((len, _)) if len >= STREAM_MSG_SIZE => {
+                    let id = u32::from_le_bytes(recv_buf[..MUX_KEY_SIZE].try_into().unwrap());
+                    let v = u64::from_le_bytes(
+                        recv_buf[MUX_KEY_SIZE..STREAM_MSG_SIZE].try_into().unwrap(),
+                    );
+                    match self.streams.iter_mut().find(|s| s.spec.id == id) {
+                        Some(stream) => match stream.to_app.send(v) {
+                            Ok(_) => stream.stats.rx += 1,
+                            Err(_) => stream.stats.rx_dropped += 1,
+                        },
+                        None => self.unknown_stream += 1,
+                    }
+                    moved += 1;
+                }
+                Ok(_) => self.unknown_stream += 1, // runt datagram
+                Err(_) => break,
+            }
+        }
+        moved
+    }
+
+    /// Counters for one stream
+    pub fn stats(&self, id: u32) -> Option<StreamStats> {
+        self.streams
+            .iter()
+            .find(|s| s.spec.id == id)
+            .map(|s| s.stats)
+    }
+
+    /// (stream id, counters) for every stream
+    pub fn all_stats(&self) -> Vec<(u32, StreamStats)> {
+        self.streams.iter().map(|s| (s.spec.id, s.stats)).collect()
+    }
+
+    /// Datagrams for stream ids we don't serve (or too short to parse)
+    pub fn unknown_stream(&self) -> u64 {
+        self.unknown_stream
+    }
+
+    /// Whether the app side of a stream has connected
+    pub fn is_connected(&self, id: u32) -> bool {
+        self.streams
+            .iter()
+            .any(|s| s.spec.id == id && s.from_app.is_some())
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("kaos-stream-{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_spec_parse() {
+        assert_eq!(
+            StreamSpec::parse("7:/tmp/a:/tmp/b"),
+            Some(StreamSpec::new(7, "/tmp/a", "/tmp/b"))
+        );
+        assert_eq!(StreamSpec::parse("x:/tmp/a:/tmp/b"), None);
+        assert_eq!(StreamSpec::parse("7:/tmp/a"), None);
+        assert_eq!(StreamSpec::parse("7::/tmp/b"), None);
+    }
+
+    #[test]
+    fn test_streams_routed_by_key() {
+        // Two drivers talking to each other, two streams each
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (a_addr, b_addr) = (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap());
+        let specs = |side: &str| {
+            vec![
+                StreamSpec::new(1, path(&format!("{side}-1s")), path(&format!("{side}-1r"))),
+                StreamSpec::new(2, path(&format!("{side}-2s")), path(&format!("{side}-2r"))),
+            ]
+        };
+        let (a_specs, b_specs) = (specs("a"), specs("b"));
+
+        let mut a = StreamDriver::with_socket(a_sock, b_addr, &a_specs, 1024).unwrap();
+        let mut b = StreamDriver::with_socket(b_sock, a_addr, &b_specs, 1024).unwrap();
+
+        // Apps on side a: create send rings; apps on side b: open recv rings
+        let mut a_app1 = Publisher::create(&a_specs[0].send_path, 1024).unwrap();
+        let mut a_app2 = Publisher::create(&a_specs[1].send_path, 1024).unwrap();
+        let mut b_app1 = Subscriber::open(&b_specs[0].recv_path).unwrap();
+        let mut b_app2 = Subscriber::open(&b_specs[1].recv_path).unwrap();
+
+        for v in 0..5 {
+            a_app1.send(100 + v).unwrap();
+        }
+        a_app2.send(200).unwrap();
+
+        for _ in 0..20 {
+            a.poll();
+            b.poll();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let mut got1 = Vec::new();
+        b_app1.receive(|v| got1.push(v));
+        let mut got2 = Vec::new();
+        b_app2.receive(|v| got2.push(v));
+        assert_eq!(got1, vec![100, 101, 102, 103, 104]);
+        assert_eq!(got2, vec![200]);
+
+        assert_eq!(a.stats(1).unwrap().tx, 5);
+        assert_eq!(a.stats(2).unwrap().tx, 1);
+        assert_eq!(b.stats(1).unwrap().rx, 5);
+        assert_eq!(b.stats(2).unwrap().rx, 1);
+        assert!(a.is_connected(1) && !b.is_connected(1));
+
+        // Unknown stream id is counted, not delivered
+        let stray = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut pkt = 9u32.to_le_bytes().to_vec();
+        pkt.extend_from_slice(&1u64.to_le_bytes());
+        stray.send_to(&pkt, b.local_addr().unwrap()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        b.poll();
+        assert_eq!(b.unknown_stream(), 1);
+
+        for spec in a_specs.iter().chain(&b_specs) {
+            let _ = std::fs::remove_file(&spec.send_path);
+            let _ = std::fs::remove_file(&spec.recv_path);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_stream_ids_rejected() {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer = sock.local_addr().unwrap();
+        let specs = [
+            StreamSpec::new(1, path("dup-s1"), path("dup-r1")),
+            StreamSpec::new(1, path("dup-s2"), path("dup-r2")),
+        ];
+        assert!(StreamDriver::with_socket(sock, peer, &specs, 64).is_err());
+        let _ = std::fs::remove_file(&specs[0].recv_path);
+    }
+}
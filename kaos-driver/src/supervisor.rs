@@ -0,0 +1,477 @@
+//! Driver supervision: spawn, monitor and restart the media driver from the app.
+//!
+//! The driver publishes a heartbeat (unix millis) on an IPC ring when started
+//! with `--heartbeat <path>`. `DriverHandle` runs the driver as a child
+//! process, watches that heartbeat and the process itself, and restarts it
+//! with exponential backoff when it exits or stops beating.
+//!
+//! ```rust,no_run
+//! use kaos_driver::supervisor::{DriverConfig, DriverHandle};
+//!
+//! let config = DriverConfig::new("kaos-driver")
+//!     .with_args(["0.0.0.0:9000", "10.0.0.2:9000"]);
+//! let driver = DriverHandle::spawn(config, |state| println!("driver: {:?}", state)).unwrap();
+//! // ... app runs; dropping the handle stops the driver
+//! # drop(driver);
+//! ```
+
+use kaos_ipc::{Publisher, Subscriber};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Heartbeat ring slots (monitor drains every poll, so this only absorbs stalls)
+const HEARTBEAT_RING: usize = 64;
+
+/// Monitor loop granularity
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Driver-side heartbeat publisher
+pub struct Heartbeat {
+    publisher: Publisher,
+}
+
+impl Heartbeat {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            publisher: Publisher::create(path, HEARTBEAT_RING)?,
+        })
+    }
+
+    /// Publish "alive now" (dropped if the monitor isn't draining)
+    pub fn beat(&mut self) {
+        let _ = self.publisher.send(unix_ms());
+    }
+
+    /// Beat every `interval` on a background thread until `running` clears
+    pub fn spawn(mut self, interval: Duration, running: Arc<AtomicBool>) -> JoinHandle<()> {
+        thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                self.beat();
+                thread::sleep(interval);
+            }
+        })
+    }
+}
+
+/// Driver lifecycle as seen by the supervisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriverState {
+    /// Process spawned, waiting for the first heartbeat
+    Starting,
+    /// Heartbeat is fresh
+    Running,
+    /// Heartbeat went stale (process is killed and restarted)
+    Unresponsive,
+    /// Process exited on its own (exit code if any)
+    Exited(Option<i32>),
+    /// Waiting `delay` before restart number `attempt`
+    Restarting { attempt: u32, delay: Duration },
+    /// Gave up after `max_restarts`
+    Failed,
+    /// Stopped by the app
+    Stopped,
+}
+
+/// Supervision settings
+#[derive(Debug, Clone)]
+pub struct DriverConfig {
+    /// Driver executable
+    pub program: PathBuf,
+    /// Driver arguments (`--heartbeat <path>` is appended)
+    pub args: Vec<String>,
+    /// Heartbeat ring path
+    pub heartbeat_path: PathBuf,
+    /// Restart if no heartbeat for this long
+    pub heartbeat_timeout: Duration,
+    /// Restart if the first heartbeat doesn't arrive within this
+    pub startup_timeout: Duration,
+    /// First restart delay (doubles per consecutive failure)
+    pub backoff_initial: Duration,
+    /// Restart delay ceiling
+    pub backoff_max: Duration,
+    /// Running this long resets the backoff
+    pub stable_after: Duration,
+    /// Give up after this many consecutive restarts (`None` = never)
+    pub max_restarts: Option<u32>,
+}
+
+impl DriverConfig {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            heartbeat_path: PathBuf::from("/tmp/kaos-heartbeat"),
+            heartbeat_timeout: Duration::from_secs(3),
+            startup_timeout: Duration::from_secs(5),
+            backoff_initial: Duration::from_millis(100),
+            backoff_max: Duration::from_secs(30),
+            stable_after: Duration::from_secs(10),
+            max_restarts: None,
+        }
+    }
+
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_heartbeat_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.heartbeat_path = path.into();
+        self
+    }
+
+    pub fn with_heartbeat_timeout(mut self, timeout: Duration) -> Self {
+        self.heartbeat_timeout = timeout;
+        self
+    }
+
+    pub fn with_startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff_initial = initial;
+        self.backoff_max = max;
+        self
+    }
+
+    pub fn with_max_restarts(mut self, max: u32) -> Self {
+        self.max_restarts = Some(max);
+        self
+    }
+
+    /// Delay before restart number `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.backoff_initial
+            .saturating_mul(factor)
+            .min(self.backoff_max)
+    }
+}
+
+impl Default for DriverConfig {
+    fn default() -> Self {
+        Self::new("kaos-driver")
+    }
+}
+
+struct Shared {
+    stop: AtomicBool,
+    state: Mutex<DriverState>,
+    restarts: AtomicU32,
+    pid: AtomicU32,
+}
+
+/// Supervised media driver process. Dropping the handle stops the driver.
+pub struct DriverHandle {
+    shared: Arc<Shared>,
+    monitor: Option<JoinHandle<()>>,
+}
+
+impl DriverHandle {
+    /// Spawn the driver and a monitor thread. `on_state` runs on the monitor
+    /// thread for every state change.
+    pub fn spawn<F>(config: DriverConfig, on_state: F) -> io::Result<Self>
+    where
+        F: FnMut(DriverState) + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            stop: AtomicBool::new(false),
+            state: Mutex::new(DriverState::Starting),
+            restarts: AtomicU32::new(0),
+            pid: AtomicU32::new(0),
+        });
+        // Fail fast on a bad program path instead of backing off forever
+        let child = start(&config)?;
+        let monitor = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("kaos-driver-supervisor".into())
+                .spawn(move || Monitor::new(config, shared, on_state).run(child))?
+        };
+        Ok(Self {
+            shared,
+            monitor: Some(monitor),
+        })
+    }
+
+    /// Latest state
+    pub fn state(&self) -> DriverState {
+        *self.shared.state.lock().unwrap()
+    }
+
+    /// Restarts so far
+    pub fn restarts(&self) -> u32 {
+        self.shared.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Current driver process id (None between restarts)
+    pub fn pid(&self) -> Option<u32> {
+        match self.shared.pid.load(Ordering::Relaxed) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// Whether the driver is up and beating
+    pub fn is_running(&self) -> bool {
+        self.state() == DriverState::Running
+    }
+
+    /// Stop supervising and kill the driver
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(monitor) = self.monitor.take() {
+            let _ = monitor.join();
+        }
+    }
+}
+
+impl Drop for DriverHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Spawn one driver process (stale heartbeat ring removed first)
+fn start(config: &DriverConfig) -> io::Result<Child> {
+    let _ = std::fs::remove_file(&config.heartbeat_path);
+    Command::new(&config.program)
+        .args(&config.args)
+        .arg("--heartbeat")
+        .arg(&config.heartbeat_path)
+        .stdin(Stdio::null())
+        .spawn()
+}
+
+struct Monitor<F> {
+    config: DriverConfig,
+    shared: Arc<Shared>,
+    on_state: F,
+}
+
+impl<F: FnMut(DriverState)> Monitor<F> {
+    fn new(config: DriverConfig, shared: Arc<Shared>, on_state: F) -> Self {
+        Self {
+            config,
+            shared,
+            on_state,
+        }
+    }
+
+    fn set_state(&mut self, state: DriverState) {
+        let changed = {
+            let mut current = self.shared.state.lock().unwrap();
+            let changed = *current != state;
+            *current = state;
+            changed
+        };
+        if changed {
+            (self.on_state)(state);
+        }
+    }
+
+    fn stopping(&self) -> bool {
+        self.shared.stop.load(Ordering::Relaxed)
+    }
+
+    fn run(mut self, first: Child) {
+        let mut child = Some(first);
+        let mut attempt = 0u32;
+        (self.on_state)(DriverState::Starting);
+
+        while !self.stopping() {
+            let mut proc = match child.take() {
+                Some(c) => c,
+                None => match start(&self.config) {
+                    Ok(c) => {
+                        self.set_state(DriverState::Starting);
+                        c
+                    }
+                    Err(_) => {
+                        // Treat a failed spawn like an immediate exit
+                        self.set_state(DriverState::Exited(None));
+                        if !self.backoff(&mut attempt) {
+                            break;
+                        }
+                        continue;
+                    }
+                },
+            };
+            self.shared.pid.store(proc.id(), Ordering::Relaxed);
+
+            let healthy_for = self.watch(&mut proc);
+            let _ = proc.kill();
+            let _ = proc.wait();
+            self.shared.pid.store(0, Ordering::Relaxed);
+
+            if self.stopping() {
+                break;
+            }
+            if healthy_for >= self.config.stable_after {
+                attempt = 0;
+            }
+            if !self.backoff(&mut attempt) {
+                break;
+            }
+        }
+        if !self.stopping() {
+            return; // Failed already reported
+        }
+        self.set_state(DriverState::Stopped);
+    }
+
+    /// Watch one process until it exits, goes silent, or we're stopped.
+    /// Returns how long it ran healthy.
+    fn watch(&mut self, proc: &mut Child) -> Duration {
+        let started = Instant::now();
+        let mut heartbeat: Option<Subscriber> = None;
+        let mut last_beat: Option<Instant> = None;
+        let mut healthy_since: Option<Instant> = None;
+
+        loop {
+            if self.stopping() {
+                break;
+            }
+            if let Ok(Some(status)) = proc.try_wait() {
+                self.set_state(DriverState::Exited(status.code()));
+                break;
+            }
+
+            if heartbeat.is_none() {
+                heartbeat = Subscriber::open(&self.config.heartbeat_path).ok();
+            }
+            if let Some(hb) = heartbeat.as_mut() {
+                let mut newest = 0;
+                hb.receive(|ts| newest = newest.max(ts));
+                let fresh_ms = self.config.heartbeat_timeout.as_millis() as u64;
+                if newest > 0 && unix_ms().saturating_sub(newest) < fresh_ms {
+                    last_beat = Some(Instant::now());
+                }
+            }
+
+            let now = Instant::now();
+            let stale = match last_beat {
+                Some(t) => now.duration_since(t) > self.config.heartbeat_timeout,
+                None => now.duration_since(started) > self.config.startup_timeout,
+            };
+            if stale {
+                self.set_state(DriverState::Unresponsive);
+                break;
+            }
+            if last_beat.is_some() {
+                healthy_since.get_or_insert(now);
+                self.set_state(DriverState::Running);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        healthy_since.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    /// Sleep before the next restart. False = give up (or stopped).
+    fn backoff(&mut self, attempt: &mut u32) -> bool {
+        *attempt += 1;
+        if self.config.max_restarts.is_some_and(|max| *attempt > max) {
+            self.set_state(DriverState::Failed);
+            return false;
+        }
+        let delay = self.config.backoff(*attempt);
+        self.set_state(DriverState::Restarting {
+            attempt: *attempt,
+            delay,
+        });
+        let until = Instant::now() + delay;
+        while Instant::now() < until {
+            if self.stopping() {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL.min(until - Instant::now()));
+        }
+        self.shared.restarts.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_to_ceiling() {
+        let config = DriverConfig::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+        assert_eq!(config.backoff(1), Duration::from_millis(100));
+        assert_eq!(config.backoff(2), Duration::from_millis(200));
+        assert_eq!(config.backoff(4), Duration::from_millis(800));
+        assert_eq!(config.backoff(5), Duration::from_secs(1));
+        assert_eq!(config.backoff(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_heartbeat_roundtrip() {
+        let path = std::env::temp_dir().join(format!("kaos-hb-unit-{}", std::process::id()));
+        let mut hb = Heartbeat::create(&path).unwrap();
+        hb.beat();
+        let mut sub = Subscriber::open(&path).unwrap();
+        let mut got = Vec::new();
+        sub.receive(|v| got.push(v));
+        assert_eq!(got.len(), 1);
+        assert!(unix_ms() - got[0] < 1000);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_restarts() {
+        // `true` exits immediately and never beats
+        let path = std::env::temp_dir().join(format!("kaos-hb-fail-{}", std::process::id()));
+        let config = DriverConfig::new("true")
+            .with_heartbeat_path(&path)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(20))
+            .with_max_restarts(2);
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let seen = states.clone();
+        let handle = DriverHandle::spawn(config, move |s| seen.lock().unwrap().push(s)).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while handle.state() != DriverState::Failed && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(handle.state(), DriverState::Failed);
+        assert_eq!(handle.restarts(), 2);
+        let states = states.lock().unwrap();
+        assert!(states.contains(&DriverState::Exited(Some(0))));
+        assert!(states.contains(&DriverState::Restarting {
+            attempt: 2,
+            delay: Duration::from_millis(20)
+        }));
+    }
+
+    #[test]
+    fn test_missing_program_fails_fast() {
+        let config = DriverConfig::new("/nonexistent/kaos-driver");
+        assert!(DriverHandle::spawn(config, |_| {}).is_err());
+    }
+}
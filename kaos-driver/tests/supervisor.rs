@@ -0,0 +1,66 @@
+//! End-to-end supervision of the real driver binary.
+
+use kaos_driver::supervisor::{DriverConfig, DriverHandle, DriverState};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn wait_for(handle: &DriverHandle, timeout: Duration, f: impl Fn(&DriverHandle) -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if f(handle) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn test_driver_restarted_after_kill() {
+    let tmp = std::env::temp_dir();
+    let id = std::process::id();
+    let hb = tmp.join(format!("kaos-sup-hb-{id}"));
+    let send = tmp.join(format!("kaos-sup-send-{id}"));
+    let recv = tmp.join(format!("kaos-sup-recv-{id}"));
+
+    let config = DriverConfig::new(env!("CARGO_BIN_EXE_kaos-driver"))
+        .with_args([
+            "127.0.0.1:0".to_string(),
+            "--echo".to_string(),
+            send.to_string_lossy().into_owned(),
+            recv.to_string_lossy().into_owned(),
+        ])
+        .with_heartbeat_path(&hb)
+        .with_backoff(Duration::from_millis(20), Duration::from_millis(100));
+    let states = Arc::new(Mutex::new(Vec::new()));
+    let seen = states.clone();
+    let handle = DriverHandle::spawn(config, move |s| seen.lock().unwrap().push(s)).unwrap();
+
+    assert!(wait_for(&handle, Duration::from_secs(5), |h| h.is_running()));
+    let first_pid = handle.pid().unwrap();
+
+    // Kill the driver behind the supervisor's back
+    std::process::Command::new("kill")
+        .args(["-9", &first_pid.to_string()])
+        .status()
+        .unwrap();
+
+    assert!(wait_for(&handle, Duration::from_secs(5), |h| {
+        h.restarts() == 1 && h.is_running()
+    }));
+    assert_ne!(handle.pid(), Some(first_pid));
+
+    handle.stop();
+    let states = states.lock().unwrap();
+    assert_eq!(states.first(), Some(&DriverState::Starting));
+    assert!(states.iter().any(|s| matches!(s, DriverState::Exited(_))));
+    assert!(states
+        .iter()
+        .any(|s| matches!(s, DriverState::Restarting { attempt: 1, .. })));
+    assert_eq!(states.last(), Some(&DriverState::Stopped));
+
+    for p in [&hb, &send, &recv] {
+        let _ = std::fs::remove_file(p);
+    }
+}
@@ -54,7 +54,7 @@ fn main() {
                 break;
             }
         }
-        while let Some(_) = from_driver.try_receive() {
+        while from_driver.try_receive().is_some() {
             received += 1;
         }
     }
@@ -15,11 +15,21 @@
 //!     println!("Got: {}", val);
 //! }
 //! ```
+//!
+//! For one-to-many fan-out (e.g. a driver feeding several application
+//! processes) use `BroadcastPublisher` / `BroadcastSubscriber`: every
+//! subscriber sees every message.
+//!
+//! Crash safety: both sides `heartbeat()` from their poll loops; the publisher
+//! checks `peer_status(lease)` and reclaims a dead or hung subscriber instead
+//! of stalling on its cursor forever.
 
-use kaos::disruptor::{SharedRingBuffer, Slot8};
+pub use kaos::disruptor::{HugePageSize, PageBacking, PeerStatus};
+use kaos::disruptor::{SharedBroadcastRingBuffer, SharedRingBuffer, Slot8};
 use kaos::{record_receive, record_send};
 use std::io;
 use std::path::Path;
+use std::time::Duration;
 
 /// Publisher (producer) - creates the shared memory file
 pub struct Publisher {
@@ -33,6 +43,22 @@ impl Publisher {
         })
     }
 
+    /// Create backed by huge pages where available (see `page_backing`)
+    pub fn create_with_hugepages<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+        size: HugePageSize,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            inner: SharedRingBuffer::create_with_hugepages(path, capacity, size)?,
+        })
+    }
+
+    /// Pages the kernel actually backs the ring with
+    pub fn page_backing(&self) -> PageBacking {
+        self.inner.page_backing()
+    }
+
     /// Send a u64 value (returns sequence number)
     pub fn send(&mut self, value: u64) -> io::Result<u64> {
         let result = self.inner.try_send(&value.to_le_bytes());
@@ -50,6 +76,21 @@ impl Publisher {
         }
         result
     }
+
+    /// Refresh this publisher's lease
+    pub fn heartbeat(&self) {
+        self.inner.heartbeat();
+    }
+
+    /// Liveness of the subscriber process
+    pub fn peer_status(&self, lease: Duration) -> PeerStatus {
+        self.inner.peer_status(lease)
+    }
+
+    /// Drop the subscriber and skip its unread messages (returns how many)
+    pub fn reclaim_subscriber(&mut self) -> u64 {
+        self.inner.reclaim_consumer()
+    }
 }
 
 /// Subscriber (consumer) - opens existing shared memory file
@@ -86,6 +127,113 @@ impl Subscriber {
     pub fn available(&mut self) -> u64 {
         self.inner.available()
     }
+
+    /// Refresh this subscriber's lease
+    pub fn heartbeat(&self) {
+        self.inner.heartbeat();
+    }
+
+    /// Liveness of the publisher process
+    pub fn peer_status(&self, lease: Duration) -> PeerStatus {
+        self.inner.peer_status(lease)
+    }
+
+    /// False once the publisher reclaimed this subscriber
+    pub fn is_attached(&self) -> bool {
+        self.inner.is_attached()
+    }
+}
+
+/// Broadcast publisher - creates the shared memory file, fans out to all subscribers
+pub struct BroadcastPublisher {
+    inner: SharedBroadcastRingBuffer<Slot8>,
+}
+
+impl BroadcastPublisher {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+        max_subscribers: usize,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            inner: SharedBroadcastRingBuffer::create(path, capacity, max_subscribers)?,
+        })
+    }
+
+    /// Send a u64 value (returns sequence number). Fails while the slowest subscriber is a full ring behind.
+    pub fn send(&mut self, value: u64) -> io::Result<u64> {
+        let result = self.inner.try_send(&value.to_le_bytes());
+        if result.is_ok() {
+            record_send(8);
+        }
+        result
+    }
+
+    /// Subscribers currently attached
+    pub fn subscribers(&self) -> usize {
+        self.inner.active_subscribers()
+    }
+
+    /// Refresh this publisher's lease
+    pub fn heartbeat(&self) {
+        self.inner.heartbeat();
+    }
+
+    /// Free dead subscribers and those silent past `lease` (returns how many)
+    pub fn reclaim_subscribers(&mut self, lease: Duration) -> usize {
+        self.inner.reclaim_subscribers(lease)
+    }
+}
+
+/// Broadcast subscriber - joins an existing broadcast file at the current tail
+pub struct BroadcastSubscriber {
+    inner: SharedBroadcastRingBuffer<Slot8>,
+}
+
+impl BroadcastSubscriber {
+    pub fn subscribe<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            inner: SharedBroadcastRingBuffer::subscribe(path)?,
+        })
+    }
+
+    /// Try to receive one message (non-blocking, for polling)
+    pub fn try_receive(&mut self) -> Option<u64> {
+        let result = self.inner.try_receive().map(|slot| slot.value);
+        if result.is_some() {
+            record_receive(8);
+        }
+        result
+    }
+
+    /// Receive all available messages via callback (batch, faster)
+    pub fn receive<F: FnMut(u64)>(&mut self, mut f: F) -> usize {
+        let count = self.inner.receive(|slot| f(slot.value));
+        if count > 0 {
+            record_receive((count * 8) as u64);
+        }
+        count
+    }
+
+    /// Number of messages available
+    pub fn available(&self) -> u64 {
+        self.inner.available()
+    }
+
+    /// Refresh this subscriber's lease
+    pub fn heartbeat(&self) {
+        self.inner.heartbeat();
+    }
+
+    /// Liveness of the publisher process
+    pub fn peer_status(&self, lease: Duration) -> PeerStatus {
+        self.inner.peer_status(lease)
+    }
+
+    /// False once the publisher reclaimed this subscriber
+    pub fn is_subscribed(&self) -> bool {
+        self.inner.is_subscribed()
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +255,34 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_reclaim_subscriber() {
+        let path = "/tmp/kaos-ipc-test-reclaim";
+        let _ = fs::remove_file(path);
+        let lease = Duration::from_secs(60);
+
+        let mut pub_ = Publisher::create(path, 4).unwrap();
+        assert_eq!(pub_.peer_status(lease), PeerStatus::Detached);
+        let sub = Subscriber::open(path).unwrap();
+        assert_eq!(pub_.peer_status(lease), PeerStatus::Alive);
+        assert_eq!(sub.peer_status(lease), PeerStatus::Alive);
+
+        for i in 0..4 {
+            pub_.send(i).unwrap();
+        }
+        assert!(pub_.send(4).is_err());
+        assert_eq!(pub_.reclaim_subscriber(), 4);
+        assert!(!sub.is_attached());
+        pub_.send(4).unwrap();
+
+        // A replacement starts after the skipped messages
+        drop(sub);
+        let mut next = Subscriber::open(path).unwrap();
+        assert_eq!(pub_.peer_status(lease), PeerStatus::Alive);
+        assert_eq!(next.try_receive(), Some(4));
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_throughput() {
         let path = "/tmp/kaos-ipc-test-throughput";
@@ -137,4 +313,26 @@ mod tests {
         assert_eq!(sum, (0..N).sum::<u64>());
         let _ = fs::remove_file(path);
     }
+
+    #[test]
+    fn test_broadcast_each_subscriber_sees_all() {
+        let path = "/tmp/kaos-ipc-test-broadcast";
+        let _ = fs::remove_file(path);
+
+        let mut pub_ = BroadcastPublisher::create(path, 1024, 4).unwrap();
+        let mut subs: Vec<_> = (0..3)
+            .map(|_| BroadcastSubscriber::subscribe(path).unwrap())
+            .collect();
+        assert_eq!(pub_.subscribers(), 3);
+
+        for i in 0..100 {
+            pub_.send(i).unwrap();
+        }
+        for sub in &mut subs {
+            let mut sum = 0;
+            assert_eq!(sub.receive(|v| sum += v), 100);
+            assert_eq!(sum, (0..100).sum::<u64>());
+        }
+        let _ = fs::remove_file(path);
+    }
 }
@@ -44,8 +44,8 @@ fn run_rudp_bench(total_events: u64) -> (f64, u64) {
 
     while sent < total_events {
         let batch_count = ((total_events - sent) as usize).min(BATCH_SIZE);
-        for i in 0..batch_count {
-            batch_data[i] = (((sent + i as u64) % 5) + 1).to_le_bytes();
+        for (i, slot) in batch_data.iter_mut().enumerate().take(batch_count) {
+            *slot = (((sent + i as u64) % 5) + 1).to_le_bytes();
         }
         let refs: [&[u8]; 16] = [
             &batch_data[0],
@@ -72,7 +72,7 @@ fn run_rudp_bench(total_events: u64) -> (f64, u64) {
                 std::hint::spin_loop();
             }
         }
-        if sent % 10000 == 0 {
+        if sent.is_multiple_of(10000) {
             transport.process_acks();
         }
     }
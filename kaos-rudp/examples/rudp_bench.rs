@@ -93,9 +93,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     while sent < target_messages {
         let count = ((target_messages - sent) as usize).min(batch_size);
 
-        for i in 0..count {
+        for (i, buf) in batch_bufs.iter_mut().enumerate().take(count) {
             let val = sent + (i as u64);
-            batch_bufs[i][0..8].copy_from_slice(&val.to_le_bytes());
+            buf[0..8].copy_from_slice(&val.to_le_bytes());
         }
 
         let refs: Vec<&[u8]> = batch_bufs[..count].iter().map(|b| &b[..]).collect();
@@ -112,7 +112,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         // Periodically process ACKs to keep the window flowing
-        if sent % 1000 == 0 {
+        if sent.is_multiple_of(1000) {
             client.process_acks();
         }
 
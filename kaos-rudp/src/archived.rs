@@ -214,21 +214,37 @@ impl ArchivedTransport {
     #[inline]
     pub fn send(&mut self, data: &[u8]) -> Result<u64, ArchivedError> {
         let seq = self.producer_seq;
+        self.tap(data);
+        self.inner.send(data)?;
+        Ok(seq)
+    }
 
-        // Write to tap buffer (lock-free, ~5ns)
-        let buffer = unsafe { self.tap_buffer.producer() };
-        if let Some((claimed_seq, slots)) = buffer.try_claim_slots(1) {
-            slots[0].set_sequence(claimed_seq);
-            slots[0].set_data(data);
-            buffer.publish_batch(claimed_seq, 1);
-            self.producer_seq += 1;
-            self.msg_count.fetch_add(1, Ordering::Release);
+    /// Archive `data` without sending it (e.g. to send it framed, see
+    /// `replication`). Returns its archive sequence.
+    #[inline]
+    pub fn record(&mut self, data: &[u8]) -> Result<u64, ArchivedError> {
+        let seq = self.producer_seq;
+        if !self.tap(data) {
+            return Err(ArchivedError::BufferFull);
         }
-
-        self.inner.send(data)?;
         Ok(seq)
     }
 
+    /// Write to tap buffer (lock-free, ~5ns); false if it's full
+    #[inline]
+    fn tap(&mut self, data: &[u8]) -> bool {
+        let buffer = unsafe { self.tap_buffer.producer() };
+        let Some((claimed_seq, slots)) = buffer.try_claim_slots(1) else {
+            return false;
+        };
+        slots[0].set_sequence(claimed_seq);
+        slots[0].set_data(data);
+        buffer.publish_batch(claimed_seq, 1);
+        self.producer_seq += 1;
+        self.msg_count.fetch_add(1, Ordering::Release);
+        true
+    }
+
     #[inline]
     pub fn send_batch(&mut self, messages: &[&[u8]]) -> Result<usize, ArchivedError> {
         if messages.is_empty() {
@@ -314,6 +330,10 @@ impl ArchivedTransport {
     pub fn inner(&self) -> &RudpTransport {
         &self.inner
     }
+
+    pub(crate) fn inner_mut(&mut self) -> &mut RudpTransport {
+        &mut self.inner
+    }
 }
 
 // Trait implementations for composability
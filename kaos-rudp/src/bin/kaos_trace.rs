@@ -0,0 +1,110 @@
+//! Render RUDP packet traces recorded with `RudpTransport::enable_trace`.
+//!
+//! Timeline: kaos-trace <file> [--kind send,recv,rtx,...] [--from-ms N] [--to-ms N] [--seq N]
+//! Summary:  kaos-trace <file> --summary
+//! Stalls:   kaos-trace <file> --stalls <ms>
+
+use kaos_rudp::{TraceKind, TraceReader, TraceRecord, TraceSummary};
+use std::io::{self, BufWriter, Write};
+
+fn usage() -> ! {
+    eprintln!("Kaos Trace Viewer");
+    eprintln!("=================");
+    eprintln!("Timeline: kaos-trace <file> [--kind send,recv,rtx,ack>,ack<,nak>,nak<,dlvr]");
+    eprintln!("                            [--from-ms N] [--to-ms N] [--seq N]");
+    eprintln!("Summary:  kaos-trace <file> --summary");
+    eprintln!("Stalls:   kaos-trace <file> --stalls <ms>   (receive gaps longer than <ms>)");
+    std::process::exit(1);
+}
+
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == name).map(|i| {
+        args.get(i + 1)
+            .map(|s| s.as_str())
+            .unwrap_or_else(|| usage())
+    })
+}
+
+fn parse_ms(value: Option<&str>) -> Option<u64> {
+    value.map(|v| {
+        let ms: f64 = v.parse().unwrap_or_else(|_| usage());
+        (ms * 1000.0) as u64
+    })
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 || args[1].starts_with('-') {
+        usage();
+    }
+
+    let reader = TraceReader::open(&args[1]).unwrap_or_else(|e| {
+        eprintln!("{}: {}", args[1], e);
+        std::process::exit(1);
+    });
+    let start_unix_us = reader.start_unix_us();
+    let records: Vec<TraceRecord> = reader.collect::<io::Result<_>>().unwrap_or_else(|e| {
+        eprintln!("{}: {}", args[1], e);
+        std::process::exit(1);
+    });
+
+    let out = io::stdout();
+    let mut out = BufWriter::new(out.lock());
+
+    if args.iter().any(|a| a == "--summary") {
+        let _ = writeln!(out, "trace         {} ({} events)", args[1], records.len());
+        let _ = writeln!(out, "started       {} (unix us)", start_unix_us);
+        let _ = writeln!(out, "{}", TraceSummary::from_records(records));
+        return;
+    }
+
+    if let Some(threshold) = parse_ms(flag_value(&args, "--stalls")) {
+        let mut last_recv: Option<u64> = None;
+        for r in records.iter().filter(|r| r.kind == TraceKind::Recv) {
+            if let Some(prev) = last_recv {
+                let gap = r.ts_us - prev;
+                if gap >= threshold {
+                    let _ = writeln!(
+                        out,
+                        "{:>12.3}ms  stall {:.3}ms before seq={}",
+                        r.ts_us as f64 / 1000.0,
+                        gap as f64 / 1000.0,
+                        r.seq
+                    );
+                }
+            }
+            last_recv = Some(r.ts_us);
+        }
+        return;
+    }
+
+    let kinds: Option<Vec<TraceKind>> = flag_value(&args, "--kind").map(|list| {
+        list.split(',')
+            .map(|k| {
+                TraceKind::parse(k).unwrap_or_else(|| {
+                    eprintln!("unknown event kind: {}", k);
+                    std::process::exit(1);
+                })
+            })
+            .collect()
+    });
+    let from_us = parse_ms(flag_value(&args, "--from-ms")).unwrap_or(0);
+    let to_us = parse_ms(flag_value(&args, "--to-ms")).unwrap_or(u64::MAX);
+    let seq: Option<u64> =
+        flag_value(&args, "--seq").map(|s| s.parse().unwrap_or_else(|_| usage()));
+
+    for r in records {
+        if r.ts_us < from_us || r.ts_us > to_us {
+            continue;
+        }
+        if kinds.as_ref().is_some_and(|k| !k.contains(&r.kind)) {
+            continue;
+        }
+        if seq.is_some_and(|s| s != r.seq) {
+            continue;
+        }
+        if writeln!(out, "{}", r).is_err() {
+            return; // e.g. piped into `head`
+        }
+    }
+}
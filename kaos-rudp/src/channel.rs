@@ -0,0 +1,154 @@
+//! Delivery channels.
+//!
+//! Every data packet carries a channel ID (0-255) and a `Reliability`.
+//! Reliable-ordered messages on all channels share the connection's
+//! sequence space, send window and retransmits, so they stay ordered with
+//! respect to each other. Unreliable channels number packets per channel
+//! and keep their own receive window: nothing is retransmitted or waited
+//! for, and sequenced channels drop anything older than what they delivered.
+
+use std::collections::HashMap;
+
+/// Delivery guarantee of a message (see `RudpTransport::send_on_channel`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reliability {
+    /// Retransmitted until ACKed, delivered in order (commands, chat)
+    #[default]
+    ReliableOrdered,
+    /// Fire and forget, delivered as it arrives
+    Unreliable,
+    /// Fire and forget, older than the newest delivered is dropped
+    /// (position updates)
+    UnreliableSequenced,
+}
+
+/// Most unreliable messages held between receive calls
+const MAX_INBOX: usize = 1024;
+
+/// Per-channel state of a connection
+pub(crate) struct Channels {
+    /// Next sequence per unreliable channel (starts at 1)
+    next_send: Box<[u64; 256]>,
+    /// Newest sequence delivered per unreliable channel (0 = none)
+    last_recv: Box<[u64; 256]>,
+    /// Channel of reliable packets not on channel 0, by sequence
+    reliable: HashMap<u64, u8>,
+    /// Unreliable messages waiting for the next receive: channel and range
+    /// of `inbox_data`
+    inbox: Vec<(u8, usize, usize)>,
+    inbox_data: Vec<u8>,
+    dropped: u64,
+}
+
+impl Channels {
+    pub fn new() -> Self {
+        Self {
+            next_send: Box::new([1; 256]),
+            last_recv: Box::new([0; 256]),
+            reliable: HashMap::new(),
+            inbox: Vec::new(),
+            inbox_data: Vec::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Sequence for the next unreliable packet on `channel`
+    pub fn next_seq(&mut self, channel: u8) -> u64 {
+        let seq = self.next_send[channel as usize];
+        self.next_send[channel as usize] = seq.wrapping_add(1);
+        seq
+    }
+
+    /// Remember the channel of reliable packet `seq`
+    pub fn mark_reliable(&mut self, seq: u64, channel: u8) {
+        if channel != 0 {
+            self.reliable.insert(seq, channel);
+        }
+    }
+
+    /// Channel of delivered reliable packet `seq`
+    pub fn reliable_channel(&mut self, seq: u64) -> u8 {
+        if self.reliable.is_empty() {
+            return 0;
+        }
+        self.reliable.remove(&seq).unwrap_or(0)
+    }
+
+    /// Hold an unreliable message for delivery, or drop it if it's stale
+    /// (sequenced) or the inbox is full
+    pub fn accept(&mut self, channel: u8, reliability: Reliability, seq: u64, payload: &[u8]) {
+        let last = &mut self.last_recv[channel as usize];
+        if reliability == Reliability::UnreliableSequenced {
+            if seq <= *last {
+                self.dropped += 1;
+                return;
+            }
+            *last = seq;
+        }
+        if self.inbox.len() >= MAX_INBOX {
+            self.dropped += 1;
+            return;
+        }
+        let start = self.inbox_data.len();
+        self.inbox_data.extend_from_slice(payload);
+        self.inbox.push((channel, start, self.inbox_data.len()));
+    }
+
+    /// Hand held unreliable messages to `f` and empty the inbox
+    pub fn drain<F: FnMut(u8, &[u8])>(&mut self, mut f: F) -> usize {
+        let count = self.inbox.len();
+        for &(channel, start, end) in &self.inbox {
+            f(channel, &self.inbox_data[start..end]);
+        }
+        self.inbox.clear();
+        self.inbox_data.clear();
+        count
+    }
+
+    /// Unreliable messages dropped (stale or inbox full)
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(channels: &mut Channels) -> Vec<(u8, Vec<u8>)> {
+        let mut got = Vec::new();
+        channels.drain(|channel, msg| got.push((channel, msg.to_vec())));
+        got
+    }
+
+    #[test]
+    fn test_sequenced_drops_stale() {
+        let mut channels = Channels::new();
+        channels.accept(2, Reliability::UnreliableSequenced, 3, b"c");
+        channels.accept(2, Reliability::UnreliableSequenced, 1, b"a");
+        channels.accept(2, Reliability::UnreliableSequenced, 3, b"c");
+        // Other channels have their own window
+        channels.accept(5, Reliability::UnreliableSequenced, 1, b"x");
+        channels.accept(2, Reliability::UnreliableSequenced, 4, b"d");
+        assert_eq!(
+            drain(&mut channels),
+            [(2, b"c".to_vec()), (5, b"x".to_vec()), (2, b"d".to_vec())]
+        );
+        assert_eq!(channels.dropped(), 2);
+        assert!(drain(&mut channels).is_empty());
+    }
+
+    #[test]
+    fn test_unreliable_keeps_arrival_order() {
+        let mut channels = Channels::new();
+        channels.accept(1, Reliability::Unreliable, 2, b"b");
+        channels.accept(1, Reliability::Unreliable, 1, b"a");
+        assert_eq!(
+            drain(&mut channels),
+            [(1, b"b".to_vec()), (1, b"a".to_vec())]
+        );
+        assert_eq!(channels.next_seq(1), 1);
+        assert_eq!(channels.next_seq(1), 2);
+        assert_eq!(channels.next_seq(0), 1);
+    }
+}
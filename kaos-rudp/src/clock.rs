@@ -0,0 +1,96 @@
+//! Time source abstraction.
+//!
+//! Session timeouts and congestion control read time through a `Clock` so
+//! tests can drive them with `TestClock` instead of sleeping:
+//!
+//! ```rust,ignore
+//! let clock = TestClock::new();
+//! server.set_clock(Arc::new(clock.clone()));
+//! clock.advance(Duration::from_secs(31)); // past the client timeout
+//! server.poll(); // clients time out, no sleep
+//! ```
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of monotonic time
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> Instant;
+
+    /// Block for `duration` (test clocks advance instead)
+    fn sleep(&self, duration: Duration);
+}
+
+/// Wall-clock time (`Instant::now`, `thread::sleep`)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Shared default clock
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Manually driven clock. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct TestClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl TestClock {
+    /// Starts at the current `Instant` and only moves on `advance`/`sleep`
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Move time forward
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_only_moves_when_advanced() {
+        let clock = TestClock::new();
+        let shared = clock.clone();
+        let t0 = clock.now();
+        assert_eq!(clock.now(), t0);
+
+        shared.advance(Duration::from_secs(5));
+        clock.sleep(Duration::from_millis(250));
+        assert_eq!(clock.now() - t0, Duration::from_millis(5250));
+    }
+}
@@ -1,9 +1,149 @@
-//! Congestion Control (AIMD)
+//! Congestion Control
 //!
-//! Additive Increase Multiplicative Decrease for network fairness.
+//! `CongestionControl` is the interface the transport drives; pick an
+//! algorithm with `CongestionAlgorithm`:
+//!
+//! - AIMD (`CongestionController`, default): Additive Increase Multiplicative
+//!   Decrease for network fairness
+//! - CUBIC (`Cubic`): cubic window growth, recovers fast on high-BDP paths
+//! - BBR (`Bbr`): window from estimated bottleneck bandwidth × min RTT,
+//!   ignores random loss
+//! - Fixed (`FixedRate`): token-bucket pacing at a set rate for LANs and
+//!   multicast, no reaction to loss
 
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crate::clock::{system_clock, Clock};
+
+/// Snapshot of a connection's congestion signals (see `rate::RateAdapter`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionQuality {
+    /// Smoothed RTT (microseconds)
+    pub rtt_us: u64,
+    /// Congestion window (packets)
+    pub window: u32,
+    /// Window ceiling (packets)
+    pub max_window: u32,
+    /// Packets in flight
+    pub in_flight: u32,
+    /// Loss signals (NAKs) seen
+    pub loss_events: u64,
+    /// ECN CE echoes seen
+    pub ecn_ce: u64,
+}
+
+/// Pluggable congestion control algorithm.
+///
+/// The transport asks `can_send()` before each packet and reports sends,
+/// ACKs (one call per packet), losses (NAKs) and RTT samples.
+pub trait CongestionControl: Send {
+    /// Can we send another packet now?
+    fn can_send(&self) -> bool;
+
+    /// Record packet sent
+    fn on_send(&mut self);
+
+    /// Record packet acknowledged
+    fn on_ack(&mut self);
+
+    /// Record loss (NAK)
+    fn on_loss(&mut self);
+
+    /// Record ECN congestion-experienced echo
+    fn on_ecn_ce(&mut self);
+
+    /// RTT sample (microseconds)
+    fn update_rtt(&mut self, sample_us: u64);
+
+    /// Congestion window (packets)
+    fn window_size(&self) -> u32;
+
+    /// Packets in flight
+    fn in_flight(&self) -> u32;
+
+    /// Smoothed RTT (microseconds)
+    fn rtt_us(&self) -> u64;
+
+    /// Switch time source
+    fn set_clock(&mut self, clock: Arc<dyn Clock>);
+
+    /// Current congestion signals
+    fn quality(&self) -> ConnectionQuality;
+}
+
+/// Congestion control selection (see `ReliableUdpConfig::congestion`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CongestionAlgorithm {
+    /// Slow start, then +1 per ACK and halve on loss
+    #[default]
+    Aimd,
+    /// CUBIC (RFC 9438)
+    Cubic,
+    /// BBR-style bandwidth and RTT estimation
+    Bbr,
+    /// Pace at `packets_per_sec`, bursting up to `burst` packets
+    Fixed { packets_per_sec: u32, burst: u32 },
+}
+
+impl CongestionAlgorithm {
+    /// Controller starting at `initial_window`, never above `max_window`
+    pub fn build(
+        self,
+        initial_window: u32,
+        max_window: u32,
+        clock: Arc<dyn Clock>,
+    ) -> Box<dyn CongestionControl> {
+        match self {
+            Self::Aimd => {
+                Box::new(CongestionController::new(initial_window, max_window).with_clock(clock))
+            }
+            Self::Cubic => Box::new(Cubic::new(initial_window, max_window).with_clock(clock)),
+            Self::Bbr => Box::new(Bbr::new(initial_window, max_window).with_clock(clock)),
+            Self::Fixed {
+                packets_per_sec,
+                burst,
+            } => Box::new(FixedRate::new(packets_per_sec, burst).with_clock(clock)),
+        }
+    }
+}
+
+/// Counters every algorithm keeps
+#[derive(Debug)]
+struct Signals {
+    in_flight: u32,
+    /// Smoothed RTT (microseconds)
+    rtt_us: u64,
+    loss_count: u64,
+    ecn_ce_count: u64,
+}
+
+impl Signals {
+    fn new() -> Self {
+        Self {
+            in_flight: 0,
+            rtt_us: 1000, // 1ms initial
+            loss_count: 0,
+            ecn_ce_count: 0,
+        }
+    }
+
+    fn update_rtt(&mut self, sample_us: u64) {
+        self.rtt_us = (self.rtt_us * 7 + sample_us) / 8;
+    }
+
+    fn quality(&self, window: u32, max_window: u32) -> ConnectionQuality {
+        ConnectionQuality {
+            rtt_us: self.rtt_us,
+            window,
+            max_window,
+            in_flight: self.in_flight,
+            loss_events: self.loss_count,
+            ecn_ce: self.ecn_ce_count,
+        }
+    }
+}
+
 /// AIMD congestion controller
 pub struct CongestionController {
     /// Current window size (packets)
@@ -20,6 +160,12 @@ pub struct CongestionController {
     last_loss: Instant,
     /// Packets in flight
     in_flight: u32,
+    /// ECN congestion-experienced signals received
+    ecn_ce_count: u64,
+    /// Loss signals received
+    loss_count: u64,
+    /// Time source for loss spacing
+    clock: Arc<dyn Clock>,
 }
 
 impl CongestionController {
@@ -32,9 +178,24 @@ impl CongestionController {
             rtt_us: 1000, // 1ms initial
             last_loss: Instant::now(),
             in_flight: 0,
+            ecn_ce_count: 0,
+            loss_count: 0,
+            clock: system_clock(),
         }
     }
 
+    /// Use `clock` instead of system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    /// Switch time source (restarts loss spacing)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.last_loss = clock.now();
+        self.clock = clock;
+    }
+
     /// Can we send more packets?
     #[inline]
     pub fn can_send(&self) -> bool {
@@ -64,13 +225,21 @@ impl CongestionController {
         }
     }
 
+    /// Record ECN congestion-experienced echo (treated like loss, nothing to resend)
+    pub fn on_ecn_ce(&mut self) {
+        self.ecn_ce_count += 1;
+        self.on_loss();
+    }
+
     /// Record loss (multiplicative decrease)
     pub fn on_loss(&mut self) {
+        self.loss_count += 1;
         // Don't decrease too frequently (at most once per RTT)
-        if self.last_loss.elapsed() > Duration::from_micros(self.rtt_us) {
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_loss) > Duration::from_micros(self.rtt_us) {
             self.ssthresh = (self.window / 2).max(self.min_window);
             self.window = self.ssthresh;
-            self.last_loss = Instant::now();
+            self.last_loss = now;
         }
     }
 
@@ -94,6 +263,28 @@ impl CongestionController {
     pub fn rtt_us(&self) -> u64 {
         self.rtt_us
     }
+
+    /// Get ECN CE echoes received
+    pub fn ecn_ce_count(&self) -> u64 {
+        self.ecn_ce_count
+    }
+
+    /// Get loss signals received (includes ECN CE)
+    pub fn loss_count(&self) -> u64 {
+        self.loss_count
+    }
+
+    /// Current congestion signals
+    pub fn quality(&self) -> ConnectionQuality {
+        ConnectionQuality {
+            rtt_us: self.rtt_us,
+            window: self.window,
+            max_window: self.max_window,
+            in_flight: self.in_flight,
+            loss_events: self.loss_count,
+            ecn_ce: self.ecn_ce_count,
+        }
+    }
 }
 
 impl Default for CongestionController {
@@ -102,6 +293,455 @@ impl Default for CongestionController {
     }
 }
 
+impl CongestionControl for CongestionController {
+    fn can_send(&self) -> bool {
+        CongestionController::can_send(self)
+    }
+
+    fn on_send(&mut self) {
+        CongestionController::on_send(self)
+    }
+
+    fn on_ack(&mut self) {
+        CongestionController::on_ack(self)
+    }
+
+    fn on_loss(&mut self) {
+        CongestionController::on_loss(self)
+    }
+
+    fn on_ecn_ce(&mut self) {
+        CongestionController::on_ecn_ce(self)
+    }
+
+    fn update_rtt(&mut self, sample_us: u64) {
+        CongestionController::update_rtt(self, sample_us)
+    }
+
+    fn window_size(&self) -> u32 {
+        self.window
+    }
+
+    fn in_flight(&self) -> u32 {
+        self.in_flight
+    }
+
+    fn rtt_us(&self) -> u64 {
+        self.rtt_us
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        CongestionController::set_clock(self, clock)
+    }
+
+    fn quality(&self) -> ConnectionQuality {
+        CongestionController::quality(self)
+    }
+}
+
+/// CUBIC scaling constant
+const CUBIC_C: f64 = 0.4;
+/// CUBIC multiplicative decrease
+const CUBIC_BETA: f64 = 0.7;
+
+/// CUBIC congestion controller (RFC 9438): after a loss the window climbs a
+/// cubic curve back to where the loss happened, then probes beyond it
+pub struct Cubic {
+    signals: Signals,
+    window: f64,
+    min_window: u32,
+    max_window: u32,
+    ssthresh: f64,
+    /// Window at the last loss
+    w_max: f64,
+    /// Seconds the cubic curve takes to get back to `w_max`
+    k: f64,
+    /// Start of the current congestion avoidance epoch
+    epoch: Instant,
+    last_loss: Instant,
+    clock: Arc<dyn Clock>,
+}
+
+impl Cubic {
+    pub fn new(initial_window: u32, max_window: u32) -> Self {
+        let clock = system_clock();
+        let now = clock.now();
+        Self {
+            signals: Signals::new(),
+            window: initial_window as f64,
+            min_window: 4,
+            max_window,
+            ssthresh: (max_window / 2) as f64,
+            w_max: initial_window as f64,
+            k: 0.0,
+            epoch: now,
+            last_loss: now,
+            clock,
+        }
+    }
+
+    /// Use `clock` instead of system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        CongestionControl::set_clock(&mut self, clock);
+        self
+    }
+}
+
+impl CongestionControl for Cubic {
+    fn can_send(&self) -> bool {
+        self.signals.in_flight < self.window_size()
+    }
+
+    fn on_send(&mut self) {
+        self.signals.in_flight = self.signals.in_flight.saturating_add(1);
+    }
+
+    fn on_ack(&mut self) {
+        self.signals.in_flight = self.signals.in_flight.saturating_sub(1);
+        if self.window < self.ssthresh {
+            // Slow start
+            self.window += 1.0;
+        } else {
+            let rtt = self.signals.rtt_us as f64 / 1e6;
+            let t = self
+                .clock
+                .now()
+                .saturating_duration_since(self.epoch)
+                .as_secs_f64();
+            // Where the curve will be one RTT from now
+            let target = CUBIC_C * (t + rtt - self.k).powi(3) + self.w_max;
+            // Never slower than Reno would be (TCP-friendly region)
+            let reno = self.w_max * CUBIC_BETA
+                + 3.0 * (1.0 - CUBIC_BETA) / (1.0 + CUBIC_BETA) * (t / rtt.max(1e-6));
+            let target = target.max(reno);
+            if target > self.window {
+                self.window += (target - self.window) / self.window;
+            } else {
+                self.window += 0.01 / self.window;
+            }
+        }
+        self.window = self.window.min(self.max_window as f64);
+    }
+
+    fn on_loss(&mut self) {
+        self.signals.loss_count += 1;
+        // At most once per RTT
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_loss)
+            <= Duration::from_micros(self.signals.rtt_us)
+        {
+            return;
+        }
+        // Fast convergence: give up bandwidth to newer flows
+        self.w_max = if self.window < self.w_max {
+            self.window * (1.0 + CUBIC_BETA) / 2.0
+        } else {
+            self.window
+        };
+        self.window = (self.window * CUBIC_BETA).max(self.min_window as f64);
+        self.ssthresh = self.window;
+        self.k = (self.w_max * (1.0 - CUBIC_BETA) / CUBIC_C).cbrt();
+        self.epoch = now;
+        self.last_loss = now;
+    }
+
+    fn on_ecn_ce(&mut self) {
+        self.signals.ecn_ce_count += 1;
+        self.on_loss();
+    }
+
+    fn update_rtt(&mut self, sample_us: u64) {
+        self.signals.update_rtt(sample_us);
+    }
+
+    fn window_size(&self) -> u32 {
+        self.window as u32
+    }
+
+    fn in_flight(&self) -> u32 {
+        self.signals.in_flight
+    }
+
+    fn rtt_us(&self) -> u64 {
+        self.signals.rtt_us
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        let now = clock.now();
+        self.epoch = now;
+        self.last_loss = now;
+        self.clock = clock;
+    }
+
+    fn quality(&self) -> ConnectionQuality {
+        self.signals.quality(self.window_size(), self.max_window)
+    }
+}
+
+/// Delivery rate samples kept for the bottleneck bandwidth max filter (rounds)
+const BBR_BW_ROUNDS: usize = 10;
+/// Re-measure the min RTT at least this often
+const BBR_MIN_RTT_WINDOW: Duration = Duration::from_secs(10);
+/// Window is this many bandwidth-delay products
+const BBR_CWND_GAIN: f64 = 2.0;
+/// Per-round gains while probing: probe up, drain, cruise
+const BBR_PROBE_GAINS: [f64; 8] = [1.25, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+
+/// BBR-style controller: estimates bottleneck bandwidth (max delivery rate
+/// over recent rounds) and the min RTT, and sizes the window to their
+/// product. Loss alone doesn't shrink the window.
+pub struct Bbr {
+    signals: Signals,
+    window: u32,
+    min_window: u32,
+    max_window: u32,
+    /// Lowest RTT sample and when it was taken
+    min_rtt_us: u64,
+    min_rtt_at: Instant,
+    /// Delivery rates (packets/s) of the last rounds
+    bw_samples: [f64; BBR_BW_ROUNDS],
+    round: usize,
+    round_start: Instant,
+    round_delivered: u32,
+    /// Startup: double per round until the bandwidth stops growing
+    startup: bool,
+    full_bw: f64,
+    full_bw_rounds: u32,
+    clock: Arc<dyn Clock>,
+}
+
+impl Bbr {
+    pub fn new(initial_window: u32, max_window: u32) -> Self {
+        let clock = system_clock();
+        let now = clock.now();
+        Self {
+            signals: Signals::new(),
+            window: initial_window,
+            min_window: 4,
+            max_window,
+            min_rtt_us: u64::MAX,
+            min_rtt_at: now,
+            bw_samples: [0.0; BBR_BW_ROUNDS],
+            round: 0,
+            round_start: now,
+            round_delivered: 0,
+            startup: true,
+            full_bw: 0.0,
+            full_bw_rounds: 0,
+            clock,
+        }
+    }
+
+    /// Use `clock` instead of system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        CongestionControl::set_clock(&mut self, clock);
+        self
+    }
+
+    /// Estimated bottleneck bandwidth (packets/s)
+    pub fn bandwidth(&self) -> f64 {
+        self.bw_samples.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// Lowest recent RTT (microseconds)
+    pub fn min_rtt_us(&self) -> u64 {
+        if self.min_rtt_us == u64::MAX {
+            self.signals.rtt_us
+        } else {
+            self.min_rtt_us
+        }
+    }
+
+    /// Still in startup (exponential growth)?
+    pub fn in_startup(&self) -> bool {
+        self.startup
+    }
+
+    /// Close a round: record its delivery rate and resize the window
+    fn end_round(&mut self, now: Instant, elapsed: Duration) {
+        let rate = self.round_delivered as f64 / elapsed.as_secs_f64();
+        self.bw_samples[self.round % BBR_BW_ROUNDS] = rate;
+        self.round += 1;
+        self.round_delivered = 0;
+        self.round_start = now;
+
+        let bw = self.bandwidth();
+        if self.startup {
+            if bw >= self.full_bw * 1.25 {
+                self.full_bw = bw;
+                self.full_bw_rounds = 0;
+            } else {
+                self.full_bw_rounds += 1;
+                self.startup = self.full_bw_rounds < 3;
+            }
+        }
+        if !self.startup {
+            let bdp = bw * self.min_rtt_us() as f64 / 1e6;
+            let gain = BBR_PROBE_GAINS[self.round % BBR_PROBE_GAINS.len()];
+            let window = (BBR_CWND_GAIN * gain * bdp).ceil() as u32;
+            self.window = window.clamp(self.min_window, self.max_window);
+        }
+    }
+}
+
+impl CongestionControl for Bbr {
+    fn can_send(&self) -> bool {
+        self.signals.in_flight < self.window
+    }
+
+    fn on_send(&mut self) {
+        self.signals.in_flight = self.signals.in_flight.saturating_add(1);
+    }
+
+    fn on_ack(&mut self) {
+        self.signals.in_flight = self.signals.in_flight.saturating_sub(1);
+        self.round_delivered += 1;
+        if self.startup {
+            self.window = (self.window + 1).min(self.max_window);
+        }
+        // One round per min RTT
+        let now = self.clock.now();
+        let elapsed = now.saturating_duration_since(self.round_start);
+        if elapsed >= Duration::from_micros(self.min_rtt_us().max(1)) {
+            self.end_round(now, elapsed);
+        }
+    }
+
+    fn on_loss(&mut self) {
+        self.signals.loss_count += 1;
+    }
+
+    fn on_ecn_ce(&mut self) {
+        self.signals.ecn_ce_count += 1;
+        self.signals.loss_count += 1;
+    }
+
+    fn update_rtt(&mut self, sample_us: u64) {
+        self.signals.update_rtt(sample_us);
+        let now = self.clock.now();
+        if sample_us <= self.min_rtt_us
+            || now.saturating_duration_since(self.min_rtt_at) >= BBR_MIN_RTT_WINDOW
+        {
+            self.min_rtt_us = sample_us;
+            self.min_rtt_at = now;
+        }
+    }
+
+    fn window_size(&self) -> u32 {
+        self.window
+    }
+
+    fn in_flight(&self) -> u32 {
+        self.signals.in_flight
+    }
+
+    fn rtt_us(&self) -> u64 {
+        self.signals.rtt_us
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        let now = clock.now();
+        self.min_rtt_at = now;
+        self.round_start = now;
+        self.clock = clock;
+    }
+
+    fn quality(&self) -> ConnectionQuality {
+        self.signals.quality(self.window, self.max_window)
+    }
+}
+
+/// Fixed-rate pacing for LANs and multicast: a token bucket refilled at
+/// `packets_per_sec`, holding up to `burst` packets. Loss doesn't slow it.
+pub struct FixedRate {
+    signals: Signals,
+    packets_per_sec: u32,
+    burst: u32,
+    /// Tokens at `refilled_at`
+    tokens: f64,
+    refilled_at: Instant,
+    clock: Arc<dyn Clock>,
+}
+
+impl FixedRate {
+    pub fn new(packets_per_sec: u32, burst: u32) -> Self {
+        let clock = system_clock();
+        Self {
+            signals: Signals::new(),
+            packets_per_sec,
+            burst: burst.max(1),
+            tokens: burst.max(1) as f64,
+            refilled_at: clock.now(),
+            clock,
+        }
+    }
+
+    /// Use `clock` instead of system time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        CongestionControl::set_clock(&mut self, clock);
+        self
+    }
+
+    /// Tokens available now
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        (self.tokens + elapsed * self.packets_per_sec as f64).min(self.burst as f64)
+    }
+}
+
+impl CongestionControl for FixedRate {
+    fn can_send(&self) -> bool {
+        self.tokens_at(self.clock.now()) >= 1.0
+    }
+
+    fn on_send(&mut self) {
+        self.signals.in_flight = self.signals.in_flight.saturating_add(1);
+        let now = self.clock.now();
+        self.tokens = (self.tokens_at(now) - 1.0).max(0.0);
+        self.refilled_at = now;
+    }
+
+    fn on_ack(&mut self) {
+        self.signals.in_flight = self.signals.in_flight.saturating_sub(1);
+    }
+
+    fn on_loss(&mut self) {
+        self.signals.loss_count += 1;
+    }
+
+    fn on_ecn_ce(&mut self) {
+        self.signals.ecn_ce_count += 1;
+        self.signals.loss_count += 1;
+    }
+
+    fn update_rtt(&mut self, sample_us: u64) {
+        self.signals.update_rtt(sample_us);
+    }
+
+    fn window_size(&self) -> u32 {
+        self.burst
+    }
+
+    fn in_flight(&self) -> u32 {
+        self.signals.in_flight
+    }
+
+    fn rtt_us(&self) -> u64 {
+        self.signals.rtt_us
+    }
+
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.refilled_at = clock.now();
+        self.clock = clock;
+    }
+
+    fn quality(&self) -> ConnectionQuality {
+        self.signals.quality(self.burst, self.burst)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,6 +764,44 @@ mod tests {
         assert!(cc.window < before);
     }
 
+    #[test]
+    fn test_ecn_ce_shrinks_window() {
+        let mut cc = CongestionController::new(32, 100);
+        std::thread::sleep(Duration::from_millis(2));
+        cc.on_ecn_ce();
+        assert_eq!(cc.window, 16);
+        assert_eq!(cc.ecn_ce_count(), 1);
+        assert_eq!(cc.in_flight(), 0);
+
+        let q = cc.quality();
+        assert_eq!((q.window, q.max_window), (16, 100));
+        assert_eq!((q.loss_events, q.ecn_ce), (1, 1));
+    }
+
+    #[test]
+    fn test_loss_backoff_at_most_once_per_rtt() {
+        use crate::clock::TestClock;
+
+        let clock = TestClock::new();
+        let mut cc = CongestionController::new(64, 256).with_clock(Arc::new(clock.clone()));
+        cc.update_rtt(1000);
+
+        // Same instant as construction: within one RTT of the last "loss"
+        cc.on_loss();
+        assert_eq!(cc.window, 64);
+
+        clock.advance(Duration::from_micros(1001));
+        cc.on_loss();
+        assert_eq!(cc.window, 32);
+        cc.on_loss();
+        assert_eq!(cc.window, 32);
+
+        clock.advance(Duration::from_millis(2));
+        cc.on_loss();
+        assert_eq!(cc.window, 16);
+        assert_eq!(cc.loss_count(), 4);
+    }
+
     #[test]
     fn test_window_grows_on_ack() {
         let mut cc = CongestionController::new(10, 1000);
@@ -173,6 +851,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cubic_backoff_and_regrowth() {
+        use crate::clock::TestClock;
+
+        let clock = TestClock::new();
+        let mut cc = Cubic::new(100, 1000).with_clock(Arc::new(clock.clone()));
+        cc.update_rtt(1000);
+        clock.advance(Duration::from_millis(2));
+        cc.on_loss();
+        assert_eq!(cc.window_size(), 70);
+
+        // Climbs back towards the window at the loss, then past it
+        for _ in 0..20 {
+            clock.advance(Duration::from_millis(100));
+            for _ in 0..cc.window_size() {
+                cc.on_send();
+                cc.on_ack();
+            }
+        }
+        assert!(cc.window_size() > 100, "window {}", cc.window_size());
+        assert_eq!(cc.in_flight(), 0);
+        assert_eq!(cc.quality().loss_events, 1);
+    }
+
+    #[test]
+    fn test_bbr_window_tracks_bdp() {
+        use crate::clock::TestClock;
+
+        let clock = TestClock::new();
+        let mut cc = Bbr::new(10, 10_000).with_clock(Arc::new(clock.clone()));
+        cc.update_rtt(10_000);
+        // Path delivers 100 packets per 10ms RTT = 10k packets/s
+        for _ in 0..40 {
+            for _ in 0..100 {
+                cc.on_send();
+                cc.on_ack();
+                clock.advance(Duration::from_micros(100));
+            }
+        }
+        assert!(!cc.in_startup());
+        assert!((cc.bandwidth() - 10_000.0).abs() < 1_000.0);
+        // 2 × BDP (100 packets), give or take the probe gain
+        let window = cc.window_size();
+        assert!((150..=250).contains(&window), "window {}", window);
+
+        // Random loss doesn't shrink it
+        cc.on_loss();
+        assert_eq!(cc.window_size(), window);
+    }
+
+    #[test]
+    fn test_fixed_rate_paces() {
+        use crate::clock::TestClock;
+
+        let clock = TestClock::new();
+        let mut cc = FixedRate::new(1000, 4).with_clock(Arc::new(clock.clone()));
+        let mut sent = 0;
+        while cc.can_send() {
+            cc.on_send();
+            sent += 1;
+        }
+        assert_eq!(sent, 4);
+
+        // 1000/s = one packet per ms
+        clock.advance(Duration::from_millis(2));
+        sent = 0;
+        while cc.can_send() {
+            cc.on_send();
+            sent += 1;
+        }
+        assert_eq!(sent, 2);
+        cc.on_loss();
+        clock.advance(Duration::from_millis(1));
+        assert!(cc.can_send());
+    }
+
+    #[test]
+    fn test_algorithm_build() {
+        let clock = system_clock();
+        let aimd = CongestionAlgorithm::default().build(64, 256, clock.clone());
+        assert_eq!(aimd.window_size(), 64);
+        let fixed = CongestionAlgorithm::Fixed {
+            packets_per_sec: 100,
+            burst: 8,
+        }
+        .build(64, 256, clock);
+        assert_eq!(fixed.window_size(), 8);
+    }
+
     #[test]
     fn test_can_send_respects_window() {
         let mut cc = CongestionController::new(2, 10);
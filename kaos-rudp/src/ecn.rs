@@ -0,0 +1,211 @@
+//! Explicit Congestion Notification (RFC 3168) for UDP sockets (Linux)
+//!
+//! Senders mark packets ECT(0); routers set CE instead of dropping when
+//! queues build. Receivers read the TOS/TCLASS byte from `recvmsg` control
+//! data and echo CE back on ACKs (`FLAG_ECN_ECHO`), which the sender treats
+//! as a loss-equivalent congestion signal.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::io;
+use std::net::UdpSocket;
+
+/// ECN codepoints (low two bits of TOS / traffic class)
+pub const ECN_NOT_ECT: u8 = 0b00;
+#[allow(dead_code)]
+pub const ECN_ECT1: u8 = 0b01;
+pub const ECN_ECT0: u8 = 0b10;
+pub const ECN_CE: u8 = 0b11;
+pub const ECN_MASK: u8 = 0b11;
+
+/// Control buffer size for one TOS/TCLASS cmsg (u64 for alignment)
+#[cfg(target_os = "linux")]
+pub const CMSG_BUF_WORDS: usize = 8;
+
+/// Mark outgoing packets ECT(0) and ask the kernel for incoming TOS bytes.
+/// The DSCP bits already set on the socket are kept.
+#[cfg(target_os = "linux")]
+pub fn enable(socket: &UdpSocket) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = socket.as_raw_fd();
+    let (level, tos, recv) = if socket.local_addr()?.is_ipv6() {
+        (libc::IPPROTO_IPV6, libc::IPV6_TCLASS, libc::IPV6_RECVTCLASS)
+    } else {
+        (libc::IPPROTO_IP, libc::IP_TOS, libc::IP_RECVTOS)
+    };
+    let current = getsockopt(fd, level, tos)?;
+    setsockopt(
+        fd,
+        level,
+        tos,
+        (current & !(ECN_MASK as i32)) | ECN_ECT0 as i32,
+    )?;
+    setsockopt(fd, level, recv, 1)
+}
+
+/// ECN needs recvmsg control data (Linux only).
+#[cfg(not(target_os = "linux"))]
+pub fn enable(_socket: &UdpSocket) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "ECN: Linux only",
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn setsockopt(fd: i32, level: i32, name: i32, value: i32) -> io::Result<()> {
+    // Safety: fd is a socket, option value is a valid i32
+    let r = unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            &value as *const i32 as *const libc::c_void,
+            std::mem::size_of::<i32>() as u32,
+        )
+    };
+    if r < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn getsockopt(fd: i32, level: i32, name: i32) -> io::Result<i32> {
+    let mut value = 0i32;
+    let mut len = std::mem::size_of::<i32>() as libc::socklen_t;
+    // Safety: fd is a socket, value/len point at a valid i32 and its size
+    let r = unsafe {
+        libc::getsockopt(
+            fd,
+            level,
+            name,
+            &mut value as *mut i32 as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if r < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(value)
+    }
+}
+
+/// ECN codepoint from a received message's control data (NOT-ECT if absent).
+///
+/// # Safety
+/// `msg` must come from a completed `recvmsg`/`recvmmsg` with valid control buffer.
+#[cfg(target_os = "linux")]
+pub unsafe fn from_cmsg(msg: &libc::msghdr) -> u8 {
+    if msg.msg_control.is_null() {
+        return ECN_NOT_ECT;
+    }
+    let mut cmsg = libc::CMSG_FIRSTHDR(msg);
+    while !cmsg.is_null() {
+        let (level, ty) = ((*cmsg).cmsg_level, (*cmsg).cmsg_type);
+        // IP_TOS arrives as one byte; IPV6_TCLASS as an int
+        if level == libc::IPPROTO_IP && ty == libc::IP_TOS {
+            return *libc::CMSG_DATA(cmsg) & ECN_MASK;
+        }
+        if level == libc::IPPROTO_IPV6 && ty == libc::IPV6_TCLASS {
+            let tclass = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const i32);
+            return tclass as u8 & ECN_MASK;
+        }
+        cmsg = libc::CMSG_NXTHDR(msg, cmsg);
+    }
+    ECN_NOT_ECT
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn test_enable_keeps_dscp() {
+        // DSCP EF (46) in the upper six bits
+        const EF: i32 = 46 << 2;
+        let v4 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        setsockopt(v4.as_raw_fd(), libc::IPPROTO_IP, libc::IP_TOS, EF).unwrap();
+        enable(&v4).unwrap();
+        let tos = getsockopt(v4.as_raw_fd(), libc::IPPROTO_IP, libc::IP_TOS).unwrap();
+        assert_eq!(tos, EF | ECN_ECT0 as i32);
+
+        let Ok(v6) = UdpSocket::bind("[::1]:0") else {
+            return; // no IPv6 loopback
+        };
+        setsockopt(v6.as_raw_fd(), libc::IPPROTO_IPV6, libc::IPV6_TCLASS, EF).unwrap();
+        enable(&v6).unwrap();
+        let tclass = getsockopt(v6.as_raw_fd(), libc::IPPROTO_IPV6, libc::IPV6_TCLASS).unwrap();
+        assert_eq!(tclass, EF | ECN_ECT0 as i32);
+    }
+
+    #[test]
+    fn test_ecn_codepoint_roundtrip() {
+        let rx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        enable(&rx).unwrap();
+        enable(&tx).unwrap();
+        // Pretend a router marked congestion
+        setsockopt(
+            tx.as_raw_fd(),
+            libc::IPPROTO_IP,
+            libc::IP_TOS,
+            ECN_CE as i32,
+        )
+        .unwrap();
+        tx.send_to(b"ce", rx.local_addr().unwrap()).unwrap();
+
+        let mut buf = [0u8; 16];
+        let mut control = [0u64; CMSG_BUF_WORDS];
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut _,
+            iov_len: buf.len(),
+        };
+        // Safety: msghdr is valid when zeroed; buffers outlive the call
+        let ecn = unsafe {
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr() as *mut _;
+            msg.msg_controllen = std::mem::size_of_val(&control) as _;
+            assert_eq!(libc::recvmsg(rx.as_raw_fd(), &mut msg, 0), 2);
+            from_cmsg(&msg)
+        };
+        assert_eq!(ecn, ECN_CE);
+    }
+
+    #[test]
+    fn test_ce_echo_shrinks_sender_window() {
+        use crate::RudpTransport;
+
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (a_addr, b_addr) = (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap());
+        drop((a_sock, b_sock));
+
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        assert!(a.enable_ecn());
+        assert!(b.enable_ecn());
+        // Pretend a router marked a's packets
+        let fd = a.socket().as_raw_fd();
+        setsockopt(fd, libc::IPPROTO_IP, libc::IP_TOS, ECN_CE as i32).unwrap();
+
+        let window = a.congestion_window();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        a.send(b"one").unwrap();
+        a.send(b"two").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let mut got = 0;
+        b.receive_batch_with(64, |_| got += 1);
+        assert_eq!(got, 2);
+        assert_eq!(b.ecn_ce_received(), 2);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        a.process_acks();
+        assert!(a.congestion_window() < window);
+    }
+}
@@ -0,0 +1,170 @@
+//! Fragmentation of messages larger than one packet.
+//!
+//! `send` splits them into fragments of up to `MAX_FRAGMENT_PAYLOAD` bytes,
+//! each with its own sequence number and `FLAG_FRAGMENT` set. Delivery is
+//! in order, so the receiver only glues consecutive fragments back together.
+
+use crate::header::ReliableUdpHeader;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Largest fragment payload: header + payload must fit a retransmit slot
+/// (`MessageSlot` holds 1024 bytes), which also keeps packets under the MTU
+pub const MAX_FRAGMENT_PAYLOAD: usize = 1024 - ReliableUdpHeader::SIZE;
+/// Default limit on a (reassembled) message
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024;
+/// Default time a half-reassembled message may wait for its next fragment
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Receive side: rebuilds fragmented messages as they are delivered
+pub(crate) struct Reassembler {
+    /// `(index, count)` of received fragments not delivered yet, by sequence
+    pending: HashMap<u64, (u16, u16)>,
+    buf: Vec<u8>,
+    /// Next fragment index expected; `count` 0 = nothing in progress
+    next: u16,
+    count: u16,
+    started: Instant,
+    max_message_size: usize,
+    timeout: Duration,
+    dropped: u64,
+}
+
+impl Reassembler {
+    pub fn new(max_message_size: usize, timeout: Duration) -> Self {
+        Self {
+            pending: HashMap::new(),
+            buf: Vec::new(),
+            next: 0,
+            count: 0,
+            started: Instant::now(),
+            max_message_size,
+            timeout,
+            dropped: 0,
+        }
+    }
+
+    pub fn set_max_message_size(&mut self, size: usize) {
+        self.max_message_size = size;
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Messages dropped half-reassembled
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Remember that `seq` is fragment `index` of `count`
+    pub fn mark(&mut self, seq: u64, index: u16, count: u16) {
+        self.pending.insert(seq, (index, count));
+    }
+
+    /// Drop a partial message that waited longer than the timeout
+    pub fn expire(&mut self, now: Instant) {
+        if self.count > 0 && now.saturating_duration_since(self.started) >= self.timeout {
+            self.abandon();
+        }
+    }
+
+    /// Deliver message `seq`: whole messages pass through, fragments are
+    /// buffered and the message is returned once the last one arrives
+    pub fn push<'a>(&'a mut self, seq: u64, msg: &'a [u8], now: Instant) -> Option<&'a [u8]> {
+        if self.pending.is_empty() && self.count == 0 {
+            return Some(msg);
+        }
+        self.expire(now);
+        let Some((index, count)) = self.pending.remove(&seq) else {
+            // A lost tail can't be retransmitted as part of a newer message
+            if self.count > 0 {
+                self.abandon();
+            }
+            return Some(msg);
+        };
+
+        if index == 0 {
+            if self.count > 0 {
+                self.abandon();
+            }
+            self.buf.clear();
+            self.count = count;
+            self.next = 0;
+            self.started = now;
+        } else if self.count == 0 || index != self.next || count != self.count {
+            if self.count > 0 {
+                self.abandon();
+            }
+            return None;
+        }
+
+        if self.buf.len() + msg.len() > self.max_message_size {
+            self.abandon();
+            return None;
+        }
+        self.buf.extend_from_slice(msg);
+        self.next += 1;
+        if self.next < self.count {
+            return None;
+        }
+        self.count = 0;
+        Some(&self.buf)
+    }
+
+    fn abandon(&mut self) {
+        self.count = 0;
+        self.buf.clear();
+        self.dropped += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reassembly() {
+        let now = Instant::now();
+        let mut r = Reassembler::new(DEFAULT_MAX_MESSAGE_SIZE, DEFAULT_REASSEMBLY_TIMEOUT);
+        assert_eq!(r.push(1, b"whole", now), Some(&b"whole"[..]));
+        for (seq, index) in (2..5).zip(0..3) {
+            r.mark(seq, index, 3);
+        }
+        assert_eq!(r.push(2, b"ab", now), None);
+        assert_eq!(r.push(3, b"cd", now), None);
+        assert_eq!(r.push(4, b"ef", now), Some(&b"abcdef"[..]));
+        assert_eq!(r.dropped(), 0);
+    }
+
+    #[test]
+    fn test_reassembly_limits() {
+        let now = Instant::now();
+        let mut r = Reassembler::new(4, Duration::from_millis(10));
+        r.mark(1, 0, 3);
+        r.mark(2, 1, 3);
+        r.mark(3, 2, 3);
+        assert_eq!(r.push(1, b"ab", now), None);
+        assert_eq!(r.push(2, b"cd", now), None);
+        // Over max_message_size
+        assert_eq!(r.push(3, b"ef", now), None);
+        assert_eq!(r.dropped(), 1);
+
+        r.mark(4, 0, 2);
+        r.mark(5, 1, 2);
+        assert_eq!(r.push(4, b"ab", now), None);
+        // Tail arrives after the timeout
+        let late = now + Duration::from_millis(20);
+        assert_eq!(r.push(5, b"cd", late), None);
+        assert_eq!(r.dropped(), 2);
+        assert_eq!(r.push(6, b"next", late), Some(&b"next"[..]));
+    }
+}
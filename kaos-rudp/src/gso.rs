@@ -0,0 +1,392 @@
+//! UDP GSO/GRO segmentation offload (Linux)
+//!
+//! GSO (`UDP_SEGMENT`): one sendmsg carries N equal-size segments, the kernel
+//! (or NIC) splits them into N datagrams. GRO (`UDP_GRO`): the kernel coalesces
+//! same-flow datagrams and reports the segment size in a cmsg.
+//!
+//! Both fall back automatically: GSO to sendmmsg, GRO to one datagram per recv.
+
+use std::io;
+use std::net::SocketAddr;
+
+#[cfg(target_os = "linux")]
+use crate::sendmmsg::BatchSender;
+
+/// SOL_UDP socket options (linux/udp.h)
+#[cfg(target_os = "linux")]
+const UDP_SEGMENT: i32 = 103;
+#[cfg(target_os = "linux")]
+const UDP_GRO: i32 = 104;
+
+/// Max segments per GSO send (UDP_MAX_SEGMENTS on older kernels)
+pub const MAX_GSO_SEGMENTS: usize = 64;
+
+/// Max bytes per GSO send (IPv4 UDP payload limit)
+const MAX_GSO_BYTES: usize = 65507;
+
+/// GRO receive buffer (one coalesced super-packet)
+#[cfg(target_os = "linux")]
+const GRO_BUFFER_SIZE: usize = 65535;
+
+/// Number of leading packets that can share one GSO send.
+///
+/// All segments must have the same size except the last, which may be shorter.
+pub fn gso_run_len(packets: &[&[u8]]) -> usize {
+    let Some(first) = packets.first() else {
+        return 0;
+    };
+    let segment = first.len();
+    if segment == 0 {
+        return 1;
+    }
+
+    let mut n = 1;
+    let mut total = segment;
+    while n < packets.len() && n < MAX_GSO_SEGMENTS {
+        let len = packets[n].len();
+        if len == 0 || len > segment || total + len > MAX_GSO_BYTES {
+            break;
+        }
+        total += len;
+        n += 1;
+        if len < segment {
+            break; // short segment must be last
+        }
+    }
+    n
+}
+
+/// Errors meaning the kernel/device can't do GSO/GRO (switch to fallback)
+#[cfg(target_os = "linux")]
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EIO) | Some(libc::EINVAL) | Some(libc::ENOPROTOOPT) | Some(libc::EOPNOTSUPP)
+    )
+}
+
+/// GSO sender with automatic sendmmsg fallback.
+#[cfg(target_os = "linux")]
+pub struct GsoSender {
+    buf: Vec<u8>,
+    fallback: BatchSender,
+    enabled: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl Default for GsoSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl GsoSender {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::with_capacity(MAX_GSO_BYTES),
+            fallback: BatchSender::new(MAX_GSO_SEGMENTS),
+            enabled: true,
+        }
+    }
+
+    /// False once the kernel rejected UDP_SEGMENT (sticky).
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Send packets to `addr`. Runs of equal-size packets go out as one GSO
+    /// super-packet. Returns number of datagrams handed to the kernel.
+    ///
+    /// # Safety
+    /// `fd` must be a valid UDP socket.
+    pub unsafe fn send_batch(
+        &mut self,
+        fd: i32,
+        packets: &[&[u8]],
+        addr: &SocketAddr,
+    ) -> io::Result<usize> {
+        let mut sent = 0;
+        while sent < packets.len() {
+            if !self.enabled {
+                return Ok(sent + self.send_fallback(fd, &packets[sent..], addr)?);
+            }
+
+            let run = gso_run_len(&packets[sent..]);
+            match self.send_segments(fd, &packets[sent..sent + run], addr) {
+                Ok(()) => sent += run,
+                Err(e) if is_unsupported(&e) => self.enabled = false,
+                Err(e) if sent > 0 && e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(sent)
+    }
+
+    unsafe fn send_fallback(
+        &mut self,
+        fd: i32,
+        packets: &[&[u8]],
+        addr: &SocketAddr,
+    ) -> io::Result<usize> {
+        let mut sent = 0;
+        for chunk in packets.chunks(MAX_GSO_SEGMENTS) {
+            let n = self.fallback.send_batch(fd, chunk, addr)?;
+            sent += n;
+            if n < chunk.len() {
+                break;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// One sendmsg; attaches UDP_SEGMENT when there's more than one segment.
+    unsafe fn send_segments(
+        &mut self,
+        fd: i32,
+        segments: &[&[u8]],
+        addr: &SocketAddr,
+    ) -> io::Result<()> {
+        self.buf.clear();
+        for s in segments {
+            self.buf.extend_from_slice(s);
+        }
+
+        let sockaddr = socket2::SockAddr::from(*addr);
+        let mut iov = libc::iovec {
+            iov_base: self.buf.as_mut_ptr() as *mut _,
+            iov_len: self.buf.len(),
+        };
+        let mut control = [0u64; 4];
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_name = sockaddr.as_ptr() as *mut _;
+        msg.msg_namelen = sockaddr.len();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+
+        if segments.len() > 1 {
+            let space = libc::CMSG_SPACE(std::mem::size_of::<u16>() as u32) as usize;
+            msg.msg_control = control.as_mut_ptr() as *mut _;
+            msg.msg_controllen = space as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_UDP;
+            (*cmsg).cmsg_type = UDP_SEGMENT;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(std::mem::size_of::<u16>() as u32) as _;
+            std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut u16, segments[0].len() as u16);
+        }
+
+        if libc::sendmsg(fd, &msg, 0) < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// Safety: GsoSender owns all its data and doesn't share references across threads
+#[cfg(target_os = "linux")]
+unsafe impl Send for GsoSender {}
+
+/// GRO receiver - splits coalesced super-packets back into datagrams.
+#[cfg(target_os = "linux")]
+pub struct GroReceiver {
+    buf: Vec<u8>,
+    /// ECN codepoint of the last receive (coalesced segments share it)
+    ecn: u8,
+}
+
+#[cfg(target_os = "linux")]
+impl GroReceiver {
+    /// Enable UDP_GRO on `fd`. Returns `None` if the kernel doesn't support it.
+    pub fn enable(fd: i32) -> Option<Self> {
+        let on: i32 = 1;
+        // Safety: fd is a socket, option value is a valid i32
+        let r = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_UDP,
+                UDP_GRO,
+                &on as *const i32 as *const libc::c_void,
+                std::mem::size_of::<i32>() as u32,
+            )
+        };
+        if r < 0 {
+            return None;
+        }
+        Some(Self {
+            buf: vec![0u8; GRO_BUFFER_SIZE],
+            ecn: 0,
+        })
+    }
+
+    /// Non-blocking receive of one (possibly coalesced) packet.
+    /// Calls `f` per datagram and returns the datagram count (0 on WouldBlock).
+    ///
+    /// # Safety
+    /// `fd` must be a valid UDP socket.
+    pub unsafe fn recv_with<F: FnMut(&[u8])>(&mut self, fd: i32, mut f: F) -> io::Result<usize> {
+        let mut iov = libc::iovec {
+            iov_base: self.buf.as_mut_ptr() as *mut _,
+            iov_len: self.buf.len(),
+        };
+        let mut control = [0u64; 8];
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut _;
+        msg.msg_controllen = std::mem::size_of_val(&control) as _;
+
+        let r = libc::recvmsg(fd, &mut msg, libc::MSG_DONTWAIT);
+        if r < 0 {
+            let e = io::Error::last_os_error();
+            return if e.kind() == io::ErrorKind::WouldBlock {
+                Ok(0)
+            } else {
+                Err(e)
+            };
+        }
+        let len = r as usize;
+        self.ecn = crate::ecn::from_cmsg(&msg);
+
+        // Segment size from UDP_GRO cmsg; absent = single datagram
+        let mut segment = len;
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_UDP && (*cmsg).cmsg_type == UDP_GRO {
+                let size = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const i32);
+                if size > 0 {
+                    segment = size as usize;
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+
+        if len == 0 {
+            f(&[]);
+            return Ok(1);
+        }
+        let mut count = 0;
+        for datagram in self.buf[..len].chunks(segment) {
+            f(datagram);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// ECN codepoint of the last `recv_with`
+    pub fn ecn(&self) -> u8 {
+        self.ecn
+    }
+}
+
+// Safety: GroReceiver owns all its data and doesn't share references across threads
+#[cfg(target_os = "linux")]
+unsafe impl Send for GroReceiver {}
+
+// Non-Linux: stubs (API compatibility)
+#[cfg(not(target_os = "linux"))]
+#[allow(dead_code)]
+#[derive(Default)]
+pub struct GsoSender;
+
+#[cfg(not(target_os = "linux"))]
+#[allow(dead_code)]
+impl GsoSender {
+    pub fn new() -> Self {
+        Self
+    }
+    pub fn is_enabled(&self) -> bool {
+        false
+    }
+    pub unsafe fn send_batch(&mut self, _: i32, _: &[&[u8]], _: &SocketAddr) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "GSO: Linux only",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gso_run_len_equal_sizes() {
+        let p = [0u8; 100];
+        let packets: Vec<&[u8]> = vec![&p; 10];
+        assert_eq!(gso_run_len(&packets), 10);
+    }
+
+    #[test]
+    fn test_gso_run_len_short_tail() {
+        let full = [0u8; 100];
+        let short = [0u8; 40];
+        let packets: Vec<&[u8]> = vec![&full, &full, &short, &full];
+        // Short segment ends the run
+        assert_eq!(gso_run_len(&packets), 3);
+    }
+
+    #[test]
+    fn test_gso_run_len_larger_breaks() {
+        let small = [0u8; 10];
+        let big = [0u8; 20];
+        let packets: Vec<&[u8]> = vec![&small, &big];
+        assert_eq!(gso_run_len(&packets), 1);
+        assert_eq!(gso_run_len(&[]), 0);
+    }
+
+    #[test]
+    fn test_gso_run_len_limits() {
+        let p = [0u8; 8];
+        let packets: Vec<&[u8]> = vec![&p; MAX_GSO_SEGMENTS * 2];
+        assert_eq!(gso_run_len(&packets), MAX_GSO_SEGMENTS);
+
+        let jumbo = vec![0u8; 30000];
+        let packets: Vec<&[u8]> = vec![&jumbo; 4];
+        assert_eq!(gso_run_len(&packets), 2); // 3 x 30000 > 65507
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_gso_send_gro_receive() {
+        use std::net::UdpSocket;
+        use std::os::unix::io::AsRawFd;
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let recv_addr = receiver.local_addr().unwrap();
+
+        let mut gro = GroReceiver::enable(receiver.as_raw_fd());
+
+        let packets: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 100]).collect();
+        let refs: Vec<&[u8]> = packets.iter().map(|p| p.as_slice()).collect();
+        let mut gso = GsoSender::new();
+        let sent = unsafe {
+            gso.send_batch(sender.as_raw_fd(), &refs, &recv_addr)
+                .unwrap()
+        };
+        assert_eq!(sent, 10);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        // Either path must yield the original 10 datagrams in order
+        let mut got: Vec<Vec<u8>> = Vec::new();
+        match gro.as_mut() {
+            Some(gro) => loop {
+                let n = unsafe { gro.recv_with(receiver.as_raw_fd(), |d| got.push(d.to_vec())) }
+                    .unwrap();
+                if n == 0 {
+                    break;
+                }
+            },
+            None => {
+                receiver.set_nonblocking(true).unwrap();
+                let mut buf = [0u8; 2048];
+                while let Ok(len) = receiver.recv(&mut buf) {
+                    got.push(buf[..len].to_vec());
+                }
+            }
+        }
+        assert_eq!(got, packets);
+    }
+}
@@ -2,8 +2,9 @@
 //!
 //! Re-exports shared types from `kaos-shared` for backward compatibility.
 
+use crate::channel::Reliability;
 use bytemuck::{Pod, Zeroable};
-use kaos::crc32;
+use kaos::checksum::Checksum;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // Re-export MessageType from kaos-shared (single source of truth)
@@ -11,6 +12,26 @@ pub use kaos_shared::MessageType;
 
 /// Header flags
 pub const FLAG_NO_CRC: u8 = 0x01;
+/// Ping/Pong is a path MTU probe (sequence = probe size in bytes)
+pub const FLAG_MTU_PROBE: u8 = 0x02;
+/// ACK echoes a CE mark seen since the previous ACK (ECN-Echo)
+pub const FLAG_ECN_ECHO: u8 = 0x04;
+/// Ping/Pong carries a NAT traversal control message (see `nat`)
+pub const FLAG_NAT: u8 = 0x08;
+/// Data/Ping/Pong belongs to a server relay (payload starts with the u64 token)
+pub const FLAG_RELAY: u8 = 0x10;
+/// ACK payload carries SACK blocks (see `sack`)
+pub const FLAG_SACK: u8 = 0x20;
+/// Data is one fragment of a larger message (see `set_fragment`)
+pub const FLAG_FRAGMENT: u8 = 0x40;
+/// Data is on an unreliable channel: `sequence` counts per channel and it's
+/// never retransmitted (see `set_reliability`)
+pub const FLAG_UNRELIABLE: u8 = 0x80;
+
+/// Most fragments in one message (12-bit count)
+pub const MAX_FRAGMENTS: usize = 0xFFF;
+/// `session_id` bit set on unreliable packets that must not arrive out of order
+const SEQUENCED_BIT: u32 = 1;
 
 /// Magic marker for FastHeader format
 pub const FAST_HEADER_MAGIC: u32 = 0x80000000;
@@ -35,7 +56,10 @@ impl FastHeader {
     }
 }
 
-/// Full 24-byte header with CRC
+/// Full 24-byte header with CRC.
+///
+/// `session_id` carries the channel in its top byte, and below it either
+/// the fragment count and index (12 bits each) or the unreliable mode.
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct ReliableUdpHeader {
@@ -89,16 +113,84 @@ impl ReliableUdpHeader {
     }
 
     pub fn calculate_checksum(&mut self, payload: &[u8]) {
+        self.calculate_checksum_with(Checksum::Crc32, payload);
+    }
+
+    pub fn verify_checksum(&self, payload: &[u8]) -> bool {
+        self.verify_checksum_with(Checksum::Crc32, payload)
+    }
+
+    /// Checksum with the connection's algorithm (`None` sets `FLAG_NO_CRC`)
+    pub fn calculate_checksum_with(&mut self, kind: Checksum, payload: &[u8]) {
+        if kind == Checksum::None {
+            self.flags |= FLAG_NO_CRC;
+        }
         self.checksum = 0;
         let header_bytes = bytemuck::bytes_of(self);
-        let mut crc = crc32::crc32_simd(header_bytes);
-        crc = crc32::crc32_incremental(crc, payload);
-        self.checksum = crc;
+        self.checksum = kind
+            .builder()
+            .update(header_bytes)
+            .update(payload)
+            .finalize();
     }
 
-    pub fn verify_checksum(&self, payload: &[u8]) -> bool {
+    /// Mark as fragment `index` of `count` (at most `MAX_FRAGMENTS`); set
+    /// before the checksum
+    pub fn set_fragment(&mut self, index: u16, count: u16) {
+        debug_assert!(index < count && count as usize <= MAX_FRAGMENTS);
+        self.flags |= FLAG_FRAGMENT;
+        self.session_id = (self.session_id & 0xFF00_0000) | ((count as u32) << 12) | index as u32;
+    }
+
+    /// `(index, count)` if this is a fragment
+    pub fn fragment(&self) -> Option<(u16, u16)> {
+        if self.flags & FLAG_FRAGMENT == 0 {
+            return None;
+        }
+        let session_id = self.session_id;
+        Some((
+            (session_id & 0xFFF) as u16,
+            ((session_id >> 12) & 0xFFF) as u16,
+        ))
+    }
+
+    /// Channel the packet belongs to; set before the checksum
+    pub fn set_channel(&mut self, channel: u8) {
+        self.session_id = (self.session_id & 0x00FF_FFFF) | ((channel as u32) << 24);
+    }
+
+    pub fn channel(&self) -> u8 {
+        (self.session_id >> 24) as u8
+    }
+
+    /// Delivery guarantee for a data packet; set before the checksum
+    pub fn set_reliability(&mut self, reliability: Reliability) {
+        match reliability {
+            Reliability::ReliableOrdered => self.flags &= !FLAG_UNRELIABLE,
+            Reliability::Unreliable => self.flags |= FLAG_UNRELIABLE,
+            Reliability::UnreliableSequenced => {
+                self.flags |= FLAG_UNRELIABLE;
+                self.session_id |= SEQUENCED_BIT;
+            }
+        }
+    }
+
+    pub fn reliability(&self) -> Reliability {
+        if self.flags & FLAG_UNRELIABLE == 0 {
+            Reliability::ReliableOrdered
+        } else if self.session_id & SEQUENCED_BIT != 0 {
+            Reliability::UnreliableSequenced
+        } else {
+            Reliability::Unreliable
+        }
+    }
+
+    pub fn verify_checksum_with(&self, kind: Checksum, payload: &[u8]) -> bool {
+        if kind == Checksum::None {
+            return true;
+        }
         let mut temp = *self;
-        temp.calculate_checksum(payload);
+        temp.calculate_checksum_with(kind, payload);
         temp.checksum == self.checksum
     }
 }
@@ -24,10 +24,17 @@
 //!
 //! - Sequence numbering for ordering
 //! - NAK (Negative Acknowledgment) for retransmission requests
+//! - Data, ACKs and NAKs share one socket, told apart by `MessageType`
+//!   (`new_legacy` keeps the old NAK socket on port + 1 for older peers)
 //! - Sliding window flow control
+//! - Messages larger than a packet are fragmented and reassembled
+//! - Channels: reliable-ordered, unreliable and unreliable-sequenced
+//! - Path MTU discovery caps batched datagrams at what the path carries
 //! - Multicast-friendly (no ACKs required)
 
-use kaos::disruptor::{MessageRingBuffer, RingBufferConfig, RingBufferEntry};
+use kaos::disruptor::{
+    MessageSlot, OverflowRingBuffer, Placement, RingBufferConfig, RingBufferEntry,
+};
 use std::cell::RefCell;
 use std::net::{SocketAddr, UdpSocket};
 
@@ -41,14 +48,29 @@ const LARGE_MSG_SIZE: usize = 2048;
 const RECV_PACKET_SIZE: usize = 2048;
 /// Batch size for recvmmsg (4 packets per syscall - memory optimized)
 /// Memory per thread: 4 × 2KB = 8KB (vs 16 × 64KB = 1MB before)
+#[cfg(not(any(target_os = "linux", windows)))]
 const RECV_BATCH_SIZE: usize = 4;
 /// Socket buffer size (2MB for reasonable throughput)
 const SOCKET_BUFFER_SIZE: i32 = 2 * 1024 * 1024;
+/// Keepalive ping after this long without sending (see `set_keepalive_interval`)
+pub const DEFAULT_KEEPALIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// Peer counts as dead after this long without a packet (see `set_peer_timeout`)
+pub const DEFAULT_PEER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Retransmission timeout bounds
+const MIN_RTO: std::time::Duration = std::time::Duration::from_millis(200);
+const MAX_RTO: std::time::Duration = std::time::Duration::from_secs(10);
+/// Unacked packets resent per retransmission timeout
+const RTO_BURST: usize = 8;
+/// recv_packets rounds per `process_acks` on the shared socket, so a peer
+/// that never stops sending can't keep it from returning
+const ACK_RECV_ROUNDS: usize = 16;
 
 thread_local! {
     static SEND_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(SEND_BUFFER_SIZE));
     static LARGE_MSG_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(LARGE_MSG_SIZE));
+    #[cfg(not(any(target_os = "linux", windows)))]
     static RECV_BUFFERS: RefCell<Vec<[u8; RECV_PACKET_SIZE]>> = RefCell::new(vec![[0u8; RECV_PACKET_SIZE]; RECV_BATCH_SIZE]);
+    #[cfg(not(any(target_os = "linux", windows)))]
     static RECV_LENS: RefCell<Vec<usize>> = RefCell::new(vec![0usize; RECV_BATCH_SIZE]);
 }
 
@@ -57,20 +79,39 @@ mod transport;
 
 #[cfg(feature = "archive")]
 pub mod archived;
+mod channel;
+pub mod clock;
 pub mod congestion;
 #[cfg(feature = "driver")]
 pub mod driver;
+mod ecn;
+mod fragment;
+mod gso;
 #[cfg(feature = "multicast")]
 pub mod multicast;
 #[cfg(feature = "mux")]
 pub mod mux;
 #[cfg(feature = "mux")]
 pub mod mux_adapter;
+pub mod nat;
+pub mod pmtud;
+pub mod rate;
+#[cfg(feature = "mux")]
+pub mod relay;
+#[cfg(feature = "archive")]
+pub mod replication;
+pub mod sack;
+#[cfg(feature = "encryption")]
+pub mod secure;
 mod sendmmsg;
+pub mod trace;
 // server.rs removed - use MuxRudpServer with mux_key=0 for single-game servers
 mod window;
 
-pub use header::{FastHeader, MessageType, ReliableUdpHeader, FAST_HEADER_MAGIC, FLAG_NO_CRC};
+pub use header::{
+    FastHeader, MessageType, ReliableUdpHeader, FAST_HEADER_MAGIC, FLAG_ECN_ECHO, FLAG_FRAGMENT,
+    FLAG_MTU_PROBE, FLAG_NAT, FLAG_NO_CRC, FLAG_RELAY, FLAG_SACK, FLAG_UNRELIABLE, MAX_FRAGMENTS,
+};
 
 // Tracing macros - no-op when feature disabled
 #[cfg(feature = "tracing")]
@@ -94,44 +135,111 @@ pub use transport::{ClientTransport, ClientTransportConfig};
 
 #[cfg(feature = "archive")]
 pub use archived::{ArchivedError, ArchivedTransport};
-use congestion::CongestionController;
+pub use channel::Reliability;
+pub use clock::{Clock, SystemClock, TestClock};
 pub use congestion::CongestionController as Congestion;
+pub use congestion::{
+    Bbr, CongestionAlgorithm, CongestionControl, CongestionController, ConnectionQuality, Cubic,
+    FixedRate,
+};
 #[cfg(feature = "driver")]
 pub use driver::DriverTransport;
+pub use fragment::{DEFAULT_MAX_MESSAGE_SIZE, DEFAULT_REASSEMBLY_TIMEOUT, MAX_FRAGMENT_PAYLOAD};
+pub use kaos::checksum::Checksum;
 use kaos::{record_backpressure, record_receive, record_retransmit, record_send};
 #[cfg(feature = "multicast")]
-pub use multicast::{MulticastSocket, MulticastTransport};
+pub use multicast::{
+    Delivery, MulticastConfig, MulticastSocket, MulticastTransport, ReceiverLag, ResyncRequest,
+    SequenceStats,
+};
 #[cfg(feature = "mux")]
-pub use mux::{MuxHandler, MuxRudpServer};
+pub use mux::{MuxHandler, MuxRudpServer, RendezvousStats};
 #[cfg(feature = "mux")]
 pub use mux_adapter::MuxRudpAdapter;
+pub use nat::{HolePuncher, NatMessage, PunchConfig, PunchState};
+pub use pmtud::{FixedMtu, MtuDiscovery, PathMtuProber, PmtudConfig};
+pub use rate::{DetailLevel, RateAdapter, RateAdapterConfig, SendRate};
+#[cfg(feature = "mux")]
+pub use relay::{RelayConfig, RelayStats};
+#[cfg(feature = "archive")]
+pub use replication::{ReplicaReceiver, ReplicationSender};
+pub use sack::SendWindowOccupancy;
+#[cfg(feature = "encryption")]
+pub use secure::SecureTransport;
+pub use trace::{TraceKind, TraceReader, TraceRecord, TraceRecorder, TraceSummary};
 // RudpServer removed - use MuxRudpServer/MuxRudpAdapter instead
 use window::BitmapWindow;
 
 /// Reliable UDP transport with ring buffer for retransmission.
 pub struct RudpTransport {
     socket: std::sync::Arc<UdpSocket>,
-    nak_socket: UdpSocket,
-    send_window: MessageRingBuffer,
+    /// Compat mode (`new_legacy`): ACK/NAK on local port + 1
+    nak_socket: Option<UdpSocket>,
+    /// Retransmit ring; spills into its overflow queue if `set_send_overflow` is on
+    send_window: OverflowRingBuffer<MessageSlot>,
     recv_window: BitmapWindow,
     window_size: usize,
     next_send_seq: u64,
     acked_seq: u64,
+    /// SACKed sequences above the cumulative ACK
+    sack: sack::SackScoreboard,
     remote_addr: SocketAddr,
+    /// Where ACKs and NAKs go: `remote_addr`, or its port + 1 in compat mode
     remote_nak_addr: SocketAddr,
-    congestion: CongestionController,
+    /// Congestion control (see `set_congestion`)
+    congestion: Box<dyn CongestionControl>,
+    congestion_algorithm: CongestionAlgorithm,
     /// Last send timestamp for RTT measurement
     last_send_time: std::time::Instant,
     /// Last NAK send time for backoff
     last_nak_time: std::time::Instant,
+    /// Time source for RTT samples, NAK pacing and congestion control
+    clock: std::sync::Arc<dyn clock::Clock>,
     /// Pending retransmits (limited queue)
     retransmit_queue: std::collections::VecDeque<u64>,
-    /// Linux batch sender for sendmmsg optimization
+    /// Linux GSO sender for batch retransmit (falls back to sendmmsg)
     #[cfg(target_os = "linux")]
+    gso_sender: gso::GsoSender,
+    /// Windows batch sender (WSASendMsg) for batch retransmit
+    #[cfg(windows)]
     batch_sender: sendmmsg::BatchSender,
-    /// Linux batch receiver for recvmmsg optimization
-    #[cfg(target_os = "linux")]
+    /// Batch receiver (recvmmsg on Linux, WSARecvMsg on Windows)
+    #[cfg(any(target_os = "linux", windows))]
     batch_receiver: sendmmsg::BatchReceiver,
+    /// Linux GRO receiver (opt-in via `enable_gro()`)
+    #[cfg(target_os = "linux")]
+    gro_receiver: Option<gso::GroReceiver>,
+    /// ECN marking/reading enabled (`enable_ecn()`)
+    ecn_enabled: bool,
+    /// CE seen since the last ACK (echo on next ACK)
+    ecn_ce_pending: bool,
+    /// CE-marked packets received
+    ecn_ce_received: u64,
+    /// Packet trace (see `enable_trace`)
+    trace: Option<trace::TraceRecorder>,
+    /// Header checksum (both peers must agree, see `set_checksum`)
+    checksum: Checksum,
+    /// Rebuilds fragmented messages on delivery
+    reassembler: fragment::Reassembler,
+    /// Per-channel sequences and receive windows
+    channels: channel::Channels,
+    /// Keepalive ping when nothing was sent for this long
+    keepalive_interval: std::time::Duration,
+    last_ping: std::time::Instant,
+    /// Silence after which the peer counts as dead
+    peer_timeout: std::time::Duration,
+    /// Anything received since the last `poll_timers`
+    peer_heard: bool,
+    last_heard: std::time::Instant,
+    peer_alive: bool,
+    on_disconnect: Option<Box<dyn FnMut(SocketAddr) + Send>>,
+    /// Retransmission timer: armed while packets are unacked, restarted by
+    /// ACK progress, doubled per consecutive timeout
+    rto_armed: bool,
+    rto_start: std::time::Instant,
+    rto_backoff: u32,
+    /// Path MTU discovery; bounds `send_batch_ultra` datagrams
+    mtu: Box<dyn MtuDiscovery>,
 }
 
 #[derive(Debug, Clone)]
@@ -139,6 +247,22 @@ pub struct ReliableUdpConfig {
     pub local_addr: String,
     pub remote_addr: String,
     pub window_size: usize,
+    /// Header checksum; must match the peer's
+    pub checksum: Checksum,
+    /// ACK/NAK on port + 1 for peers that predate the single socket
+    pub legacy_nak_port: bool,
+    /// Largest message `send` accepts and the receiver reassembles
+    pub max_message_size: usize,
+    /// How long a half-reassembled message waits for its next fragment
+    pub reassembly_timeout: std::time::Duration,
+    /// Congestion control algorithm
+    pub congestion: CongestionAlgorithm,
+    /// Keepalive ping after this long without sending
+    pub keepalive_interval: std::time::Duration,
+    /// Peer counts as dead after this long without a packet
+    pub peer_timeout: std::time::Duration,
+    /// Path MTU probing; `None` assumes `pmtud::BASE_MTU`
+    pub pmtud: Option<PmtudConfig>,
 }
 
 impl Default for ReliableUdpConfig {
@@ -147,27 +271,76 @@ impl Default for ReliableUdpConfig {
             local_addr: "127.0.0.1:0".to_string(),
             remote_addr: "127.0.0.1:0".to_string(),
             window_size: 1024,
+            checksum: Checksum::default(),
+            legacy_nak_port: false,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            reassembly_timeout: DEFAULT_REASSEMBLY_TIMEOUT,
+            congestion: CongestionAlgorithm::default(),
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            peer_timeout: DEFAULT_PEER_TIMEOUT,
+            pmtud: Some(PmtudConfig::default()),
         }
     }
 }
 
 impl RudpTransport {
+    /// Data, ACKs and NAKs on one socket, so only `bind_addr`'s port has
+    /// to get through NATs and firewalls.
     pub fn new(
         bind_addr: SocketAddr,
         remote_addr: SocketAddr,
         window_size: usize,
     ) -> std::io::Result<Self> {
+        Self::bind(bind_addr, remote_addr, window_size, false)
+    }
+
+    /// Compat mode for peers that predate the single socket: ACKs and NAKs
+    /// go through a second socket on the bound port + 1, to the remote's
+    /// port + 1. Both peers must use it.
+    pub fn new_legacy(
+        bind_addr: SocketAddr,
+        remote_addr: SocketAddr,
+        window_size: usize,
+    ) -> std::io::Result<Self> {
+        Self::bind(bind_addr, remote_addr, window_size, true)
+    }
+
+    fn bind(
+        bind_addr: SocketAddr,
+        remote_addr: SocketAddr,
+        window_size: usize,
+        legacy_nak_port: bool,
+    ) -> std::io::Result<Self> {
+        // Unspecified bind follows the remote's IP version ("0.0.0.0" -> "[::]" for v6 peers)
+        let bind_addr =
+            if bind_addr.ip().is_unspecified() && bind_addr.is_ipv4() != remote_addr.is_ipv4() {
+                let ip: std::net::IpAddr = if remote_addr.is_ipv4() {
+                    std::net::Ipv4Addr::UNSPECIFIED.into()
+                } else {
+                    std::net::Ipv6Addr::UNSPECIFIED.into()
+                };
+                SocketAddr::new(ip, bind_addr.port())
+            } else {
+                bind_addr
+            };
+
         let socket = UdpSocket::bind(bind_addr)?;
         socket.set_nonblocking(true)?;
+        // Oversized datagrams are dropped, not fragmented (see `path_mtu`)
+        pmtud::set_dont_fragment(&socket)?;
 
         // Get actual bound port (important when bind_addr uses port 0)
         let actual_addr = socket.local_addr()?;
 
-        // Create NAK socket on actual_port+1
-        let nak_port = actual_addr.port() + 1;
-        let nak_bind_addr = SocketAddr::new(actual_addr.ip(), nak_port);
-        let nak_socket = UdpSocket::bind(nak_bind_addr)?;
-        nak_socket.set_nonblocking(true)?;
+        // Compat: NAK socket on actual_port+1
+        let nak_socket = if legacy_nak_port {
+            let nak_bind_addr = SocketAddr::new(actual_addr.ip(), actual_addr.port() + 1);
+            let nak_socket = UdpSocket::bind(nak_bind_addr)?;
+            nak_socket.set_nonblocking(true)?;
+            Some(nak_socket)
+        } else {
+            None
+        };
 
         #[cfg(unix)]
         {
@@ -192,15 +365,18 @@ impl RudpTransport {
             }
         }
 
-        // Remote NAK port is also port+1
-        let remote_nak_addr = SocketAddr::new(remote_addr.ip(), remote_addr.port() + 1);
+        let remote_nak_addr = if legacy_nak_port {
+            SocketAddr::new(remote_addr.ip(), remote_addr.port() + 1)
+        } else {
+            remote_addr
+        };
 
         let config = RingBufferConfig::new(window_size)
             .map_err(|e| std::io::Error::other(format!("Invalid window size: {}", e)))?
             .with_consumers(1)
             .map_err(|e| std::io::Error::other(format!("Config error: {}", e)))?;
 
-        let send_window = MessageRingBuffer::new(config)
+        let send_window = OverflowRingBuffer::new(config, 0)
             .map_err(|e| std::io::Error::other(format!("RingBuffer error: {}", e)))?;
 
         Ok(Self {
@@ -211,16 +387,48 @@ impl RudpTransport {
             window_size,
             next_send_seq: 0,
             acked_seq: 0,
+            sack: sack::SackScoreboard::new(window_size),
             remote_addr,
             remote_nak_addr,
-            congestion: CongestionController::new(64, window_size as u32),
+            congestion: CongestionAlgorithm::default().build(
+                64,
+                window_size as u32,
+                clock::system_clock(),
+            ),
+            congestion_algorithm: CongestionAlgorithm::default(),
             last_send_time: std::time::Instant::now(),
             last_nak_time: std::time::Instant::now(),
+            clock: clock::system_clock(),
             retransmit_queue: std::collections::VecDeque::with_capacity(64),
             #[cfg(target_os = "linux")]
+            gso_sender: gso::GsoSender::new(),
+            #[cfg(windows)]
             batch_sender: sendmmsg::BatchSender::new(64),
-            #[cfg(target_os = "linux")]
+            #[cfg(any(target_os = "linux", windows))]
             batch_receiver: sendmmsg::BatchReceiver::new(64, RECV_PACKET_SIZE),
+            #[cfg(target_os = "linux")]
+            gro_receiver: None,
+            ecn_enabled: false,
+            ecn_ce_pending: false,
+            ecn_ce_received: 0,
+            trace: None,
+            checksum: Checksum::default(),
+            reassembler: fragment::Reassembler::new(
+                DEFAULT_MAX_MESSAGE_SIZE,
+                DEFAULT_REASSEMBLY_TIMEOUT,
+            ),
+            channels: channel::Channels::new(),
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            last_ping: std::time::Instant::now(),
+            peer_timeout: DEFAULT_PEER_TIMEOUT,
+            peer_heard: false,
+            last_heard: std::time::Instant::now(),
+            peer_alive: true,
+            on_disconnect: None,
+            rto_armed: false,
+            rto_start: std::time::Instant::now(),
+            rto_backoff: 0,
+            mtu: Box::new(PathMtuProber::default()),
         })
     }
 
@@ -237,12 +445,53 @@ impl RudpTransport {
                 format!("Invalid remote_addr: {}", e),
             )
         })?;
-        Self::new(bind_addr, remote_addr, config.window_size)
+        let mut transport = Self::bind(
+            bind_addr,
+            remote_addr,
+            config.window_size,
+            config.legacy_nak_port,
+        )?;
+        transport.set_checksum(config.checksum);
+        transport.set_max_message_size(config.max_message_size);
+        transport.set_reassembly_timeout(config.reassembly_timeout);
+        transport.set_congestion(config.congestion);
+        transport.set_keepalive_interval(config.keepalive_interval);
+        transport.set_peer_timeout(config.peer_timeout);
+        transport.set_mtu_discovery(match config.pmtud {
+            Some(cfg) => Box::new(PathMtuProber::new(cfg)),
+            None => Box::new(FixedMtu::default()),
+        });
+        Ok(transport)
     }
 
+    /// Send a message. Messages over `MAX_FRAGMENT_PAYLOAD` bytes go out as
+    /// fragments (one sequence number each, the last one is returned) and
+    /// are reassembled by the receiver; up to `set_max_message_size`.
     pub fn send(&mut self, data: &[u8]) -> std::io::Result<u64> {
-        // Congestion control: check if we can send
-        if !self.congestion.can_send() {
+        self.send_on_channel(0, data, Reliability::ReliableOrdered)
+    }
+
+    /// Send a message on `channel`. Reliable-ordered messages on every
+    /// channel share one sequence space (see `send`); unreliable ones are
+    /// sent right away, never retransmitted, must fit in one packet
+    /// (`MAX_FRAGMENT_PAYLOAD`) and return their per-channel sequence.
+    pub fn send_on_channel(
+        &mut self,
+        channel: u8,
+        data: &[u8],
+        reliability: Reliability,
+    ) -> std::io::Result<u64> {
+        if reliability != Reliability::ReliableOrdered {
+            return self.send_unreliable(channel, data, reliability);
+        }
+        self.flush_send_queue();
+        if data.len() > MAX_FRAGMENT_PAYLOAD {
+            return self.send_fragments(channel, data);
+        }
+
+        // Congestion control: check if we can send (or queue, in overflow mode)
+        let congested = !self.congestion.can_send();
+        if congested && self.send_window.overflow_capacity() == 0 {
             record_backpressure();
             return Err(std::io::Error::new(
                 std::io::ErrorKind::WouldBlock,
@@ -250,9 +499,119 @@ impl RudpTransport {
             ));
         }
 
+        self.send_packet(channel, data, None, congested)
+    }
+
+    /// Unreliable packet straight to the socket, outside the send window
+    fn send_unreliable(
+        &mut self,
+        channel: u8,
+        data: &[u8],
+        reliability: Reliability,
+    ) -> std::io::Result<u64> {
+        if data.len() > MAX_FRAGMENT_PAYLOAD {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Unreliable message of {} bytes exceeds {}",
+                    data.len(),
+                    MAX_FRAGMENT_PAYLOAD
+                ),
+            ));
+        }
+        let seq = self.channels.next_seq(channel);
+        let mut header = ReliableUdpHeader::new(0, seq, MessageType::Data, data.len() as u16);
+        header.set_channel(channel);
+        header.set_reliability(reliability);
+        header.calculate_checksum_with(self.checksum, data);
+
+        SEND_BUFFER.with(|buf_cell| {
+            let mut buffer = buf_cell.borrow_mut();
+            buffer.clear();
+            // Safe: ReliableUdpHeader derives Pod
+            buffer.extend_from_slice(bytemuck::bytes_of(&header));
+            buffer.extend_from_slice(data);
+            self.socket.send_to(&buffer, self.remote_addr)?;
+            record_send(buffer.len() as u64);
+            Ok::<_, std::io::Error>(())
+        })?;
+        self.trace(
+            TraceKind::Send,
+            MessageType::Data as u8,
+            header.flags,
+            seq,
+            data.len(),
+        );
+        Ok(seq)
+    }
+
+    /// Split `data` into fragments. The send window (plus overflow queue)
+    /// must have room for all of them, and the congestion window is checked
+    /// once per message, so a message never goes out half sent.
+    fn send_fragments(&mut self, channel: u8, data: &[u8]) -> std::io::Result<u64> {
+        let max = self.reassembler.max_message_size();
+        if data.len() > max {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Message of {} bytes exceeds max {}", data.len(), max),
+            ));
+        }
+        let count = data.len().div_ceil(MAX_FRAGMENT_PAYLOAD);
+        if count > MAX_FRAGMENTS {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Too many fragments",
+            ));
+        }
+        let count = count as u16;
+
+        let occupancy = self.send_window_occupancy();
+        let ring_room = if occupancy.queued == 0 {
+            occupancy.available()
+        } else {
+            0
+        };
+        let overflow_room = self.send_window.overflow_capacity() - occupancy.queued;
+        let congested = !self.congestion.can_send();
+        let room = if congested {
+            overflow_room
+        } else {
+            ring_room + overflow_room
+        };
+        if room < count as usize {
+            record_backpressure();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::WouldBlock,
+                if congested {
+                    "Congestion window full"
+                } else {
+                    "Send window full"
+                },
+            ));
+        }
+
+        let mut seq = self.next_send_seq;
+        for (index, chunk) in data.chunks(MAX_FRAGMENT_PAYLOAD).enumerate() {
+            seq = self.send_packet(channel, chunk, Some((index as u16, count)), congested)?;
+        }
+        Ok(seq)
+    }
+
+    /// Build a data packet (`fragment` = index, count) and publish it
+    fn send_packet(
+        &mut self,
+        channel: u8,
+        data: &[u8],
+        fragment: Option<(u16, u16)>,
+        defer: bool,
+    ) -> std::io::Result<u64> {
         let seq = self.next_send_seq;
         let mut header = ReliableUdpHeader::new(0, seq, MessageType::Data, data.len() as u16);
-        header.calculate_checksum(data);
+        header.set_channel(channel);
+        if let Some((index, count)) = fragment {
+            header.set_fragment(index, count);
+        }
+        header.calculate_checksum_with(self.checksum, data);
 
         const MAX_STACK_SIZE: usize = 256;
         let total_len = ReliableUdpHeader::SIZE + data.len();
@@ -270,53 +629,97 @@ impl RudpTransport {
                 // Safe: ReliableUdpHeader derives Pod
                 buffer.extend_from_slice(bytemuck::bytes_of(&header));
                 buffer.extend_from_slice(data);
-
-                if let Some((slot_seq, slots)) = self.send_window.try_claim_slots(1) {
-                    slots[0].set_sequence(slot_seq);
-                    slots[0].set_data(&buffer);
-                    self.send_window.publish_batch(slot_seq, 1);
-
-                    self.socket.send_to(&buffer, self.remote_addr)?;
-                    self.congestion.on_send();
-                    self.last_send_time = std::time::Instant::now();
-                    record_send(buffer.len() as u64);
-                    self.next_send_seq = self.next_send_seq.wrapping_add(1);
-                    Ok(seq)
-                } else {
-                    record_backpressure();
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::WouldBlock,
-                        "Send window full",
-                    ))
-                }
+                self.publish_packet(seq, &buffer, data.len(), defer)
             });
         };
 
-        if let Some((slot_seq, slots)) = self.send_window.try_claim_slots(1) {
-            slots[0].set_sequence(slot_seq);
-            slots[0].set_data(packet);
-            self.send_window.publish_batch(slot_seq, 1);
+        self.publish_packet(seq, packet, data.len(), defer)
+    }
 
-            self.socket.send_to(packet, self.remote_addr)?;
-            self.congestion.on_send();
-            self.last_send_time = std::time::Instant::now();
-            record_send(packet.len() as u64);
-            self.next_send_seq = self.next_send_seq.wrapping_add(1);
-            Ok(seq)
+    /// Put a data packet in the send window and on the wire, or queue it
+    /// (overflow mode) if the window is full or `defer` is set
+    fn publish_packet(
+        &mut self,
+        seq: u64,
+        packet: &[u8],
+        len: usize,
+        defer: bool,
+    ) -> std::io::Result<u64> {
+        let placement = if defer {
+            self.send_window
+                .defer_with(|slot| slot.set_data(packet))
+                .map(Placement::Overflow)
         } else {
-            record_backpressure();
-            Err(std::io::Error::new(
-                std::io::ErrorKind::WouldBlock,
-                "Send window full",
-            ))
+            self.send_window.publish_with(|slot| slot.set_data(packet))
+        };
+        match placement {
+            Some(Placement::Ring(_)) => {
+                // Published either way: a failed send is resent like a lost
+                // packet, so the sequence has to advance with the ring
+                match self.socket.send_to(packet, self.remote_addr) {
+                    Ok(_) => record_send(packet.len() as u64),
+                    Err(_e) => {
+                        trace_warn!("[SEND] seq {} not sent, left for retransmit: {}", seq, _e);
+                    }
+                }
+                self.congestion.on_send();
+                self.trace(TraceKind::Send, MessageType::Data as u8, 0, seq, len);
+                self.last_send_time = self.clock.now();
+            }
+            // Sent by `flush_send_queue` once the window has room
+            Some(Placement::Overflow(_)) => {}
+            None => {
+                record_backpressure();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::WouldBlock,
+                    "Send window full",
+                ));
+            }
+        }
+        debug_assert_eq!(placement.map(|p| p.sequence()), Some(seq));
+        self.next_send_seq = self.next_send_seq.wrapping_add(1);
+        Ok(seq)
+    }
+
+    /// Send packets queued in overflow mode as the send window and
+    /// congestion window allow. Called by `send` and `process_acks`.
+    /// Returns the number sent.
+    pub fn flush_send_queue(&mut self) -> usize {
+        let mut sent = 0;
+        while self.send_window.queued() > 0 && self.congestion.can_send() {
+            let (socket, remote_addr) = (&self.socket, self.remote_addr);
+            let mut packet = None;
+            self.send_window.drain(1, |slot| {
+                let _ = socket.send_to(slot.data(), remote_addr);
+                packet = Some((slot.sequence(), slot.data().len()));
+            });
+            // Window full: wait for ACKs
+            let Some((seq, len)) = packet else {
+                break;
+            };
+            self.congestion.on_send();
+            let payload_len = len.saturating_sub(ReliableUdpHeader::SIZE);
+            self.trace(
+                TraceKind::Send,
+                MessageType::Data as u8,
+                0,
+                seq,
+                payload_len,
+            );
+            self.last_send_time = self.clock.now();
+            record_send(len as u64);
+            sent += 1;
         }
+        sent
     }
 
     pub fn send_batch(&mut self, data: &[&[u8]]) -> std::io::Result<usize> {
         self.send_batch_ultra(data)
     }
 
-    ///  Batch send with minimal header and no CRC or timestamp
+    ///  Batch send with minimal header and no CRC or timestamp. Messages
+    /// are coalesced into datagrams of at most `path_mtu` bytes; each one
+    /// must fit a datagram on its own.
     #[inline]
     pub fn send_batch_ultra(&mut self, data: &[&[u8]]) -> std::io::Result<usize> {
         let batch_size = data.len();
@@ -324,7 +727,27 @@ impl RudpTransport {
             return Ok(0);
         }
 
-        if let Some((slot_seq, _slots)) = self.send_window.try_claim_slots(batch_size) {
+        let mtu = self.path_mtu();
+        if let Some(msg) = data.iter().find(|m| FastHeader::SIZE + m.len() > mtu) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Batch message of {} bytes exceeds path MTU {} (use send)",
+                    msg.len(),
+                    mtu
+                ),
+            ));
+        }
+
+        // Queued sends go first; don't overtake them
+        if self.send_window.queued() > 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::WouldBlock,
+                "Send queue not drained",
+            ));
+        }
+
+        if let Some((slot_seq, _slots)) = self.send_window.ring_mut().try_claim_slots(batch_size) {
             let actual = batch_size;
 
             SEND_BUFFER.with(|buf_cell| {
@@ -341,6 +764,12 @@ impl RudpTransport {
                 for (i, msg) in data.iter().take(actual).enumerate() {
                     let seq = (slot_seq + (i as u64)) as u32;
 
+                    // Datagram full: send it and start the next
+                    if !buf.is_empty() && buf.len() + FastHeader::SIZE + msg.len() > mtu {
+                        let _ = self.socket.send_to(&buf, self.remote_addr);
+                        buf.clear();
+                    }
+
                     // Minimal 8-byte header
                     let header = FastHeader::new(seq, msg.len());
                     // Safe: FastHeader derives Pod
@@ -351,7 +780,14 @@ impl RudpTransport {
                 let _ = self.socket.send_to(&buf, self.remote_addr);
             });
 
-            self.send_window.publish_batch(slot_seq, actual);
+            if self.trace.is_some() {
+                for (i, msg) in data.iter().take(actual).enumerate() {
+                    let seq = slot_seq + i as u64;
+                    self.trace(TraceKind::Send, MessageType::Data as u8, 0, seq, msg.len());
+                }
+            }
+
+            self.send_window.ring().publish_batch(slot_seq, actual);
             // Keep messages for retransmission - only advance on ACK
             self.next_send_seq = slot_seq + (actual as u64);
             Ok(actual)
@@ -393,12 +829,23 @@ impl RudpTransport {
 
     /// Retransmit immediately (internal)
     fn retransmit_now(&mut self, lost_seq: u64) {
-        let slots = self.send_window.peek_batch(0, self.window_size);
+        if self.sack.is_sacked(lost_seq) {
+            return;
+        }
+        let slots = self.send_window.ring().peek_batch(0, self.window_size);
         if let Some(slot) = slots.iter().find(|s| s.sequence() == lost_seq) {
             let pkt_data = slot.data();
             if !pkt_data.is_empty() {
                 record_retransmit();
                 let _ = self.socket.send_to(pkt_data, self.remote_addr);
+                let len = pkt_data.len().saturating_sub(ReliableUdpHeader::SIZE);
+                self.trace(
+                    TraceKind::Retransmit,
+                    MessageType::Data as u8,
+                    0,
+                    lost_seq,
+                    len,
+                );
             }
         }
     }
@@ -412,10 +859,18 @@ impl RudpTransport {
         let mut packet = Vec::with_capacity(ReliableUdpHeader::SIZE + 16);
         let payload = [start_seq.to_le_bytes(), end_seq.to_le_bytes()].concat();
         let mut header = ReliableUdpHeader::new(0, start_seq, MessageType::Nak, 16);
-        header.calculate_checksum(&payload);
+        header.calculate_checksum_with(self.checksum, &payload);
         // Safe: ReliableUdpHeader derives Pod
         packet.extend_from_slice(bytemuck::bytes_of(&header));
         packet.extend_from_slice(&payload);
+        let range_len = end_seq.saturating_sub(start_seq) + 1;
+        self.trace(
+            TraceKind::NakSent,
+            MessageType::Nak as u8,
+            0,
+            start_seq,
+            range_len as usize,
+        );
 
         trace_debug!(
             "[NAK-SEND] Sending batch NAK for seq {}-{} to {}",
@@ -423,7 +878,7 @@ impl RudpTransport {
             end_seq,
             self.remote_nak_addr
         );
-        if let Err(_e) = self.nak_socket.send_to(&packet, self.remote_nak_addr) {
+        if let Err(_e) = self.control_socket().send_to(&packet, self.remote_nak_addr) {
             trace_warn!("[NAK-SEND-ERROR] Failed to send NAK: {}", _e);
         } else {
             trace_debug!("[NAK-SEND-OK] Batch NAK sent successfully");
@@ -432,65 +887,65 @@ impl RudpTransport {
 
     /// Send ACK to confirm receipt up to a sequence number
     pub fn send_ack(&self, acked_seq: u64) {
-        let mut header = ReliableUdpHeader::new(0, acked_seq, MessageType::Ack, 0);
-        header.calculate_checksum(&[]);
-        // Safe: ReliableUdpHeader derives Pod
-        let packet = bytemuck::bytes_of(&header);
+        self.send_ack_with(acked_seq, 0, &[]);
+    }
+
+    /// ACK with flags; non-empty `sack` blocks set `FLAG_SACK`
+    fn send_ack_with(&self, acked_seq: u64, mut flags: u8, sack: &[(u64, u64)]) {
+        let mut payload = Vec::new();
+        if !sack.is_empty() {
+            flags |= FLAG_SACK;
+            sack::encode_blocks(sack, &mut payload);
+        }
+        let mut header =
+            ReliableUdpHeader::new(0, acked_seq, MessageType::Ack, payload.len() as u16);
+        header.flags = flags;
+        header.calculate_checksum_with(self.checksum, &payload);
+        let mut packet = bytemuck::bytes_of(&header).to_vec();
+        packet.extend_from_slice(&payload);
+        self.trace(
+            TraceKind::AckSent,
+            MessageType::Ack as u8,
+            flags,
+            acked_seq,
+            0,
+        );
 
         trace_debug!(
             "[ACK-SEND] Sending ACK for seq {} to {}",
             acked_seq,
             self.remote_nak_addr
         );
-        let _ = self.nak_socket.send_to(packet, self.remote_nak_addr);
+        let _ = self.control_socket().send_to(&packet, self.remote_nak_addr);
+    }
+
+    /// Socket ACKs and NAKs are sent from
+    fn control_socket(&self) -> &UdpSocket {
+        self.nak_socket.as_ref().unwrap_or(&self.socket)
     }
 
-    /// Process incoming ACKs and advance send window
+    /// Process incoming ACKs and advance send window. With the shared
+    /// socket, data read on the way is kept for `receive_batch_with`.
     pub fn process_acks(&mut self) {
+        let Some(nak_socket) = self.nak_socket.take() else {
+            for _ in 0..ACK_RECV_ROUNDS {
+                if self.recv_packets(64) == 0 {
+                    break;
+                }
+            }
+            self.flush_send_queue();
+            self.poll_timers();
+            return;
+        };
         let mut buf = [0u8; 256];
 
         loop {
-            match self.nak_socket.recv_from(&mut buf) {
+            match nak_socket.recv_from(&mut buf) {
                 Ok((len, _)) => {
-                    if len < ReliableUdpHeader::SIZE {
-                        continue;
-                    }
-
-                    if let Some((header, _payload)) =
+                    if let Some((header, payload)) =
                         ReliableUdpHeader::from_packet_with_payload_check(&buf[..len])
                     {
-                        if header.msg_type == (MessageType::Ack as u8) {
-                            let acked = header.sequence;
-                            if acked > self.acked_seq {
-                                // Count newly acknowledged packets
-                                let newly_acked = acked.saturating_sub(self.acked_seq);
-
-                                trace_debug!(
-                                    "[ACK-RECV] ACK seq {}, {} packets acked",
-                                    acked,
-                                    newly_acked
-                                );
-
-                                // Call on_ack() for EACH acked packet
-                                for _ in 0..newly_acked {
-                                    self.congestion.on_ack();
-                                }
-
-                                // Measure RTT (approximate: time since last send)
-                                let rtt_us = self.last_send_time.elapsed().as_micros() as u64;
-                                if rtt_us > 0 && rtt_us < 1_000_000 {
-                                    self.congestion.update_rtt(rtt_us);
-                                }
-
-                                self.acked_seq = acked;
-                                self.send_window.advance_consumer(0, acked);
-                            }
-                        } else if header.msg_type == (MessageType::Nak as u8) {
-                            // Handle NAK - queue for paced retransmit
-                            self.congestion.on_loss();
-                            let sequence = header.sequence;
-                            self.queue_retransmit(sequence);
-                        }
+                        self.on_control(header, payload);
                     }
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -501,16 +956,259 @@ impl RudpTransport {
                 }
             }
         }
+        self.nak_socket = Some(nak_socket);
+        self.flush_send_queue();
+        self.poll_timers();
+    }
+
+    /// Time-driven work, run by `process_acks`: keepalive pings when idle,
+    /// retransmission timeout for unacked packets (a lost tail never shows
+    /// up as a gap to NAK) and dead-peer detection
+    pub fn poll_timers(&mut self) {
+        let now = self.clock.now();
+
+        if std::mem::take(&mut self.peer_heard) {
+            self.last_heard = now;
+            self.peer_alive = true;
+        } else if self.peer_alive
+            && now.saturating_duration_since(self.last_heard) >= self.peer_timeout
+        {
+            self.peer_alive = false;
+            trace_warn!("[PEER] {} silent, presumed dead", self.remote_addr);
+            if let Some(on_disconnect) = self.on_disconnect.as_mut() {
+                on_disconnect(self.remote_addr);
+            }
+        }
+
+        if now.saturating_duration_since(self.last_send_time.max(self.last_ping))
+            >= self.keepalive_interval
+        {
+            self.send_ping();
+            self.last_ping = now;
+        }
+
+        if self.send_window_occupancy().unacked == 0 {
+            self.rto_armed = false;
+            self.rto_backoff = 0;
+        } else if !self.rto_armed {
+            self.rto_armed = true;
+            self.rto_start = now;
+        } else if now.saturating_duration_since(self.rto_start) >= self.rto() {
+            self.on_rto(now);
+        }
+
+        self.poll_mtu();
+    }
+
+    /// Current retransmission timeout: 4 × smoothed RTT within
+    /// [200ms, 10s], doubled per consecutive timeout
+    pub fn rto(&self) -> std::time::Duration {
+        let base =
+            std::time::Duration::from_micros(self.congestion.rtt_us() * 4).clamp(MIN_RTO, MAX_RTO);
+        (base * (1 << self.rto_backoff.min(6))).min(MAX_RTO)
+    }
+
+    /// No ACK progress for an RTO: resend the oldest unacked packets
+    fn on_rto(&mut self, now: std::time::Instant) {
+        trace_debug!(
+            "[RTO] {} unacked after {:?}",
+            self.sack.next_unacked(),
+            self.rto()
+        );
+        self.congestion.on_loss();
+        let end = self.next_send_seq - self.send_window.queued() as u64;
+        let mut seq = self.sack.next_unacked();
+        let mut queued = 0;
+        while seq < end && queued < RTO_BURST {
+            if !self.sack.is_sacked(seq) {
+                self.queue_retransmit(seq);
+                queued += 1;
+            }
+            seq += 1;
+        }
+        self.process_retransmits();
+        self.rto_backoff += 1;
+        self.rto_start = now;
+    }
+
+    /// Keepalive: lets the peer know we're alive while there's nothing to send
+    fn send_ping(&self) {
+        let mut header = ReliableUdpHeader::new(0, 0, MessageType::Ping, 0);
+        header.calculate_checksum_with(self.checksum, &[]);
+        let _ = self
+            .socket
+            .send_to(bytemuck::bytes_of(&header), self.remote_addr);
+    }
+
+    /// Replace the MTU discovery strategy (default `PathMtuProber`;
+    /// `FixedMtu` for a known path)
+    pub fn set_mtu_discovery(&mut self, mtu: Box<dyn MtuDiscovery>) {
+        self.mtu = mtu;
+        self.poll_mtu();
+    }
+
+    /// Current path MTU (UDP payload bytes). Fragments always fit
+    /// `pmtud::BASE_MTU`; batches from `send_batch_ultra` are cut to this.
+    pub fn path_mtu(&self) -> usize {
+        self.mtu.current_mtu()
+    }
+
+    /// Send an MTU probe if one is due (also called from `poll_timers`)
+    pub fn poll_mtu(&mut self) {
+        if let Some(size) = self.mtu.next_probe(self.clock.now()) {
+            let _ = self.send_mtu_probe(size);
+        }
+    }
+
+    /// Padded Ping of exactly `size` bytes; sequence carries the size
+    fn send_mtu_probe(&self, size: usize) -> std::io::Result<usize> {
+        let padding = vec![0u8; size.saturating_sub(ReliableUdpHeader::SIZE)];
+        let mut header =
+            ReliableUdpHeader::new(0, size as u64, MessageType::Ping, padding.len() as u16);
+        header.flags = FLAG_MTU_PROBE;
+        header.calculate_checksum_with(self.checksum, &padding);
+        let mut packet = bytemuck::bytes_of(&header).to_vec();
+        packet.extend_from_slice(&padding);
+        self.socket.send_to(&packet, self.remote_addr)
+    }
+
+    /// Answer an MTU probe with a small Pong (sequence = size received)
+    fn send_probe_ack(&self, size: usize) {
+        let mut header = ReliableUdpHeader::new(0, size as u64, MessageType::Pong, 0);
+        header.flags = FLAG_MTU_PROBE;
+        header.calculate_checksum_with(self.checksum, &[]);
+        let _ = self
+            .socket
+            .send_to(bytemuck::bytes_of(&header), self.remote_addr);
+    }
+
+    /// Keepalive ping after `interval` without sending (default
+    /// `DEFAULT_KEEPALIVE_INTERVAL`). Keep it well under the peer's timeout.
+    pub fn set_keepalive_interval(&mut self, interval: std::time::Duration) {
+        self.keepalive_interval = interval;
+    }
+
+    /// Peer counts as dead after `timeout` without a packet (default
+    /// `DEFAULT_PEER_TIMEOUT`)
+    pub fn set_peer_timeout(&mut self, timeout: std::time::Duration) {
+        self.peer_timeout = timeout;
+    }
+
+    /// False once nothing arrived from the peer for the peer timeout
+    /// (checked by `poll_timers`); true again when it's heard from
+    pub fn is_peer_alive(&self) -> bool {
+        self.peer_alive
+    }
+
+    /// Called with the peer's address when it's presumed dead (once per
+    /// silence, see `is_peer_alive`)
+    pub fn on_disconnect<F: FnMut(SocketAddr) + Send + 'static>(&mut self, f: F) {
+        self.on_disconnect = Some(Box::new(f));
+    }
+
+    /// Handle an ACK (advance the send window), NAK (queue a paced
+    /// retransmit) or MTU probe
+    fn on_control(&mut self, header: &ReliableUdpHeader, payload: &[u8]) {
+        self.peer_heard = true;
+        if header.flags & FLAG_MTU_PROBE != 0 {
+            if header.msg_type == MessageType::Ping as u8 {
+                // Echo the size that made it through
+                self.send_probe_ack(ReliableUdpHeader::SIZE + payload.len());
+            } else if header.msg_type == MessageType::Pong as u8 {
+                self.mtu
+                    .on_probe_ack(header.sequence as usize, self.clock.now());
+            }
+        } else if header.msg_type == (MessageType::Ack as u8) {
+            self.trace(
+                TraceKind::AckRecv,
+                header.msg_type,
+                header.flags,
+                header.sequence,
+                0,
+            );
+            if header.flags & FLAG_ECN_ECHO != 0 {
+                // Peer saw CE: back off without waiting for a drop
+                self.congestion.on_ecn_ce();
+            }
+            if header.flags & FLAG_SACK != 0 {
+                // Already received: out of the network, not to be resent
+                for (start, end) in sack::decode_blocks(payload) {
+                    if start >= self.next_send_seq {
+                        continue;
+                    }
+                    let end = end.min(self.next_send_seq - 1);
+                    for _ in 0..self.sack.mark(start, end) {
+                        self.congestion.on_ack();
+                    }
+                }
+            }
+            let acked = header.sequence;
+            let first_unacked = self.sack.next_unacked();
+            if acked >= first_unacked && acked < self.next_send_seq {
+                // Count newly acknowledged packets (SACKed ones were counted already)
+                let sacked = self.sack.advance(acked + 1) as u64;
+                let newly_acked = (acked + 1 - first_unacked).saturating_sub(sacked);
+
+                trace_debug!(
+                    "[ACK-RECV] ACK seq {}, {} packets acked",
+                    acked,
+                    newly_acked
+                );
+
+                // Call on_ack() for EACH acked packet
+                for _ in 0..newly_acked {
+                    self.congestion.on_ack();
+                }
+
+                // Measure RTT (approximate: time since last send)
+                let now = self.clock.now();
+                let rtt_us = now
+                    .saturating_duration_since(self.last_send_time)
+                    .as_micros() as u64;
+                if rtt_us > 0 && rtt_us < 1_000_000 {
+                    self.congestion.update_rtt(rtt_us);
+                }
+
+                // Gap filled: release the SACKed run behind it too
+                let acked = self.sack.advance_past_sacked() - 1;
+                self.acked_seq = acked;
+                self.send_window.ring().advance_consumer(0, acked);
+                // Progress: restart the retransmission timer
+                self.rto_start = now;
+                self.rto_backoff = 0;
+            }
+        } else if header.msg_type == (MessageType::Nak as u8) {
+            if let Some(ranges) = nak_ranges(payload) {
+                // Batch NAK: resend every range now, as the port + 1 path does
+                for (start_seq, end_seq) in ranges {
+                    let count = end_seq.saturating_sub(start_seq) as usize + 1;
+                    self.trace(TraceKind::NakRecv, header.msg_type, 0, start_seq, count);
+                    self.retransmit_batch(start_seq, end_seq);
+                }
+                return;
+            }
+            // Single NAK - queue for paced retransmit
+            self.congestion.on_loss();
+            let sequence = header.sequence;
+            self.trace(TraceKind::NakRecv, header.msg_type, 0, sequence, 1);
+            self.queue_retransmit(sequence);
+        }
     }
 
-    /// Process incoming NAKs and retransmit as needed
+    /// Process incoming NAKs and retransmit as needed. With the shared
+    /// socket: `process_acks`, then send every queued retransmit.
     pub fn process_naks(&mut self) {
+        let Some(nak_socket) = self.nak_socket.take() else {
+            self.process_acks();
+            while self.process_retransmits() > 0 {}
+            return;
+        };
         let mut buf = [0u8; 2048];
         let mut _nak_count = 0;
         let mut _retransmit_count = 0;
 
         loop {
-            match self.nak_socket.recv_from(&mut buf) {
+            match nak_socket.recv_from(&mut buf) {
                 Ok((len, _src)) => {
                     _nak_count += 1;
 
@@ -535,32 +1233,23 @@ impl RudpTransport {
 
                         let sequence = header.sequence;
 
-                        if payload.len() >= 16 && payload.len() % 16 == 0 {
-                            let range_count = payload.len() / 16;
-
+                        if let Some(ranges) = nak_ranges(payload) {
                             trace_debug!(
                                 "[NAK] Received batch NAK from {} with {} ranges",
                                 _src,
-                                range_count
+                                payload.len() / 16
                             );
 
-                            for i in 0..range_count {
-                                let offset = i * 16;
-                                let start_seq = u64::from_le_bytes(
-                                    payload[offset..offset + 8].try_into().unwrap(),
-                                );
-                                let end_seq = u64::from_le_bytes(
-                                    payload[offset + 8..offset + 16].try_into().unwrap(),
-                                );
-                                let count = (end_seq - start_seq + 1) as usize;
+                            for (start_seq, end_seq) in ranges {
+                                let count = end_seq.saturating_sub(start_seq) as usize + 1;
 
                                 trace_debug!(
-                                    "[NAK] Range {}: seq {}-{} ({} packets)",
-                                    i,
+                                    "[NAK] Range seq {}-{} ({} packets)",
                                     start_seq,
                                     end_seq,
                                     count
                                 );
+                                self.trace(TraceKind::NakRecv, msg_type, 0, start_seq, count);
                                 self.retransmit_batch(start_seq, end_seq);
                                 _retransmit_count += count;
                             }
@@ -570,6 +1259,7 @@ impl RudpTransport {
                                 _src,
                                 sequence
                             );
+                            self.trace(TraceKind::NakRecv, msg_type, 0, sequence, 1);
                             self.retransmit(sequence);
                             _retransmit_count += 1;
                         }
@@ -586,28 +1276,40 @@ impl RudpTransport {
                 }
             }
         }
+        self.nak_socket = Some(nak_socket);
     }
 
     /// Retransmit a batch of lost packets (on batch NAK)
-    /// On Linux, uses sendmmsg for reduced syscall overhead.
-    #[cfg(target_os = "linux")]
+    /// On Linux, uses UDP GSO (sendmmsg fallback) for reduced syscall overhead;
+    /// on Windows, WSASendMsg over preallocated headers.
+    #[cfg(any(target_os = "linux", windows))]
     pub fn retransmit_batch(&mut self, start_seq: u64, end_seq: u64) {
-        use std::os::unix::io::AsRawFd;
-
         self.congestion.on_loss(); // Loss event triggers congestion control
-        let slots = self.send_window.peek_batch(0, self.window_size);
+        let slots = self.send_window.ring().peek_batch(0, self.window_size);
 
         // Collect packets to retransmit
         let packets: Vec<&[u8]> = slots
             .iter()
             .filter(|s| {
                 let seq = s.sequence();
-                seq >= start_seq && seq <= end_seq
+                seq >= start_seq && seq <= end_seq && !self.sack.is_sacked(seq)
             })
             .filter_map(|slot| {
                 let data = slot.data();
                 if !data.is_empty() {
                     record_retransmit();
+                    if let Some(trace) = &self.trace {
+                        let len = data.len().saturating_sub(ReliableUdpHeader::SIZE);
+                        let cwnd = self.congestion.window_size();
+                        trace.record(
+                            TraceKind::Retransmit,
+                            MessageType::Data as u8,
+                            0,
+                            slot.sequence(),
+                            len as u32,
+                            cwnd,
+                        );
+                    }
                     Some(data)
                 } else {
                     None
@@ -619,33 +1321,240 @@ impl RudpTransport {
             return;
         }
 
-        // Use sendmmsg for batch retransmit
-        let fd = self.socket.as_raw_fd();
-        // Safety: fd is valid, packets contains valid slices, remote_addr is valid
-        let _ = unsafe {
-            self.batch_sender
-                .send_batch(fd, &packets, &self.remote_addr)
-        };
+        // Use GSO for batch retransmit (same-size packets share one sendmsg)
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::io::AsRawFd;
+            let fd = self.socket.as_raw_fd();
+            // Safety: fd is valid, packets contains valid slices, remote_addr is valid
+            let _ = unsafe { self.gso_sender.send_batch(fd, &packets, &self.remote_addr) };
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::io::AsRawSocket;
+            let socket = self.socket.as_raw_socket();
+            // Safety: socket is valid, packets contains valid slices, remote_addr is valid
+            let _ = unsafe {
+                self.batch_sender
+                    .send_batch(socket, &packets, &self.remote_addr)
+            };
+        }
     }
 
     /// Retransmit a batch of lost packets (on batch NAK)
-    /// Non-Linux fallback: sends packets one at a time.
-    #[cfg(not(target_os = "linux"))]
+    /// Fallback: sends packets one at a time.
+    #[cfg(not(any(target_os = "linux", windows)))]
     pub fn retransmit_batch(&mut self, start_seq: u64, end_seq: u64) {
         self.congestion.on_loss(); // Loss event triggers congestion control
-        let slots = self.send_window.peek_batch(0, self.window_size);
+        let slots = self.send_window.ring().peek_batch(0, self.window_size);
         for slot in slots.iter().filter(|s| {
             let seq = s.sequence();
-            seq >= start_seq && seq <= end_seq
+            seq >= start_seq && seq <= end_seq && !self.sack.is_sacked(seq)
         }) {
             let pkt_data = slot.data();
             if !pkt_data.is_empty() {
                 record_retransmit();
                 let _ = self.socket.send_to(pkt_data, self.remote_addr);
+                let len = pkt_data.len().saturating_sub(ReliableUdpHeader::SIZE);
+                self.trace(
+                    TraceKind::Retransmit,
+                    MessageType::Data as u8,
+                    0,
+                    slot.sequence(),
+                    len,
+                );
             }
         }
     }
 
+    /// Enable UDP GRO on the data socket (Linux).
+    /// Returns false when unsupported; receive stays on recvmmsg.
+    #[cfg(target_os = "linux")]
+    pub fn enable_gro(&mut self) -> bool {
+        use std::os::unix::io::AsRawFd;
+
+        if self.gro_receiver.is_none() {
+            self.gro_receiver = gso::GroReceiver::enable(self.socket.as_raw_fd());
+        }
+        self.gro_receiver.is_some()
+    }
+
+    /// Enable UDP GRO on the data socket (Linux only - always false here).
+    #[cfg(not(target_os = "linux"))]
+    pub fn enable_gro(&mut self) -> bool {
+        false
+    }
+
+    /// Whether batch retransmits still use UDP GSO (false after kernel rejects it)
+    pub fn gso_enabled(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            self.gso_sender.is_enabled()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            false
+        }
+    }
+
+    /// Mark outgoing packets ECT(0) and echo received CE marks on ACKs.
+    /// Returns false when unsupported (non-Linux or kernel refused).
+    pub fn enable_ecn(&mut self) -> bool {
+        self.ecn_enabled = ecn::enable(&self.socket).is_ok();
+        self.ecn_enabled
+    }
+
+    /// ECN marking/reading is active
+    pub fn ecn_enabled(&self) -> bool {
+        self.ecn_enabled
+    }
+
+    /// CE-marked packets received so far
+    pub fn ecn_ce_received(&self) -> u64 {
+        self.ecn_ce_received
+    }
+
+    #[inline]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn on_ecn(&mut self, codepoint: u8, packets: u64) {
+        if codepoint == ecn::ECN_CE {
+            self.ecn_ce_pending = true;
+            self.ecn_ce_received += packets;
+        }
+    }
+
+    /// Record a packet trace of this connection to `path` (see `kaos-trace` CLI)
+    pub fn enable_trace<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
+        self.trace = Some(trace::TraceRecorder::create(path)?);
+        Ok(())
+    }
+
+    /// Install (or remove with `None`) a trace recorder
+    pub fn set_trace(&mut self, recorder: Option<trace::TraceRecorder>) {
+        self.trace = recorder;
+    }
+
+    /// Active trace recorder, if any
+    pub fn trace_recorder(&self) -> Option<&trace::TraceRecorder> {
+        self.trace.as_ref()
+    }
+
+    /// Time source for RTT samples, NAK pacing and congestion control
+    /// (`TestClock` for deterministic tests)
+    pub fn set_clock(&mut self, clock: std::sync::Arc<dyn clock::Clock>) {
+        self.last_send_time = clock.now();
+        self.last_nak_time = clock.now();
+        self.last_ping = clock.now();
+        self.last_heard = clock.now();
+        self.rto_start = clock.now();
+        self.congestion.set_clock(clock.clone());
+        self.clock = clock;
+    }
+
+    /// Start a new session at sequence 0 on the same sockets (e.g. after the
+    /// peer restarted). Unacked and queued packets are dropped, as are
+    /// datagrams still waiting in the socket buffers.
+    pub fn reset(&mut self) -> std::io::Result<()> {
+        let config = RingBufferConfig::new(self.window_size)
+            .map_err(|e| std::io::Error::other(format!("Invalid window size: {}", e)))?
+            .with_consumers(1)
+            .map_err(|e| std::io::Error::other(format!("Config error: {}", e)))?;
+        self.send_window = OverflowRingBuffer::new(config, self.send_window.overflow_capacity())
+            .map_err(|e| std::io::Error::other(format!("RingBuffer error: {}", e)))?;
+        self.recv_window = BitmapWindow::new(self.window_size, 0);
+        self.next_send_seq = 0;
+        self.acked_seq = 0;
+        self.sack = sack::SackScoreboard::new(self.window_size);
+        self.congestion =
+            self.congestion_algorithm
+                .build(64, self.window_size as u32, self.clock.clone());
+        self.retransmit_queue.clear();
+        self.reassembler = fragment::Reassembler::new(
+            self.reassembler.max_message_size(),
+            self.reassembler.timeout(),
+        );
+        self.channels = channel::Channels::new();
+        self.last_heard = self.clock.now();
+        self.peer_alive = true;
+        self.rto_armed = false;
+        self.rto_backoff = 0;
+
+        let mut buf = [0u8; 2048];
+        while self.socket.recv_from(&mut buf).is_ok() {}
+        if let Some(nak_socket) = &self.nak_socket {
+            while nak_socket.recv_from(&mut buf).is_ok() {}
+        }
+        Ok(())
+    }
+
+    /// Header checksum for this connection. There is no handshake, so
+    /// both peers must pick the same one (default CRC32).
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum = checksum;
+    }
+
+    pub fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    #[inline]
+    fn trace(&self, kind: TraceKind, msg_type: u8, flags: u8, seq: u64, len: usize) {
+        if let Some(trace) = &self.trace {
+            let cwnd = self.congestion.window_size();
+            trace.record(kind, msg_type, flags, seq, len as u32, cwnd);
+        }
+    }
+
+    /// Send window usage: unacked packets hold slots until the cumulative
+    /// ACK (or a filled gap in front of SACKed packets) releases them
+    pub fn send_window_occupancy(&self) -> SendWindowOccupancy {
+        let queued = self.send_window.queued();
+        let unacked = (self.next_send_seq - queued as u64).saturating_sub(self.sack.next_unacked());
+        SendWindowOccupancy {
+            capacity: self.window_size,
+            unacked: (unacked as usize).min(self.window_size),
+            sacked: self.sack.sacked(),
+            queued,
+        }
+    }
+
+    /// Overflow mode: when the send window or congestion window is full,
+    /// queue up to `capacity` packets (sent as ACKs free room) instead of
+    /// failing with `WouldBlock`. 0 (default) disables it.
+    pub fn set_send_overflow(&mut self, capacity: usize) {
+        self.send_window.set_overflow_capacity(capacity);
+    }
+
+    /// Largest message `send` accepts (fragmented above
+    /// `MAX_FRAGMENT_PAYLOAD`) and the receive side reassembles. Default
+    /// `DEFAULT_MAX_MESSAGE_SIZE`; both peers should agree.
+    pub fn set_max_message_size(&mut self, size: usize) {
+        self.reassembler.set_max_message_size(size);
+    }
+
+    /// How long a half-reassembled message waits for its next fragment
+    /// before it's dropped (default `DEFAULT_REASSEMBLY_TIMEOUT`)
+    pub fn set_reassembly_timeout(&mut self, timeout: std::time::Duration) {
+        self.reassembler.set_timeout(timeout);
+    }
+
+    /// Fragmented messages dropped before they were complete
+    pub fn reassembly_dropped(&self) -> u64 {
+        self.reassembler.dropped()
+    }
+
+    /// Congestion control algorithm (default AIMD). Starts a fresh
+    /// controller, so pick it before sending.
+    pub fn set_congestion(&mut self, algorithm: CongestionAlgorithm) {
+        self.congestion = algorithm.build(64, self.window_size as u32, self.clock.clone());
+        self.congestion_algorithm = algorithm;
+    }
+
+    /// Selected congestion control algorithm
+    pub fn congestion_algorithm(&self) -> CongestionAlgorithm {
+        self.congestion_algorithm
+    }
+
     /// Get congestion window size
     pub fn congestion_window(&self) -> u32 {
         self.congestion.window_size()
@@ -656,12 +1565,19 @@ impl RudpTransport {
         self.congestion.in_flight()
     }
 
-    /// Parse a received packet and insert into receive window
-    fn parse_and_insert_packet(&mut self, data: &[u8]) {
+    /// Congestion/RTT signals for rate adaptation (see `RateAdapter`)
+    pub fn quality(&self) -> ConnectionQuality {
+        self.congestion.quality()
+    }
+
+    /// Parse a received datagram: data into the receive window, ACKs and
+    /// NAKs to `on_control`
+    fn handle_packet(&mut self, data: &[u8]) {
         let len = data.len();
         if len < FastHeader::SIZE {
             return;
         }
+        self.peer_heard = true;
 
         // Detect format via magic bit in first u32
         let first_u32 = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
@@ -687,6 +1603,13 @@ impl RudpTransport {
 
                 if frame_len >= FastHeader::SIZE && offset + frame_len <= len {
                     let payload = &data[offset + FastHeader::SIZE..offset + frame_len];
+                    self.trace(
+                        TraceKind::Recv,
+                        MessageType::Data as u8,
+                        0,
+                        seq,
+                        payload.len(),
+                    );
                     self.recv_window.insert(seq, payload);
                     offset += frame_len;
                 } else {
@@ -717,10 +1640,18 @@ impl RudpTransport {
                         if pkt_len >= ReliableUdpHeader::SIZE + payload_len {
                             let payload = &data[offset + ReliableUdpHeader::SIZE
                                 ..offset + ReliableUdpHeader::SIZE + payload_len];
-                            let valid =
-                                (flags & FLAG_NO_CRC) != 0 || header.verify_checksum(payload);
+                            let valid = (flags & FLAG_NO_CRC) != 0
+                                || header.verify_checksum_with(self.checksum, payload);
                             if valid {
-                                self.recv_window.insert(header.sequence, payload);
+                                let seq = header.sequence;
+                                self.trace(
+                                    TraceKind::Recv,
+                                    header.msg_type,
+                                    flags,
+                                    seq,
+                                    payload_len,
+                                );
+                                self.insert_data(&header, payload);
                             }
                         }
                     }
@@ -736,74 +1667,138 @@ impl RudpTransport {
                 if len >= ReliableUdpHeader::SIZE + payload_len {
                     let payload =
                         &data[ReliableUdpHeader::SIZE..ReliableUdpHeader::SIZE + payload_len];
-                    let checksum_ok = header.verify_checksum(payload);
-                    if checksum_ok {
-                        self.recv_window.insert(header.sequence, payload);
+                    let checksum_ok = header.verify_checksum_with(self.checksum, payload);
+                    let msg_type = header.msg_type;
+                    if checksum_ok && msg_type == MessageType::Data as u8 {
+                        let seq = header.sequence;
+                        self.trace(TraceKind::Recv, msg_type, header.flags, seq, payload_len);
+                        self.insert_data(&header, payload);
+                    } else if checksum_ok {
+                        self.on_control(&header, payload);
                     }
                 }
             }
         }
     }
 
+    /// Put a data packet in the receive window, noting fragments for
+    /// reassembly; unreliable ones go to their channel
+    fn insert_data(&mut self, header: &ReliableUdpHeader, payload: &[u8]) {
+        let seq = header.sequence;
+        let reliability = header.reliability();
+        if reliability != Reliability::ReliableOrdered {
+            self.channels
+                .accept(header.channel(), reliability, seq, payload);
+            return;
+        }
+        if header.session_id != 0 {
+            // Already delivered duplicates would never be collected
+            if seq < self.recv_window.ring.next_expected_seq {
+                return;
+            }
+            if let Some((index, count)) = header.fragment() {
+                self.reassembler.mark(seq, index, count);
+            }
+            self.channels.mark_reliable(seq, header.channel());
+        }
+        self.recv_window.insert(seq, payload);
+    }
+
     /// Callback-based delivery: process each message with the provided closure.
-    /// On Linux, uses recvmmsg for reduced syscall overhead.
-    #[cfg(target_os = "linux")]
+    /// Uses recvmmsg (or GRO, see `enable_gro`) on Linux and WSARecvMsg on
+    /// Windows; ACKs and NAKs read on the way are handled too. Messages on
+    /// every channel are delivered (see `receive_channels_with`).
     pub fn receive_batch_with<F: FnMut(&[u8])>(&mut self, max_count: usize, mut f: F) {
+        self.receive_channels_with(max_count, |_, msg| f(msg));
+    }
+
+    /// Like `receive_batch_with`, with each message's channel. Unreliable
+    /// messages come first, then the reliable ones in order.
+    pub fn receive_channels_with<F: FnMut(u8, &[u8])>(&mut self, max_count: usize, f: F) {
+        self.recv_packets(max_count);
+        self.deliver_and_ack(f);
+    }
+
+    /// Unreliable messages dropped on receive (older than the newest on a
+    /// sequenced channel, or too many held between receive calls)
+    pub fn unreliable_dropped(&self) -> u64 {
+        self.channels.dropped()
+    }
+
+    /// Read up to `max_count` datagrams from the socket into `handle_packet`.
+    /// Returns the number read.
+    #[cfg(target_os = "linux")]
+    fn recv_packets(&mut self, max_count: usize) -> usize {
         use std::os::unix::io::AsRawFd;
 
         let fd = self.socket.as_raw_fd();
+
+        // GRO: each recvmsg may return many coalesced datagrams
+        if let Some(mut gro) = self.gro_receiver.take() {
+            let mut received = 0;
+            while received < max_count {
+                // Safety: fd is valid, gro owns its buffer
+                match unsafe { gro.recv_with(fd, |pkt| self.handle_packet(pkt)) } {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        self.on_ecn(gro.ecn(), n as u64);
+                        received += n;
+                    }
+                }
+            }
+            self.gro_receiver = Some(gro);
+            return received;
+        }
+
+        self.recv_batch_into_window(max_count)
+    }
+
+    #[cfg(windows)]
+    fn recv_packets(&mut self, max_count: usize) -> usize {
+        self.recv_batch_into_window(max_count)
+    }
+
+    /// Batch-receive (up to 64) and insert into the receive window.
+    #[cfg(any(target_os = "linux", windows))]
+    fn recv_batch_into_window(&mut self, max_count: usize) -> usize {
+        #[cfg(target_os = "linux")]
+        let handle = std::os::unix::io::AsRawFd::as_raw_fd(&self.socket);
+        #[cfg(windows)]
+        let handle = std::os::windows::io::AsRawSocket::as_raw_socket(&self.socket);
         let max_recv = max_count.min(64); // batch_receiver was created with 64 slots
 
-        // Use recvmmsg for batch receive
-        // Safety: fd is valid, batch_receiver buffers are properly sized
-        let received = unsafe { self.batch_receiver.recv_batch(fd) }.unwrap_or(0);
+        // Use recvmmsg (WSARecvMsg on Windows) for batch receive
+        // Safety: handle is our non-blocking socket, batch_receiver buffers are properly sized
+        let received = unsafe { self.batch_receiver.recv_batch(handle) }.unwrap_or(0);
 
-        // Copy packet lengths first to avoid borrow conflict with parse_and_insert_packet(&mut self)
+        // Copy packet lengths first to avoid borrow conflict with handle_packet(&mut self)
         // We use a stack-allocated array for the lengths, then process packets one by one
         let count = received.min(max_recv);
         let mut packet_lens = [0usize; 64];
-        for i in 0..count {
-            packet_lens[i] = self.batch_receiver.packet(i).len();
+        for (i, len) in packet_lens.iter_mut().enumerate().take(count) {
+            *len = self.batch_receiver.packet(i).len();
         }
 
         // Now process each packet - we re-borrow batch_receiver for each one
-        for i in 0..count {
-            let len = packet_lens[i];
+        for (i, &len) in packet_lens.iter().enumerate().take(count) {
             if len > 0 {
                 // Copy packet data to stack buffer to release borrow
                 let mut buf = [0u8; 2048];
                 let data = self.batch_receiver.packet(i);
                 let copy_len = len.min(buf.len());
                 buf[..copy_len].copy_from_slice(&data[..copy_len]);
-                self.parse_and_insert_packet(&buf[..copy_len]);
+                self.handle_packet(&buf[..copy_len]);
+                if self.ecn_enabled {
+                    self.on_ecn(self.batch_receiver.ecn(i), 1);
+                }
             }
         }
-
-        self.recv_window.deliver_in_order_with(|msg| {
-            record_receive(msg.len() as u64);
-            f(msg);
-        });
-
-        // Send ACK for highest delivered sequence
-        let last_delivered = self.recv_window.last_delivered_seq();
-        if last_delivered > 0 {
-            self.send_ack(last_delivered);
-        }
-
-        // NAK backoff: limit to once per RTT
-        let nak_interval = std::time::Duration::from_micros(self.congestion.rtt_us().max(1000));
-        if self.last_nak_time.elapsed() >= nak_interval {
-            self.recv_window.send_batch_naks_for_gaps(|start, end| {
-                self.send_batch_nak(start, end);
-            });
-            self.last_nak_time = std::time::Instant::now();
-        }
+        count
     }
 
-    /// Callback-based delivery: process each message with the provided closure.
-    /// Non-Linux fallback: receives packets one at a time.
-    #[cfg(not(target_os = "linux"))]
-    pub fn receive_batch_with<F: FnMut(&[u8])>(&mut self, max_count: usize, mut f: F) {
+    /// Fallback: receives packets one at a time.
+    #[cfg(not(any(target_os = "linux", windows)))]
+    fn recv_packets(&mut self, max_count: usize) -> usize {
         RECV_BUFFERS.with(|bufs_cell| {
             RECV_LENS.with(|lens_cell| {
                 let mut bufs = bufs_cell.borrow_mut();
@@ -828,30 +1823,72 @@ impl RudpTransport {
                 }
                 for i in 0..n {
                     let data = &bufs[i][..lens[i]];
-                    self.parse_and_insert_packet(data);
-                }
-                self.recv_window.deliver_in_order_with(|msg| {
-                    record_receive(msg.len() as u64);
-                    f(msg);
-                });
-
-                // Send ACK for highest delivered sequence
-                let last_delivered = self.recv_window.last_delivered_seq();
-                if last_delivered > 0 {
-                    self.send_ack(last_delivered);
+                    self.handle_packet(data);
                 }
+                n
+            })
+        })
+    }
 
-                // NAK backoff: limit to once per RTT
-                let nak_interval =
-                    std::time::Duration::from_micros(self.congestion.rtt_us().max(1000));
-                if self.last_nak_time.elapsed() >= nak_interval {
-                    self.recv_window.send_batch_naks_for_gaps(|start, end| {
-                        self.send_batch_nak(start, end);
-                    });
-                    self.last_nak_time = std::time::Instant::now();
-                }
-            });
+    /// Deliver in-order messages, ACK the highest delivered, NAK gaps (once per RTT)
+    fn deliver_and_ack<F: FnMut(u8, &[u8])>(&mut self, mut f: F) {
+        self.channels.drain(|channel, msg| {
+            record_receive(msg.len() as u64);
+            f(channel, msg);
+        });
+
+        let trace = self.trace.as_ref();
+        let cwnd = self.congestion.window_size();
+        let channels = &mut self.channels;
+        let reassembler = &mut self.reassembler;
+        let now = self.clock.now();
+        reassembler.expire(now);
+        let mut seq = self.recv_window.ring.next_expected_seq;
+        self.recv_window.deliver_in_order_with(|msg| {
+            let delivered = seq;
+            seq += 1;
+            let channel = channels.reliable_channel(delivered);
+            let Some(msg) = reassembler.push(delivered, msg, now) else {
+                return;
+            };
+            record_receive(msg.len() as u64);
+            if let Some(trace) = trace {
+                let data = MessageType::Data as u8;
+                trace.record(
+                    TraceKind::Deliver,
+                    data,
+                    0,
+                    delivered,
+                    msg.len() as u32,
+                    cwnd,
+                );
+            }
+            f(channel, msg);
         });
+
+        // Send ACK for highest delivered sequence, SACKing what's held past a gap
+        let last_delivered = self.recv_window.last_delivered_seq();
+        if self.recv_window.ring.next_expected_seq > 0 {
+            let flags = if self.ecn_ce_pending {
+                FLAG_ECN_ECHO
+            } else {
+                0
+            };
+            let mut blocks = [(0, 0); sack::MAX_SACK_BLOCKS];
+            let n = self.recv_window.sack_blocks(&mut blocks);
+            self.send_ack_with(last_delivered, flags, &blocks[..n]);
+            self.ecn_ce_pending = false;
+        }
+
+        // NAK backoff: limit to once per RTT
+        let nak_interval = std::time::Duration::from_micros(self.congestion.rtt_us().max(1000));
+        let now = self.clock.now();
+        if now.saturating_duration_since(self.last_nak_time) >= nak_interval {
+            self.recv_window.send_batch_naks_for_gaps(|start, end| {
+                self.send_batch_nak(start, end);
+            });
+            self.last_nak_time = now;
+        }
     }
 
     /// Get reference to the underlying socket
@@ -859,9 +1896,15 @@ impl RudpTransport {
         &self.socket
     }
 
-    /// Get reference to the NAK socket
+    /// Socket ACKs and NAKs go through: the data socket, or the port + 1
+    /// socket in compat mode (`new_legacy`)
     pub fn nak_socket(&self) -> &UdpSocket {
-        &self.nak_socket
+        self.control_socket()
+    }
+
+    /// NAK/ACK socket on port + 1, in compat mode (`new_legacy`)
+    pub fn legacy_nak_socket(&self) -> Option<&UdpSocket> {
+        self.nak_socket.as_ref()
     }
 
     /// Get remote address
@@ -892,6 +1935,19 @@ impl BatchTransport for RudpTransport {
     }
 }
 
+/// `[start, end]` ranges of a batch NAK (`send_batch_nak`), `None` for a
+/// single NAK without range payload
+fn nak_ranges(payload: &[u8]) -> Option<impl Iterator<Item = (u64, u64)> + '_> {
+    if payload.is_empty() || !payload.len().is_multiple_of(16) {
+        return None;
+    }
+    Some(payload.chunks_exact(16).filter_map(|range| {
+        let start = u64::from_le_bytes(range[..8].try_into().unwrap());
+        let end = u64::from_le_bytes(range[8..].try_into().unwrap());
+        (start <= end).then_some((start, end))
+    }))
+}
+
 impl Reliable for RudpTransport {
     fn retransmit_pending(&mut self) -> std::io::Result<usize> {
         // NAK-based: retransmission triggered by receive
@@ -902,3 +1958,326 @@ impl Reliable for RudpTransport {
         self.acked_seq
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn free_addrs() -> (SocketAddr, SocketAddr) {
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap())
+    }
+
+    /// Send 20 messages a -> b and return how many b got and a saw ACKed
+    fn exchange(a: &mut RudpTransport, b: &mut RudpTransport) -> (usize, u64) {
+        for i in 0..20u8 {
+            a.send(&[i]).unwrap();
+        }
+        let mut got = 0;
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(2));
+            b.receive_batch_with(64, |_| got += 1);
+            a.process_acks();
+            if a.acked_seq == 19 {
+                break;
+            }
+        }
+        (got, a.acked_seq)
+    }
+
+    #[test]
+    fn test_single_socket() {
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        assert!(a.legacy_nak_socket().is_none());
+        assert_eq!(
+            a.nak_socket().local_addr().unwrap(),
+            a.socket().local_addr().unwrap()
+        );
+        // Nothing listens on port + 1
+        let a_next = SocketAddr::new(a_addr.ip(), a_addr.port() + 1);
+        drop(UdpSocket::bind(a_next).unwrap());
+
+        assert_eq!(exchange(&mut a, &mut b), (20, 19));
+        // Data the other way still arrives while ACKs share the socket
+        b.send(b"reply").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        a.process_acks();
+        let mut got = Vec::new();
+        a.receive_batch_with(64, |msg| got.push(msg.to_vec()));
+        assert_eq!(got, [b"reply".to_vec()]);
+    }
+
+    #[test]
+    fn test_legacy_nak_port() {
+        let (a_addr, b_addr) = free_addrs();
+        let config = |local: SocketAddr, remote: SocketAddr| ReliableUdpConfig {
+            local_addr: local.to_string(),
+            remote_addr: remote.to_string(),
+            window_size: 256,
+            legacy_nak_port: true,
+            ..Default::default()
+        };
+        let mut a = RudpTransport::auto(config(a_addr, b_addr)).unwrap();
+        let mut b = RudpTransport::new_legacy(b_addr, a_addr, 256).unwrap();
+        let nak_addr = a.nak_socket().local_addr().unwrap();
+        assert_eq!(
+            a.legacy_nak_socket().map(|s| s.local_addr().unwrap()),
+            Some(nak_addr)
+        );
+        assert_eq!(nak_addr.port(), a_addr.port() + 1);
+        assert_eq!(exchange(&mut a, &mut b), (20, 19));
+    }
+
+    #[test]
+    fn test_fragmented_message() {
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        a.set_max_message_size(8000);
+
+        let big: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+        let first = a.next_send_seq;
+        let last = a.send(&big).unwrap();
+        assert_eq!(
+            last - first + 1,
+            5000u64.div_ceil(MAX_FRAGMENT_PAYLOAD as u64)
+        );
+        a.send(b"after").unwrap();
+        let err = a.send(&[0u8; 8001]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let mut got = Vec::new();
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(2));
+            b.receive_batch_with(64, |msg| got.push(msg.to_vec()));
+            a.process_acks();
+            if got.len() == 2 {
+                break;
+            }
+        }
+        assert_eq!(got, [big, b"after".to_vec()]);
+        assert_eq!(b.reassembly_dropped(), 0);
+    }
+
+    #[test]
+    fn test_channels() {
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+
+        a.send_on_channel(1, b"cmd", Reliability::ReliableOrdered)
+            .unwrap();
+        let big = vec![7u8; 3000];
+        a.send_on_channel(2, &big, Reliability::ReliableOrdered)
+            .unwrap();
+        for i in 1..=3u8 {
+            let seq = a
+                .send_on_channel(5, &[i], Reliability::UnreliableSequenced)
+                .unwrap();
+            assert_eq!(seq, i as u64);
+        }
+        a.send_on_channel(6, b"ping", Reliability::Unreliable)
+            .unwrap();
+        a.send(b"plain").unwrap();
+        let err = a
+            .send_on_channel(5, &big, Reliability::Unreliable)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let mut got = Vec::new();
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(2));
+            b.receive_channels_with(64, |channel, msg| got.push((channel, msg.to_vec())));
+            a.process_acks();
+            if got.len() == 7 {
+                break;
+            }
+        }
+        let (unreliable, reliable) = got.split_at(4);
+        assert_eq!(
+            unreliable,
+            [
+                (5, vec![1]),
+                (5, vec![2]),
+                (5, vec![3]),
+                (6, b"ping".to_vec())
+            ]
+        );
+        assert_eq!(
+            reliable,
+            [(1, b"cmd".to_vec()), (2, big), (0, b"plain".to_vec())]
+        );
+        // Unreliable packets take no send window slots
+        assert_eq!(a.acked_seq, a.next_send_seq - 1);
+        assert_eq!(b.unreliable_dropped(), 0);
+    }
+
+    #[test]
+    fn test_batch_nak_resends_whole_range() {
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        // Frozen clock: the RTO never fires, only NAKs can recover the run
+        a.set_clock(std::sync::Arc::new(TestClock::new()));
+
+        for i in 0..4u8 {
+            a.send(&[i]).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        let mut buf = [0u8; 2048];
+        while b.socket().recv_from(&mut buf).is_ok() {}
+
+        // 4 arrives, b NAKs 0..=3 in one batch NAK on the shared socket
+        a.send(&[4]).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        let mut got = Vec::new();
+        b.receive_batch_with(64, |msg| got.push(msg[0]));
+        assert!(got.is_empty());
+
+        // One NAK round resends the whole run
+        std::thread::sleep(Duration::from_millis(5));
+        a.process_naks();
+        std::thread::sleep(Duration::from_millis(5));
+        b.receive_batch_with(64, |msg| got.push(msg[0]));
+        assert_eq!(got, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_rto_recovers_lost_tail() {
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        let clock = TestClock::new();
+        a.set_clock(std::sync::Arc::new(clock.clone()));
+
+        let mut got = Vec::new();
+        for i in 0..3u8 {
+            a.send(&[i]).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        b.receive_batch_with(64, |msg| got.push(msg[0]));
+        std::thread::sleep(Duration::from_millis(5));
+        a.process_acks();
+        assert_eq!(a.acked_seq, 2);
+
+        // The last packet of the burst is lost: no gap for b to NAK
+        a.send(&[3]).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        let mut buf = [0u8; 2048];
+        while b.socket().recv_from(&mut buf).is_ok() {}
+        a.process_acks();
+        clock.advance(a.rto() + Duration::from_millis(1));
+        a.process_acks();
+
+        std::thread::sleep(Duration::from_millis(5));
+        b.receive_batch_with(64, |msg| got.push(msg[0]));
+        assert_eq!(got, [0, 1, 2, 3]);
+        std::thread::sleep(Duration::from_millis(5));
+        a.process_acks();
+        assert_eq!(a.acked_seq, 3);
+        assert_eq!(a.send_window_occupancy().unacked, 0);
+    }
+
+    #[test]
+    fn test_path_mtu_batches() {
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 1024).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 1024).unwrap();
+        assert_eq!(a.path_mtu(), pmtud::BASE_MTU);
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(2));
+            a.process_acks();
+            b.process_acks();
+            if a.path_mtu() == pmtud::MAX_MTU {
+                break;
+            }
+        }
+        assert_eq!(a.path_mtu(), pmtud::MAX_MTU);
+
+        // 300 × 28 bytes: several datagrams, none over the path MTU
+        let msgs: Vec<[u8; 20]> = (0..300).map(|i| [i as u8; 20]).collect();
+        let refs: Vec<&[u8]> = msgs.iter().map(|m| &m[..]).collect();
+        assert_eq!(a.send_batch(&refs).unwrap(), 300);
+        let mut got = Vec::new();
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(2));
+            b.receive_batch_with(512, |msg| got.push(msg.to_vec()));
+            if got.len() == 300 {
+                break;
+            }
+        }
+        assert_eq!(got.len(), 300);
+        assert_eq!(got[299], [43u8; 20]);
+
+        a.set_mtu_discovery(Box::new(FixedMtu(1200)));
+        let big = [0u8; 1200];
+        let err = a.send_batch(&[&big[..]]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_dead_peer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (a_addr, b_addr) = free_addrs();
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        let clock = TestClock::new();
+        a.set_clock(std::sync::Arc::new(clock.clone()));
+        a.set_peer_timeout(Duration::from_secs(1));
+        let disconnects = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = disconnects.clone();
+        a.on_disconnect(move |addr| {
+            assert_eq!(addr, b_addr);
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        a.process_acks();
+        assert!(a.is_peer_alive());
+        clock.advance(Duration::from_secs(2));
+        a.process_acks();
+        a.process_acks();
+        assert!(!a.is_peer_alive());
+        assert_eq!(disconnects.load(Ordering::SeqCst), 1);
+
+        // b's keepalive brings it back
+        b.set_keepalive_interval(Duration::ZERO);
+        b.poll_timers();
+        std::thread::sleep(Duration::from_millis(5));
+        a.process_acks();
+        assert!(a.is_peer_alive());
+        assert_eq!(disconnects.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_congestion_algorithm_config() {
+        let (a_addr, b_addr) = free_addrs();
+        let config = |local: SocketAddr, remote: SocketAddr, congestion| ReliableUdpConfig {
+            local_addr: local.to_string(),
+            remote_addr: remote.to_string(),
+            window_size: 256,
+            congestion,
+            ..Default::default()
+        };
+        let fixed = CongestionAlgorithm::Fixed {
+            packets_per_sec: 100_000,
+            burst: 32,
+        };
+        let mut a = RudpTransport::auto(config(a_addr, b_addr, fixed)).unwrap();
+        let mut b = RudpTransport::auto(config(b_addr, a_addr, CongestionAlgorithm::Bbr)).unwrap();
+        assert_eq!(a.congestion_algorithm(), fixed);
+        assert_eq!(a.congestion_window(), 32);
+        assert_eq!(exchange(&mut a, &mut b), (20, 19));
+
+        b.set_congestion(CongestionAlgorithm::Cubic);
+        a.set_congestion(CongestionAlgorithm::Cubic);
+        a.reset().unwrap();
+        b.reset().unwrap();
+        assert_eq!(a.congestion_algorithm(), CongestionAlgorithm::Cubic);
+        assert_eq!(exchange(&mut a, &mut b), (20, 19));
+    }
+}
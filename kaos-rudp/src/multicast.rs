@@ -17,10 +17,62 @@
 //!     println!("got {} bytes", msg.len());
 //! });
 //! ```
+//!
+//! IPv6 groups (`ff02::/16` link-local, `ff05::/16` site-local) work the same way:
+//! pass an `Ipv6Addr` group and bind to `[::]:PORT`.
+//!
+//! Source-specific multicast (IGMPv3 / MLDv2), interface selection and
+//! runtime membership changes go through `MulticastConfig` and
+//! `join_group` / `join_source` / `leave_group`:
+//!
+//! ```rust,no_run
+//! use kaos_rudp::{MulticastConfig, MulticastSocket};
+//! use std::net::Ipv4Addr;
+//!
+//! // Only accept the match feed from the game server, on eth1
+//! let config = MulticastConfig::default()
+//!     .with_interface("eth1")
+//!     .with_source(Ipv4Addr::new(10, 0, 0, 5));
+//! let mut socket =
+//!     MulticastSocket::join_with("0.0.0.0:5000", Ipv4Addr::new(232, 1, 0, 7), config).unwrap();
+//!
+//! // Spectator switches match
+//! socket.leave_group(Ipv4Addr::new(232, 1, 0, 7)).unwrap();
+//! socket.join_source(Ipv4Addr::new(232, 1, 0, 8), Ipv4Addr::new(10, 0, 0, 5)).unwrap();
+//! ```
+//!
+//! Sequenced transports (`with_sequencing` / `with_resync_after`) let a
+//! receiver that fell too far behind ask the sender for a state snapshot over
+//! unicast instead of NAKing every lost message, so one slow box doesn't
+//! cost the rest of the group anything:
+//!
+//! ```rust,no_run
+//! use kaos_rudp::{Delivery, MulticastConfig, MulticastTransport};
+//! use std::net::Ipv4Addr;
+//!
+//! let group = Ipv4Addr::new(239, 255, 0, 1);
+//! let config = MulticastConfig::default().with_resync_after(256);
+//! let mut spectator = MulticastTransport::with_config("0.0.0.0:5000", group, 1024, config).unwrap();
+//! spectator.receive_sequenced(64, |delivery, msg| match delivery {
+//!     Delivery::Message(seq) => println!("event {} ({} bytes)", seq, msg.len()),
+//!     Delivery::Snapshot(seq) => println!("state as of {}", seq),
+//! });
+//!
+//! // Sender side: answer resync requests
+//! # let mut server = spectator;
+//! # let state = Vec::new();
+//! server.receive_sequenced(64, |_, _| {});
+//! for request in server.take_resync_requests() {
+//!     server.send_snapshot(request.from, &state).unwrap();
+//! }
+//! ```
 
 use kaos::disruptor::{MessageRingBuffer, RingBufferConfig};
+use std::collections::HashMap;
 use std::io;
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 /// Recv buffer size per packet (> MTU 1500)
 const RECV_PACKET_SIZE: usize = 2048;
@@ -28,62 +80,590 @@ const RECV_PACKET_SIZE: usize = 2048;
 /// Socket buffer size (4MB for throughput)
 const SOCKET_BUFFER_SIZE: usize = 4 * 1024 * 1024;
 
-/// Create multicast socket with SO_REUSEADDR.
-fn create_multicast_socket(bind_addr: SocketAddr, group: Ipv4Addr) -> io::Result<UdpSocket> {
+/// Sequenced frame: `[kind u8][sequence u64 LE][payload]`
+const SEQ_HEADER_SIZE: usize = 9;
+/// Group message
+const FRAME_DATA: u8 = 0x51;
+/// Receiver -> sender (unicast): send me a snapshot, sequence = next expected
+const FRAME_RESYNC: u8 = 0x52;
+/// Sender -> receiver (unicast): state snapshot, sequence = next group message
+const FRAME_SNAPSHOT: u8 = 0x53;
+
+/// Resend an unanswered resync request after this long
+const RESYNC_RETRY: Duration = Duration::from_millis(250);
+
+/// Multicast socket options
+#[derive(Debug, Clone)]
+pub struct MulticastConfig {
+    /// Interface name ("eth0") for joins and sends; `None` = kernel default
+    pub interface: Option<String>,
+    /// TTL / hop limit (1 = local network, 255 = unrestricted)
+    pub ttl: u32,
+    /// Receive own messages
+    pub loopback: bool,
+    /// Source-specific multicast: accept only these senders (empty = any source)
+    pub sources: Vec<IpAddr>,
+    /// Prefix messages with a sequence number (every group member must agree)
+    pub sequenced: bool,
+    /// Sequenced receivers missing more than this many messages at once ask
+    /// the sender for a snapshot instead of skipping ahead (`None` = skip)
+    pub resync_after: Option<u64>,
+}
+
+impl Default for MulticastConfig {
+    fn default() -> Self {
+        Self {
+            interface: None,
+            ttl: 1,
+            loopback: false,
+            sources: Vec::new(),
+            sequenced: false,
+            resync_after: None,
+        }
+    }
+}
+
+impl MulticastConfig {
+    pub fn with_interface(mut self, name: impl Into<String>) -> Self {
+        self.interface = Some(name.into());
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_loopback(mut self, enable: bool) -> Self {
+        self.loopback = enable;
+        self
+    }
+
+    /// Add an SSM source filter
+    pub fn with_source(mut self, source: impl Into<IpAddr>) -> Self {
+        self.sources.push(source.into());
+        self
+    }
+
+    /// Sequence numbers for gap tracking (see `receive_sequenced`)
+    pub fn with_sequencing(mut self, enable: bool) -> Self {
+        self.sequenced = enable;
+        self
+    }
+
+    /// Request a snapshot after a gap of more than `gap` messages (implies sequencing)
+    pub fn with_resync_after(mut self, gap: u64) -> Self {
+        self.sequenced = true;
+        self.resync_after = Some(gap);
+        self
+    }
+}
+
+/// What `receive_sequenced` hands to the handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Next group message, with its sequence
+    Message(u64),
+    /// Snapshot answering our resync request; group messages resume at this sequence
+    Snapshot(u64),
+}
+
+/// Receive counters for one sender of a sequenced group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// Messages handed to the application
+    pub delivered: u64,
+    /// Messages never received (gaps)
+    pub lost: u64,
+    /// Received while waiting for a snapshot, dropped
+    pub discarded: u64,
+    /// Late or duplicate messages, dropped
+    pub stale: u64,
+    /// Resync requests sent (including retries)
+    pub resyncs: u64,
+    /// Messages this receiver is behind the newest one it has seen
+    pub lag: u64,
+}
+
+/// A receiver asking for a snapshot (see `take_resync_requests`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResyncRequest {
+    /// Where to `send_snapshot`
+    pub from: SocketAddr,
+    /// Next sequence the receiver expected
+    pub expected: u64,
+}
+
+/// Sender-side view of a receiver that asked for resyncs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiverLag {
+    /// Resync requests received from it
+    pub requests: u64,
+    /// Messages it was behind at its last request
+    pub behind: u64,
+    pub last_request: Instant,
+}
+
+/// What to do with a sequenced message
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Deliver,
+    Drop,
+    /// Drop and (re)send a resync request
+    Resync,
+}
+
+/// Gap tracking for one sender
+#[derive(Debug, Default)]
+struct SourceTracker {
+    /// Next sequence to deliver (set by the first message)
+    expected: Option<u64>,
+    /// Highest sequence seen + 1
+    seen: u64,
+    /// When the outstanding resync request went out
+    resync_sent: Option<Instant>,
+    stats: SequenceStats,
+}
+
+impl SourceTracker {
+    fn on_data(&mut self, seq: u64, resync_after: Option<u64>, now: Instant) -> Action {
+        self.seen = self.seen.max(seq + 1);
+        if let Some(sent) = self.resync_sent {
+            self.stats.discarded += 1;
+            if now.duration_since(sent) < RESYNC_RETRY {
+                return Action::Drop;
+            }
+            self.resync_sent = Some(now);
+            self.stats.resyncs += 1;
+            return Action::Resync;
+        }
+
+        let expected = *self.expected.get_or_insert(seq);
+        if seq < expected {
+            self.stats.stale += 1;
+            return Action::Drop;
+        }
+        let gap = seq - expected;
+        self.stats.lost += gap;
+        if resync_after.is_some_and(|limit| gap > limit) {
+            self.stats.discarded += 1;
+            self.resync_sent = Some(now);
+            self.stats.resyncs += 1;
+            return Action::Resync;
+        }
+        self.expected = Some(seq + 1);
+        self.stats.delivered += 1;
+        Action::Deliver
+    }
+
+    /// Snapshot taken when the sender's next sequence was `seq`.
+    /// Ignored unless we asked for one.
+    fn on_snapshot(&mut self, seq: u64) -> bool {
+        if self.resync_sent.take().is_none() {
+            return false;
+        }
+        self.expected = Some(seq);
+        self.seen = self.seen.max(seq);
+        true
+    }
+
+    fn stats(&self) -> SequenceStats {
+        SequenceStats {
+            lag: self.seen - self.expected.unwrap_or(self.seen).min(self.seen),
+            ..self.stats
+        }
+    }
+}
+
+/// Append a sequenced frame to `buf`
+fn write_frame(buf: &mut Vec<u8>, kind: u8, seq: u64, data: &[u8]) {
+    buf.push(kind);
+    buf.extend_from_slice(&seq.to_le_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// Interface index for a name (IPv6 joins, `IPV6_MULTICAST_IF`).
+#[cfg(unix)]
+pub fn interface_index(name: &str) -> io::Result<u32> {
+    let c_name = std::ffi::CString::new(name)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "bad interface name"))?;
+    // Safety: c_name is a valid NUL-terminated string
+    let index = unsafe { libc::if_nametoindex(c_name.as_ptr()) };
+    if index == 0 {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no interface {}", name),
+        ))
+    } else {
+        Ok(index)
+    }
+}
+
+/// Interface lookup by name needs `if_nametoindex` (unix only).
+#[cfg(not(unix))]
+pub fn interface_index(_name: &str) -> io::Result<u32> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "interface by name: unix only",
+    ))
+}
+
+/// First IPv4 address of an interface (IPv4 joins, `IP_MULTICAST_IF`).
+#[cfg(unix)]
+pub fn interface_ipv4(name: &str) -> io::Result<Ipv4Addr> {
+    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    // Safety: getifaddrs fills a list we free below
+    if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut found = None;
+    let mut cur = addrs;
+    while !cur.is_null() {
+        // Safety: cur walks the list returned by getifaddrs
+        unsafe {
+            let ifa = &*cur;
+            let matches = std::ffi::CStr::from_ptr(ifa.ifa_name).to_bytes() == name.as_bytes();
+            if matches
+                && !ifa.ifa_addr.is_null()
+                && (*ifa.ifa_addr).sa_family as i32 == libc::AF_INET
+            {
+                let sin = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                found = Some(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)));
+                break;
+            }
+            cur = ifa.ifa_next;
+        }
+    }
+    // Safety: addrs came from getifaddrs
+    unsafe { libc::freeifaddrs(addrs) };
+    found.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no IPv4 address on {}", name),
+        )
+    })
+}
+
+/// Interface lookup by name needs `getifaddrs` (unix only).
+#[cfg(not(unix))]
+pub fn interface_ipv4(_name: &str) -> io::Result<Ipv4Addr> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "interface by name: unix only",
+    ))
+}
+
+/// Group memberships held by one socket (left on drop).
+#[derive(Debug, Default)]
+struct Memberships {
+    interface: Option<String>,
+    /// (group, SSM source)
+    joined: Vec<(IpAddr, Option<IpAddr>)>,
+}
+
+impl Memberships {
+    fn iface_v4(&self) -> io::Result<Ipv4Addr> {
+        match &self.interface {
+            Some(name) => interface_ipv4(name),
+            None => Ok(Ipv4Addr::UNSPECIFIED),
+        }
+    }
+
+    fn iface_index(&self) -> io::Result<u32> {
+        match &self.interface {
+            Some(name) => interface_index(name),
+            None => Ok(0), // kernel picks (default route)
+        }
+    }
+
+    fn join(
+        &mut self,
+        socket: &UdpSocket,
+        group: IpAddr,
+        source: Option<IpAddr>,
+    ) -> io::Result<()> {
+        if !group.is_multicast() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a multicast group",
+            ));
+        }
+        if group.is_ipv4() != socket.local_addr()?.is_ipv4()
+            || source.is_some_and(|s| s.is_ipv4() != group.is_ipv4())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "socket, group and source must be the same IP version",
+            ));
+        }
+        if self.joined.contains(&(group, source)) {
+            return Ok(());
+        }
+        let sock = socket2::SockRef::from(socket);
+        match (group, source) {
+            (IpAddr::V4(g), None) => sock.join_multicast_v4(&g, &self.iface_v4()?)?,
+            (IpAddr::V4(g), Some(IpAddr::V4(src))) => {
+                sock.join_ssm_v4(&src, &g, &self.iface_v4()?)?
+            }
+            (IpAddr::V6(g), None) => sock.join_multicast_v6(&g, self.iface_index()?)?,
+            (IpAddr::V6(g), Some(IpAddr::V6(src))) => {
+                ssm_v6(socket, g, src, self.iface_index()?, true)?
+            }
+            _ => unreachable!("families checked above"),
+        }
+        self.joined.push((group, source));
+        Ok(())
+    }
+
+    /// Leave matching memberships (`source: None` = every membership of `group`)
+    fn leave(
+        &mut self,
+        socket: &UdpSocket,
+        group: IpAddr,
+        source: Option<IpAddr>,
+    ) -> io::Result<()> {
+        let mut result = Ok(());
+        let mut kept = Vec::with_capacity(self.joined.len());
+        for (g, src) in std::mem::take(&mut self.joined) {
+            if g != group || (source.is_some() && src != source) {
+                kept.push((g, src));
+                continue;
+            }
+            let r = self.leave_one(socket, g, src);
+            if result.is_ok() {
+                result = r;
+            }
+        }
+        self.joined = kept;
+        result
+    }
+
+    fn leave_one(
+        &self,
+        socket: &UdpSocket,
+        group: IpAddr,
+        source: Option<IpAddr>,
+    ) -> io::Result<()> {
+        let sock = socket2::SockRef::from(socket);
+        match (group, source) {
+            (IpAddr::V4(g), None) => sock.leave_multicast_v4(&g, &self.iface_v4()?),
+            (IpAddr::V4(g), Some(IpAddr::V4(src))) => {
+                sock.leave_ssm_v4(&src, &g, &self.iface_v4()?)
+            }
+            (IpAddr::V6(g), None) => sock.leave_multicast_v6(&g, self.iface_index()?),
+            (IpAddr::V6(g), Some(IpAddr::V6(src))) => {
+                ssm_v6(socket, g, src, self.iface_index()?, false)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Leave everything (best effort, used on drop).
+    fn leave_all(&mut self, socket: &UdpSocket) {
+        for (g, src) in std::mem::take(&mut self.joined) {
+            let _ = self.leave_one(socket, g, src);
+        }
+    }
+}
+
+/// IPv6 source-specific join/leave (`MCAST_{JOIN,LEAVE}_SOURCE_GROUP`, Linux).
+#[cfg(target_os = "linux")]
+fn ssm_v6(
+    socket: &UdpSocket,
+    group: Ipv6Addr,
+    source: Ipv6Addr,
+    iface: u32,
+    join: bool,
+) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    fn storage(ip: Ipv6Addr) -> libc::sockaddr_storage {
+        // Safety: all-zero is a valid sockaddr_storage
+        let mut ss: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+        let sin6 = &mut ss as *mut _ as *mut libc::sockaddr_in6;
+        // Safety: sockaddr_storage is large enough and aligned for sockaddr_in6
+        unsafe {
+            (*sin6).sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            (*sin6).sin6_addr.s6_addr = ip.octets();
+        }
+        ss
+    }
+
+    let req = libc::group_source_req {
+        gsr_interface: iface,
+        gsr_group: storage(group),
+        gsr_source: storage(source),
+    };
+    let opt = if join {
+        libc::MCAST_JOIN_SOURCE_GROUP
+    } else {
+        libc::MCAST_LEAVE_SOURCE_GROUP
+    };
+    // Safety: fd is a socket, req is a valid group_source_req
+    let r = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            opt,
+            &req as *const _ as *const libc::c_void,
+            std::mem::size_of::<libc::group_source_req>() as libc::socklen_t,
+        )
+    };
+    if r < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// IPv6 SSM needs `group_source_req` (Linux only).
+#[cfg(not(target_os = "linux"))]
+fn ssm_v6(
+    _socket: &UdpSocket,
+    _group: Ipv6Addr,
+    _source: Ipv6Addr,
+    _iface: u32,
+    _join: bool,
+) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "IPv6 source-specific multicast: Linux only",
+    ))
+}
+
+/// Create multicast socket with SO_REUSEADDR (IPv4 or IPv6, following `group`),
+/// apply `config` and join `group` (once per SSM source, or any-source).
+fn create_multicast_socket(
+    bind_addr: SocketAddr,
+    group: IpAddr,
+    config: &MulticastConfig,
+) -> io::Result<(UdpSocket, Memberships)> {
+    if !group.is_multicast() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a multicast group",
+        ));
+    }
+    // "0.0.0.0:PORT" with a v6 group means "[::]:PORT"
+    let bind_addr = match (bind_addr.ip(), group) {
+        (IpAddr::V4(ip), IpAddr::V6(_)) if ip.is_unspecified() => {
+            SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), bind_addr.port())
+        }
+        (ip, g) if ip.is_ipv4() != g.is_ipv4() => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bind address and group must be the same IP version",
+            ))
+        }
+        _ => bind_addr,
+    };
+
     let socket2 = socket2::Socket::new(
-        socket2::Domain::IPV4,
+        socket2::Domain::for_address(bind_addr),
         socket2::Type::DGRAM,
         Some(socket2::Protocol::UDP),
     )?;
     socket2.set_reuse_address(true)?;
     socket2.set_send_buffer_size(SOCKET_BUFFER_SIZE)?;
     socket2.set_recv_buffer_size(SOCKET_BUFFER_SIZE)?;
-
-    let addr = SocketAddrV4::new(
-        match bind_addr.ip() {
-            std::net::IpAddr::V4(ip) => ip,
-            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "IPv4 only")),
-        },
-        bind_addr.port(),
-    );
-    socket2.bind(&addr.into())?;
+    if bind_addr.is_ipv6() {
+        socket2.set_only_v6(true)?;
+    }
+    socket2.bind(&bind_addr.into())?;
 
     let socket: UdpSocket = socket2.into();
-    socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)?;
-    socket.set_multicast_loop_v4(false)?;
-    socket.set_multicast_ttl_v4(1)?;
+    let mut memberships = Memberships {
+        interface: config.interface.clone(),
+        joined: Vec::new(),
+    };
 
-    Ok(socket)
+    set_multicast_loop(&socket, group, config.loopback)?;
+    set_multicast_ttl(&socket, group, config.ttl)?;
+    if config.interface.is_some() {
+        // Send out of the same interface we joined on
+        let sock = socket2::SockRef::from(&socket);
+        match group {
+            IpAddr::V4(_) => sock.set_multicast_if_v4(&memberships.iface_v4()?)?,
+            IpAddr::V6(_) => sock.set_multicast_if_v6(memberships.iface_index()?)?,
+        }
+    }
+
+    if config.sources.is_empty() {
+        memberships.join(&socket, group, None)?;
+    } else {
+        for &source in &config.sources {
+            memberships.join(&socket, group, Some(source))?;
+        }
+    }
+
+    Ok((socket, memberships))
+}
+
+/// Set TTL / hop limit for either family.
+fn set_multicast_ttl(socket: &UdpSocket, group: IpAddr, ttl: u32) -> io::Result<()> {
+    match group {
+        IpAddr::V4(_) => socket.set_multicast_ttl_v4(ttl),
+        IpAddr::V6(_) => socket2::SockRef::from(socket).set_multicast_hops_v6(ttl),
+    }
+}
+
+/// Enable/disable loopback for either family.
+fn set_multicast_loop(socket: &UdpSocket, group: IpAddr, enable: bool) -> io::Result<()> {
+    match group {
+        IpAddr::V4(_) => socket.set_multicast_loop_v4(enable),
+        IpAddr::V6(_) => socket.set_multicast_loop_v6(enable),
+    }
 }
 
 /// UDP multicast transport with Kaos ring buffer.
 pub struct MulticastTransport {
     socket: UdpSocket,
-    group: Ipv4Addr,
+    group: IpAddr,
+    memberships: Memberships,
     port: u16,
     send_ring: MessageRingBuffer,
     consumer_seq: u64,
+    sequenced: bool,
+    resync_after: Option<u64>,
+    /// Sequence of the next group message we send
+    send_seq: AtomicU64,
+    /// Gap tracking per sender
+    sources: HashMap<SocketAddr, SourceTracker>,
+    resync_requests: Vec<ResyncRequest>,
+    receivers: HashMap<SocketAddr, ReceiverLag>,
 }
 
 impl MulticastTransport {
     /// Create multicast transport.
     ///
-    /// - `bind_addr`: Local address (use "0.0.0.0:PORT")
-    /// - `group`: Multicast group (224.0.0.0 - 239.255.255.255)
+    /// - `bind_addr`: Local address (use "0.0.0.0:PORT" or "[::]:PORT")
+    /// - `group`: Multicast group (224.0.0.0/4 or ff00::/8)
     /// - `ring_size`: Ring buffer size (must be power of 2)
     pub fn new<A: ToSocketAddrs>(
         bind_addr: A,
-        group: Ipv4Addr,
+        group: impl Into<IpAddr>,
+        ring_size: usize,
+    ) -> io::Result<Self> {
+        Self::with_config(bind_addr, group, ring_size, MulticastConfig::default())
+    }
+
+    /// Create multicast transport with interface / TTL / SSM source options.
+    pub fn with_config<A: ToSocketAddrs>(
+        bind_addr: A,
+        group: impl Into<IpAddr>,
         ring_size: usize,
+        config: MulticastConfig,
     ) -> io::Result<Self> {
+        let group = group.into();
         let bind_addr = bind_addr
             .to_socket_addrs()?
             .next()
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid address"))?;
 
-        let socket = create_multicast_socket(bind_addr, group)?;
+        let (socket, memberships) = create_multicast_socket(bind_addr, group, &config)?;
         socket.set_nonblocking(true)?;
 
+        let (sequenced, resync_after) = (config.sequenced, config.resync_after);
         let config = RingBufferConfig::new(ring_size)
             .map_err(|e| io::Error::other(format!("ring config: {}", e)))?
             .with_consumers(1)
@@ -95,9 +675,16 @@ impl MulticastTransport {
         Ok(Self {
             socket,
             group,
+            memberships,
             port: bind_addr.port(),
             send_ring,
             consumer_seq: 0,
+            sequenced,
+            resync_after,
+            send_seq: AtomicU64::new(0),
+            sources: HashMap::new(),
+            resync_requests: Vec::new(),
+            receivers: HashMap::new(),
         })
     }
 
@@ -128,7 +715,7 @@ impl MulticastTransport {
 
     /// Flush send ring to network.
     pub fn flush(&mut self) -> io::Result<usize> {
-        let dest = SocketAddr::new(self.group.into(), self.port);
+        let dest = SocketAddr::new(self.group, self.port);
         let mut sent = 0;
 
         let slots = self.send_ring.peek_batch(0, 1024);
@@ -137,7 +724,17 @@ impl MulticastTransport {
             if data.is_empty() {
                 break;
             }
-            match self.socket.send_to(data, dest) {
+            let result = if self.sequenced {
+                let seq = self.send_seq.load(Ordering::Relaxed);
+                let result = self.send_framed(FRAME_DATA, seq, data, dest);
+                if result.is_ok() {
+                    self.send_seq.store(seq + 1, Ordering::Relaxed);
+                }
+                result
+            } else {
+                self.socket.send_to(data, dest)
+            };
+            match result {
                 Ok(_) => {
                     sent += 1;
                     self.consumer_seq += 1;
@@ -160,11 +757,27 @@ impl MulticastTransport {
 
     /// Send immediately (bypass ring).
     pub fn send_now(&self, data: &[u8]) -> io::Result<usize> {
-        let dest = SocketAddr::new(self.group.into(), self.port);
+        let dest = SocketAddr::new(self.group, self.port);
+        if self.sequenced {
+            let seq = self.send_seq.fetch_add(1, Ordering::Relaxed);
+            return self.send_framed(FRAME_DATA, seq, data, dest);
+        }
         self.socket.send_to(data, dest)
     }
 
-    /// Receive batch of messages.
+    /// Send one sequenced frame; returns payload bytes sent
+    fn send_framed(&self, kind: u8, seq: u64, data: &[u8], dest: SocketAddr) -> io::Result<usize> {
+        crate::SEND_BUFFER.with(|buf_cell| {
+            let mut buf = buf_cell.borrow_mut();
+            buf.clear();
+            write_frame(&mut buf, kind, seq, data);
+            self.socket
+                .send_to(&buf, dest)
+                .map(|n| n.saturating_sub(SEQ_HEADER_SIZE))
+        })
+    }
+
+    /// Receive batch of raw datagrams (sequenced transports: `receive_sequenced`).
     pub fn receive_batch<F>(&self, max: usize, mut handler: F) -> usize
     where
         F: FnMut(&[u8]),
@@ -187,6 +800,106 @@ impl MulticastTransport {
         count
     }
 
+    /// Receive up to `max` datagrams of a sequenced group.
+    ///
+    /// Messages are delivered in order per sender. Small gaps are skipped
+    /// (counted in `sequence_stats`); a gap beyond `resync_after` sends a
+    /// unicast resync request to that sender and drops its messages until
+    /// the snapshot arrives. Resync requests from receivers are queued for
+    /// `take_resync_requests`. Returns number of deliveries.
+    pub fn receive_sequenced<F>(&mut self, max: usize, mut handler: F) -> usize
+    where
+        F: FnMut(Delivery, &[u8]),
+    {
+        let mut buf = [0u8; RECV_PACKET_SIZE];
+        let mut count = 0;
+
+        for _ in 0..max {
+            let Ok((len, from)) = self.socket.recv_from(&mut buf) else {
+                break;
+            };
+            if len < SEQ_HEADER_SIZE {
+                continue;
+            }
+            let seq = u64::from_le_bytes(buf[1..SEQ_HEADER_SIZE].try_into().unwrap());
+            let payload = &buf[SEQ_HEADER_SIZE..len];
+            match buf[0] {
+                FRAME_DATA => {
+                    let tracker = self.sources.entry(from).or_default();
+                    match tracker.on_data(seq, self.resync_after, Instant::now()) {
+                        Action::Deliver => {
+                            handler(Delivery::Message(seq), payload);
+                            count += 1;
+                        }
+                        Action::Resync => {
+                            let expected = tracker.expected.unwrap_or(seq);
+                            let _ = self.send_framed(FRAME_RESYNC, expected, &[], from);
+                        }
+                        Action::Drop => {}
+                    }
+                }
+                // Unsolicited snapshots are ignored
+                FRAME_SNAPSHOT
+                    if self
+                        .sources
+                        .get_mut(&from)
+                        .is_some_and(|tracker| tracker.on_snapshot(seq)) =>
+                {
+                    handler(Delivery::Snapshot(seq), payload);
+                    count += 1;
+                }
+                FRAME_RESYNC => {
+                    let next = self.send_seq.load(Ordering::Relaxed);
+                    let lag = self.receivers.entry(from).or_insert(ReceiverLag {
+                        requests: 0,
+                        behind: 0,
+                        last_request: Instant::now(),
+                    });
+                    lag.requests += 1;
+                    lag.behind = next.saturating_sub(seq);
+                    lag.last_request = Instant::now();
+                    self.resync_requests.push(ResyncRequest {
+                        from,
+                        expected: seq,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        count
+    }
+
+    /// Resync requests received since the last call
+    pub fn take_resync_requests(&mut self) -> Vec<ResyncRequest> {
+        std::mem::take(&mut self.resync_requests)
+    }
+
+    /// Unicast a state snapshot to a receiver that asked for one. `state`
+    /// must cover every message sent so far (`flush` first); the receiver
+    /// resumes at the next sequence.
+    pub fn send_snapshot(&self, to: SocketAddr, state: &[u8]) -> io::Result<usize> {
+        let next = self.send_seq.load(Ordering::Relaxed);
+        self.send_framed(FRAME_SNAPSHOT, next, state, to)
+    }
+
+    /// Sequence the next group message will carry
+    pub fn next_sequence(&self) -> u64 {
+        self.send_seq.load(Ordering::Relaxed)
+    }
+
+    /// Receive counters per sender (sequenced transports)
+    pub fn sequence_stats(&self) -> impl Iterator<Item = (SocketAddr, SequenceStats)> + '_ {
+        self.sources
+            .iter()
+            .map(|(addr, tracker)| (*addr, tracker.stats()))
+    }
+
+    /// Receivers that asked this sender for resyncs
+    pub fn receiver_lag(&self) -> impl Iterator<Item = (SocketAddr, ReceiverLag)> + '_ {
+        self.receivers.iter().map(|(addr, lag)| (*addr, *lag))
+    }
+
     /// Receive single message (blocking).
     pub fn recv(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
         self.socket.set_nonblocking(false)?;
@@ -197,19 +910,54 @@ impl MulticastTransport {
 
     /// Set TTL (hop limit). 1 = local network, 255 = unrestricted.
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
-        self.socket.set_multicast_ttl_v4(ttl)
+        set_multicast_ttl(&self.socket, self.group, ttl)
     }
 
     /// Enable/disable receiving own messages.
     pub fn set_loopback(&self, enable: bool) -> io::Result<()> {
-        self.socket.set_multicast_loop_v4(enable)
+        set_multicast_loop(&self.socket, self.group, enable)
     }
 
-    /// Get multicast group.
-    pub fn group(&self) -> Ipv4Addr {
+    /// Get multicast group (send destination).
+    pub fn group(&self) -> IpAddr {
         self.group
     }
 
+    /// Join another group (any source) at runtime.
+    pub fn join_group(&mut self, group: impl Into<IpAddr>) -> io::Result<()> {
+        self.memberships.join(&self.socket, group.into(), None)
+    }
+
+    /// Join a group for one source only (SSM, IGMPv3 / MLDv2).
+    pub fn join_source(
+        &mut self,
+        group: impl Into<IpAddr>,
+        source: impl Into<IpAddr>,
+    ) -> io::Result<()> {
+        self.memberships
+            .join(&self.socket, group.into(), Some(source.into()))
+    }
+
+    /// Leave every membership (any-source and SSM) of a group.
+    pub fn leave_group(&mut self, group: impl Into<IpAddr>) -> io::Result<()> {
+        self.memberships.leave(&self.socket, group.into(), None)
+    }
+
+    /// Stop accepting one SSM source of a group.
+    pub fn leave_source(
+        &mut self,
+        group: impl Into<IpAddr>,
+        source: impl Into<IpAddr>,
+    ) -> io::Result<()> {
+        self.memberships
+            .leave(&self.socket, group.into(), Some(source.into()))
+    }
+
+    /// Current memberships: (group, SSM source)
+    pub fn memberships(&self) -> &[(IpAddr, Option<IpAddr>)] {
+        &self.memberships.joined
+    }
+
     /// Get underlying socket.
     pub fn socket(&self) -> &UdpSocket {
         &self.socket
@@ -226,8 +974,13 @@ impl Transport for MulticastTransport {
         Ok(self.consumer_seq)
     }
 
-    fn receive<F: FnMut(&[u8])>(&mut self, handler: F) -> usize {
-        let count = MulticastTransport::receive_batch(self, 64, handler);
+    fn receive<F: FnMut(&[u8])>(&mut self, mut handler: F) -> usize {
+        let count = if self.sequenced {
+            // Messages and snapshots alike
+            MulticastTransport::receive_sequenced(self, 64, |_, msg| handler(msg))
+        } else {
+            MulticastTransport::receive_batch(self, 64, handler)
+        };
         self.consumer_seq += count as u64;
         count
     }
@@ -241,9 +994,7 @@ impl BatchTransport for MulticastTransport {
 
 impl Drop for MulticastTransport {
     fn drop(&mut self) {
-        let _ = self
-            .socket
-            .leave_multicast_v4(&self.group, &Ipv4Addr::UNSPECIFIED);
+        self.memberships.leave_all(&self.socket);
     }
 }
 
@@ -251,31 +1002,42 @@ impl Drop for MulticastTransport {
 /// Simple UDP multicast socket (no ring buffer).
 pub struct MulticastSocket {
     socket: UdpSocket,
-    group: Ipv4Addr,
+    group: IpAddr,
+    memberships: Memberships,
     port: u16,
 }
 
 impl MulticastSocket {
     /// Join a multicast group.
-    pub fn join<A: ToSocketAddrs>(bind_addr: A, group: Ipv4Addr) -> io::Result<Self> {
+    pub fn join<A: ToSocketAddrs>(bind_addr: A, group: impl Into<IpAddr>) -> io::Result<Self> {
+        Self::join_with(bind_addr, group, MulticastConfig::default())
+    }
+
+    /// Join a multicast group with interface / TTL / SSM source options.
+    pub fn join_with<A: ToSocketAddrs>(
+        bind_addr: A,
+        group: impl Into<IpAddr>,
+        config: MulticastConfig,
+    ) -> io::Result<Self> {
+        let group = group.into();
         let bind_addr = bind_addr
             .to_socket_addrs()?
             .next()
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid address"))?;
 
-        let socket = create_multicast_socket(bind_addr, group)?;
+        let (socket, memberships) = create_multicast_socket(bind_addr, group, &config)?;
 
         Ok(Self {
             socket,
             group,
+            memberships,
             port: bind_addr.port(),
         })
     }
 
     /// Send to all group members.
     pub fn send(&self, data: &[u8], port: u16) -> io::Result<usize> {
-        self.socket
-            .send_to(data, SocketAddr::new(self.group.into(), port))
+        self.socket.send_to(data, SocketAddr::new(self.group, port))
     }
 
     /// Send to group on same port.
@@ -295,19 +1057,54 @@ impl MulticastSocket {
 
     /// Set TTL.
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
-        self.socket.set_multicast_ttl_v4(ttl)
+        set_multicast_ttl(&self.socket, self.group, ttl)
     }
 
     /// Set loopback.
     pub fn set_loopback(&self, enable: bool) -> io::Result<()> {
-        self.socket.set_multicast_loop_v4(enable)
+        set_multicast_loop(&self.socket, self.group, enable)
     }
 
-    /// Get group.
-    pub fn group(&self) -> Ipv4Addr {
+    /// Get group (send destination).
+    pub fn group(&self) -> IpAddr {
         self.group
     }
 
+    /// Join another group (any source) at runtime.
+    pub fn join_group(&mut self, group: impl Into<IpAddr>) -> io::Result<()> {
+        self.memberships.join(&self.socket, group.into(), None)
+    }
+
+    /// Join a group for one source only (SSM, IGMPv3 / MLDv2).
+    pub fn join_source(
+        &mut self,
+        group: impl Into<IpAddr>,
+        source: impl Into<IpAddr>,
+    ) -> io::Result<()> {
+        self.memberships
+            .join(&self.socket, group.into(), Some(source.into()))
+    }
+
+    /// Leave every membership (any-source and SSM) of a group.
+    pub fn leave_group(&mut self, group: impl Into<IpAddr>) -> io::Result<()> {
+        self.memberships.leave(&self.socket, group.into(), None)
+    }
+
+    /// Stop accepting one SSM source of a group.
+    pub fn leave_source(
+        &mut self,
+        group: impl Into<IpAddr>,
+        source: impl Into<IpAddr>,
+    ) -> io::Result<()> {
+        self.memberships
+            .leave(&self.socket, group.into(), Some(source.into()))
+    }
+
+    /// Current memberships: (group, SSM source)
+    pub fn memberships(&self) -> &[(IpAddr, Option<IpAddr>)] {
+        &self.memberships.joined
+    }
+
     /// Get socket.
     pub fn socket(&self) -> &UdpSocket {
         &self.socket
@@ -316,9 +1113,7 @@ impl MulticastSocket {
 
 impl Drop for MulticastSocket {
     fn drop(&mut self) {
-        let _ = self
-            .socket
-            .leave_multicast_v4(&self.group, &Ipv4Addr::UNSPECIFIED);
+        self.memberships.leave_all(&self.socket);
     }
 }
 
@@ -342,4 +1137,215 @@ mod tests {
             assert_eq!(s.group(), group);
         }
     }
+
+    #[test]
+    fn test_ipv6_group_create() {
+        let group: Ipv6Addr = "ff02::4b:6173".parse().unwrap();
+        // "0.0.0.0" is promoted to "[::]" for v6 groups; may fail without v6 multicast
+        if let Ok(s) = MulticastSocket::join("0.0.0.0:0", group) {
+            assert_eq!(s.group(), group);
+            assert!(s.socket().local_addr().unwrap().is_ipv6());
+            s.set_ttl(2).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_multicast_and_mixed_families() {
+        let unicast = Ipv4Addr::new(10, 0, 0, 1);
+        assert!(MulticastSocket::join("0.0.0.0:0", unicast).is_err());
+
+        let v4_group = Ipv4Addr::new(239, 255, 0, 1);
+        assert!(MulticastSocket::join("[::]:0", v4_group).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_interface_lookup() {
+        assert!(interface_index("lo").unwrap_or(1) > 0);
+        if let Ok(ip) = interface_ipv4("lo") {
+            assert!(ip.is_loopback());
+        }
+        assert!(interface_index("no-such-if0").is_err());
+        assert!(interface_ipv4("no-such-if0").is_err());
+    }
+
+    /// Loopback multicast receiver on `lo`, or None if the sandbox forbids it
+    fn lo_receiver(group: Ipv4Addr, config: MulticastConfig) -> Option<MulticastSocket> {
+        let config = config.with_interface("lo").with_loopback(true);
+        let s = MulticastSocket::join_with("0.0.0.0:0", group, config).ok()?;
+        s.set_nonblocking(true).ok()?;
+        Some(s)
+    }
+
+    fn lo_send(group: Ipv4Addr, port: u16, data: &[u8]) {
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tx.set_multicast_loop_v4(true).unwrap();
+        socket2::SockRef::from(&tx)
+            .set_multicast_if_v4(&Ipv4Addr::LOCALHOST)
+            .unwrap();
+        tx.send_to(data, (group, port)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+
+    fn drain(s: &MulticastSocket) -> Vec<Vec<u8>> {
+        let mut buf = [0u8; 64];
+        let mut got = Vec::new();
+        while let Ok((n, _)) = s.recv(&mut buf) {
+            got.push(buf[..n].to_vec());
+        }
+        got
+    }
+
+    #[test]
+    fn test_ssm_source_filter() {
+        let group = Ipv4Addr::new(232, 1, 2, 3);
+        let Some(allowed) = lo_receiver(
+            group,
+            MulticastConfig::default().with_source(Ipv4Addr::LOCALHOST),
+        ) else {
+            return;
+        };
+        let other = Ipv4Addr::new(10, 255, 0, 9);
+        let Some(blocked) = lo_receiver(group, MulticastConfig::default().with_source(other))
+        else {
+            return;
+        };
+        assert_eq!(
+            allowed.memberships(),
+            &[(group.into(), Some(Ipv4Addr::LOCALHOST.into()))]
+        );
+
+        let port = allowed.socket().local_addr().unwrap().port();
+        lo_send(group, port, b"feed");
+        if drain(&allowed).is_empty() {
+            return; // no multicast routing on lo here
+        }
+        let port = blocked.socket().local_addr().unwrap().port();
+        lo_send(group, port, b"feed");
+        assert!(drain(&blocked).is_empty());
+    }
+
+    #[test]
+    fn test_gap_tracking() {
+        let now = Instant::now();
+        let mut t = SourceTracker::default();
+        // Late joiner starts wherever the group is
+        assert_eq!(t.on_data(10, Some(4), now), Action::Deliver);
+        assert_eq!(t.on_data(11, Some(4), now), Action::Deliver);
+        assert_eq!(t.on_data(14, Some(4), now), Action::Deliver); // small gap: skip ahead
+        assert_eq!(t.on_data(13, Some(4), now), Action::Drop); // late
+        assert_eq!(t.stats().lost, 2);
+        assert_eq!(t.stats().stale, 1);
+
+        // Fell too far behind: ask once, drop until the snapshot
+        assert_eq!(t.on_data(30, Some(4), now), Action::Resync);
+        assert_eq!(t.on_data(31, Some(4), now), Action::Drop);
+        assert_eq!(t.stats().lag, 17);
+        assert_eq!(t.on_data(32, Some(4), now + RESYNC_RETRY), Action::Resync);
+        assert!(t.on_snapshot(33));
+        assert!(!t.on_snapshot(33)); // answer to the retry
+        assert_eq!(t.on_data(32, Some(4), now), Action::Drop);
+        assert_eq!(t.on_data(33, Some(4), now), Action::Deliver);
+
+        let stats = t.stats();
+        assert_eq!((stats.delivered, stats.discarded, stats.resyncs), (4, 3, 2));
+        assert_eq!(stats.lag, 0);
+
+        // No resync configured: always skip ahead
+        let mut t = SourceTracker::default();
+        t.on_data(0, None, now);
+        assert_eq!(t.on_data(1000, None, now), Action::Deliver);
+        assert_eq!(t.stats().lost, 999);
+    }
+
+    fn frame(kind: u8, seq: u64, data: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, kind, seq, data);
+        buf
+    }
+
+    #[test]
+    fn test_resync_round_trip() {
+        let group = Ipv4Addr::new(239, 255, 7, 7);
+        let config = MulticastConfig::default().with_resync_after(8);
+        // Unicast over loopback stands in for the group; may fail in sandboxes
+        let Ok(mut rx) = MulticastTransport::with_config("0.0.0.0:0", group, 64, config.clone())
+        else {
+            return;
+        };
+        let Ok(mut tx) = MulticastTransport::with_config("0.0.0.0:0", group, 64, config) else {
+            return;
+        };
+        let rx_addr: SocketAddr = ([127, 0, 0, 1], rx.socket().local_addr().unwrap().port()).into();
+        let tx_addr: SocketAddr = ([127, 0, 0, 1], tx.socket().local_addr().unwrap().port()).into();
+        let settle = || std::thread::sleep(Duration::from_millis(20));
+
+        // Sender's group traffic, with a gap the receiver can't bridge
+        for seq in [0u64, 1, 50] {
+            tx.socket()
+                .send_to(&frame(FRAME_DATA, seq, b"event"), rx_addr)
+                .unwrap();
+        }
+        tx.send_seq.store(60, Ordering::Relaxed);
+        settle();
+        let mut got = Vec::new();
+        assert_eq!(rx.receive_sequenced(64, |d, _| got.push(d)), 2);
+        assert_eq!(got, [Delivery::Message(0), Delivery::Message(1)]);
+
+        // The resync request reaches the sender over unicast
+        settle();
+        tx.receive_sequenced(64, |_, _| {});
+        let requests = tx.take_resync_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].expected, 2);
+        let (_, lag) = tx.receiver_lag().next().unwrap();
+        assert_eq!((lag.requests, lag.behind), (1, 58));
+
+        tx.send_snapshot(requests[0].from, b"state").unwrap();
+        tx.socket()
+            .send_to(&frame(FRAME_DATA, 60, b"event"), rx_addr)
+            .unwrap();
+        settle();
+        let mut got = Vec::new();
+        rx.receive_sequenced(64, |d, msg| got.push((d, msg.to_vec())));
+        assert_eq!(
+            got,
+            [
+                (Delivery::Snapshot(60), b"state".to_vec()),
+                (Delivery::Message(60), b"event".to_vec())
+            ]
+        );
+        let (from, stats) = rx.sequence_stats().next().unwrap();
+        assert_eq!(from.port(), tx_addr.port());
+        assert_eq!((stats.delivered, stats.lost, stats.resyncs), (3, 48, 1));
+    }
+
+    #[test]
+    fn test_runtime_join_and_leave() {
+        let (a, b) = (Ipv4Addr::new(239, 9, 0, 1), Ipv4Addr::new(239, 9, 0, 2));
+        let Some(mut s) = lo_receiver(a, MulticastConfig::default()) else {
+            return;
+        };
+        let port = s.socket().local_addr().unwrap().port();
+        lo_send(a, port, b"a");
+        if drain(&s).is_empty() {
+            return; // no multicast routing on lo here
+        }
+
+        s.join_group(b).unwrap();
+        s.join_group(b).unwrap(); // idempotent
+        assert_eq!(s.memberships().len(), 2);
+        lo_send(b, port, b"b");
+        assert_eq!(drain(&s), vec![b"b".to_vec()]);
+
+        s.leave_group(a).unwrap();
+        assert_eq!(s.memberships(), &[(b.into(), None)]);
+        lo_send(a, port, b"a");
+        assert!(drain(&s).is_empty());
+
+        assert!(s.join_group(Ipv4Addr::new(10, 0, 0, 1)).is_err());
+        assert!(s
+            .join_source(b, "::1".parse::<Ipv6Addr>().unwrap())
+            .is_err());
+    }
 }
@@ -35,16 +35,28 @@
 //!     // Game logic...
 //! }
 //! ```
+//!
+//! ## Dual-stack
+//!
+//! `MuxRudpServer::bind_dual_stack(port)` serves IPv4 and IPv6 clients from one
+//! `[::]` socket. IPv4 clients appear as v4-mapped addresses (`::ffff:a.b.c.d`);
+//! plain IPv4 addresses passed to `send`/`broadcast_to`/`disconnect` are mapped
+//! automatically.
 
 use std::collections::HashMap;
 use std::io;
-use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::congestion::CongestionController;
-use crate::header::{MessageType, ReliableUdpHeader};
+use crate::clock::{system_clock, Clock};
+use crate::congestion::{CongestionController, ConnectionQuality};
+use crate::header::{MessageType, ReliableUdpHeader, FLAG_MTU_PROBE, FLAG_NAT, FLAG_RELAY};
+use crate::nat::NatMessage;
+use crate::relay::{RelayConfig, RelayStats, RelayTable};
+use crate::sendmmsg::BatchSender;
 use crate::window::BitmapWindow;
+use kaos::checksum::Checksum;
 use kaos::disruptor::{MessageRingBuffer, RingBufferConfig, RingBufferEntry};
 
 /// Socket buffer size (4MB for high-throughput with 1000+ clients)
@@ -56,6 +68,12 @@ const DEFAULT_WINDOW_SIZE: usize = 256;
 /// Client timeout (30 seconds)
 const DEFAULT_CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Unmatched rendezvous registrations expire after this
+const RENDEZVOUS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default cap on unmatched rendezvous registrations
+const DEFAULT_MAX_RENDEZVOUS: usize = 4096;
+
 /// Max receive buffer size (8KB - handles most game packets, larger need fragmentation)
 /// UDP max is 65535 but typical game packets are under MTU (1500 bytes)
 const RECV_BUFFER_SIZE: usize = 8192;
@@ -66,6 +84,12 @@ const MUX_KEY_SIZE: usize = 4;
 /// Max packets per poll batch (pre-allocated)
 const MAX_POLL_BATCH: usize = 64;
 
+/// Max packets per broadcast sendmmsg call
+const BROADCAST_BATCH: usize = 64;
+
+/// Broadcast recipient: (addr, mux_key, seq, checksum)
+type FanOutTarget = (SocketAddr, u32, u64, Checksum);
+
 /// Pooled buffer for zero-allocation packet handling
 struct PooledBuffer {
     /// Pre-allocated buffers
@@ -130,6 +154,8 @@ struct MuxClientState {
     acked_seq: u64,
     /// Last activity time
     last_seen: Instant,
+    /// Last data send (approximate RTT sampling on ACK)
+    last_send: Instant,
     /// Connection open flag
     open: bool,
     /// Client address
@@ -138,10 +164,17 @@ struct MuxClientState {
     nak_addr: SocketAddr,
     /// Window size
     window_size: usize,
+    /// Header checksum offered in the client's handshake
+    checksum: Checksum,
 }
 
 impl MuxClientState {
-    fn new(addr: SocketAddr, mux_key: u32, window_size: usize) -> io::Result<Self> {
+    fn new(
+        addr: SocketAddr,
+        mux_key: u32,
+        window_size: usize,
+        clock: &Arc<dyn Clock>,
+    ) -> io::Result<Self> {
         let config = RingBufferConfig::new(window_size)
             .map_err(|e| io::Error::other(format!("Invalid window size: {}", e)))?
             .with_consumers(1)
@@ -156,26 +189,55 @@ impl MuxClientState {
             mux_key,
             send_window,
             recv_window: BitmapWindow::new(window_size, 0),
-            congestion: CongestionController::new(64, window_size as u32),
+            congestion: CongestionController::new(64, window_size as u32).with_clock(clock.clone()),
             next_send_seq: 0,
             acked_seq: 0,
-            last_seen: Instant::now(),
+            last_seen: clock.now(),
+            last_send: clock.now(),
             open: true,
             addr,
             nak_addr,
             window_size,
+            checksum: Checksum::default(),
         })
     }
 
-    fn touch(&mut self) {
-        self.last_seen = Instant::now();
+    fn touch(&mut self, now: Instant) {
+        self.last_seen = now;
     }
 
-    fn is_timed_out(&self, timeout: Duration) -> bool {
-        self.last_seen.elapsed() > timeout
+    fn is_timed_out(&self, now: Instant, timeout: Duration) -> bool {
+        now.saturating_duration_since(self.last_seen) > timeout
+    }
+
+    fn on_ack(&mut self, acked_seq: u64, now: Instant) {
+        if acked_seq > self.acked_seq {
+            let newly_acked = acked_seq - self.acked_seq;
+            for _ in 0..newly_acked {
+                self.congestion.on_ack();
+            }
+            // Approximate RTT: time since last send (same as RudpTransport)
+            let rtt_us = now.saturating_duration_since(self.last_send).as_micros() as u64;
+            if rtt_us > 0 && rtt_us < 1_000_000 {
+                self.congestion.update_rtt(rtt_us);
+            }
+            self.acked_seq = acked_seq;
+            self.send_window.advance_consumer(0, acked_seq);
+        }
     }
 }
 
+/// Rendezvous pairing counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RendezvousStats {
+    /// Registrations matched into a peer pair
+    pub paired: u64,
+    /// Registrations whose mux_key didn't match the waiting client's
+    pub mismatched: u64,
+    /// Registrations dropped because the table was full
+    pub rejected: u64,
+}
+
 /// Multiplexed RUDP server - routes packets by mux_key
 pub struct MuxRudpServer {
     /// Main data socket
@@ -188,10 +250,23 @@ pub struct MuxRudpServer {
     handlers: HashMap<u32, Box<dyn MuxHandler>>,
     /// Server local address
     local_addr: SocketAddr,
+    /// `[::]` socket accepting v4-mapped clients
+    dual_stack: bool,
+    /// Hole punch rendezvous: token -> (first client, registered at)
+    rendezvous: HashMap<u64, (SocketAddr, Instant)>,
+    /// Cap on unmatched rendezvous registrations
+    max_rendezvous: usize,
+    rendezvous_stats: RendezvousStats,
+    /// Server-relayed peer channels (token -> members)
+    relays: RelayTable,
+    /// Open a relay for every rendezvous pair (punch fallback)
+    rendezvous_relay: Option<RelayConfig>,
     /// Window size for new clients
     window_size: usize,
     /// Client timeout
     client_timeout: Duration,
+    /// Time source for timeouts and congestion control
+    clock: Arc<dyn Clock>,
     /// Pre-allocated packet buffer pool (zero-allocation receive)
     packet_pool: PooledBuffer,
     /// Pending new clients (addr, mux_key)
@@ -201,6 +276,12 @@ pub struct MuxRudpServer {
     pending_message_indices: Vec<(u32, SocketAddr, usize, usize)>,
     /// Message delivery pool (pre-allocated for dispatch)
     message_pool: PooledBuffer,
+    /// sendmmsg fan-out for broadcasts (one syscall per BROADCAST_BATCH clients)
+    broadcast_sender: BatchSender,
+    /// Staging buffer for per-client broadcast packets
+    broadcast_buf: Vec<u8>,
+    /// Reused target list for broadcasts
+    broadcast_targets: Vec<FanOutTarget>,
 }
 
 impl MuxRudpServer {
@@ -213,6 +294,35 @@ impl MuxRudpServer {
     pub fn bind_with_window<A: ToSocketAddrs>(addr: A, window_size: usize) -> io::Result<Self> {
         let socket = UdpSocket::bind(addr)?;
         let local_addr = socket.local_addr()?;
+
+        // NAK socket on port + 1
+        let nak_addr = SocketAddr::new(local_addr.ip(), local_addr.port() + 1);
+        let nak_socket = UdpSocket::bind(nak_addr)?;
+
+        Self::with_sockets(socket, nak_socket, false, window_size)
+    }
+
+    /// Bind `[::]:port` accepting both IPv6 and IPv4 (v4-mapped) clients
+    pub fn bind_dual_stack(port: u16) -> io::Result<Self> {
+        Self::bind_dual_stack_with_window(port, DEFAULT_WINDOW_SIZE)
+    }
+
+    /// Dual-stack bind with custom window size
+    pub fn bind_dual_stack_with_window(port: u16, window_size: usize) -> io::Result<Self> {
+        let socket = bind_dual_stack_socket(port)?;
+        let local_port = socket.local_addr()?.port();
+        let nak_socket = bind_dual_stack_socket(local_port + 1)?;
+
+        Self::with_sockets(socket, nak_socket, true, window_size)
+    }
+
+    fn with_sockets(
+        socket: UdpSocket,
+        nak_socket: UdpSocket,
+        dual_stack: bool,
+        window_size: usize,
+    ) -> io::Result<Self> {
+        let local_addr = socket.local_addr()?;
         socket.set_nonblocking(true)?;
 
         // Set large socket buffers
@@ -239,9 +349,6 @@ impl MuxRudpServer {
             }
         }
 
-        // NAK socket on port + 1
-        let nak_addr = SocketAddr::new(local_addr.ip(), local_addr.port() + 1);
-        let nak_socket = UdpSocket::bind(nak_addr)?;
         nak_socket.set_nonblocking(true)?;
 
         Ok(Self {
@@ -250,12 +357,22 @@ impl MuxRudpServer {
             clients: HashMap::new(),
             handlers: HashMap::new(),
             local_addr,
+            dual_stack,
+            rendezvous: HashMap::new(),
+            max_rendezvous: DEFAULT_MAX_RENDEZVOUS,
+            rendezvous_stats: RendezvousStats::default(),
+            relays: RelayTable::new(),
+            rendezvous_relay: None,
             window_size,
             client_timeout: DEFAULT_CLIENT_TIMEOUT,
+            clock: system_clock(),
             packet_pool: PooledBuffer::new(MAX_POLL_BATCH, RECV_BUFFER_SIZE),
             pending_accepts: Vec::new(),
             pending_message_indices: Vec::with_capacity(MAX_POLL_BATCH),
             message_pool: PooledBuffer::new(MAX_POLL_BATCH * 4, RECV_BUFFER_SIZE),
+            broadcast_sender: BatchSender::new(BROADCAST_BATCH),
+            broadcast_buf: Vec::with_capacity(BROADCAST_BATCH * RECV_BUFFER_SIZE),
+            broadcast_targets: Vec::new(),
         })
     }
 
@@ -274,6 +391,27 @@ impl MuxRudpServer {
         self.client_timeout = timeout;
     }
 
+    /// Time source for client timeouts, rendezvous expiry and congestion
+    /// control (set before clients connect; `TestClock` for tests)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// True when bound with `bind_dual_stack`
+    pub fn is_dual_stack(&self) -> bool {
+        self.dual_stack
+    }
+
+    /// Client-table form of an address (IPv4 -> v4-mapped on dual-stack sockets)
+    pub fn client_key(&self, addr: &SocketAddr) -> SocketAddr {
+        match addr.ip() {
+            IpAddr::V4(v4) if self.dual_stack => {
+                SocketAddr::new(IpAddr::V6(v4.to_ipv6_mapped()), addr.port())
+            }
+            _ => *addr,
+        }
+    }
+
     /// Get server's local address
     pub fn local_addr(&self) -> SocketAddr {
         self.local_addr
@@ -284,6 +422,55 @@ impl MuxRudpServer {
         self.clients.len()
     }
 
+    /// Congestion/RTT signals for one client (see `RateAdapter`)
+    pub fn client_quality(&self, addr: &SocketAddr) -> Option<ConnectionQuality> {
+        self.clients
+            .get(&self.client_key(addr))
+            .map(|c| c.congestion.quality())
+    }
+
+    /// Header checksum a client negotiated in its handshake
+    pub fn client_checksum(&self, addr: &SocketAddr) -> Option<Checksum> {
+        self.clients.get(&self.client_key(addr)).map(|c| c.checksum)
+    }
+
+    /// Open a relay; clients join with `ClientTransport::join_relay(token)`
+    pub fn open_relay(&mut self, token: u64, config: RelayConfig) {
+        self.relays.open(token, config, self.clock.now());
+    }
+
+    /// Close a relay, returning its final accounting
+    pub fn close_relay(&mut self, token: u64) -> Option<RelayStats> {
+        self.relays.close(token)
+    }
+
+    /// Forwarding/drop counters for a relay
+    pub fn relay_stats(&self, token: u64) -> Option<RelayStats> {
+        self.relays.stats(token)
+    }
+
+    /// Open a relay (with both clients joined) whenever a rendezvous pairs two
+    /// clients, so a failed hole punch can fall back to relaying. `None` disables.
+    pub fn set_rendezvous_relay(&mut self, config: Option<RelayConfig>) {
+        self.rendezvous_relay = config;
+    }
+
+    /// Pending (unmatched) rendezvous registrations
+    pub fn pending_rendezvous(&self) -> usize {
+        self.rendezvous.len()
+    }
+
+    /// Cap on pending rendezvous registrations; new tokens beyond it are
+    /// dropped (and counted in `rendezvous_stats`) until some pair or expire
+    pub fn set_max_pending_rendezvous(&mut self, max: usize) {
+        self.max_rendezvous = max;
+    }
+
+    /// Rendezvous pairing counters
+    pub fn rendezvous_stats(&self) -> RendezvousStats {
+        self.rendezvous_stats
+    }
+
     /// Get clients for a specific mux_key
     pub fn clients_for_mux_key(&self, mux_key: u32) -> impl Iterator<Item = &SocketAddr> {
         self.clients
@@ -366,7 +553,8 @@ impl MuxRudpServer {
         // Ensure client exists
         let is_new = !self.clients.contains_key(&src_addr);
         if is_new {
-            if let Ok(state) = MuxClientState::new(src_addr, mux_key, self.window_size) {
+            if let Ok(state) = MuxClientState::new(src_addr, mux_key, self.window_size, &self.clock)
+            {
                 self.clients.insert(src_addr, state);
                 self.pending_accepts.push((src_addr, mux_key));
             } else {
@@ -374,6 +562,7 @@ impl MuxRudpServer {
             }
         }
 
+        let now = self.clock.now();
         let client = self.clients.get_mut(&src_addr).unwrap();
 
         // Verify mux_key matches (client can't switch games)
@@ -381,7 +570,7 @@ impl MuxRudpServer {
             return;
         }
 
-        client.touch();
+        client.touch(now);
 
         // If payload is too small for RUDP header, treat as raw data
         if payload.len() < ReliableUdpHeader::SIZE {
@@ -395,23 +584,22 @@ impl MuxRudpServer {
         if let Some((header, msg_payload)) =
             ReliableUdpHeader::from_packet_with_payload_check(payload)
         {
+            let checksum = client.checksum;
             match header.msg_type {
-                t if t == MessageType::Data as u8 => {
-                    if header.verify_checksum(msg_payload) {
-                        client.recv_window.insert(header.sequence, msg_payload);
-                        self.send_ack_to(src_addr, header.sequence);
-                    }
+                t if t == MessageType::Data as u8
+                    && header.flags & FLAG_RELAY != 0
+                    && header.verify_checksum_with(checksum, msg_payload) =>
+                {
+                    self.forward_relay(src_addr, msg_payload, data);
+                }
+                t if t == MessageType::Data as u8
+                    && header.verify_checksum_with(checksum, msg_payload) =>
+                {
+                    client.recv_window.insert(header.sequence, msg_payload);
+                    self.send_ack_to(src_addr, header.sequence);
                 }
                 t if t == MessageType::Ack as u8 => {
-                    let acked_seq = header.sequence;
-                    if acked_seq > client.acked_seq {
-                        let newly_acked = acked_seq - client.acked_seq;
-                        for _ in 0..newly_acked {
-                            client.congestion.on_ack();
-                        }
-                        client.acked_seq = acked_seq;
-                        client.send_window.advance_consumer(0, acked_seq);
-                    }
+                    client.on_ack(header.sequence, now);
                 }
                 t if t == MessageType::Nak as u8 => {
                     client.congestion.on_loss();
@@ -421,6 +609,10 @@ impl MuxRudpServer {
                     // Handshake received - advance receive window past the handshake sequence
                     // so we expect the first data packet (handshake seq + 1)
                     let handshake_seq = header.sequence;
+                    // Payload byte 0: the client's checksum (absent = CRC32)
+                    if let Some(kind) = msg_payload.first().and_then(|&id| Checksum::from_id(id)) {
+                        client.checksum = kind;
+                    }
                     if handshake_seq == 0 {
                         // Client sends handshake with seq 0, then data starts at seq 1
                         // We need to advance the window to expect seq 1
@@ -429,6 +621,21 @@ impl MuxRudpServer {
                     // Send ACK for handshake
                     self.send_ack_to(src_addr, handshake_seq);
                 }
+                t if t == MessageType::Ping as u8 && header.flags & FLAG_MTU_PROBE != 0 => {
+                    // Path MTU probe: echo the size that actually arrived
+                    self.send_probe_ack(src_addr, mux_key, data.len());
+                }
+                t if t == MessageType::Ping as u8 && header.flags & FLAG_RELAY != 0 => {
+                    if let Some(token) = relay_token(msg_payload) {
+                        let status = self.relays.join(token, src_addr, self.clock.now());
+                        self.send_relay_status(src_addr, mux_key, token, status, checksum);
+                    }
+                }
+                t if t == MessageType::Ping as u8 && header.flags & FLAG_NAT != 0 => {
+                    if let Some(msg) = NatMessage::decode(MessageType::Ping, msg_payload) {
+                        self.handle_nat(src_addr, mux_key, msg);
+                    }
+                }
                 _ => {}
             }
         }
@@ -479,17 +686,11 @@ impl MuxRudpServer {
         }
 
         // Process ACK events
+        let now = self.clock.now();
         for (client_addr, acked_seq) in ack_events {
             if let Some(client) = self.clients.get_mut(&client_addr) {
-                client.touch();
-                if acked_seq > client.acked_seq {
-                    let newly_acked = acked_seq - client.acked_seq;
-                    for _ in 0..newly_acked {
-                        client.congestion.on_ack();
-                    }
-                    client.acked_seq = acked_seq;
-                    client.send_window.advance_consumer(0, acked_seq);
-                }
+                client.touch(now);
+                client.on_ack(acked_seq, now);
             }
         }
 
@@ -504,14 +705,127 @@ impl MuxRudpServer {
 
     /// Send ACK to a client
     fn send_ack_to(&self, client_addr: SocketAddr, seq: u64) {
+        let checksum = self.client_checksum(&client_addr).unwrap_or_default();
         let mut header = ReliableUdpHeader::new(0, seq, MessageType::Ack, 0);
-        header.calculate_checksum(&[]);
+        header.calculate_checksum_with(checksum, &[]);
         let packet = bytemuck::bytes_of(&header);
 
         let nak_addr = SocketAddr::new(client_addr.ip(), client_addr.port().wrapping_add(1));
         let _ = self.nak_socket.send_to(packet, nak_addr);
     }
 
+    /// Answer a path MTU probe with a small Pong (sequence = received size)
+    fn send_probe_ack(&self, client_addr: SocketAddr, mux_key: u32, size: usize) {
+        let mut header = ReliableUdpHeader::new(0, size as u64, MessageType::Pong, 0);
+        header.flags = FLAG_MTU_PROBE;
+        header.calculate_checksum(&[]);
+
+        let mut packet = [0u8; MUX_KEY_SIZE + ReliableUdpHeader::SIZE];
+        packet[..MUX_KEY_SIZE].copy_from_slice(&mux_key.to_le_bytes());
+        packet[MUX_KEY_SIZE..].copy_from_slice(bytemuck::bytes_of(&header));
+        let _ = self.socket.send_to(&packet, client_addr);
+    }
+
+    /// STUN-style address report and rendezvous pairing
+    fn handle_nat(&mut self, src_addr: SocketAddr, mux_key: u32, msg: NatMessage) {
+        match msg {
+            NatMessage::WhoAmI => {
+                self.send_nat(src_addr, mux_key, &NatMessage::ObservedAddr(src_addr));
+            }
+            NatMessage::Rendezvous { token } => {
+                // Only connected clients of this game may register
+                if self.clients.get(&src_addr).map(|c| c.mux_key) != Some(mux_key) {
+                    return;
+                }
+                match self.rendezvous.get(&token) {
+                    Some(&(first, _)) if first != src_addr => {
+                        // Only pair clients of the same game; the waiting
+                        // registration stays for its real peer
+                        if self.clients.get(&first).map(|c| c.mux_key) != Some(mux_key) {
+                            self.rendezvous_stats.mismatched += 1;
+                            return;
+                        }
+                        self.rendezvous.remove(&token);
+                        self.rendezvous_stats.paired += 1;
+                        let to_first = NatMessage::PeerInfo {
+                            token,
+                            peer: src_addr,
+                        };
+                        let to_second = NatMessage::PeerInfo { token, peer: first };
+                        self.send_nat(first, mux_key, &to_first);
+                        self.send_nat(src_addr, mux_key, &to_second);
+                        if let Some(config) = self.rendezvous_relay.clone() {
+                            let now = self.clock.now();
+                            self.relays.open(token, config, now);
+                            self.relays.join(token, first, now);
+                            self.relays.join(token, src_addr, now);
+                        }
+                    }
+                    Some(_) => {
+                        self.rendezvous.insert(token, (src_addr, self.clock.now()));
+                    }
+                    None if self.rendezvous.len() >= self.max_rendezvous => {
+                        self.rendezvous_stats.rejected += 1;
+                    }
+                    None => {
+                        self.rendezvous.insert(token, (src_addr, self.clock.now()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Forward a relay datagram unchanged to the other relay members
+    fn forward_relay(&mut self, src_addr: SocketAddr, payload: &[u8], datagram: &[u8]) {
+        let Some(token) = relay_token(payload) else {
+            return;
+        };
+        let members = self
+            .relays
+            .route(token, src_addr, datagram.len(), self.clock.now());
+        for member in members.iter().filter(|&&m| m != src_addr) {
+            let _ = self.socket.send_to(datagram, member);
+        }
+    }
+
+    /// Answer a relay join (Pong + FLAG_RELAY, payload = token + status)
+    fn send_relay_status(
+        &self,
+        client_addr: SocketAddr,
+        mux_key: u32,
+        token: u64,
+        status: u8,
+        checksum: Checksum,
+    ) {
+        let mut payload = [0u8; 9];
+        payload[..8].copy_from_slice(&token.to_le_bytes());
+        payload[8] = status;
+        let mut header = ReliableUdpHeader::new(0, 0, MessageType::Pong, payload.len() as u16);
+        header.flags = FLAG_RELAY;
+        header.calculate_checksum_with(checksum, &payload);
+
+        let mut packet = Vec::with_capacity(MUX_KEY_SIZE + ReliableUdpHeader::SIZE + payload.len());
+        packet.extend_from_slice(&mux_key.to_le_bytes());
+        packet.extend_from_slice(bytemuck::bytes_of(&header));
+        packet.extend_from_slice(&payload);
+        let _ = self.socket.send_to(&packet, client_addr);
+    }
+
+    /// Send a NAT control reply (Pong + FLAG_NAT)
+    fn send_nat(&self, client_addr: SocketAddr, mux_key: u32, msg: &NatMessage) {
+        let (msg_type, payload) = msg.encode();
+        let mut header = ReliableUdpHeader::new(0, 0, msg_type, payload.len() as u16);
+        header.flags = FLAG_NAT;
+        header.calculate_checksum(&payload);
+
+        let mut packet = Vec::with_capacity(MUX_KEY_SIZE + ReliableUdpHeader::SIZE + payload.len());
+        packet.extend_from_slice(&mux_key.to_le_bytes());
+        packet.extend_from_slice(bytemuck::bytes_of(&header));
+        packet.extend_from_slice(&payload);
+        let _ = self.socket.send_to(&packet, client_addr);
+    }
+
     /// Retransmit a single packet
     fn retransmit_for_client(&self, client_addr: SocketAddr, seq: u64) {
         if let Some(client) = self.clients.get(&client_addr) {
@@ -544,18 +858,25 @@ impl MuxRudpServer {
     /// Cleanup timed out clients
     fn cleanup_timed_out(&mut self) {
         let timeout = self.client_timeout;
+        let now = self.clock.now();
         let mut disconnected: Vec<(u32, SocketAddr)> = Vec::new();
 
         self.clients.retain(|addr, client| {
-            let timed_out = client.is_timed_out(timeout);
+            let timed_out = client.is_timed_out(now, timeout);
             if timed_out {
                 disconnected.push((client.mux_key, *addr));
             }
             !timed_out
         });
+        let clients = &self.clients;
+        self.rendezvous.retain(|_, (addr, at)| {
+            clients.contains_key(addr) && now.saturating_duration_since(*at) < RENDEZVOUS_TIMEOUT
+        });
+        self.relays.expire_idle(now);
 
         // Notify handlers of disconnects
         for (mux_key, addr) in disconnected {
+            self.relays.remove_member(&addr);
             if let Some(handler) = self.handlers.get_mut(&mux_key) {
                 handler.on_disconnect(addr);
             }
@@ -597,11 +918,12 @@ impl MuxRudpServer {
                 // Send NAKs for gaps
                 let nak_socket = self.nak_socket.clone();
                 let nak_addr = client.nak_addr;
+                let checksum = client.checksum;
                 client.recv_window.send_batch_naks_for_gaps(|start, end| {
                     let mut packet = Vec::with_capacity(ReliableUdpHeader::SIZE + 16);
                     let payload = [start.to_le_bytes(), end.to_le_bytes()].concat();
                     let mut header = ReliableUdpHeader::new(0, start, MessageType::Nak, 16);
-                    header.calculate_checksum(&payload);
+                    header.calculate_checksum_with(checksum, &payload);
                     packet.extend_from_slice(bytemuck::bytes_of(&header));
                     packet.extend_from_slice(&payload);
                     let _ = nak_socket.send_to(&packet, nak_addr);
@@ -620,6 +942,7 @@ impl MuxRudpServer {
 
     /// Send data to a client (with mux_key prefix)
     pub fn send(&mut self, client_addr: &SocketAddr, data: &[u8]) -> io::Result<u64> {
+        let client_addr = &self.client_key(client_addr);
         let client = self
             .clients
             .get_mut(client_addr)
@@ -642,7 +965,7 @@ impl MuxRudpServer {
         let mux_key = client.mux_key;
         let seq = client.next_send_seq;
         let mut header = ReliableUdpHeader::new(0, seq, MessageType::Data, data.len() as u16);
-        header.calculate_checksum(data);
+        header.calculate_checksum_with(client.checksum, data);
 
         // Build packet with mux_key prefix (4 bytes)
         let mut packet = Vec::with_capacity(MUX_KEY_SIZE + ReliableUdpHeader::SIZE + data.len());
@@ -665,6 +988,7 @@ impl MuxRudpServer {
         // Send
         self.socket.send_to(&packet, *client_addr)?;
         client.congestion.on_send();
+        client.last_send = self.clock.now();
         client.next_send_seq = seq.wrapping_add(1);
 
         Ok(seq)
@@ -690,32 +1014,74 @@ impl MuxRudpServer {
     /// - No ACK overhead (2000 clients = 2000 fewer ACKs/tick)
     /// - Stale data is discarded, not retransmitted
     pub fn broadcast_unreliable(&mut self, mux_key: u32, data: &[u8]) -> usize {
-        let clients_data: Vec<(SocketAddr, u32, u64)> = self
-            .clients
-            .iter_mut()
-            .filter(|(_, c)| c.mux_key == mux_key && c.open)
-            .map(|(a, c)| {
-                let seq = c.next_send_seq;
-                c.next_send_seq = seq.wrapping_add(1);
-                (*a, c.mux_key, seq)
-            })
-            .collect();
+        let mut targets = std::mem::take(&mut self.broadcast_targets);
+        targets.clear();
+        targets.extend(
+            self.clients
+                .iter_mut()
+                .filter(|(_, c)| c.mux_key == mux_key && c.open)
+                .map(|(a, c)| {
+                    let seq = c.next_send_seq;
+                    c.next_send_seq = seq.wrapping_add(1);
+                    (*a, c.mux_key, seq, c.checksum)
+                }),
+        );
+
+        let sent = self.fan_out(&targets, data);
+        self.broadcast_targets = targets;
+        sent
+    }
+
+    /// Unreliable broadcast to an explicit set of clients
+    ///
+    /// Each client gets its own mux_key and sequence number stamped into the
+    /// packet; the batch goes out via sendmmsg (one syscall per 64 clients on Linux).
+    /// Unknown or closed clients are skipped. Returns number of packets sent.
+    pub fn broadcast_to(&mut self, clients: &[SocketAddr], data: &[u8]) -> usize {
+        let mut targets = std::mem::take(&mut self.broadcast_targets);
+        targets.clear();
+        for addr in clients {
+            let addr = self.client_key(addr);
+            if let Some(c) = self.clients.get_mut(&addr) {
+                if c.open {
+                    let seq = c.next_send_seq;
+                    c.next_send_seq = seq.wrapping_add(1);
+                    targets.push((addr, c.mux_key, seq, c.checksum));
+                }
+            }
+        }
+
+        let sent = self.fan_out(&targets, data);
+        self.broadcast_targets = targets;
+        sent
+    }
+
+    /// Build one packet per (addr, mux_key, seq, checksum) and send in sendmmsg batches
+    fn fan_out(&mut self, targets: &[FanOutTarget], data: &[u8]) -> usize {
+        let packet_len = MUX_KEY_SIZE + ReliableUdpHeader::SIZE + data.len();
+        // Build packet without storing in send window (unreliable)
+        let mut header = ReliableUdpHeader::new(0, 0, MessageType::Data, data.len() as u16);
 
         let mut sent = 0;
-        for (addr, client_mux_key, seq) in clients_data {
-            // Build packet without storing in send window (unreliable)
-            let mut header = ReliableUdpHeader::new(0, seq, MessageType::Data, data.len() as u16);
-            header.calculate_checksum(data);
-
-            let mut packet = Vec::with_capacity(MUX_KEY_SIZE + ReliableUdpHeader::SIZE + data.len());
-            packet.extend_from_slice(&client_mux_key.to_le_bytes());
-            packet.extend_from_slice(bytemuck::bytes_of(&header));
-            packet.extend_from_slice(data);
-
-            // Send without retry - if it fails, next tick will send fresh data
-            if self.socket.send_to(&packet, addr).is_ok() {
-                sent += 1;
+        for chunk in targets.chunks(BROADCAST_BATCH) {
+            self.broadcast_buf.clear();
+            for &(_, mux_key, seq, checksum) in chunk {
+                header.sequence = seq;
+                header.flags = 0;
+                header.calculate_checksum_with(checksum, data);
+                self.broadcast_buf.extend_from_slice(&mux_key.to_le_bytes());
+                self.broadcast_buf
+                    .extend_from_slice(bytemuck::bytes_of(&header));
+                self.broadcast_buf.extend_from_slice(data);
             }
+
+            sent += send_many(
+                &self.socket,
+                &mut self.broadcast_sender,
+                &self.broadcast_buf,
+                packet_len,
+                chunk,
+            );
         }
         sent
     }
@@ -741,6 +1107,9 @@ impl MuxRudpServer {
 
     /// Disconnect a client
     pub fn disconnect(&mut self, client_addr: &SocketAddr) {
+        let client_addr = &self.client_key(client_addr);
+        self.relays.remove_member(client_addr);
+        self.rendezvous.retain(|_, (addr, _)| addr != client_addr);
         if let Some(client) = self.clients.remove(client_addr) {
             if let Some(handler) = self.handlers.get_mut(&client.mux_key) {
                 handler.on_disconnect(*client_addr);
@@ -749,6 +1118,69 @@ impl MuxRudpServer {
     }
 }
 
+/// Relay token from the front of a FLAG_RELAY payload
+fn relay_token(payload: &[u8]) -> Option<u64> {
+    Some(u64::from_le_bytes(payload.get(..8)?.try_into().ok()?))
+}
+
+/// `[::]:port` socket with IPV6_V6ONLY off (IPv4 arrives v4-mapped)
+fn bind_dual_stack_socket(port: u16) -> io::Result<UdpSocket> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::IPV6,
+        socket2::Type::DGRAM,
+        Some(socket2::Protocol::UDP),
+    )?;
+    socket.set_only_v6(false)?;
+    socket.bind(&SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port).into())?;
+    Ok(socket.into())
+}
+
+/// Send the i-th `packet_len` slice of `packets` to `targets[i]`; sendmmsg
+/// (WSASendMsg on Windows), picking up after partial sends, with per-packet
+/// send_to fallback. Returns the number of packets sent.
+fn send_many(
+    socket: &UdpSocket,
+    sender: &mut BatchSender,
+    packets: &[u8],
+    packet_len: usize,
+    targets: &[FanOutTarget],
+) -> usize {
+    #[cfg(unix)]
+    use std::os::unix::io::AsRawFd as AsRaw;
+    #[cfg(windows)]
+    use std::os::windows::io::AsRawSocket as AsRaw;
+
+    let mut sent = 0;
+    while sent < targets.len() {
+        let batch = packets[sent * packet_len..]
+            .chunks(packet_len)
+            .zip(targets[sent..].iter().map(|t| &t.0));
+        // Safety: the socket is a valid UDP socket, packets outlive the call
+        #[cfg(unix)]
+        let r = unsafe { sender.send_to_many(socket.as_raw_fd(), batch) };
+        #[cfg(windows)]
+        let r = unsafe { sender.send_to_many(socket.as_raw_socket(), batch) };
+        #[cfg(not(any(unix, windows)))]
+        let r: io::Result<usize> = Err(io::ErrorKind::Unsupported.into());
+        match r {
+            Ok(0) => break,
+            // Short count: the next call resumes at the packet that failed
+            Ok(n) => sent += n,
+            // Socket buffer full - drop the rest, next tick sends fresh data
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return sent,
+            // Unsupported platform or IPv6 peer: send_to the rest one by one
+            Err(_) => break,
+        }
+    }
+
+    // Send without retry - if it fails, next tick will send fresh data
+    sent + packets[sent * packet_len..]
+        .chunks(packet_len)
+        .zip(&targets[sent..])
+        .filter(|(packet, t)| socket.send_to(packet, t.0).is_ok())
+        .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -801,6 +1233,146 @@ mod tests {
         assert!(server.handlers.contains_key(&0x00000002));
     }
 
+    /// Connect a client socket to the server with the given mux_key
+    fn connect_client(server: &mut MuxRudpServer, mux_key: u32) -> UdpSocket {
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut hello = mux_key.to_le_bytes().to_vec();
+        hello.extend_from_slice(b"hi");
+        client.send_to(&hello, server.local_addr()).unwrap();
+        for _ in 0..100 {
+            server.poll();
+            if server.clients.contains_key(&client.local_addr().unwrap()) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        client
+    }
+
+    fn recv_data(client: &UdpSocket) -> (u32, u64, Vec<u8>) {
+        let mut buf = [0u8; 256];
+        let len = client.recv(&mut buf).unwrap();
+        let mux_key = u32::from_le_bytes(buf[..MUX_KEY_SIZE].try_into().unwrap());
+        let (header, payload) =
+            ReliableUdpHeader::from_packet_with_payload_check(&buf[MUX_KEY_SIZE..len]).unwrap();
+        assert!(header.verify_checksum(payload));
+        (mux_key, header.sequence, payload.to_vec())
+    }
+
+    #[test]
+    fn test_broadcast_to_stamps_per_client_sequence() {
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(0x00000001, Box::new(TestHandler::new()));
+        server.register(0x00000002, Box::new(TestHandler::new()));
+
+        let a = connect_client(&mut server, 0x00000001);
+        let b = connect_client(&mut server, 0x00000002);
+        let a_addr = a.local_addr().unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        // Advance a's sequence so the two clients diverge
+        server.send(&a_addr, b"first").unwrap();
+        assert_eq!(recv_data(&a).1, 0);
+
+        let sent = server.broadcast_to(&[a_addr, b_addr], b"state");
+        assert_eq!(sent, 2);
+
+        assert_eq!(recv_data(&a), (0x00000001, 1, b"state".to_vec()));
+        assert_eq!(recv_data(&b), (0x00000002, 0, b"state".to_vec()));
+    }
+
+    #[test]
+    fn test_relay_idle_expiry_follows_clock() {
+        use crate::clock::TestClock;
+
+        let clock = TestClock::new();
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        // Well ahead of the wall clock, so wall-clock stamps would never look idle
+        clock.advance(Duration::from_secs(3600));
+        server.set_clock(Arc::new(clock.clone()));
+        server.open_relay(
+            0xabc,
+            RelayConfig {
+                idle_timeout: Duration::from_secs(10),
+                ..Default::default()
+            },
+        );
+
+        clock.advance(Duration::from_secs(9));
+        server.poll();
+        assert!(server.relay_stats(0xabc).is_some());
+
+        clock.advance(Duration::from_secs(1));
+        server.poll();
+        assert!(server.relay_stats(0xabc).is_none());
+    }
+
+    #[test]
+    fn test_send_many_resumes_after_failed_packet() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receivers: Vec<UdpSocket> = (0..2)
+            .map(|_| UdpSocket::bind("127.0.0.1:0").unwrap())
+            .collect();
+        // The IPv6 target can't be reached from an IPv4 socket and fails mid-batch
+        let v6: SocketAddr = "[::1]:9".parse().unwrap();
+        let addrs = [
+            receivers[0].local_addr().unwrap(),
+            v6,
+            receivers[1].local_addr().unwrap(),
+        ];
+        let targets: Vec<FanOutTarget> = addrs
+            .iter()
+            .map(|&a| (a, 1, 0, Checksum::default()))
+            .collect();
+
+        let mut sender = BatchSender::new(BROADCAST_BATCH);
+        let sent = send_many(&socket, &mut sender, b"p0p1p2", 2, &targets);
+        assert_eq!(sent, 2);
+
+        let mut buf = [0u8; 8];
+        for (r, expected) in receivers.iter().zip([b"p0", b"p2"]) {
+            r.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+            let len = r.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..len], expected);
+        }
+    }
+
+    #[test]
+    fn test_client_timeout_follows_clock() {
+        use crate::clock::TestClock;
+
+        let clock = TestClock::new();
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.set_clock(Arc::new(clock.clone()));
+        server.set_client_timeout(Duration::from_secs(30));
+        server.register(0x00000001, Box::new(TestHandler::new()));
+        let _client = connect_client(&mut server, 0x00000001);
+        assert_eq!(server.client_count(), 1);
+
+        clock.advance(Duration::from_secs(30));
+        server.poll();
+        assert_eq!(server.client_count(), 1);
+
+        clock.advance(Duration::from_millis(1));
+        server.poll();
+        assert_eq!(server.client_count(), 0);
+    }
+
+    #[test]
+    fn test_broadcast_to_skips_unknown_clients() {
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(0x00000001, Box::new(TestHandler::new()));
+        let a = connect_client(&mut server, 0x00000001);
+
+        let stranger: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let sent = server.broadcast_to(&[stranger, a.local_addr().unwrap()], b"tick");
+        assert_eq!(sent, 1);
+        assert_eq!(recv_data(&a).2, b"tick".to_vec());
+    }
+
     #[test]
     fn test_mux_server_poll_empty() {
         let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
@@ -808,4 +1380,44 @@ mod tests {
         server.poll(); // Should not panic
         assert_eq!(server.client_count(), 0);
     }
+
+    #[test]
+    fn test_dual_stack_accepts_v4_and_v6() {
+        let Ok(mut server) = MuxRudpServer::bind_dual_stack(0) else {
+            return; // no IPv6 in this environment
+        };
+        assert!(server.is_dual_stack());
+        server.register(0x00000001, Box::new(TestHandler::new()));
+        let port = server.local_addr().port();
+
+        let v4 = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let Ok(v6) = UdpSocket::bind("[::1]:0") else {
+            return;
+        };
+        for (client, server_addr) in [(&v4, "127.0.0.1"), (&v6, "::1")] {
+            let server_addr = SocketAddr::new(server_addr.parse().unwrap(), port);
+            let mut packet = 0x00000001u32.to_le_bytes().to_vec();
+            packet.extend_from_slice(bytemuck::bytes_of(&ReliableUdpHeader::new(
+                0,
+                0,
+                MessageType::Handshake,
+                0,
+            )));
+            client.send_to(&packet, server_addr).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(20));
+        server.poll();
+        assert_eq!(server.client_count(), 2);
+
+        // Plain IPv4 address maps onto the v4-mapped client entry
+        v4.set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        server.send(&v4.local_addr().unwrap(), b"hi-v4").unwrap();
+        let mut buf = [0u8; 256];
+        let len = v4.recv(&mut buf).unwrap();
+        assert_eq!(&buf[len - 5..len], b"hi-v4");
+
+        server.disconnect(&v4.local_addr().unwrap());
+        assert_eq!(server.client_count(), 1);
+    }
 }
@@ -0,0 +1,352 @@
+//! NAT traversal: STUN-style address discovery and UDP hole punching.
+//!
+//! Control messages ride on `Ping`/`Pong` with `FLAG_NAT`; the first payload
+//! byte is the op. Requests are Pings, replies are Pongs.
+//!
+//! ```text
+//! client -> server  Ping  [WHOAMI]
+//! server -> client  Pong  [WHOAMI][observed addr]
+//!
+//! A -> server       Ping  [RENDEZVOUS][token]     (same for B)
+//! server -> A       Pong  [RENDEZVOUS][token][B's observed addr]
+//! server -> B       Pong  [RENDEZVOUS][token][A's observed addr]
+//!
+//! A <-> B           Ping  [PUNCH][token]          (every interval until answered)
+//! B <-> A           Pong  [PUNCH][token]
+//! ```
+//!
+//! Tokens pair two clients and should be unguessable (hand them out from the
+//! lobby/matchmaker). If no punch gets through before the timeout the puncher
+//! settles on `Relay` and traffic goes via the server.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use crate::header::MessageType;
+
+/// Ask the server for our observed (public) address
+pub const NAT_OP_WHOAMI: u8 = 1;
+/// Register for / announce a rendezvous
+pub const NAT_OP_RENDEZVOUS: u8 = 2;
+/// Peer-to-peer hole punch probe
+pub const NAT_OP_PUNCH: u8 = 3;
+
+/// NAT traversal control message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NatMessage {
+    /// Client asks for its observed address
+    WhoAmI,
+    /// Server reports the client's observed address
+    ObservedAddr(SocketAddr),
+    /// Client registers for rendezvous
+    Rendezvous { token: u64 },
+    /// Server introduces the other party
+    PeerInfo { token: u64, peer: SocketAddr },
+    /// Peer probe
+    Punch { token: u64 },
+    /// Peer probe answered
+    PunchAck { token: u64 },
+}
+
+impl NatMessage {
+    /// Message type and payload for the wire
+    pub fn encode(&self) -> (MessageType, Vec<u8>) {
+        let mut buf = Vec::with_capacity(32);
+        let msg_type = match *self {
+            Self::WhoAmI => {
+                buf.push(NAT_OP_WHOAMI);
+                MessageType::Ping
+            }
+            Self::ObservedAddr(addr) => {
+                buf.push(NAT_OP_WHOAMI);
+                encode_addr(&addr, &mut buf);
+                MessageType::Pong
+            }
+            Self::Rendezvous { token } => {
+                buf.push(NAT_OP_RENDEZVOUS);
+                buf.extend_from_slice(&token.to_le_bytes());
+                MessageType::Ping
+            }
+            Self::PeerInfo { token, peer } => {
+                buf.push(NAT_OP_RENDEZVOUS);
+                buf.extend_from_slice(&token.to_le_bytes());
+                encode_addr(&peer, &mut buf);
+                MessageType::Pong
+            }
+            Self::Punch { token } => {
+                buf.push(NAT_OP_PUNCH);
+                buf.extend_from_slice(&token.to_le_bytes());
+                MessageType::Ping
+            }
+            Self::PunchAck { token } => {
+                buf.push(NAT_OP_PUNCH);
+                buf.extend_from_slice(&token.to_le_bytes());
+                MessageType::Pong
+            }
+        };
+        (msg_type, buf)
+    }
+
+    /// Parse a `FLAG_NAT` Ping/Pong payload
+    pub fn decode(msg_type: MessageType, payload: &[u8]) -> Option<Self> {
+        let (&op, body) = payload.split_first()?;
+        let token = || {
+            body.get(..8)
+                .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        };
+        match (msg_type, op) {
+            (MessageType::Ping, NAT_OP_WHOAMI) => Some(Self::WhoAmI),
+            (MessageType::Pong, NAT_OP_WHOAMI) => decode_addr(body).map(Self::ObservedAddr),
+            (MessageType::Ping, NAT_OP_RENDEZVOUS) => Some(Self::Rendezvous { token: token()? }),
+            (MessageType::Pong, NAT_OP_RENDEZVOUS) => Some(Self::PeerInfo {
+                token: token()?,
+                peer: decode_addr(&body[8..])?,
+            }),
+            (MessageType::Ping, NAT_OP_PUNCH) => Some(Self::Punch { token: token()? }),
+            (MessageType::Pong, NAT_OP_PUNCH) => Some(Self::PunchAck { token: token()? }),
+            _ => None,
+        }
+    }
+}
+
+/// `[family 4|6][port u16 LE][ip bytes]`
+pub fn encode_addr(addr: &SocketAddr, buf: &mut Vec<u8>) {
+    match addr.ip() {
+        IpAddr::V4(ip) => {
+            buf.push(4);
+            buf.extend_from_slice(&addr.port().to_le_bytes());
+            buf.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            buf.push(6);
+            buf.extend_from_slice(&addr.port().to_le_bytes());
+            buf.extend_from_slice(&ip.octets());
+        }
+    }
+}
+
+/// Inverse of `encode_addr`
+pub fn decode_addr(buf: &[u8]) -> Option<SocketAddr> {
+    let (&family, rest) = buf.split_first()?;
+    let port = u16::from_le_bytes(rest.get(..2)?.try_into().ok()?);
+    let ip: IpAddr = match family {
+        4 => {
+            let b: [u8; 4] = rest.get(2..6)?.try_into().ok()?;
+            Ipv4Addr::from(b).into()
+        }
+        6 => {
+            let b: [u8; 16] = rest.get(2..18)?.try_into().ok()?;
+            Ipv6Addr::from(b).into()
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Hole punch tuning
+#[derive(Debug, Clone)]
+pub struct PunchConfig {
+    /// Time between punch probes
+    pub interval: Duration,
+    /// Give up and relay after this long
+    pub timeout: Duration,
+}
+
+impl Default for PunchConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+/// Hole punch progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PunchState {
+    /// No rendezvous requested
+    Idle,
+    /// Registered with the server, waiting for the peer
+    Waiting { token: u64 },
+    /// Probing the peer's observed address
+    Punching { token: u64, peer: SocketAddr },
+    /// Direct path open
+    Direct { token: u64, peer: SocketAddr },
+    /// Punching failed - use the server relay
+    Relay { token: u64, peer: SocketAddr },
+}
+
+/// Hole punching state machine (no I/O - the transport sends what `poll` returns).
+#[derive(Debug)]
+pub struct HolePuncher {
+    config: PunchConfig,
+    state: PunchState,
+    started: Instant,
+    last_probe: Option<Instant>,
+}
+
+impl Default for HolePuncher {
+    fn default() -> Self {
+        Self::new(PunchConfig::default())
+    }
+}
+
+impl HolePuncher {
+    pub fn new(config: PunchConfig) -> Self {
+        Self {
+            config,
+            state: PunchState::Idle,
+            started: Instant::now(),
+            last_probe: None,
+        }
+    }
+
+    pub fn state(&self) -> PunchState {
+        self.state
+    }
+
+    /// Registered with the server under `token`
+    pub fn start(&mut self, token: u64) {
+        self.state = PunchState::Waiting { token };
+    }
+
+    /// Server introduced the peer - start probing
+    pub fn on_peer_info(&mut self, token: u64, peer: SocketAddr, now: Instant) {
+        if self.state == (PunchState::Waiting { token }) {
+            self.state = PunchState::Punching { token, peer };
+            self.started = now;
+            self.last_probe = None;
+        }
+    }
+
+    /// Probe to send now: `(peer, token)`
+    pub fn poll(&mut self, now: Instant) -> Option<(SocketAddr, u64)> {
+        let PunchState::Punching { token, peer } = self.state else {
+            return None;
+        };
+        if now.duration_since(self.started) >= self.config.timeout {
+            self.state = PunchState::Relay { token, peer };
+            return None;
+        }
+        match self.last_probe {
+            Some(t) if now.duration_since(t) < self.config.interval => None,
+            _ => {
+                self.last_probe = Some(now);
+                Some((peer, token))
+            }
+        }
+    }
+
+    /// Peer probe arrived. Returns true if it should be answered.
+    pub fn on_punch(&mut self, from: SocketAddr, token: u64) -> bool {
+        match self.state {
+            PunchState::Punching { token: t, .. } | PunchState::Direct { token: t, .. }
+                if t == token =>
+            {
+                // Symmetric NATs may remap the port - trust where it came from
+                self.state = PunchState::Direct { token, peer: from };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Our probe was answered
+    pub fn on_punch_ack(&mut self, from: SocketAddr, token: u64) {
+        if let PunchState::Punching { token: t, .. } = self.state {
+            if t == token {
+                self.state = PunchState::Direct { token, peer: from };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let v4: SocketAddr = "203.0.113.7:40000".parse().unwrap();
+        let v6: SocketAddr = "[2001:db8::1]:5000".parse().unwrap();
+        let msgs = [
+            NatMessage::WhoAmI,
+            NatMessage::ObservedAddr(v4),
+            NatMessage::ObservedAddr(v6),
+            NatMessage::Rendezvous { token: 42 },
+            NatMessage::PeerInfo {
+                token: 42,
+                peer: v6,
+            },
+            NatMessage::Punch { token: 7 },
+            NatMessage::PunchAck { token: 7 },
+        ];
+        for msg in msgs {
+            let (msg_type, payload) = msg.encode();
+            assert_eq!(NatMessage::decode(msg_type, &payload), Some(msg));
+        }
+        assert_eq!(
+            NatMessage::decode(MessageType::Pong, &[NAT_OP_WHOAMI, 9]),
+            None
+        );
+        assert_eq!(NatMessage::decode(MessageType::Ping, &[]), None);
+    }
+
+    #[test]
+    fn test_punch_succeeds_on_ack() {
+        let peer: SocketAddr = "198.51.100.2:6000".parse().unwrap();
+        let remapped: SocketAddr = "198.51.100.2:6001".parse().unwrap();
+        let mut p = HolePuncher::default();
+        let now = Instant::now();
+
+        // Peer info for an unknown token is ignored
+        p.on_peer_info(1, peer, now);
+        assert_eq!(p.state(), PunchState::Idle);
+
+        p.start(1);
+        p.on_peer_info(1, peer, now);
+        assert_eq!(p.poll(now), Some((peer, 1)));
+        assert_eq!(p.poll(now + Duration::from_millis(50)), None);
+        assert_eq!(p.poll(now + Duration::from_millis(100)), Some((peer, 1)));
+
+        p.on_punch_ack(remapped, 2); // wrong token
+        p.on_punch_ack(remapped, 1);
+        assert_eq!(
+            p.state(),
+            PunchState::Direct {
+                token: 1,
+                peer: remapped
+            }
+        );
+        assert_eq!(p.poll(now + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn test_incoming_punch_opens_path() {
+        let peer: SocketAddr = "198.51.100.2:6000".parse().unwrap();
+        let mut p = HolePuncher::default();
+        assert!(!p.on_punch(peer, 5));
+
+        p.start(5);
+        p.on_peer_info(5, peer, Instant::now());
+        assert!(p.on_punch(peer, 5));
+        assert_eq!(p.state(), PunchState::Direct { token: 5, peer });
+        // Keep answering so the other side also sees the path open
+        assert!(p.on_punch(peer, 5));
+    }
+
+    #[test]
+    fn test_timeout_falls_back_to_relay() {
+        let peer: SocketAddr = "198.51.100.2:6000".parse().unwrap();
+        let mut p = HolePuncher::new(PunchConfig {
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(100),
+        });
+        let now = Instant::now();
+        p.start(9);
+        p.on_peer_info(9, peer, now);
+        assert!(p.poll(now).is_some());
+        assert_eq!(p.poll(now + Duration::from_millis(100)), None);
+        assert_eq!(p.state(), PunchState::Relay { token: 9, peer });
+    }
+}
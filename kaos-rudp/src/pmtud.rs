@@ -0,0 +1,422 @@
+//! Path MTU discovery (DPLPMTUD-style, RFC 8899) with blackhole detection.
+//!
+//! Probes are padded `Ping` packets flagged `FLAG_MTU_PROBE`; the peer answers
+//! with a small `Pong` echoing the probe size in `sequence`. The prober
+//! binary-searches between `base_mtu` and `max_mtu`, periodically re-validates
+//! the confirmed size and falls back to `base_mtu` when it stops getting through.
+//!
+//! Sizes are UDP payload bytes (everything after the UDP header).
+
+use std::time::{Duration, Instant};
+
+/// Safe UDP payload floor (QUIC minimum - survives nearly every path)
+pub const BASE_MTU: usize = 1200;
+
+/// Largest probe on Ethernet (1500 - 20 IPv4 - 8 UDP)
+pub const MAX_MTU: usize = 1472;
+
+/// Stop searching when the bracket is narrower than this
+const SEARCH_GRANULARITY: usize = 16;
+
+/// Pluggable MTU discovery strategy.
+///
+/// Transports ask `next_probe()` each poll and send a probe of the returned
+/// size; probe replies and data-path loss feed back in.
+pub trait MtuDiscovery: Send {
+    /// Current usable UDP payload size
+    fn current_mtu(&self) -> usize;
+
+    /// Size of the probe to send now, if one is due
+    fn next_probe(&mut self, now: Instant) -> Option<usize>;
+
+    /// Peer confirmed a probe of `size`
+    fn on_probe_ack(&mut self, size: usize, now: Instant);
+
+    /// A data packet of `size` bytes was lost (blackhole signal)
+    fn on_packet_lost(&mut self, _size: usize, _now: Instant) {}
+
+    /// A data packet of `size` bytes was delivered
+    fn on_packet_acked(&mut self, _size: usize) {}
+}
+
+/// No probing - always reports the configured size.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedMtu(pub usize);
+
+impl Default for FixedMtu {
+    fn default() -> Self {
+        Self(BASE_MTU)
+    }
+}
+
+impl MtuDiscovery for FixedMtu {
+    fn current_mtu(&self) -> usize {
+        self.0
+    }
+
+    fn next_probe(&mut self, _now: Instant) -> Option<usize> {
+        None
+    }
+
+    fn on_probe_ack(&mut self, _size: usize, _now: Instant) {}
+}
+
+/// Prober tuning
+#[derive(Debug, Clone)]
+pub struct PmtudConfig {
+    /// Starting/fallback size (always assumed to work)
+    pub base_mtu: usize,
+    /// Upper bound for probing
+    pub max_mtu: usize,
+    /// Time to wait for a probe reply
+    pub probe_timeout: Duration,
+    /// Probes of one size before declaring it too big
+    pub max_probes: u32,
+    /// Re-validate the confirmed size this often (blackhole check)
+    pub validate_interval: Duration,
+    /// Search upward again this often (path may have grown)
+    pub raise_interval: Duration,
+    /// Consecutive large-packet losses that trigger fallback
+    pub blackhole_threshold: u32,
+}
+
+impl Default for PmtudConfig {
+    fn default() -> Self {
+        Self {
+            base_mtu: BASE_MTU,
+            max_mtu: MAX_MTU,
+            probe_timeout: Duration::from_millis(500),
+            max_probes: 3,
+            validate_interval: Duration::from_secs(30),
+            raise_interval: Duration::from_secs(600),
+            blackhole_threshold: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProbeState {
+    /// Binary search in progress
+    Search,
+    /// Converged - waiting for validate/raise timers
+    Complete,
+}
+
+/// Binary-search path MTU prober with blackhole fallback.
+#[derive(Debug)]
+pub struct PathMtuProber {
+    config: PmtudConfig,
+    state: ProbeState,
+    /// Confirmed size
+    mtu: usize,
+    /// Largest size known to work
+    low: usize,
+    /// Largest size not yet known to fail
+    high: usize,
+    /// Outstanding probe (size, sent_at)
+    probe: Option<(usize, Instant)>,
+    /// Timeouts for the outstanding probe size
+    probe_count: u32,
+    /// Consecutive losses of packets larger than base_mtu
+    large_losses: u32,
+    next_validate: Instant,
+    next_raise: Instant,
+    blackholes: u64,
+}
+
+impl Default for PathMtuProber {
+    fn default() -> Self {
+        Self::new(PmtudConfig::default())
+    }
+}
+
+impl PathMtuProber {
+    pub fn new(config: PmtudConfig) -> Self {
+        let now = Instant::now();
+        let base = config.base_mtu;
+        let high = config.max_mtu.max(base);
+        Self {
+            next_validate: now + config.validate_interval,
+            next_raise: now + config.raise_interval,
+            config,
+            state: ProbeState::Search,
+            mtu: base,
+            low: base,
+            high,
+            probe: None,
+            probe_count: 0,
+            large_losses: 0,
+            blackholes: 0,
+        }
+    }
+
+    /// True once the search has converged
+    pub fn is_complete(&self) -> bool {
+        self.state == ProbeState::Complete
+    }
+
+    /// Number of blackhole fallbacks so far
+    pub fn blackholes(&self) -> u64 {
+        self.blackholes
+    }
+
+    fn start_search(&mut self, now: Instant) {
+        self.state = ProbeState::Search;
+        self.low = self.mtu;
+        self.high = self.config.max_mtu.max(self.mtu);
+        self.probe = None;
+        self.probe_count = 0;
+        self.next_raise = now + self.config.raise_interval;
+    }
+
+    fn on_blackhole(&mut self, now: Instant) {
+        self.blackholes += 1;
+        self.large_losses = 0;
+        self.mtu = self.config.base_mtu;
+        self.start_search(now);
+    }
+
+    fn send_probe(&mut self, size: usize, now: Instant) -> Option<usize> {
+        self.probe = Some((size, now));
+        Some(size)
+    }
+}
+
+impl MtuDiscovery for PathMtuProber {
+    fn current_mtu(&self) -> usize {
+        self.mtu
+    }
+
+    fn next_probe(&mut self, now: Instant) -> Option<usize> {
+        // Outstanding probe: wait, retry, or give up on that size
+        if let Some((size, sent)) = self.probe {
+            if now.duration_since(sent) < self.config.probe_timeout {
+                return None;
+            }
+            self.probe_count += 1;
+            if self.probe_count < self.config.max_probes {
+                return self.send_probe(size, now);
+            }
+            self.probe = None;
+            self.probe_count = 0;
+            if size <= self.mtu {
+                // Confirmed size no longer gets through
+                self.on_blackhole(now);
+            } else {
+                self.high = size - 1;
+            }
+        }
+
+        match self.state {
+            ProbeState::Search => {
+                if self.high < self.low + SEARCH_GRANULARITY {
+                    self.state = ProbeState::Complete;
+                    self.next_validate = now + self.config.validate_interval;
+                    return None;
+                }
+                // Try the top first (common case: full Ethernet MTU works)
+                let size = if self.high == self.config.max_mtu {
+                    self.high
+                } else {
+                    (self.low + self.high).div_ceil(2)
+                };
+                self.send_probe(size, now)
+            }
+            ProbeState::Complete => {
+                if now >= self.next_raise && self.mtu < self.config.max_mtu {
+                    self.start_search(now);
+                    return self.next_probe(now);
+                }
+                if now >= self.next_validate && self.mtu > self.config.base_mtu {
+                    self.next_validate = now + self.config.validate_interval;
+                    return self.send_probe(self.mtu, now);
+                }
+                None
+            }
+        }
+    }
+
+    fn on_probe_ack(&mut self, size: usize, _now: Instant) {
+        let Some((probe_size, _)) = self.probe else {
+            return;
+        };
+        if size != probe_size {
+            return;
+        }
+        self.probe = None;
+        self.probe_count = 0;
+        self.large_losses = 0;
+        if size > self.mtu {
+            self.mtu = size;
+        }
+        self.low = self.low.max(size);
+    }
+
+    fn on_packet_lost(&mut self, size: usize, now: Instant) {
+        if size <= self.config.base_mtu || size > self.mtu {
+            return;
+        }
+        self.large_losses += 1;
+        if self.large_losses >= self.config.blackhole_threshold {
+            self.on_blackhole(now);
+        }
+    }
+
+    fn on_packet_acked(&mut self, size: usize) {
+        if size > self.config.base_mtu {
+            self.large_losses = 0;
+        }
+    }
+}
+
+/// Set DF on outgoing packets so oversized probes are dropped, not fragmented.
+#[cfg(target_os = "linux")]
+pub fn set_dont_fragment(socket: &std::net::UdpSocket) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = socket.as_raw_fd();
+    let (level, name, value) = if socket.local_addr()?.is_ipv6() {
+        (
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MTU_DISCOVER,
+            libc::IPV6_PMTUDISC_PROBE,
+        )
+    } else {
+        (
+            libc::IPPROTO_IP,
+            libc::IP_MTU_DISCOVER,
+            libc::IP_PMTUDISC_PROBE,
+        )
+    };
+    // Safety: fd is a socket, option value is a valid i32
+    let r = unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            &value as *const i32 as *const libc::c_void,
+            std::mem::size_of::<i32>() as u32,
+        )
+    };
+    if r < 0 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Set DF on outgoing packets (Linux only - no-op elsewhere).
+#[cfg(not(target_os = "linux"))]
+pub fn set_dont_fragment(_socket: &std::net::UdpSocket) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PmtudConfig {
+        PmtudConfig {
+            probe_timeout: Duration::from_millis(10),
+            max_probes: 2,
+            ..Default::default()
+        }
+    }
+
+    /// Drive the prober against a path with the given MTU until it converges
+    fn converge(prober: &mut PathMtuProber, path_mtu: usize, mut now: Instant) -> Instant {
+        for _ in 0..1000 {
+            if let Some(size) = prober.next_probe(now) {
+                if size <= path_mtu {
+                    prober.on_probe_ack(size, now);
+                }
+            }
+            if prober.is_complete() {
+                return now;
+            }
+            now += Duration::from_millis(11);
+        }
+        panic!("prober did not converge");
+    }
+
+    #[test]
+    fn test_full_mtu_first_probe() {
+        let mut prober = PathMtuProber::new(config());
+        let now = Instant::now();
+        assert_eq!(prober.next_probe(now), Some(MAX_MTU));
+        prober.on_probe_ack(MAX_MTU, now);
+        assert_eq!(prober.next_probe(now), None);
+        assert!(prober.is_complete());
+        assert_eq!(prober.current_mtu(), MAX_MTU);
+    }
+
+    #[test]
+    fn test_search_converges_below_path_mtu() {
+        let mut prober = PathMtuProber::new(config());
+        converge(&mut prober, 1400, Instant::now());
+        let mtu = prober.current_mtu();
+        assert!(
+            mtu <= 1400 && mtu > 1400 - SEARCH_GRANULARITY,
+            "mtu={}",
+            mtu
+        );
+    }
+
+    #[test]
+    fn test_base_mtu_when_nothing_larger_works() {
+        let mut prober = PathMtuProber::new(config());
+        converge(&mut prober, BASE_MTU, Instant::now());
+        assert_eq!(prober.current_mtu(), BASE_MTU);
+    }
+
+    #[test]
+    fn test_validation_failure_falls_back() {
+        let cfg = PmtudConfig {
+            validate_interval: Duration::from_secs(1),
+            ..config()
+        };
+        let mut prober = PathMtuProber::new(cfg);
+        let mut now = converge(&mut prober, MAX_MTU, Instant::now());
+        assert_eq!(prober.current_mtu(), MAX_MTU);
+
+        // Path shrinks: validation probes go unanswered
+        now += Duration::from_secs(2);
+        assert_eq!(prober.next_probe(now), Some(MAX_MTU));
+        for _ in 0..3 {
+            now += Duration::from_millis(11);
+            prober.next_probe(now);
+        }
+        assert_eq!(prober.blackholes(), 1);
+        assert!(prober.current_mtu() < MAX_MTU);
+
+        converge(&mut prober, 1300, now);
+        assert!(prober.current_mtu() <= 1300);
+    }
+
+    #[test]
+    fn test_large_packet_losses_trigger_blackhole() {
+        let mut prober = PathMtuProber::new(config());
+        let now = converge(&mut prober, MAX_MTU, Instant::now());
+
+        prober.on_packet_lost(1400, now);
+        prober.on_packet_acked(1400); // resets the streak
+        prober.on_packet_lost(1400, now);
+        prober.on_packet_lost(1400, now);
+        assert_eq!(prober.blackholes(), 0);
+
+        prober.on_packet_lost(1400, now);
+        assert_eq!(prober.blackholes(), 1);
+        assert_eq!(prober.current_mtu(), BASE_MTU);
+
+        // Small packet losses are congestion, not MTU
+        prober.on_packet_lost(100, now);
+        assert_eq!(prober.current_mtu(), BASE_MTU);
+    }
+
+    #[test]
+    fn test_fixed_mtu_never_probes() {
+        let mut fixed = FixedMtu(1280);
+        assert_eq!(fixed.next_probe(Instant::now()), None);
+        assert_eq!(fixed.current_mtu(), 1280);
+    }
+}
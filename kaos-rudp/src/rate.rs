@@ -0,0 +1,243 @@
+//! Congestion-aware send rate adaptation.
+//!
+//! Maps a connection's congestion signals (`ConnectionQuality`) to a send
+//! rate and detail level, so a server ticking at 60Hz backs off per client
+//! when its window collapses instead of blasting a dying link:
+//!
+//! ```rust,ignore
+//! let mut adapter = RateAdapter::default();
+//! // each tick, per client
+//! if let Some(q) = server.client_quality(&addr) {
+//!     adapter.update(&q, now);
+//! }
+//! if adapter.should_send(now) {
+//!     let snapshot = build_snapshot(adapter.rate().detail);
+//!     server.send(&addr, &snapshot)?;
+//! }
+//! ```
+//!
+//! Downgrades apply immediately; upgrades need the better tier to hold for
+//! `upgrade_after` so a window that oscillates doesn't flap the rate.
+
+use std::time::{Duration, Instant};
+
+use crate::congestion::ConnectionQuality;
+
+/// How much to put in each update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DetailLevel {
+    /// Everything
+    Full,
+    /// Drop cosmetic / far-away state
+    Reduced,
+    /// Only what the client needs to stay in sync
+    Minimal,
+}
+
+/// Chosen update rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendRate {
+    /// Updates per second
+    pub hz: u32,
+    pub detail: DetailLevel,
+}
+
+impl SendRate {
+    /// Time between updates
+    pub fn interval(&self) -> Duration {
+        Duration::from_micros(1_000_000 / self.hz.max(1) as u64)
+    }
+}
+
+/// Rate adaptation thresholds
+#[derive(Debug, Clone)]
+pub struct RateAdapterConfig {
+    /// Rate on a healthy link
+    pub full_hz: u32,
+    /// Floor on a congested link
+    pub min_hz: u32,
+    /// Window (packets) considered healthy; each halving below it drops a tier
+    pub healthy_window: u32,
+    /// RTT above this drops one extra tier
+    pub high_rtt: Duration,
+    /// A better tier must hold this long before upgrading
+    pub upgrade_after: Duration,
+}
+
+impl Default for RateAdapterConfig {
+    fn default() -> Self {
+        Self {
+            full_hz: 60,
+            min_hz: 10,
+            healthy_window: 32,
+            high_rtt: Duration::from_millis(150),
+            upgrade_after: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Tiers: full, half rate, quarter rate, floor
+const TIERS: usize = 4;
+
+/// Per-connection rate adapter (no I/O)
+#[derive(Debug)]
+pub struct RateAdapter {
+    config: RateAdapterConfig,
+    tier: usize,
+    better_since: Option<Instant>,
+    last_sent: Option<Instant>,
+}
+
+impl Default for RateAdapter {
+    fn default() -> Self {
+        Self::new(RateAdapterConfig::default())
+    }
+}
+
+impl RateAdapter {
+    pub fn new(config: RateAdapterConfig) -> Self {
+        Self {
+            config,
+            tier: 0,
+            better_since: None,
+            last_sent: None,
+        }
+    }
+
+    /// Tier the signals call for (0 = full rate)
+    fn target_tier(&self, q: &ConnectionQuality) -> usize {
+        let mut tier = 0;
+        let mut threshold = self.config.healthy_window;
+        while tier < TIERS - 1 && q.window < threshold {
+            tier += 1;
+            threshold /= 2;
+        }
+        if q.rtt_us > self.config.high_rtt.as_micros() as u64 {
+            tier += 1;
+        }
+        tier.min(TIERS - 1)
+    }
+
+    /// Feed fresh signals; returns the rate to use now
+    pub fn update(&mut self, q: &ConnectionQuality, now: Instant) -> SendRate {
+        let target = self.target_tier(q);
+        if target > self.tier {
+            self.tier = target;
+            self.better_since = None;
+        } else if target < self.tier {
+            let since = *self.better_since.get_or_insert(now);
+            if now.duration_since(since) >= self.config.upgrade_after {
+                // One tier at a time so recovery is probed gradually
+                self.tier -= 1;
+                self.better_since = None;
+            }
+        } else {
+            self.better_since = None;
+        }
+        self.rate()
+    }
+
+    /// Current rate
+    pub fn rate(&self) -> SendRate {
+        let (hz, detail) = match self.tier {
+            0 => (self.config.full_hz, DetailLevel::Full),
+            1 => (self.config.full_hz / 2, DetailLevel::Full),
+            2 => (self.config.full_hz / 4, DetailLevel::Reduced),
+            _ => (self.config.min_hz, DetailLevel::Minimal),
+        };
+        SendRate {
+            hz: hz.max(self.config.min_hz),
+            detail,
+        }
+    }
+
+    /// True if an update is due at `now` (call once per tick, send if true)
+    pub fn should_send(&mut self, now: Instant) -> bool {
+        match self.last_sent {
+            Some(t) if now.duration_since(t) < self.rate().interval() => false,
+            _ => {
+                self.last_sent = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Current tier is below full rate
+    pub fn is_degraded(&self) -> bool {
+        self.tier > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quality(window: u32, rtt_ms: u64) -> ConnectionQuality {
+        ConnectionQuality {
+            rtt_us: rtt_ms * 1000,
+            window,
+            max_window: 256,
+            in_flight: 0,
+            loss_events: 0,
+            ecn_ce: 0,
+        }
+    }
+
+    #[test]
+    fn test_tiers_follow_window_and_rtt() {
+        let now = Instant::now();
+        let cases = [
+            (64, 10, 60, DetailLevel::Full),
+            (20, 10, 30, DetailLevel::Full),
+            (12, 10, 15, DetailLevel::Reduced),
+            (4, 10, 10, DetailLevel::Minimal),
+            (64, 300, 30, DetailLevel::Full), // healthy window, slow link
+        ];
+        for (window, rtt, hz, detail) in cases {
+            let mut a = RateAdapter::default();
+            let rate = a.update(&quality(window, rtt), now);
+            assert_eq!((rate.hz, rate.detail), (hz, detail), "window {}", window);
+        }
+    }
+
+    #[test]
+    fn test_downgrade_immediate_upgrade_gradual() {
+        let mut a = RateAdapter::default();
+        let t0 = Instant::now();
+        assert_eq!(a.update(&quality(4, 10), t0).hz, 10);
+        assert!(a.is_degraded());
+
+        // Recovered, but not for long enough
+        let good = quality(64, 10);
+        assert_eq!(a.update(&good, t0).hz, 10);
+        assert_eq!(a.update(&good, t0 + Duration::from_millis(500)).hz, 10);
+        // Held for upgrade_after: one tier up
+        assert_eq!(a.update(&good, t0 + Duration::from_secs(1)).hz, 15);
+        assert_eq!(a.update(&good, t0 + Duration::from_secs(2)).hz, 15);
+        assert_eq!(a.update(&good, t0 + Duration::from_secs(3)).hz, 30);
+
+        // A dip resets the upgrade timer
+        assert_eq!(
+            a.update(&quality(4, 10), t0 + Duration::from_secs(4)).hz,
+            10
+        );
+    }
+
+    #[test]
+    fn test_should_send_paces_to_rate() {
+        let mut a = RateAdapter::new(RateAdapterConfig {
+            full_hz: 100,
+            ..Default::default()
+        });
+        let t0 = Instant::now();
+        a.update(&quality(64, 1), t0);
+        assert!(a.should_send(t0));
+        assert!(!a.should_send(t0 + Duration::from_millis(5)));
+        assert!(a.should_send(t0 + Duration::from_millis(10)));
+
+        // Degraded to the 10Hz floor: 100ms between sends
+        a.update(&quality(1, 1), t0 + Duration::from_millis(10));
+        assert!(!a.should_send(t0 + Duration::from_millis(60)));
+        assert!(a.should_send(t0 + Duration::from_millis(110)));
+    }
+}
@@ -0,0 +1,325 @@
+//! Server-relayed peer data channels.
+//!
+//! Clients in the same relay (identified by a token) exchange opaque datagrams
+//! through the server: `Data` packets flagged `FLAG_RELAY` whose payload starts
+//! with the 8-byte token. The server forwards the datagram unchanged to every
+//! other member, subject to a per-relay token-bucket bandwidth cap.
+//!
+//! ```text
+//! client -> server  Ping  FLAG_RELAY [token]             join
+//! server -> client  Pong  FLAG_RELAY [token][status]     join result
+//! client -> server  Data  FLAG_RELAY [token][opaque...]  forwarded as-is
+//! ```
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Relay join accepted
+pub const RELAY_JOINED: u8 = 0;
+/// No relay with that token
+pub const RELAY_UNKNOWN: u8 = 1;
+/// Relay already has `max_members`
+pub const RELAY_FULL: u8 = 2;
+
+/// Relay limits
+#[derive(Debug, Clone)]
+pub struct RelayConfig {
+    /// Maximum members (2 = classic P2P pair)
+    pub max_members: usize,
+    /// Sustained forwarded bytes per second (all members combined)
+    pub bytes_per_sec: u64,
+    /// Burst allowance in bytes
+    pub burst_bytes: u64,
+    /// Close the relay after this long without traffic
+    pub idle_timeout: Duration,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        Self {
+            max_members: 8,
+            bytes_per_sec: 256 * 1024,
+            burst_bytes: 64 * 1024,
+            idle_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Per-relay accounting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelayStats {
+    /// Datagrams forwarded (counted once per recipient)
+    pub packets_forwarded: u64,
+    /// Bytes forwarded (counted once per recipient)
+    pub bytes_forwarded: u64,
+    /// Datagrams dropped by the bandwidth cap
+    pub packets_dropped: u64,
+    /// Bytes dropped by the bandwidth cap
+    pub bytes_dropped: u64,
+}
+
+/// Token bucket (bytes)
+#[derive(Debug)]
+struct TokenBucket {
+    rate: u64,
+    capacity: u64,
+    tokens: u64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, capacity: u64, now: Instant) -> Self {
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn try_take(&mut self, bytes: u64, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.last_refill);
+        let refill = (elapsed.as_micros() as u64).saturating_mul(self.rate) / 1_000_000;
+        if refill > 0 {
+            self.tokens = (self.tokens + refill).min(self.capacity);
+            self.last_refill = now;
+        }
+        if self.tokens >= bytes {
+            self.tokens -= bytes;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Relay {
+    config: RelayConfig,
+    members: Vec<SocketAddr>,
+    bucket: TokenBucket,
+    stats: RelayStats,
+    last_active: Instant,
+}
+
+/// All relays on a server, keyed by token.
+#[derive(Debug, Default)]
+pub struct RelayTable {
+    relays: HashMap<u64, Relay>,
+}
+
+impl RelayTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open (or reconfigure) a relay at `now`. Existing members are kept.
+    pub fn open(&mut self, token: u64, config: RelayConfig, now: Instant) {
+        let bucket = TokenBucket::new(config.bytes_per_sec, config.burst_bytes, now);
+        match self.relays.get_mut(&token) {
+            Some(relay) => {
+                relay.members.truncate(config.max_members);
+                relay.bucket = bucket;
+                relay.config = config;
+            }
+            None => {
+                self.relays.insert(
+                    token,
+                    Relay {
+                        members: Vec::with_capacity(config.max_members),
+                        config,
+                        bucket,
+                        stats: RelayStats::default(),
+                        last_active: now,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Close a relay, returning its final stats
+    pub fn close(&mut self, token: u64) -> Option<RelayStats> {
+        self.relays.remove(&token).map(|r| r.stats)
+    }
+
+    /// Add `addr` to a relay at `now`. Returns a `RELAY_*` status.
+    pub fn join(&mut self, token: u64, addr: SocketAddr, now: Instant) -> u8 {
+        let Some(relay) = self.relays.get_mut(&token) else {
+            return RELAY_UNKNOWN;
+        };
+        if relay.members.contains(&addr) {
+            return RELAY_JOINED;
+        }
+        if relay.members.len() >= relay.config.max_members {
+            return RELAY_FULL;
+        }
+        relay.members.push(addr);
+        relay.last_active = now;
+        RELAY_JOINED
+    }
+
+    /// Members to forward a `len`-byte datagram from `from` to (includes `from` -
+    /// skip it). Empty if `from` isn't a member or the bandwidth cap drops it.
+    pub fn route(
+        &mut self,
+        token: u64,
+        from: SocketAddr,
+        len: usize,
+        now: Instant,
+    ) -> &[SocketAddr] {
+        let Some(relay) = self.relays.get_mut(&token) else {
+            return &[];
+        };
+        if !relay.members.contains(&from) {
+            return &[];
+        }
+        let recipients = (relay.members.len() - 1) as u64;
+        let bytes = len as u64 * recipients;
+        if !relay.bucket.try_take(bytes, now) {
+            relay.stats.packets_dropped += recipients;
+            relay.stats.bytes_dropped += bytes;
+            return &[];
+        }
+        relay.stats.packets_forwarded += recipients;
+        relay.stats.bytes_forwarded += bytes;
+        relay.last_active = now;
+        &relay.members
+    }
+
+    /// Stats for one relay
+    pub fn stats(&self, token: u64) -> Option<RelayStats> {
+        self.relays.get(&token).map(|r| r.stats)
+    }
+
+    /// Members of one relay
+    pub fn members(&self, token: u64) -> &[SocketAddr] {
+        self.relays
+            .get(&token)
+            .map(|r| r.members.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Number of open relays
+    pub fn len(&self) -> usize {
+        self.relays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relays.is_empty()
+    }
+
+    /// Drop a departed client from every relay
+    pub fn remove_member(&mut self, addr: &SocketAddr) {
+        for relay in self.relays.values_mut() {
+            relay.members.retain(|m| m != addr);
+        }
+    }
+
+    /// Close idle relays, returning their tokens
+    pub fn expire_idle(&mut self, now: Instant) -> Vec<u64> {
+        let mut expired = Vec::new();
+        self.relays.retain(|&token, r| {
+            let idle = now.duration_since(r.last_active) >= r.config.idle_timeout;
+            if idle {
+                expired.push(token);
+            }
+            !idle
+        });
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_join_limits() {
+        let mut table = RelayTable::new();
+        let now = Instant::now();
+        assert_eq!(table.join(1, addr(1), now), RELAY_UNKNOWN);
+
+        table.open(
+            1,
+            RelayConfig {
+                max_members: 2,
+                ..Default::default()
+            },
+            now,
+        );
+        assert_eq!(table.join(1, addr(1), now), RELAY_JOINED);
+        assert_eq!(table.join(1, addr(1), now), RELAY_JOINED); // idempotent
+        assert_eq!(table.join(1, addr(2), now), RELAY_JOINED);
+        assert_eq!(table.join(1, addr(3), now), RELAY_FULL);
+        assert_eq!(table.members(1), &[addr(1), addr(2)]);
+    }
+
+    #[test]
+    fn test_route_requires_membership() {
+        let mut table = RelayTable::new();
+        let now = Instant::now();
+        table.open(1, RelayConfig::default(), now);
+        table.join(1, addr(1), now);
+        table.join(1, addr(2), now);
+
+        assert!(table.route(1, addr(9), 100, now).is_empty());
+        assert_eq!(table.route(1, addr(1), 100, now), &[addr(1), addr(2)]);
+        let stats = table.stats(1).unwrap();
+        assert_eq!(stats.packets_forwarded, 1);
+        assert_eq!(stats.bytes_forwarded, 100);
+    }
+
+    #[test]
+    fn test_bandwidth_cap_drops_and_refills() {
+        let mut table = RelayTable::new();
+        let now = Instant::now();
+        table.open(
+            1,
+            RelayConfig {
+                bytes_per_sec: 1000,
+                burst_bytes: 1000,
+                ..Default::default()
+            },
+            now,
+        );
+        table.join(1, addr(1), now);
+        table.join(1, addr(2), now);
+
+        assert!(!table.route(1, addr(1), 600, now).is_empty());
+        assert!(table.route(1, addr(1), 600, now).is_empty());
+        let stats = table.stats(1).unwrap();
+        assert_eq!(stats.packets_dropped, 1);
+        assert_eq!(stats.bytes_dropped, 600);
+
+        // Half a second refills 500 bytes (400 left + 500 = 900)
+        assert!(!table
+            .route(1, addr(1), 600, now + Duration::from_millis(500))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_idle_expiry_and_member_removal() {
+        let mut table = RelayTable::new();
+        let now = Instant::now();
+        table.open(
+            1,
+            RelayConfig {
+                idle_timeout: Duration::from_secs(1),
+                ..Default::default()
+            },
+            now,
+        );
+        table.join(1, addr(1), now);
+        table.remove_member(&addr(1));
+        assert!(table.members(1).is_empty());
+
+        let later = now + Duration::from_secs(2);
+        assert_eq!(table.expire_idle(later), vec![1]);
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+//! Archive replication: a primary streams its archive to a warm standby.
+//!
+//! ```text
+//! ReplicationSender ── DATA [seq][msg] ──────────► ReplicaReceiver
+//!  (ArchivedTransport) ◄── RESUME [from] ─────────  (MmapArchive, same seqs)
+//! ```
+//!
+//! The replica asks to resume from its archive length when it starts and
+//! whenever it sees a gap; the sender replays from its own archive up to
+//! what it has recorded, then streams new appends as they come. After a
+//! disconnect, `ReplicationSender::reconnect` starts a new session and the
+//! reopened replica picks up where its archive ends.
+
+use crate::{ArchivedError, ArchivedTransport, RudpTransport};
+use kaos_archive::MmapArchive;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// `[DATA][seq u64][message]`
+const DATA: u8 = 1;
+/// `[RESUME][from u64]`: replica wants everything from `from` on
+const RESUME: u8 = 2;
+/// `[RESUMED][from u64]`: sender is streaming from `from`
+const RESUMED: u8 = 3;
+const FRAME_HEADER: usize = 9;
+
+/// Messages replayed from the archive per batch while catching up
+const CATCH_UP_BATCH: u64 = 64;
+const DEFAULT_RESUME_INTERVAL: Duration = Duration::from_millis(50);
+
+fn encode(frame: &mut Vec<u8>, tag: u8, seq: u64, data: &[u8]) {
+    frame.clear();
+    frame.push(tag);
+    frame.extend_from_slice(&seq.to_le_bytes());
+    frame.extend_from_slice(data);
+}
+
+fn decode(frame: &[u8]) -> Option<(u8, u64, &[u8])> {
+    if frame.len() < FRAME_HEADER {
+        return None;
+    }
+    let seq = u64::from_le_bytes(frame[1..FRAME_HEADER].try_into().unwrap());
+    Some((frame[0], seq, &frame[FRAME_HEADER..]))
+}
+
+/// `Ok(false)` when the send window is full (try again on `poll`)
+fn send_frame(transport: &mut RudpTransport, frame: &[u8]) -> Result<bool, ArchivedError> {
+    match transport.send(frame) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Primary side: archives every message and replicates it to one replica.
+pub struct ReplicationSender {
+    transport: ArchivedTransport,
+    /// Next sequence to put on the wire; `None` until the replica says
+    /// where to resume
+    next: Option<u64>,
+    frame: Vec<u8>,
+}
+
+impl ReplicationSender {
+    pub fn new<P: AsRef<Path>>(
+        local_addr: SocketAddr,
+        replica_addr: SocketAddr,
+        window_size: usize,
+        archive_path: P,
+        archive_capacity: usize,
+    ) -> Result<Self, ArchivedError> {
+        let transport = ArchivedTransport::new(
+            local_addr,
+            replica_addr,
+            window_size,
+            archive_path,
+            archive_capacity,
+        )?;
+        Ok(Self {
+            transport,
+            next: None,
+            frame: Vec::new(),
+        })
+    }
+
+    /// Archive `data` and send it to the replica if it's caught up
+    /// (otherwise `poll` replays it from the archive). Returns its sequence.
+    pub fn append(&mut self, data: &[u8]) -> Result<u64, ArchivedError> {
+        // The recorder skips empty messages, which would shift sequences
+        if data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty message").into());
+        }
+        let seq = self.transport.record(data)?;
+        if self.next == Some(seq) {
+            encode(&mut self.frame, DATA, seq, data);
+            if send_frame(self.transport.inner_mut(), &self.frame)? {
+                self.next = Some(seq + 1);
+            }
+        }
+        Ok(seq)
+    }
+
+    /// Handle resume requests, ACKs and NAKs, and replay what the replica
+    /// is missing. Returns number of messages sent from the archive.
+    pub fn poll(&mut self) -> Result<u64, ArchivedError> {
+        let mut resume = None;
+        self.transport.receive_batch_with(64, |frame| {
+            if let Some((RESUME, from, _)) = decode(frame) {
+                resume = Some(from);
+            }
+        });
+        let inner = self.transport.inner_mut();
+        inner.process_acks();
+        inner.process_retransmits();
+
+        if let Some(from) = resume {
+            encode(&mut self.frame, RESUMED, from, &[]);
+            send_frame(self.transport.inner_mut(), &self.frame)?;
+            self.next = Some(from);
+        }
+        self.catch_up()
+    }
+
+    fn catch_up(&mut self) -> Result<u64, ArchivedError> {
+        let mut sent = 0;
+        let Some(mut next) = self.next else {
+            return Ok(0);
+        };
+        let recorded = self.transport.archive_len();
+        while next < recorded {
+            let to = (next + CATCH_UP_BATCH).min(recorded);
+            let mut batch = Vec::with_capacity((to - next) as usize);
+            self.transport
+                .replay(next, to, |seq, data| batch.push((seq, data.to_vec())))?;
+            for (seq, data) in &batch {
+                encode(&mut self.frame, DATA, *seq, data);
+                if !send_frame(self.transport.inner_mut(), &self.frame)? {
+                    self.next = Some(next);
+                    return Ok(sent);
+                }
+                next = seq + 1;
+                sent += 1;
+            }
+        }
+        self.next = Some(next);
+        Ok(sent)
+    }
+
+    /// Start a new session after a disconnect (e.g. the replica restarted):
+    /// nothing is sent until the replica asks to resume.
+    pub fn reconnect(&mut self) -> Result<(), ArchivedError> {
+        self.transport.inner_mut().reset()?;
+        self.next = None;
+        Ok(())
+    }
+
+    /// Next sequence the replica will be sent, once it asked to resume
+    pub fn replicated(&self) -> Option<u64> {
+        self.next
+    }
+
+    /// Messages archived locally
+    pub fn len(&self) -> u64 {
+        self.transport.archive_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn transport(&self) -> &ArchivedTransport {
+        &self.transport
+    }
+}
+
+/// Standby side: writes replicated messages into an archive with the
+/// primary's sequence numbers.
+pub struct ReplicaReceiver {
+    transport: RudpTransport,
+    archive: MmapArchive,
+    /// Sender confirmed the resume point (or data arrived in order)
+    streaming: bool,
+    last_resume: Option<Instant>,
+    resume_interval: Duration,
+    frame: Vec<u8>,
+}
+
+impl ReplicaReceiver {
+    /// New, empty replica archive at `archive_path`
+    pub fn create<P: AsRef<Path>>(
+        local_addr: SocketAddr,
+        primary_addr: SocketAddr,
+        window_size: usize,
+        archive_path: P,
+        archive_capacity: usize,
+    ) -> Result<Self, ArchivedError> {
+        let archive = MmapArchive::create(archive_path, archive_capacity)?;
+        Self::with_archive(local_addr, primary_addr, window_size, archive)
+    }
+
+    /// Reopen a replica archive and resume from its last message
+    pub fn open<P: AsRef<Path>>(
+        local_addr: SocketAddr,
+        primary_addr: SocketAddr,
+        window_size: usize,
+        archive_path: P,
+    ) -> Result<Self, ArchivedError> {
+        let archive = MmapArchive::open(archive_path)?;
+        Self::with_archive(local_addr, primary_addr, window_size, archive)
+    }
+
+    fn with_archive(
+        local_addr: SocketAddr,
+        primary_addr: SocketAddr,
+        window_size: usize,
+        archive: MmapArchive,
+    ) -> Result<Self, ArchivedError> {
+        Ok(Self {
+            transport: RudpTransport::new(local_addr, primary_addr, window_size)?,
+            archive,
+            streaming: false,
+            last_resume: None,
+            resume_interval: DEFAULT_RESUME_INTERVAL,
+            frame: Vec::new(),
+        })
+    }
+
+    /// How often to repeat the resume request until the sender answers
+    pub fn with_resume_interval(mut self, interval: Duration) -> Self {
+        self.resume_interval = interval;
+        self
+    }
+
+    /// Append what arrived, in sequence order (replays of messages already
+    /// held are skipped). Returns number of messages appended.
+    pub fn poll(&mut self) -> Result<u64, ArchivedError> {
+        let (archive, streaming) = (&mut self.archive, &mut self.streaming);
+        let mut appended = 0;
+        let mut gap = false;
+        let mut error = None;
+        self.transport
+            .receive_batch_with(64, |frame| match decode(frame) {
+                Some((DATA, seq, data)) if error.is_none() && seq == archive.len() => {
+                    match archive.append(data) {
+                        Ok(_) => {
+                            appended += 1;
+                            *streaming = true;
+                        }
+                        Err(e) => error = Some(e),
+                    }
+                }
+                Some((DATA, seq, _)) if seq > archive.len() => gap = true,
+                Some((RESUMED, from, _)) if from == archive.len() => *streaming = true,
+                _ => {}
+            });
+        self.transport.process_acks();
+        self.transport.process_retransmits();
+        if let Some(e) = error {
+            return Err(e.into());
+        }
+
+        if gap {
+            self.streaming = false;
+            self.last_resume = None;
+        }
+        let due = self
+            .last_resume
+            .is_none_or(|at| at.elapsed() >= self.resume_interval);
+        if !self.streaming && due {
+            encode(&mut self.frame, RESUME, self.archive.len(), &[]);
+            if send_frame(&mut self.transport, &self.frame)? {
+                self.last_resume = Some(Instant::now());
+            }
+        }
+        Ok(appended)
+    }
+
+    /// Messages held by the replica
+    pub fn len(&self) -> u64 {
+        self.archive.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty()
+    }
+
+    pub fn archive(&self) -> &MmapArchive {
+        &self.archive
+    }
+
+    pub fn transport(&self) -> &RudpTransport {
+        &self.transport
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU16, Ordering};
+    use tempfile::tempdir;
+
+    static TEST_PORT: AtomicU16 = AtomicU16::new(47000);
+
+    fn next_port_pair() -> (SocketAddr, SocketAddr) {
+        let base = TEST_PORT.fetch_add(100, Ordering::Relaxed);
+        (
+            format!("127.0.0.1:{}", base).parse().unwrap(),
+            format!("127.0.0.1:{}", base + 10).parse().unwrap(),
+        )
+    }
+
+    /// Poll both sides until the replica holds `len` messages
+    fn sync(sender: &mut ReplicationSender, replica: &mut ReplicaReceiver, len: u64) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while replica.len() < len && Instant::now() < deadline {
+            sender.poll().unwrap();
+            replica.poll().unwrap();
+            std::thread::sleep(Duration::from_micros(200));
+        }
+        assert_eq!(replica.len(), len);
+    }
+
+    fn assert_identical(sender: &ReplicationSender, replica: &ReplicaReceiver) {
+        sender.transport().wait_for_archive();
+        let mut primary = Vec::new();
+        sender
+            .transport()
+            .replay(0, u64::MAX, |_, msg| primary.push(msg.to_vec()))
+            .unwrap();
+        let mut copy = Vec::new();
+        replica
+            .archive()
+            .replay(0, u64::MAX, |_, msg| copy.push(msg.to_vec()))
+            .unwrap();
+        assert_eq!(primary, copy);
+    }
+
+    #[test]
+    fn test_replicates_archive() {
+        let dir = tempdir().unwrap();
+        let (primary_addr, replica_addr) = next_port_pair();
+        let mut sender = ReplicationSender::new(
+            primary_addr,
+            replica_addr,
+            1024,
+            dir.path().join("primary"),
+            1024 * 1024,
+        )
+        .unwrap();
+
+        // Appended before the replica exists: replayed from the archive
+        for i in 0..20 {
+            sender.append(format!("move-{}", i).as_bytes()).unwrap();
+        }
+        let mut replica = ReplicaReceiver::create(
+            replica_addr,
+            primary_addr,
+            1024,
+            dir.path().join("replica"),
+            1024 * 1024,
+        )
+        .unwrap();
+        sync(&mut sender, &mut replica, 20);
+
+        // Caught up: streamed as appended
+        for i in 20..50 {
+            sender.append(format!("move-{}", i).as_bytes()).unwrap();
+        }
+        sync(&mut sender, &mut replica, 50);
+        assert_eq!(sender.replicated(), Some(50));
+        assert_eq!(replica.archive().read(42).unwrap(), b"move-42");
+        assert_identical(&sender, &replica);
+        assert!(sender.append(b"").is_err());
+    }
+
+    #[test]
+    fn test_resume_after_disconnect() {
+        let dir = tempdir().unwrap();
+        let (primary_addr, replica_addr) = next_port_pair();
+        let replica_path = dir.path().join("replica");
+        let mut sender = ReplicationSender::new(
+            primary_addr,
+            replica_addr,
+            1024,
+            dir.path().join("primary"),
+            1024 * 1024,
+        )
+        .unwrap();
+        let mut replica =
+            ReplicaReceiver::create(replica_addr, primary_addr, 1024, &replica_path, 1024 * 1024)
+                .unwrap();
+        for i in 0..30 {
+            sender.append(format!("chat-{}", i).as_bytes()).unwrap();
+        }
+        sync(&mut sender, &mut replica, 30);
+
+        // Replica goes away; the primary keeps recording
+        drop(replica);
+        for i in 30..80 {
+            sender.append(format!("chat-{}", i).as_bytes()).unwrap();
+        }
+        sender.poll().unwrap();
+
+        sender.reconnect().unwrap();
+        assert_eq!(sender.replicated(), None);
+        let mut replica =
+            ReplicaReceiver::open(replica_addr, primary_addr, 1024, &replica_path).unwrap();
+        assert_eq!(replica.len(), 30);
+        sync(&mut sender, &mut replica, 80);
+        assert_eq!(replica.archive().read(30).unwrap(), b"chat-30");
+        assert_identical(&sender, &replica);
+    }
+}
@@ -0,0 +1,289 @@
+//! Selective acknowledgement (SACK).
+//!
+//! A cumulative ACK stalls on the first gap: everything behind a lost packet
+//! stays "in flight" and the send window can't move. With `FLAG_SACK` the
+//! receiver appends up to `MAX_SACK_BLOCKS` ranges it holds above the
+//! cumulative point. The sender records them in a `SackScoreboard` so it:
+//!
+//! - releases congestion window for packets that already arrived,
+//! - skips them when retransmitting a NAKed range,
+//! - moves the send window past a run of SACKed packets as soon as the gap
+//!   in front of it is filled, without waiting for the next cumulative ACK.
+//!
+//! ACK payload with `FLAG_SACK`: `[start: u64 LE, end: u64 LE]` per block
+//! (inclusive). Peers that don't know the flag ignore the payload.
+
+/// Blocks carried in one ACK
+pub const MAX_SACK_BLOCKS: usize = 4;
+
+/// Encoded size of one block
+pub const SACK_BLOCK_SIZE: usize = 16;
+
+/// Encode blocks as an ACK payload
+pub fn encode_blocks(blocks: &[(u64, u64)], out: &mut Vec<u8>) {
+    for &(start, end) in blocks.iter().take(MAX_SACK_BLOCKS) {
+        out.extend_from_slice(&start.to_le_bytes());
+        out.extend_from_slice(&end.to_le_bytes());
+    }
+}
+
+/// Decode an ACK payload (ignores a trailing partial block)
+pub fn decode_blocks(payload: &[u8]) -> impl Iterator<Item = (u64, u64)> + '_ {
+    payload
+        .chunks_exact(SACK_BLOCK_SIZE)
+        .take(MAX_SACK_BLOCKS)
+        .map(|b| {
+            (
+                u64::from_le_bytes(b[..8].try_into().unwrap()),
+                u64::from_le_bytes(b[8..].try_into().unwrap()),
+            )
+        })
+        .filter(|(start, end)| start <= end)
+}
+
+/// Send window usage, for backpressure decisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendWindowOccupancy {
+    /// Window size (packets)
+    pub capacity: usize,
+    /// Sent but not cumulatively acknowledged (holding window slots)
+    pub unacked: usize,
+    /// Of `unacked`, already received by the peer (SACKed)
+    pub sacked: usize,
+    /// Accepted by `send` but waiting for window space (overflow mode)
+    pub queued: usize,
+}
+
+impl SendWindowOccupancy {
+    /// Packets that can be sent before the window is full
+    pub fn available(&self) -> usize {
+        self.capacity.saturating_sub(self.unacked)
+    }
+
+    /// Packets still on the wire (or lost)
+    pub fn in_flight(&self) -> usize {
+        self.unacked - self.sacked
+    }
+}
+
+/// Sender-side record of SACKed sequences above the cumulative ACK.
+pub struct SackScoreboard {
+    /// First sequence not cumulatively acknowledged
+    next_unacked: u64,
+    /// One bit per sequence in `[next_unacked, next_unacked + capacity)`, indexed by `seq & mask`
+    bits: Box<[u64]>,
+    mask: u64,
+    sacked: usize,
+}
+
+impl SackScoreboard {
+    /// `capacity` is rounded up to a power of two
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(64).next_power_of_two();
+        Self {
+            next_unacked: 0,
+            bits: vec![0; capacity / 64].into_boxed_slice(),
+            mask: capacity as u64 - 1,
+            sacked: 0,
+        }
+    }
+
+    #[inline]
+    fn in_range(&self, seq: u64) -> bool {
+        seq >= self.next_unacked && seq - self.next_unacked <= self.mask
+    }
+
+    #[inline]
+    fn bit(&self, seq: u64) -> (usize, u64) {
+        let idx = seq & self.mask;
+        ((idx >> 6) as usize, 1 << (idx & 63))
+    }
+
+    /// First sequence not cumulatively acknowledged
+    pub fn next_unacked(&self) -> u64 {
+        self.next_unacked
+    }
+
+    /// SACKed sequences held above the cumulative point
+    pub fn sacked(&self) -> usize {
+        self.sacked
+    }
+
+    pub fn is_sacked(&self, seq: u64) -> bool {
+        if !self.in_range(seq) {
+            return false;
+        }
+        let (word, mask) = self.bit(seq);
+        self.bits[word] & mask != 0
+    }
+
+    /// Record `[start, end]` as received. Returns newly SACKed sequences.
+    pub fn mark(&mut self, start: u64, end: u64) -> usize {
+        let start = start.max(self.next_unacked);
+        let end = end.min(self.next_unacked + self.mask);
+        let mut newly = 0;
+        for seq in start..=end {
+            let (word, mask) = self.bit(seq);
+            if self.bits[word] & mask == 0 {
+                self.bits[word] |= mask;
+                newly += 1;
+            }
+        }
+        self.sacked += newly;
+        newly
+    }
+
+    /// Cumulative ACK: everything below `next_unacked` is acknowledged.
+    /// Returns how many of the released sequences had been SACKed.
+    pub fn advance(&mut self, next_unacked: u64) -> usize {
+        if next_unacked <= self.next_unacked {
+            return 0;
+        }
+        let end = next_unacked.min(self.next_unacked + self.mask + 1);
+        let mut cleared = 0;
+        for seq in self.next_unacked..end {
+            let (word, mask) = self.bit(seq);
+            if self.bits[word] & mask != 0 {
+                self.bits[word] &= !mask;
+                cleared += 1;
+            }
+        }
+        self.sacked -= cleared;
+        self.next_unacked = next_unacked;
+        cleared
+    }
+
+    /// Skip the run of SACKed sequences at the cumulative point (the gap in
+    /// front of them was just filled). Returns the new `next_unacked`.
+    pub fn advance_past_sacked(&mut self) -> u64 {
+        let mut next = self.next_unacked;
+        while self.is_sacked(next) {
+            next += 1;
+        }
+        self.advance(next);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_roundtrip() {
+        let mut payload = Vec::new();
+        encode_blocks(&[(5, 9), (12, 12)], &mut payload);
+        assert_eq!(payload.len(), 2 * SACK_BLOCK_SIZE);
+        let blocks: Vec<_> = decode_blocks(&payload).collect();
+        assert_eq!(blocks, vec![(5, 9), (12, 12)]);
+    }
+
+    #[test]
+    fn test_scoreboard_releases_sacked_run() {
+        let mut board = SackScoreboard::new(256);
+        // 0..10 sent, 3 lost, 4..=9 arrived
+        assert_eq!(board.advance(3), 0);
+        assert_eq!(board.mark(4, 9), 6);
+        assert_eq!(board.mark(4, 9), 0);
+        assert!(board.is_sacked(5) && !board.is_sacked(3));
+        assert_eq!(board.sacked(), 6);
+
+        // Retransmitted 3 arrives: cumulative ACK moves to 4, then past the SACKed run
+        assert_eq!(board.advance(4), 0);
+        assert_eq!(board.advance_past_sacked(), 10);
+        assert_eq!(board.sacked(), 0);
+        assert!(!board.is_sacked(5));
+    }
+
+    #[test]
+    fn test_transport_sack_frees_window() {
+        use crate::{MessageType, ReliableUdpHeader, RudpTransport, FLAG_SACK};
+        use std::net::UdpSocket;
+
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (a_addr, b_addr) = (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap());
+        drop((a_sock, b_sock));
+
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        for i in 0..10u8 {
+            a.send(&[i]).unwrap();
+        }
+        assert_eq!(a.send_window_occupancy().unacked, 10);
+
+        // Play the receiver: ACK to a's (only) port
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let ack = |seq: u64, blocks: &[(u64, u64)]| {
+            let mut payload = Vec::new();
+            encode_blocks(blocks, &mut payload);
+            let mut header = ReliableUdpHeader::new(0, seq, MessageType::Ack, payload.len() as u16);
+            header.flags = if blocks.is_empty() { 0 } else { FLAG_SACK };
+            header.calculate_checksum(&payload);
+            let mut packet = bytemuck::bytes_of(&header).to_vec();
+            packet.extend_from_slice(&payload);
+            peer.send_to(&packet, a_addr).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+
+        // 0..=2 delivered, 3 lost, 4..=9 held by the receiver
+        ack(2, &[(4, 9)]);
+        a.process_acks();
+        let occupancy = a.send_window_occupancy();
+        assert_eq!((occupancy.unacked, occupancy.sacked), (7, 6));
+        assert_eq!(occupancy.in_flight(), 1);
+
+        // Retransmitted 3 arrives: the whole run is released at once
+        ack(3, &[]);
+        a.process_acks();
+        let occupancy = a.send_window_occupancy();
+        assert_eq!((occupancy.unacked, occupancy.sacked), (0, 0));
+        assert_eq!(occupancy.available(), 256);
+    }
+
+    #[test]
+    fn test_transport_overflow_queues_burst() {
+        use crate::{MessageType, ReliableUdpHeader, RudpTransport};
+        use std::net::UdpSocket;
+
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (a_addr, b_addr) = (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap());
+        drop((a_sock, b_sock));
+
+        let mut a = RudpTransport::new(a_addr, b_addr, 64).unwrap();
+        for i in 0..64u8 {
+            a.send(&[i]).unwrap();
+        }
+        assert!(a.send(b"full").is_err());
+
+        a.set_send_overflow(16);
+        for i in 0..16u8 {
+            assert_eq!(a.send(&[i]).unwrap(), 64 + i as u64);
+        }
+        assert!(a.send(b"queue full").is_err());
+        let occupancy = a.send_window_occupancy();
+        assert_eq!((occupancy.unacked, occupancy.queued), (64, 16));
+
+        // ACK 0..=9: queued packets go out as the windows open
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut header = ReliableUdpHeader::new(0, 9, MessageType::Ack, 0);
+        header.calculate_checksum(&[]);
+        peer.send_to(bytemuck::bytes_of(&header), a_addr).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        a.process_acks();
+
+        let occupancy = a.send_window_occupancy();
+        assert!(occupancy.queued < 16);
+        assert_eq!(occupancy.unacked + occupancy.queued, 80 - 10);
+    }
+
+    #[test]
+    fn test_scoreboard_ignores_out_of_range() {
+        let mut board = SackScoreboard::new(64);
+        board.advance(100);
+        assert_eq!(board.mark(50, 99), 0);
+        assert_eq!(board.mark(160, 1000), 4); // 160..=163 fit
+        assert_eq!(board.advance(200), 4);
+        assert_eq!(board.sacked(), 0);
+    }
+}
@@ -0,0 +1,516 @@
+//! Encryption layer (`encryption` feature).
+//!
+//! `SecureTransport` wraps any `Transport` (`RudpTransport`,
+//! `ArchivedTransport`, `DriverTransport`) with a Noise_XX handshake and
+//! per-message ChaCha20-Poly1305. Every data message carries its own nonce,
+//! so it decrypts no matter what was lost or reordered before it; a sliding
+//! window rejects replays.
+//!
+//! ```rust,ignore
+//! let keys = SecureTransport::<RudpTransport>::generate_keypair()?;
+//! let mut client = SecureTransport::initiator(rudp, &keys.private)?;
+//! while !client.is_established() {
+//!     client.receive(|_| {});
+//! }
+//! // XX sends static keys in the handshake: check who you're talking to
+//! assert_eq!(client.remote_static(), Some(&server_public[..]));
+//! client.send(b"hello")?;
+//! ```
+
+use crate::transport::Transport;
+use snow::{HandshakeState, Keypair, StatelessTransportState};
+use std::io;
+
+/// Noise protocol: mutual authentication, static keys exchanged encrypted
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// Frame types (first byte of every message on the inner transport)
+const FRAME_HANDSHAKE: u8 = 0;
+const FRAME_DATA: u8 = 1;
+/// Data frame: type + nonce
+const DATA_HEADER: usize = 9;
+/// Poly1305 tag
+const TAG_LEN: usize = 16;
+/// Largest Noise handshake message
+const MAX_HANDSHAKE_LEN: usize = 65535;
+/// Nonces tracked below the highest seen
+const REPLAY_WINDOW: u64 = 64;
+
+enum State {
+    Handshake(Box<HandshakeState>),
+    Established(Box<StatelessTransportState>),
+    /// Handshake broke off (also the stand-in while switching modes)
+    Failed,
+}
+
+/// Accepts each nonce once, and none older than the window
+#[derive(Debug, Default)]
+struct ReplayWindow {
+    /// Highest accepted nonce + 1 (0 = none yet)
+    top: u64,
+    /// Bit `i` set: nonce `top - 1 - i` was accepted
+    seen: u64,
+}
+
+impl ReplayWindow {
+    fn check(&self, nonce: u64) -> bool {
+        if nonce >= self.top {
+            return true;
+        }
+        let age = self.top - 1 - nonce;
+        age < REPLAY_WINDOW && self.seen & (1 << age) == 0
+    }
+
+    fn accept(&mut self, nonce: u64) {
+        if nonce >= self.top {
+            let shift = nonce + 1 - self.top;
+            self.seen = if shift >= REPLAY_WINDOW {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.top = nonce + 1;
+        } else {
+            self.seen |= 1 << (self.top - 1 - nonce);
+        }
+    }
+}
+
+/// Noise_XX encrypted transport over any `Transport`
+pub struct SecureTransport<T: Transport> {
+    inner: T,
+    state: State,
+    replay: ReplayWindow,
+    next_nonce: u64,
+    /// Handshake messages to send once `receive` lets go of `inner`
+    outbox: Vec<Vec<u8>>,
+    /// Messages that failed to decrypt, replays, or bad handshakes
+    rejected: u64,
+    buf: Vec<u8>,
+}
+
+impl<T: Transport> SecureTransport<T> {
+    /// New static keypair (keep `private` secret, hand `public` to peers)
+    pub fn generate_keypair() -> io::Result<Keypair> {
+        snow::Builder::new(params())
+            .generate_keypair()
+            .map_err(noise_error)
+    }
+
+    /// Start the handshake (sends the first message)
+    pub fn initiator(inner: T, private_key: &[u8]) -> io::Result<Self> {
+        let handshake = snow::Builder::new(params())
+            .local_private_key(private_key)
+            .build_initiator()
+            .map_err(noise_error)?;
+        let mut transport = Self::with_state(inner, handshake);
+        write_handshake(&mut transport.state, &mut transport.outbox)?;
+        transport.flush_outbox()?;
+        Ok(transport)
+    }
+
+    /// Wait for an initiator's handshake
+    pub fn responder(inner: T, private_key: &[u8]) -> io::Result<Self> {
+        let handshake = snow::Builder::new(params())
+            .local_private_key(private_key)
+            .build_responder()
+            .map_err(noise_error)?;
+        Ok(Self::with_state(inner, handshake))
+    }
+
+    fn with_state(inner: T, handshake: HandshakeState) -> Self {
+        Self {
+            inner,
+            state: State::Handshake(Box::new(handshake)),
+            replay: ReplayWindow::default(),
+            next_nonce: 0,
+            outbox: Vec::new(),
+            rejected: 0,
+            buf: vec![0u8; MAX_HANDSHAKE_LEN],
+        }
+    }
+
+    /// Handshake complete, `send` works
+    pub fn is_established(&self) -> bool {
+        matches!(self.state, State::Established(_))
+    }
+
+    /// Handshake broke off (bad or forged message): start over with a new
+    /// `initiator`/`responder` around `into_inner()`
+    pub fn is_failed(&self) -> bool {
+        matches!(self.state, State::Failed)
+    }
+
+    /// Peer's static public key, once it was received. XX doesn't check it
+    /// against anything: compare it with the key you expect.
+    pub fn remote_static(&self) -> Option<&[u8]> {
+        match &self.state {
+            State::Handshake(handshake) => handshake.get_remote_static(),
+            State::Established(transport) => transport.get_remote_static(),
+            State::Failed => None,
+        }
+    }
+
+    /// Messages dropped: failed authentication, replays, bad handshakes
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn flush_outbox(&mut self) -> io::Result<()> {
+        for frame in std::mem::take(&mut self.outbox) {
+            self.inner.send(&frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Transport> Transport for SecureTransport<T> {
+    /// Encrypt and send; `WouldBlock` until the handshake is done
+    fn send(&mut self, data: &[u8]) -> io::Result<u64> {
+        let transport = match &self.state {
+            State::Established(transport) => transport,
+            State::Handshake(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "Handshake in progress",
+                ))
+            }
+            State::Failed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "Handshake failed",
+                ))
+            }
+        };
+        let nonce = self.next_nonce;
+        let mut frame = vec![0u8; DATA_HEADER + data.len() + TAG_LEN];
+        frame[0] = FRAME_DATA;
+        frame[1..DATA_HEADER].copy_from_slice(&nonce.to_le_bytes());
+        let len = transport
+            .write_message(nonce, data, &mut frame[DATA_HEADER..])
+            .map_err(noise_error)?;
+        // Spent even if `inner.send` fails: the frame may still be queued
+        // for retransmit, and a nonce must never encrypt twice
+        self.next_nonce += 1;
+        frame.truncate(DATA_HEADER + len);
+        self.inner.send(&frame)
+    }
+
+    /// Receive and decrypt; handshake messages are answered on the way
+    fn receive<F: FnMut(&[u8])>(&mut self, mut handler: F) -> usize {
+        let mut count = 0;
+        let (state, replay, rejected, buf, outbox) = (
+            &mut self.state,
+            &mut self.replay,
+            &mut self.rejected,
+            &mut self.buf,
+            &mut self.outbox,
+        );
+        self.inner.receive(|msg| match msg.first() {
+            Some(&FRAME_DATA) if msg.len() >= DATA_HEADER + TAG_LEN => {
+                let State::Established(transport) = &*state else {
+                    // Ahead of our last handshake message: can't decrypt yet
+                    *rejected += 1;
+                    return;
+                };
+                let nonce = u64::from_le_bytes(msg[1..DATA_HEADER].try_into().unwrap());
+                if !replay.check(nonce) {
+                    *rejected += 1;
+                    return;
+                }
+                if buf.len() < msg.len() {
+                    buf.resize(msg.len(), 0);
+                }
+                match transport.read_message(nonce, &msg[DATA_HEADER..], buf) {
+                    Ok(len) => {
+                        replay.accept(nonce);
+                        count += 1;
+                        handler(&buf[..len]);
+                    }
+                    Err(_) => *rejected += 1,
+                }
+            }
+            Some(&FRAME_HANDSHAKE) => {
+                // Data right behind the last handshake message decrypts too
+                if on_handshake(state, buf, outbox, &msg[1..]).is_err() {
+                    *rejected += 1;
+                }
+            }
+            _ => *rejected += 1,
+        });
+
+        if self.flush_outbox().is_err() {
+            self.rejected += 1;
+        }
+        count
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+}
+
+fn params() -> snow::params::NoiseParams {
+    NOISE_PARAMS.parse().expect("valid Noise parameters")
+}
+
+/// A handshake message from the peer; queues our reply if it's our turn.
+/// Any error leaves the handshake `Failed` (snow's state is unusable).
+fn on_handshake(
+    state: &mut State,
+    buf: &mut [u8],
+    outbox: &mut Vec<Vec<u8>>,
+    message: &[u8],
+) -> io::Result<()> {
+    let State::Handshake(handshake) = state else {
+        // Late duplicate
+        return Ok(());
+    };
+    let result = handshake
+        .read_message(message, buf)
+        .map_err(noise_error)
+        .and_then(|_| finish_handshake(state))
+        .and_then(|_| {
+            if matches!(state, State::Handshake(h) if h.is_my_turn()) {
+                write_handshake(state, outbox)
+            } else {
+                Ok(())
+            }
+        });
+    if result.is_err() {
+        *state = State::Failed;
+    }
+    result
+}
+
+/// Our next handshake message into the outbox
+fn write_handshake(state: &mut State, outbox: &mut Vec<Vec<u8>>) -> io::Result<()> {
+    let State::Handshake(handshake) = state else {
+        return Ok(());
+    };
+    let mut frame = vec![FRAME_HANDSHAKE; 1 + MAX_HANDSHAKE_LEN];
+    let len = handshake
+        .write_message(&[], &mut frame[1..])
+        .map_err(noise_error)?;
+    frame.truncate(1 + len);
+    outbox.push(frame);
+    finish_handshake(state)
+}
+
+/// Switch to transport mode once the handshake is done
+fn finish_handshake(state: &mut State) -> io::Result<()> {
+    if !matches!(state, State::Handshake(h) if h.is_handshake_finished()) {
+        return Ok(());
+    }
+    let State::Handshake(handshake) = std::mem::replace(state, State::Failed) else {
+        unreachable!()
+    };
+    let transport = handshake
+        .into_stateless_transport_mode()
+        .map_err(noise_error)?;
+    *state = State::Established(Box::new(transport));
+    Ok(())
+}
+
+fn noise_error(e: snow::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Noise: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RudpTransport;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::net::{SocketAddr, UdpSocket};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    type Queue = Rc<RefCell<VecDeque<Vec<u8>>>>;
+
+    /// In-memory transport; `fail_next` queues the message but reports an
+    /// error, like a published packet whose `send_to` failed
+    #[derive(Default)]
+    struct Pipe {
+        tx: Queue,
+        rx: Queue,
+        fail_next: bool,
+    }
+
+    impl Transport for Pipe {
+        fn send(&mut self, data: &[u8]) -> io::Result<u64> {
+            self.tx.borrow_mut().push_back(data.to_vec());
+            if std::mem::take(&mut self.fail_next) {
+                return Err(io::Error::other("send failed"));
+            }
+            Ok(0)
+        }
+
+        fn receive<F: FnMut(&[u8])>(&mut self, mut handler: F) -> usize {
+            let mut count = 0;
+            while let Some(msg) = self.rx.borrow_mut().pop_front() {
+                handler(&msg);
+                count += 1;
+            }
+            count
+        }
+    }
+
+    fn pipes() -> (Pipe, Pipe) {
+        let (a, b) = (Pipe::default(), Pipe::default());
+        let b = Pipe {
+            tx: a.rx.clone(),
+            rx: a.tx.clone(),
+            ..b
+        };
+        (a, b)
+    }
+
+    fn established() -> (SecureTransport<Pipe>, SecureTransport<Pipe>) {
+        let keys = SecureTransport::<Pipe>::generate_keypair().unwrap();
+        let (a, b) = pipes();
+        let mut b = SecureTransport::responder(b, &keys.private).unwrap();
+        let mut a = SecureTransport::initiator(a, &keys.private).unwrap();
+        for _ in 0..3 {
+            b.receive(|_| {});
+            a.receive(|_| {});
+        }
+        assert!(a.is_established() && b.is_established());
+        (a, b)
+    }
+
+    fn free_addrs() -> (SocketAddr, SocketAddr) {
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap())
+    }
+
+    /// Receive on both sides until `done`
+    fn pump(
+        a: &mut SecureTransport<RudpTransport>,
+        b: &mut SecureTransport<RudpTransport>,
+        got: &mut Vec<Vec<u8>>,
+        done: impl Fn(&SecureTransport<RudpTransport>, &[Vec<u8>]) -> bool,
+    ) {
+        for _ in 0..100 {
+            std::thread::sleep(Duration::from_millis(2));
+            a.receive(|_| {});
+            b.receive(|msg| got.push(msg.to_vec()));
+            a.inner_mut().process_acks();
+            b.inner_mut().process_acks();
+            if done(a, got) {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn test_replay_window() {
+        let mut w = ReplayWindow::default();
+        for nonce in [0, 2, 1] {
+            assert!(w.check(nonce));
+            w.accept(nonce);
+        }
+        assert!(!w.check(1));
+        w.accept(100);
+        // Behind the window
+        assert!(!w.check(36));
+        assert!(w.check(37));
+        assert!(!w.check(100));
+    }
+
+    #[test]
+    fn test_handshake_and_round_trip() {
+        let (a_addr, b_addr) = free_addrs();
+        let a_keys = SecureTransport::<RudpTransport>::generate_keypair().unwrap();
+        let b_keys = SecureTransport::<RudpTransport>::generate_keypair().unwrap();
+        let mut b = SecureTransport::responder(
+            RudpTransport::new(b_addr, a_addr, 256).unwrap(),
+            &b_keys.private,
+        )
+        .unwrap();
+        let mut a = SecureTransport::initiator(
+            RudpTransport::new(a_addr, b_addr, 256).unwrap(),
+            &a_keys.private,
+        )
+        .unwrap();
+        assert_eq!(
+            a.send(b"early").unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        let mut got = Vec::new();
+        pump(&mut a, &mut b, &mut got, |a, _| a.is_established());
+        assert!(a.is_established());
+        assert_eq!(a.remote_static(), Some(&b_keys.public[..]));
+
+        // Sent right behind the last handshake message
+        a.send(b"hello").unwrap();
+        a.send(&vec![9u8; 3000]).unwrap();
+        pump(&mut a, &mut b, &mut got, |_, got| got.len() == 2);
+        assert!(b.is_established());
+        assert_eq!(b.remote_static(), Some(&a_keys.public[..]));
+        assert_eq!(got, [b"hello".to_vec(), vec![9u8; 3000]]);
+        assert_eq!(b.rejected(), 0);
+
+        // Forged data frame
+        let mut forged = vec![FRAME_DATA; DATA_HEADER + 8 + TAG_LEN];
+        forged[1..DATA_HEADER].copy_from_slice(&7u64.to_le_bytes());
+        a.inner_mut().send(&forged).unwrap();
+        pump(&mut a, &mut b, &mut got, |_, _| false);
+        assert_eq!(got.len(), 2);
+        assert_eq!(b.rejected(), 1);
+    }
+
+    #[test]
+    fn test_nonce_spent_on_failed_send() {
+        let (mut a, mut b) = established();
+        a.inner_mut().fail_next = true;
+        assert!(a.send(b"first").is_err());
+        a.send(b"second").unwrap();
+
+        let nonces: Vec<u64> = a
+            .inner()
+            .tx
+            .borrow()
+            .iter()
+            .map(|frame| u64::from_le_bytes(frame[1..DATA_HEADER].try_into().unwrap()))
+            .collect();
+        assert_eq!(nonces, [0, 1]);
+        // The failed frame may still go out (retransmit) and decrypts fine
+        let mut got = Vec::new();
+        b.receive(|msg| got.push(msg.to_vec()));
+        assert_eq!(got, [b"first".to_vec(), b"second".to_vec()]);
+        assert_eq!(b.rejected(), 0);
+    }
+
+    #[test]
+    fn test_bad_handshake_fails() {
+        let keys = SecureTransport::<Pipe>::generate_keypair().unwrap();
+        let (mut a, b) = pipes();
+        let mut b = SecureTransport::responder(b, &keys.private).unwrap();
+        a.send(&[FRAME_HANDSHAKE, 1, 2, 3]).unwrap();
+        b.receive(|_| {});
+        assert!(b.is_failed());
+        assert_eq!(b.rejected(), 1);
+        assert_eq!(
+            b.send(b"x").unwrap_err().kind(),
+            io::ErrorKind::ConnectionAborted
+        );
+        // Start over on the same transport
+        let b = SecureTransport::responder(b.into_inner(), &keys.private).unwrap();
+        assert!(!b.is_failed());
+    }
+}
@@ -1,13 +1,21 @@
-//! sendmmsg/recvmmsg batch UDP I/O (Linux)
+//! sendmmsg/recvmmsg batch UDP I/O (Linux), WSASendMsg/WSARecvMsg (Windows)
 //!
 //! 5-10x syscall reduction for bulk UDP.
 //! Used by RudpTransport for batch retransmit (sendmmsg) and batch receive (recvmmsg).
+//!
+//! Windows has no multi-datagram syscall, so its backend loops
+//! WSASendMsg/WSARecvMsg over preallocated headers and buffers (no per-packet
+//! address conversion or allocation). Same API, but methods take a
+//! `RawSocket` instead of a file descriptor.
 
 use std::io;
 use std::net::SocketAddr;
 
 #[cfg(target_os = "linux")]
-use libc::{iovec, mmsghdr, recvmmsg, sendmmsg, sockaddr_in, AF_INET};
+use libc::{
+    iovec, mmsghdr, recvmmsg, sendmmsg, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t,
+    AF_INET, AF_INET6,
+};
 #[cfg(target_os = "linux")]
 #[allow(unused_imports)]
 use std::os::unix::io::AsRawFd;
@@ -16,7 +24,7 @@ use std::os::unix::io::AsRawFd;
 pub struct BatchSender {
     msgvec: Vec<mmsghdr>,
     iovecs: Vec<iovec>,
-    addrs: Vec<sockaddr_in>,
+    addrs: Vec<sockaddr_storage>,
 }
 
 #[cfg(target_os = "linux")]
@@ -27,7 +35,7 @@ impl BatchSender {
     /// Panics if `batch_size` is 0.
     pub fn new(batch_size: usize) -> Self {
         assert!(batch_size > 0, "batch_size must be > 0");
-        // Safety: libc mmsghdr, iovec, sockaddr_in are valid when zeroed
+        // Safety: libc mmsghdr, iovec, sockaddr_storage are valid when zeroed
         Self {
             msgvec: vec![unsafe { std::mem::zeroed() }; batch_size],
             iovecs: vec![unsafe { std::mem::zeroed() }; batch_size],
@@ -46,32 +54,50 @@ impl BatchSender {
         }
         let count = packets.len().min(self.msgvec.len());
 
-        let sockaddr = match addr {
-            SocketAddr::V4(v4) => {
-                let mut a: sockaddr_in = std::mem::zeroed();
-                a.sin_family = AF_INET as u16;
-                a.sin_port = v4.port().to_be();
-                a.sin_addr.s_addr = u32::from_ne_bytes(v4.ip().octets());
-                a
-            }
-            SocketAddr::V6(_) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "IPv6 unsupported",
-                ));
-            }
-        };
+        let (sockaddr, socklen) = to_sockaddr(addr);
 
-        for i in 0..count {
-            self.iovecs[i].iov_base = packets[i].as_ptr() as *mut _;
-            self.iovecs[i].iov_len = packets[i].len();
+        for (i, packet) in packets.iter().enumerate().take(count) {
+            self.iovecs[i].iov_base = packet.as_ptr() as *mut _;
+            self.iovecs[i].iov_len = packet.len();
+            self.addrs[i] = sockaddr;
+            self.msgvec[i].msg_hdr.msg_name = &mut self.addrs[i] as *mut _ as *mut _;
+            self.msgvec[i].msg_hdr.msg_namelen = socklen;
+            self.msgvec[i].msg_hdr.msg_iov = &mut self.iovecs[i] as *mut _;
+            self.msgvec[i].msg_hdr.msg_iovlen = 1;
+        }
+
+        self.flush(fd, count)
+    }
+
+    #[cfg(feature = "mux")]
+    /// Send each `(packet, addr)` pair in one sendmmsg call (fan-out).
+    /// Takes at most `batch_size` pairs; returns how many were sent, which is
+    /// short if a send failed partway (retry from there to get the error).
+    pub unsafe fn send_to_many<'a>(
+        &mut self,
+        fd: i32,
+        packets: impl IntoIterator<Item = (&'a [u8], &'a SocketAddr)>,
+    ) -> io::Result<usize> {
+        let mut count = 0;
+        for (i, (packet, addr)) in packets.into_iter().take(self.msgvec.len()).enumerate() {
+            self.iovecs[i].iov_base = packet.as_ptr() as *mut _;
+            self.iovecs[i].iov_len = packet.len();
+            let (sockaddr, socklen) = to_sockaddr(addr);
             self.addrs[i] = sockaddr;
             self.msgvec[i].msg_hdr.msg_name = &mut self.addrs[i] as *mut _ as *mut _;
-            self.msgvec[i].msg_hdr.msg_namelen = std::mem::size_of::<sockaddr_in>() as u32;
+            self.msgvec[i].msg_hdr.msg_namelen = socklen;
             self.msgvec[i].msg_hdr.msg_iov = &mut self.iovecs[i] as *mut _;
             self.msgvec[i].msg_hdr.msg_iovlen = 1;
+            count += 1;
         }
+        if count == 0 {
+            return Ok(0);
+        }
+
+        self.flush(fd, count)
+    }
 
+    unsafe fn flush(&mut self, fd: i32, count: usize) -> io::Result<usize> {
         let r = sendmmsg(fd, self.msgvec.as_mut_ptr(), count as u32, 0);
         if r < 0 {
             Err(io::Error::last_os_error())
@@ -81,6 +107,35 @@ impl BatchSender {
     }
 }
 
+/// SocketAddr -> sockaddr_storage (+ length) for IPv4 or IPv6.
+/// The socket's family must match (or be a dual-stack v6 socket with v4-mapped peers).
+#[cfg(target_os = "linux")]
+fn to_sockaddr(addr: &SocketAddr) -> (sockaddr_storage, socklen_t) {
+    // Safety: sockaddr_storage is valid when zeroed and large enough for either family
+    let mut storage: sockaddr_storage = unsafe { std::mem::zeroed() };
+    let len = match addr {
+        SocketAddr::V4(v4) => {
+            // Safety: storage is aligned and sized for sockaddr_in
+            let a = unsafe { &mut *(&mut storage as *mut _ as *mut sockaddr_in) };
+            a.sin_family = AF_INET as u16;
+            a.sin_port = v4.port().to_be();
+            a.sin_addr.s_addr = u32::from_ne_bytes(v4.ip().octets());
+            std::mem::size_of::<sockaddr_in>()
+        }
+        SocketAddr::V6(v6) => {
+            // Safety: storage is aligned and sized for sockaddr_in6
+            let a = unsafe { &mut *(&mut storage as *mut _ as *mut sockaddr_in6) };
+            a.sin6_family = AF_INET6 as u16;
+            a.sin6_port = v6.port().to_be();
+            a.sin6_flowinfo = v6.flowinfo();
+            a.sin6_addr.s6_addr = v6.ip().octets();
+            a.sin6_scope_id = v6.scope_id();
+            std::mem::size_of::<sockaddr_in6>()
+        }
+    };
+    (storage, len as socklen_t)
+}
+
 // Safety: BatchSender owns all its data and doesn't share references across threads
 #[cfg(target_os = "linux")]
 unsafe impl Send for BatchSender {}
@@ -90,7 +145,9 @@ pub struct BatchReceiver {
     msgvec: Vec<mmsghdr>,
     iovecs: Vec<iovec>,
     buffers: Vec<Vec<u8>>,
-    addrs: Vec<sockaddr_in>,
+    addrs: Vec<sockaddr_storage>,
+    /// Per-slot cmsg space (TOS byte for ECN)
+    controls: Vec<[u64; crate::ecn::CMSG_BUF_WORDS]>,
 }
 
 #[cfg(target_os = "linux")]
@@ -102,12 +159,13 @@ impl BatchReceiver {
     pub fn new(batch_size: usize, buffer_size: usize) -> Self {
         assert!(batch_size > 0, "batch_size must be > 0");
         assert!(buffer_size > 0, "buffer_size must be > 0");
-        // Safety: libc mmsghdr, iovec, sockaddr_in are valid when zeroed
+        // Safety: libc mmsghdr, iovec, sockaddr_storage are valid when zeroed
         Self {
             msgvec: vec![unsafe { std::mem::zeroed() }; batch_size],
             iovecs: vec![unsafe { std::mem::zeroed() }; batch_size],
             buffers: (0..batch_size).map(|_| vec![0u8; buffer_size]).collect(),
             addrs: vec![unsafe { std::mem::zeroed() }; batch_size],
+            controls: vec![[0u64; crate::ecn::CMSG_BUF_WORDS]; batch_size],
         }
     }
 
@@ -117,9 +175,11 @@ impl BatchReceiver {
             self.iovecs[i].iov_base = self.buffers[i].as_mut_ptr() as *mut _;
             self.iovecs[i].iov_len = self.buffers[i].len();
             self.msgvec[i].msg_hdr.msg_name = &mut self.addrs[i] as *mut _ as *mut _;
-            self.msgvec[i].msg_hdr.msg_namelen = std::mem::size_of::<sockaddr_in>() as u32;
+            self.msgvec[i].msg_hdr.msg_namelen = std::mem::size_of::<sockaddr_storage>() as u32;
             self.msgvec[i].msg_hdr.msg_iov = &mut self.iovecs[i] as *mut _;
             self.msgvec[i].msg_hdr.msg_iovlen = 1;
+            self.msgvec[i].msg_hdr.msg_control = self.controls[i].as_mut_ptr() as *mut _;
+            self.msgvec[i].msg_hdr.msg_controllen = std::mem::size_of_val(&self.controls[i]) as _;
             self.msgvec[i].msg_len = 0;
         }
 
@@ -146,18 +206,269 @@ impl BatchReceiver {
         let len = self.msgvec[idx].msg_len as usize;
         &self.buffers[idx][..len]
     }
+
+    /// ECN codepoint of packet `idx` (NOT-ECT unless `ecn::enable` was called)
+    pub fn ecn(&self, idx: usize) -> u8 {
+        // Safety: msg_hdr was filled by the last recv_batch
+        unsafe { crate::ecn::from_cmsg(&self.msgvec[idx].msg_hdr) }
+    }
 }
 
 // Safety: BatchReceiver owns all its data and doesn't share references across threads
 #[cfg(target_os = "linux")]
 unsafe impl Send for BatchReceiver {}
 
-// Non-Linux: stubs (API compatibility)
-#[cfg(not(target_os = "linux"))]
+#[cfg(windows)]
+use std::os::windows::io::RawSocket;
+#[cfg(windows)]
+use windows_sys::Win32::Networking::WinSock::{
+    WSAGetLastError, WSAIoctl, WSASendMsg, AF_INET, AF_INET6, LPFN_WSARECVMSG,
+    SIO_GET_EXTENSION_FUNCTION_POINTER, SOCKADDR, SOCKADDR_IN, SOCKADDR_IN6, SOCKADDR_STORAGE,
+    SOCKET, SOCKET_ERROR, WSABUF, WSAID_WSARECVMSG, WSAMSG,
+};
+
+#[cfg(windows)]
+pub struct BatchSender {
+    batch_size: usize,
+    addr: SOCKADDR_STORAGE,
+    buf: WSABUF,
+    msg: WSAMSG,
+}
+
+#[cfg(windows)]
+impl BatchSender {
+    /// Create a new batch sender with the given batch size.
+    ///
+    /// # Panics
+    /// Panics if `batch_size` is 0.
+    pub fn new(batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be > 0");
+        // Safety: WinSock structs are valid when zeroed
+        Self {
+            batch_size,
+            addr: unsafe { std::mem::zeroed() },
+            buf: unsafe { std::mem::zeroed() },
+            msg: unsafe { std::mem::zeroed() },
+        }
+    }
+
+    pub unsafe fn send_batch(
+        &mut self,
+        socket: RawSocket,
+        packets: &[&[u8]],
+        addr: &SocketAddr,
+    ) -> io::Result<usize> {
+        let count = packets.len().min(self.batch_size);
+        let (sockaddr, len) = to_sockaddr(addr);
+        self.addr = sockaddr;
+        self.msg.namelen = len;
+        for (i, packet) in packets.iter().enumerate().take(count) {
+            if let Err(e) = self.send_one(socket, packet) {
+                return if i == 0 { Err(e) } else { Ok(i) };
+            }
+        }
+        Ok(count)
+    }
+
+    #[cfg(feature = "mux")]
+    /// Send each `(packet, addr)` pair (fan-out).
+    /// Takes at most `batch_size` pairs; returns how many were sent, which is
+    /// short if a send failed partway (retry from there to get the error).
+    pub unsafe fn send_to_many<'a>(
+        &mut self,
+        socket: RawSocket,
+        packets: impl IntoIterator<Item = (&'a [u8], &'a SocketAddr)>,
+    ) -> io::Result<usize> {
+        let mut count = 0;
+        for (packet, addr) in packets.into_iter().take(self.batch_size) {
+            let (sockaddr, len) = to_sockaddr(addr);
+            self.addr = sockaddr;
+            self.msg.namelen = len;
+            if let Err(e) = self.send_one(socket, packet) {
+                return if count == 0 { Err(e) } else { Ok(count) };
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// One WSASendMsg to `self.addr` (caller sets `msg.namelen`)
+    unsafe fn send_one(&mut self, socket: RawSocket, packet: &[u8]) -> io::Result<()> {
+        self.buf.len = packet.len() as u32;
+        self.buf.buf = packet.as_ptr() as *mut u8;
+        self.msg.name = &mut self.addr as *mut _ as *mut SOCKADDR;
+        self.msg.lpBuffers = &mut self.buf;
+        self.msg.dwBufferCount = 1;
+        let mut sent = 0u32;
+        let r = WSASendMsg(
+            socket as SOCKET,
+            &self.msg,
+            0,
+            &mut sent,
+            std::ptr::null_mut(),
+            None,
+        );
+        if r == SOCKET_ERROR {
+            Err(io::Error::from_raw_os_error(WSAGetLastError()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// SocketAddr -> SOCKADDR_STORAGE (+ length) for IPv4 or IPv6.
+#[cfg(windows)]
+fn to_sockaddr(addr: &SocketAddr) -> (SOCKADDR_STORAGE, i32) {
+    // Safety: SOCKADDR_STORAGE is valid when zeroed and large enough for either family
+    let mut storage: SOCKADDR_STORAGE = unsafe { std::mem::zeroed() };
+    let len = match addr {
+        SocketAddr::V4(v4) => {
+            // Safety: storage is aligned and sized for SOCKADDR_IN
+            let a = unsafe { &mut *(&mut storage as *mut _ as *mut SOCKADDR_IN) };
+            a.sin_family = AF_INET;
+            a.sin_port = v4.port().to_be();
+            a.sin_addr.S_un.S_addr = u32::from_ne_bytes(v4.ip().octets());
+            std::mem::size_of::<SOCKADDR_IN>()
+        }
+        SocketAddr::V6(v6) => {
+            // Safety: storage is aligned and sized for SOCKADDR_IN6
+            let a = unsafe { &mut *(&mut storage as *mut _ as *mut SOCKADDR_IN6) };
+            a.sin6_family = AF_INET6;
+            a.sin6_port = v6.port().to_be();
+            a.sin6_flowinfo = v6.flowinfo();
+            a.sin6_addr.u.Byte = v6.ip().octets();
+            a.Anonymous.sin6_scope_id = v6.scope_id();
+            std::mem::size_of::<SOCKADDR_IN6>()
+        }
+    };
+    (storage, len as i32)
+}
+
+// Safety: BatchSender owns all its data; the raw pointers only point into self during a call
+#[cfg(windows)]
+unsafe impl Send for BatchSender {}
+
+#[cfg(windows)]
+pub struct BatchReceiver {
+    /// WSARecvMsg is an extension function, resolved per socket on first use
+    recv_msg: Option<(RawSocket, LPFN_WSARECVMSG)>,
+    buffers: Vec<Vec<u8>>,
+    lens: Vec<usize>,
+    addrs: Vec<SOCKADDR_STORAGE>,
+}
+
+#[cfg(windows)]
+impl BatchReceiver {
+    /// Create a new batch receiver.
+    ///
+    /// # Panics
+    /// Panics if `batch_size` or `buffer_size` is 0.
+    pub fn new(batch_size: usize, buffer_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be > 0");
+        assert!(buffer_size > 0, "buffer_size must be > 0");
+        Self {
+            recv_msg: None,
+            buffers: (0..batch_size).map(|_| vec![0u8; buffer_size]).collect(),
+            lens: vec![0; batch_size],
+            // Safety: SOCKADDR_STORAGE is valid when zeroed
+            addrs: vec![unsafe { std::mem::zeroed() }; batch_size],
+        }
+    }
+
+    /// Receive until the batch is full or the socket would block.
+    /// The socket must be non-blocking.
+    pub unsafe fn recv_batch(&mut self, socket: RawSocket) -> io::Result<usize> {
+        let recv_msg = match self.recv_msg {
+            Some((s, f)) if s == socket => f,
+            _ => {
+                let f = resolve_wsarecvmsg(socket)?;
+                self.recv_msg = Some((socket, f));
+                f
+            }
+        };
+        let Some(recv_msg) = recv_msg else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "WSARecvMsg unavailable",
+            ));
+        };
+
+        let mut count = 0;
+        while count < self.buffers.len() {
+            let mut buf = WSABUF {
+                len: self.buffers[count].len() as u32,
+                buf: self.buffers[count].as_mut_ptr(),
+            };
+            // Safety: WSAMSG is valid when zeroed (no control buffer)
+            let mut msg: WSAMSG = std::mem::zeroed();
+            msg.name = &mut self.addrs[count] as *mut _ as *mut SOCKADDR;
+            msg.namelen = std::mem::size_of::<SOCKADDR_STORAGE>() as i32;
+            msg.lpBuffers = &mut buf;
+            msg.dwBufferCount = 1;
+
+            let mut received = 0u32;
+            let r = recv_msg(
+                socket as SOCKET,
+                &mut msg,
+                &mut received,
+                std::ptr::null_mut(),
+                None,
+            );
+            if r == SOCKET_ERROR {
+                let e = io::Error::from_raw_os_error(WSAGetLastError());
+                if e.kind() == io::ErrorKind::WouldBlock || count > 0 {
+                    break;
+                }
+                return Err(e);
+            }
+            self.lens[count] = received as usize;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub fn packet(&self, idx: usize) -> &[u8] {
+        &self.buffers[idx][..self.lens[idx]]
+    }
+
+    /// ECN codepoint of packet `idx` (not read on Windows: always NOT-ECT)
+    pub fn ecn(&self, _: usize) -> u8 {
+        0
+    }
+}
+
+#[cfg(windows)]
+unsafe fn resolve_wsarecvmsg(socket: RawSocket) -> io::Result<LPFN_WSARECVMSG> {
+    let mut f: LPFN_WSARECVMSG = None;
+    let mut bytes = 0u32;
+    let r = WSAIoctl(
+        socket as SOCKET,
+        SIO_GET_EXTENSION_FUNCTION_POINTER,
+        &WSAID_WSARECVMSG as *const _ as *const _,
+        std::mem::size_of_val(&WSAID_WSARECVMSG) as u32,
+        &mut f as *mut _ as *mut _,
+        std::mem::size_of::<LPFN_WSARECVMSG>() as u32,
+        &mut bytes,
+        std::ptr::null_mut(),
+        None,
+    );
+    if r == SOCKET_ERROR {
+        Err(io::Error::from_raw_os_error(WSAGetLastError()))
+    } else {
+        Ok(f)
+    }
+}
+
+// Safety: BatchReceiver owns all its data and doesn't share references across threads
+#[cfg(windows)]
+unsafe impl Send for BatchReceiver {}
+
+// Other platforms: stubs (API compatibility)
+#[cfg(not(any(target_os = "linux", windows)))]
 #[allow(dead_code)]
 pub struct BatchSender;
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(not(any(target_os = "linux", windows)))]
 #[allow(dead_code)]
 impl BatchSender {
     pub fn new(_: usize) -> Self {
@@ -169,13 +480,24 @@ impl BatchSender {
             "sendmmsg: Linux only",
         ))
     }
+    #[cfg(feature = "mux")]
+    pub unsafe fn send_to_many<'a>(
+        &mut self,
+        _: i32,
+        _: impl IntoIterator<Item = (&'a [u8], &'a SocketAddr)>,
+    ) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "sendmmsg: Linux only",
+        ))
+    }
 }
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(not(any(target_os = "linux", windows)))]
 #[allow(dead_code)]
 pub struct BatchReceiver;
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(not(any(target_os = "linux", windows)))]
 #[allow(dead_code)]
 impl BatchReceiver {
     pub fn new(_: usize, _: usize) -> Self {
@@ -190,6 +512,9 @@ impl BatchReceiver {
     pub fn packet(&self, _: usize) -> &[u8] {
         &[]
     }
+    pub fn ecn(&self, _: usize) -> u8 {
+        0
+    }
 }
 
 #[cfg(all(test, target_os = "linux"))]
@@ -271,4 +596,130 @@ mod tests {
         assert_eq!(sent, 100, "Should send all 100 packets");
         println!("sendmmsg: sent {} packets in one syscall", sent);
     }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_sendmmsg_fan_out() {
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receivers: Vec<UdpSocket> = (0..4)
+            .map(|_| UdpSocket::bind("127.0.0.1:0").unwrap())
+            .collect();
+        let addrs: Vec<SocketAddr> = receivers.iter().map(|r| r.local_addr().unwrap()).collect();
+
+        let packets: Vec<Vec<u8>> = (0..4)
+            .map(|i| format!("client-{}", i).into_bytes())
+            .collect();
+        let packet_refs: Vec<&[u8]> = packets.iter().map(|p| p.as_slice()).collect();
+
+        let mut batch_sender = BatchSender::new(64);
+        let sent = unsafe {
+            batch_sender
+                .send_to_many(sender.as_raw_fd(), packet_refs.iter().copied().zip(&addrs))
+                .unwrap()
+        };
+        assert_eq!(sent, 4);
+
+        // Each receiver gets its own packet
+        let mut buf = [0u8; 64];
+        for (i, r) in receivers.iter().enumerate() {
+            r.set_read_timeout(Some(std::time::Duration::from_secs(1)))
+                .unwrap();
+            let len = r.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..len], format!("client-{}", i).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_sendmmsg_ipv6() {
+        let Ok(sender) = UdpSocket::bind("[::1]:0") else {
+            return; // no IPv6 loopback
+        };
+        let receiver = UdpSocket::bind("[::1]:0").unwrap();
+        receiver
+            .set_read_timeout(Some(std::time::Duration::from_millis(100)))
+            .unwrap();
+        let addr = receiver.local_addr().unwrap();
+
+        let mut batch_sender = BatchSender::new(4);
+        let packets: [&[u8]; 2] = [b"v6-a", b"v6-b"];
+        let sent = unsafe { batch_sender.send_batch(sender.as_raw_fd(), &packets, &addr) };
+        assert_eq!(sent.unwrap(), 2);
+
+        let mut buf = [0u8; 16];
+        for expected in packets {
+            let (len, from) = receiver.recv_from(&mut buf).unwrap();
+            assert_eq!(&buf[..len], expected);
+            assert_eq!(from, sender.local_addr().unwrap());
+        }
+    }
+}
+
+#[cfg(all(test, windows))]
+mod windows_tests {
+    use super::*;
+    use std::net::UdpSocket;
+    use std::os::windows::io::AsRawSocket;
+
+    #[test]
+    fn test_wsamsg_batch() {
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let recv_addr = receiver.local_addr().unwrap();
+        receiver.set_nonblocking(true).unwrap();
+
+        let mut batch_sender = BatchSender::new(64);
+        let packets: Vec<Vec<u8>> = (0..10).map(|i| format!("msg-{}", i).into_bytes()).collect();
+        let packet_refs: Vec<&[u8]> = packets.iter().map(|p| p.as_slice()).collect();
+        let sent = unsafe {
+            batch_sender
+                .send_batch(sender.as_raw_socket(), &packet_refs, &recv_addr)
+                .unwrap()
+        };
+        assert_eq!(sent, 10);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let mut batch_receiver = BatchReceiver::new(64, 1024);
+        let received = unsafe { batch_receiver.recv_batch(receiver.as_raw_socket()).unwrap() };
+        assert_eq!(received, 10);
+        assert_eq!(batch_receiver.packet(0), b"msg-0");
+        assert_eq!(batch_receiver.packet(9), b"msg-9");
+
+        // Drained: would-block is an empty batch, not an error
+        let again = unsafe { batch_receiver.recv_batch(receiver.as_raw_socket()).unwrap() };
+        assert_eq!(again, 0);
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_wsamsg_fan_out() {
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receivers: Vec<UdpSocket> = (0..4)
+            .map(|_| UdpSocket::bind("127.0.0.1:0").unwrap())
+            .collect();
+        let addrs: Vec<SocketAddr> = receivers.iter().map(|r| r.local_addr().unwrap()).collect();
+        let packets: Vec<Vec<u8>> = (0..4)
+            .map(|i| format!("client-{}", i).into_bytes())
+            .collect();
+        let packet_refs: Vec<&[u8]> = packets.iter().map(|p| p.as_slice()).collect();
+
+        let mut batch_sender = BatchSender::new(64);
+        let sent = unsafe {
+            batch_sender
+                .send_to_many(
+                    sender.as_raw_socket(),
+                    packet_refs.iter().copied().zip(&addrs),
+                )
+                .unwrap()
+        };
+        assert_eq!(sent, 4);
+
+        let mut buf = [0u8; 64];
+        for (i, r) in receivers.iter().enumerate() {
+            r.set_read_timeout(Some(std::time::Duration::from_secs(1)))
+                .unwrap();
+            let len = r.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..len], format!("client-{}", i).as_bytes());
+        }
+    }
 }
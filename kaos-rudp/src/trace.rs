@@ -0,0 +1,492 @@
+//! Per-connection packet trace recording.
+//!
+//! A compact binary log of transport events (data sent/received, retransmits,
+//! ACK/NAK traffic) with microsecond timestamps and the congestion window at
+//! the time of the event. Render with the `kaos-trace` CLI:
+//!
+//! ```text
+//! kaos-trace conn.ktrace              # timeline
+//! kaos-trace conn.ktrace --summary    # counts, retransmit ratio, receive stalls
+//! ```
+//!
+//! File layout: 16-byte file header (`KTRC`, version, start time as unix
+//! micros) followed by fixed 32-byte little-endian records.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// File magic
+pub const TRACE_MAGIC: [u8; 4] = *b"KTRC";
+/// Current file format version
+pub const TRACE_VERSION: u16 = 1;
+/// File header size in bytes
+pub const TRACE_HEADER_SIZE: usize = 16;
+/// Record size in bytes
+pub const TRACE_RECORD_SIZE: usize = 32;
+
+/// What happened
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraceKind {
+    /// Data packet sent
+    Send = 0,
+    /// Data packet received (before reordering)
+    Recv = 1,
+    /// Data packet retransmitted
+    Retransmit = 2,
+    /// ACK sent
+    AckSent = 3,
+    /// ACK received
+    AckRecv = 4,
+    /// NAK sent (`seq` = first missing, `len` = range length)
+    NakSent = 5,
+    /// NAK received (`seq` = first missing, `len` = range length)
+    NakRecv = 6,
+    /// Message delivered to the application in order
+    Deliver = 7,
+}
+
+impl TraceKind {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Send),
+            1 => Some(Self::Recv),
+            2 => Some(Self::Retransmit),
+            3 => Some(Self::AckSent),
+            4 => Some(Self::AckRecv),
+            5 => Some(Self::NakSent),
+            6 => Some(Self::NakRecv),
+            7 => Some(Self::Deliver),
+            _ => None,
+        }
+    }
+
+    /// Short fixed-width label for timelines
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Send => "SEND",
+            Self::Recv => "RECV",
+            Self::Retransmit => "RTX ",
+            Self::AckSent => "ACK>",
+            Self::AckRecv => "ACK<",
+            Self::NakSent => "NAK>",
+            Self::NakRecv => "NAK<",
+            Self::Deliver => "DLVR",
+        }
+    }
+
+    /// Parse a label or lowercase name (`send`, `rtx`, `nak<` ...)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "send" => Some(Self::Send),
+            "recv" => Some(Self::Recv),
+            "rtx" | "retransmit" => Some(Self::Retransmit),
+            "ack>" | "ack_sent" => Some(Self::AckSent),
+            "ack<" | "ack_recv" => Some(Self::AckRecv),
+            "nak>" | "nak_sent" => Some(Self::NakSent),
+            "nak<" | "nak_recv" => Some(Self::NakRecv),
+            "dlvr" | "deliver" => Some(Self::Deliver),
+            _ => None,
+        }
+    }
+}
+
+/// One trace event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRecord {
+    /// Microseconds since the recorder was created
+    pub ts_us: u64,
+    /// Packet sequence (or acked / first NAKed sequence)
+    pub seq: u64,
+    /// Payload bytes, or range length for NAKs
+    pub len: u32,
+    /// Congestion window when the event was recorded
+    pub cwnd: u32,
+    pub kind: TraceKind,
+    /// Wire message type (`MessageType as u8`)
+    pub msg_type: u8,
+    /// Header flags
+    pub flags: u8,
+}
+
+impl TraceRecord {
+    pub fn to_bytes(&self) -> [u8; TRACE_RECORD_SIZE] {
+        let mut buf = [0u8; TRACE_RECORD_SIZE];
+        buf[0..8].copy_from_slice(&self.ts_us.to_le_bytes());
+        buf[8..16].copy_from_slice(&self.seq.to_le_bytes());
+        buf[16..20].copy_from_slice(&self.len.to_le_bytes());
+        buf[20..24].copy_from_slice(&self.cwnd.to_le_bytes());
+        buf[24] = self.kind as u8;
+        buf[25] = self.msg_type;
+        buf[26] = self.flags;
+        buf
+    }
+
+    pub fn from_bytes(buf: &[u8; TRACE_RECORD_SIZE]) -> Option<Self> {
+        Some(Self {
+            ts_us: u64::from_le_bytes(buf[0..8].try_into().unwrap()),
+            seq: u64::from_le_bytes(buf[8..16].try_into().unwrap()),
+            len: u32::from_le_bytes(buf[16..20].try_into().unwrap()),
+            cwnd: u32::from_le_bytes(buf[20..24].try_into().unwrap()),
+            kind: TraceKind::from_u8(buf[24])?,
+            msg_type: buf[25],
+            flags: buf[26],
+        })
+    }
+}
+
+impl fmt::Display for TraceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.ts_us as f64 / 1000.0;
+        match self.kind {
+            TraceKind::NakSent | TraceKind::NakRecv => write!(
+                f,
+                "{:>12.3}ms  {}  seq={}..{} cwnd={}",
+                ms,
+                self.kind.label(),
+                self.seq,
+                self.seq + (self.len.max(1) as u64) - 1,
+                self.cwnd
+            ),
+            TraceKind::AckSent | TraceKind::AckRecv => write!(
+                f,
+                "{:>12.3}ms  {}  seq={} flags={:#04x} cwnd={}",
+                ms,
+                self.kind.label(),
+                self.seq,
+                self.flags,
+                self.cwnd
+            ),
+            _ => write!(
+                f,
+                "{:>12.3}ms  {}  seq={} len={} cwnd={}",
+                ms,
+                self.kind.label(),
+                self.seq,
+                self.len,
+                self.cwnd
+            ),
+        }
+    }
+}
+
+/// Writes trace records to a file.
+///
+/// Methods take `&self` so hooks can sit on `&self` send paths; the writer is
+/// behind an (uncontended) mutex.
+#[derive(Debug)]
+pub struct TraceRecorder {
+    writer: Mutex<BufWriter<File>>,
+    start: Instant,
+}
+
+impl TraceRecorder {
+    /// Create (truncate) a trace file and write the header
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let start_unix_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+        let mut header = [0u8; TRACE_HEADER_SIZE];
+        header[0..4].copy_from_slice(&TRACE_MAGIC);
+        header[4..6].copy_from_slice(&TRACE_VERSION.to_le_bytes());
+        header[8..16].copy_from_slice(&start_unix_us.to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer: Mutex::new(writer),
+            start: Instant::now(),
+        })
+    }
+
+    /// Record an event (I/O errors are dropped - tracing must not break the connection)
+    pub fn record(&self, kind: TraceKind, msg_type: u8, flags: u8, seq: u64, len: u32, cwnd: u32) {
+        let record = TraceRecord {
+            ts_us: self.start.elapsed().as_micros() as u64,
+            seq,
+            len,
+            cwnd,
+            kind,
+            msg_type,
+            flags,
+        };
+        if let Ok(mut w) = self.writer.lock() {
+            let _ = w.write_all(&record.to_bytes());
+        }
+    }
+
+    /// Flush buffered records to disk
+    pub fn flush(&self) -> io::Result<()> {
+        match self.writer.lock() {
+            Ok(mut w) => w.flush(),
+            Err(_) => Err(io::Error::other("trace writer poisoned")),
+        }
+    }
+}
+
+impl Drop for TraceRecorder {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Reads a trace file record by record
+pub struct TraceReader<R: Read> {
+    reader: R,
+    start_unix_us: u64,
+}
+
+impl TraceReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> TraceReader<R> {
+    /// Validate the file header
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; TRACE_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        if header[0..4] != TRACE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a kaos trace file",
+            ));
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != TRACE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported trace version {}", version),
+            ));
+        }
+        Ok(Self {
+            reader,
+            start_unix_us: u64::from_le_bytes(header[8..16].try_into().unwrap()),
+        })
+    }
+
+    /// Wall-clock start of the recording (unix micros)
+    pub fn start_unix_us(&self) -> u64 {
+        self.start_unix_us
+    }
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = io::Result<TraceRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = [0u8; TRACE_RECORD_SIZE];
+        match self.reader.read_exact(&mut buf) {
+            Ok(()) => Some(TraceRecord::from_bytes(&buf).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "unknown trace event kind")
+            })),
+            // Truncated tail (recorder killed mid-write) ends the trace
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Aggregate view of a trace
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceSummary {
+    /// Events per kind, indexed by `TraceKind as usize`
+    pub counts: [u64; 8],
+    /// Duration covered (first to last event)
+    pub duration_us: u64,
+    /// Longest gap between consecutive received packets
+    pub max_recv_gap_us: u64,
+    /// When the longest receive gap ended
+    pub max_recv_gap_at_us: u64,
+    /// Smallest / largest congestion window seen
+    pub min_cwnd: u32,
+    pub max_cwnd: u32,
+}
+
+impl TraceSummary {
+    pub fn from_records<I: IntoIterator<Item = TraceRecord>>(records: I) -> Self {
+        let mut summary = Self {
+            min_cwnd: u32::MAX,
+            ..Default::default()
+        };
+        let mut first = None;
+        let mut last_recv = None;
+        for r in records {
+            summary.counts[r.kind as usize] += 1;
+            first.get_or_insert(r.ts_us);
+            summary.duration_us = r.ts_us - first.unwrap_or(r.ts_us);
+            summary.min_cwnd = summary.min_cwnd.min(r.cwnd);
+            summary.max_cwnd = summary.max_cwnd.max(r.cwnd);
+            if r.kind == TraceKind::Recv {
+                if let Some(prev) = last_recv {
+                    let gap = r.ts_us.saturating_sub(prev);
+                    if gap > summary.max_recv_gap_us {
+                        summary.max_recv_gap_us = gap;
+                        summary.max_recv_gap_at_us = r.ts_us;
+                    }
+                }
+                last_recv = Some(r.ts_us);
+            }
+        }
+        if first.is_none() {
+            summary.min_cwnd = 0;
+        }
+        summary
+    }
+
+    pub fn count(&self, kind: TraceKind) -> u64 {
+        self.counts[kind as usize]
+    }
+
+    /// Retransmits per original send
+    pub fn retransmit_ratio(&self) -> f64 {
+        let sent = self.count(TraceKind::Send);
+        if sent == 0 {
+            0.0
+        } else {
+            self.count(TraceKind::Retransmit) as f64 / sent as f64
+        }
+    }
+}
+
+impl fmt::Display for TraceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "duration      {:.3}ms", self.duration_us as f64 / 1000.0)?;
+        for kind in 0..8u8 {
+            let kind = TraceKind::from_u8(kind).unwrap();
+            writeln!(f, "{}          {}", kind.label(), self.count(kind))?;
+        }
+        writeln!(f, "retransmit    {:.2}%", self.retransmit_ratio() * 100.0)?;
+        writeln!(f, "cwnd          {}..{}", self.min_cwnd, self.max_cwnd)?;
+        write!(
+            f,
+            "max recv gap  {:.3}ms (ending at {:.3}ms)",
+            self.max_recv_gap_us as f64 / 1000.0,
+            self.max_recv_gap_at_us as f64 / 1000.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let r = TraceRecord {
+            ts_us: 123_456,
+            seq: u64::MAX - 1,
+            len: 1200,
+            cwnd: 64,
+            kind: TraceKind::NakRecv,
+            msg_type: 2,
+            flags: 0x04,
+        };
+        assert_eq!(TraceRecord::from_bytes(&r.to_bytes()), Some(r));
+        let mut bad = r.to_bytes();
+        bad[24] = 99;
+        assert_eq!(TraceRecord::from_bytes(&bad), None);
+        assert_eq!(TraceKind::parse("rtx"), Some(TraceKind::Retransmit));
+        assert_eq!(TraceKind::parse("NAK<"), Some(TraceKind::NakRecv));
+    }
+
+    #[test]
+    fn test_file_roundtrip_and_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("conn.ktrace");
+        {
+            let rec = TraceRecorder::create(&path).unwrap();
+            rec.record(TraceKind::Send, 0, 0, 1, 100, 64);
+            rec.record(TraceKind::Send, 0, 0, 2, 100, 64);
+            rec.record(TraceKind::NakRecv, 2, 0, 2, 1, 64);
+            rec.record(TraceKind::Retransmit, 0, 0, 2, 100, 32);
+            rec.record(TraceKind::Recv, 0, 0, 7, 50, 32);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            rec.record(TraceKind::Recv, 0, 0, 8, 50, 32);
+        }
+
+        let reader = TraceReader::open(&path).unwrap();
+        assert!(reader.start_unix_us() > 0);
+        let records: Vec<_> = reader.map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 6);
+        assert!(records.windows(2).all(|w| w[0].ts_us <= w[1].ts_us));
+
+        let summary = TraceSummary::from_records(records.iter().copied());
+        assert_eq!(summary.count(TraceKind::Send), 2);
+        assert_eq!(summary.count(TraceKind::Retransmit), 1);
+        assert_eq!(summary.retransmit_ratio(), 0.5);
+        assert_eq!((summary.min_cwnd, summary.max_cwnd), (32, 64));
+        assert!(summary.max_recv_gap_us >= 5_000);
+        assert_eq!(summary.max_recv_gap_at_us, records[5].ts_us);
+
+        assert!(records[2].to_string().contains("NAK<  seq=2..2"));
+    }
+
+    #[test]
+    fn test_reader_rejects_foreign_files_and_tolerates_truncation() {
+        assert!(
+            TraceReader::new(&b"NOPE\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00"[..]).is_err()
+        );
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&TRACE_MAGIC);
+        data.extend_from_slice(&TRACE_VERSION.to_le_bytes());
+        data.extend_from_slice(&[0u8; 10]);
+        let record = TraceRecord {
+            ts_us: 1,
+            seq: 1,
+            len: 1,
+            cwnd: 1,
+            kind: TraceKind::Send,
+            msg_type: 0,
+            flags: 0,
+        };
+        data.extend_from_slice(&record.to_bytes());
+        data.extend_from_slice(&[0u8; 7]); // partial record
+        let records: Vec<_> = TraceReader::new(&data[..]).unwrap().collect();
+        assert_eq!(records.len(), 1);
+    }
+
+    #[test]
+    fn test_transport_records_send_recv_ack() {
+        use crate::RudpTransport;
+        use std::net::UdpSocket;
+
+        let a_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (a_addr, b_addr) = (a_sock.local_addr().unwrap(), b_sock.local_addr().unwrap());
+        drop((a_sock, b_sock));
+
+        let dir = tempfile::tempdir().unwrap();
+        let (a_path, b_path) = (dir.path().join("a.ktrace"), dir.path().join("b.ktrace"));
+        let mut a = RudpTransport::new(a_addr, b_addr, 256).unwrap();
+        let mut b = RudpTransport::new(b_addr, a_addr, 256).unwrap();
+        a.enable_trace(&a_path).unwrap();
+        b.enable_trace(&b_path).unwrap();
+
+        a.send(b"one").unwrap();
+        a.send(b"two").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        b.receive_batch_with(64, |_| {});
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        a.process_acks();
+        a.set_trace(None);
+        b.set_trace(None);
+
+        let read = |p: &Path| {
+            let records: Vec<_> = TraceReader::open(p).unwrap().map(|r| r.unwrap()).collect();
+            TraceSummary::from_records(records)
+        };
+        let (sa, sb) = (read(&a_path), read(&b_path));
+        assert_eq!(sa.count(TraceKind::Send), 2);
+        assert_eq!(sa.count(TraceKind::AckRecv), 1);
+        assert_eq!(sb.count(TraceKind::Recv), 2);
+        assert_eq!(sb.count(TraceKind::Deliver), 2);
+        assert_eq!(sb.count(TraceKind::AckSent), 1);
+    }
+}
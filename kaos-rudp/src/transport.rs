@@ -11,6 +11,11 @@ const CLIENT_SOCKET_BUFFER_SIZE: i32 = 4 * 1024 * 1024;
 
 use kaos_shared::{MessageType, PacketHeader, HEADER_SIZE, MUX_KEY_SIZE};
 
+use crate::header::{FLAG_MTU_PROBE, FLAG_NAT, FLAG_NO_CRC, FLAG_RELAY};
+use crate::nat::{HolePuncher, NatMessage, PunchState};
+use crate::pmtud::{self, FixedMtu, MtuDiscovery, PathMtuProber, PmtudConfig};
+use kaos::checksum::Checksum;
+
 /// Core transport trait - all transports implement this
 pub trait Transport {
     /// Send data, returns sequence number
@@ -54,6 +59,15 @@ pub struct ClientTransportConfig {
     /// Mux key for multiplexed servers (4 bytes prefix on each packet)
     /// If None, no prefix is added (legacy mode)
     pub mux_key: Option<u32>,
+    /// Path MTU discovery (None = fixed 1200-byte MTU, no probing). Sets
+    /// DF: fragmented probes would get through and overstate the MTU.
+    pub pmtud: Option<PmtudConfig>,
+    /// Set DF on outgoing packets so the path can't silently fragment
+    /// (Linux; always on with `pmtud`)
+    pub dont_fragment: bool,
+    /// Header checksum, offered to the server in the handshake (direct
+    /// peers must use the same one)
+    pub checksum: Checksum,
 }
 
 impl Default for ClientTransportConfig {
@@ -64,6 +78,9 @@ impl Default for ClientTransportConfig {
             read_timeout: Some(Duration::from_millis(100)),
             write_timeout: Some(Duration::from_millis(1000)),
             mux_key: None,
+            pmtud: Some(PmtudConfig::default()),
+            dont_fragment: false,
+            checksum: Checksum::default(),
         }
     }
 }
@@ -111,6 +128,16 @@ pub struct ClientTransport {
     mux_key: Option<u32>,
     /// Connection state
     connected: bool,
+    /// Path MTU discovery strategy
+    mtu: Box<dyn MtuDiscovery>,
+    /// Our address as seen by the server (after `request_observed_addr`)
+    observed_addr: Option<SocketAddr>,
+    /// Peer-to-peer hole punching
+    puncher: HolePuncher,
+    /// Server relay joined via `join_relay`
+    relay_token: Option<u64>,
+    /// Header checksum for data and ACKs (see `ClientTransportConfig::checksum`)
+    checksum: Checksum,
 }
 
 impl ClientTransport {
@@ -136,6 +163,19 @@ impl ClientTransport {
 
     /// Connect with custom configuration
     pub fn connect_with_config(mut config: ClientTransportConfig) -> io::Result<Self> {
+        if let Some(pmtud) = &config.pmtud {
+            let overhead = config.mux_key.map_or(0, |_| MUX_KEY_SIZE) + HEADER_SIZE;
+            if pmtud.base_mtu <= overhead || pmtud.max_mtu < pmtud.base_mtu {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Invalid PMTUD range {}..={} (packet overhead {})",
+                        pmtud.base_mtu, pmtud.max_mtu, overhead
+                    ),
+                ));
+            }
+        }
+
         // Ensure bind_addr uses the same IP version as peer_addr (IPv4/IPv6 matching)
         if config.bind_addr.ip().is_unspecified() {
             config.bind_addr = match config.peer_addr.ip() {
@@ -201,6 +241,10 @@ impl ClientTransport {
                 })?
             }
         };
+        if config.dont_fragment || config.pmtud.is_some() {
+            pmtud::set_dont_fragment(&socket)?;
+        }
+
         let nak_local = nak_socket.local_addr()?;
         eprintln!("[RUDP] NAK socket bound to: {}", nak_local);
         nak_socket.set_nonblocking(true)?;
@@ -220,22 +264,32 @@ impl ClientTransport {
             recv_buffer: vec![0u8; 65536],
             mux_key: config.mux_key,
             connected: false,
+            mtu: match config.pmtud {
+                Some(cfg) => Box::new(PathMtuProber::new(cfg)),
+                None => Box::new(FixedMtu::default()),
+            },
+            observed_addr: None,
+            puncher: HolePuncher::default(),
+            relay_token: None,
+            checksum: config.checksum,
         };
 
-        // Send handshake
+        // Send handshake, then the first MTU probe right behind it
         transport.send_handshake()?;
         transport.connected = true;
+        transport.poll_mtu();
 
         Ok(transport)
     }
 
-    /// Send handshake packet to initiate connection
+    /// Send handshake packet to initiate connection (payload: checksum id)
     fn send_handshake(&mut self) -> io::Result<()> {
-        let header = PacketHeader::new(self.sequence, MessageType::Handshake, 0);
+        let header = PacketHeader::new(self.sequence, MessageType::Handshake, 1);
         self.sequence += 1;
-        let header_bytes = header.to_bytes();
+        let mut packet = self.create_packet_buffer(HEADER_SIZE + 1);
+        packet.extend_from_slice(&header.to_bytes());
+        packet.push(self.checksum.id());
 
-        let packet = self.prepend_mux_key(&header_bytes);
         eprintln!("[RUDP] Sending handshake to: {}", self.peer_addr);
         match self.socket.send_to(&packet, self.peer_addr) {
             Ok(n) => {
@@ -257,7 +311,7 @@ impl ClientTransport {
     /// Send raw data with RUDP header
     pub fn send_raw(&mut self, data: &[u8]) -> io::Result<usize> {
         let mut header = PacketHeader::new(self.sequence, MessageType::Data, data.len());
-        header.calculate_checksum(data);
+        self.seal(&mut header, data);
         self.sequence += 1;
 
         let mut packet = self.create_packet_buffer(HEADER_SIZE + data.len());
@@ -271,7 +325,7 @@ impl ClientTransport {
     pub fn send_unreliable(&self, data: &[u8]) -> io::Result<usize> {
         let mut header = PacketHeader::new(0, MessageType::Data, data.len());
         header.flags = 0x01; // Unreliable flag
-        header.calculate_checksum(data);
+        self.seal(&mut header, data);
 
         let mut packet = Vec::with_capacity(self.mux_prefix_len() + HEADER_SIZE + data.len());
         if let Some(mux_key) = self.mux_key {
@@ -286,7 +340,7 @@ impl ClientTransport {
     /// Send an ACK to the peer for a received sequence
     fn send_ack(&self, seq: u64) {
         let mut header = PacketHeader::new(seq, MessageType::Ack, 0);
-        header.calculate_checksum(&[]);
+        self.seal(&mut header, &[]);
         let header_bytes = header.to_bytes();
 
         let packet = self.prepend_mux_key(&header_bytes);
@@ -312,6 +366,162 @@ impl ClientTransport {
         Ok(())
     }
 
+    /// Replace the MTU discovery strategy
+    pub fn set_mtu_discovery(&mut self, mtu: Box<dyn MtuDiscovery>) {
+        self.mtu = mtu;
+        self.poll_mtu();
+    }
+
+    /// Current path MTU (UDP payload bytes, including mux prefix and header)
+    pub fn path_mtu(&self) -> usize {
+        self.mtu.current_mtu()
+    }
+
+    /// Largest `send` payload that fits in one datagram on this path
+    /// (0 if a `FixedMtu` is smaller than the headers)
+    pub fn max_payload(&self) -> usize {
+        self.path_mtu()
+            .saturating_sub(self.mux_prefix_len() + HEADER_SIZE)
+    }
+
+    /// Send an MTU probe if one is due (also called from `receive`)
+    pub fn poll_mtu(&mut self) {
+        if let Some(size) = self.mtu.next_probe(Instant::now()) {
+            let _ = self.send_mtu_probe(size);
+        }
+    }
+
+    /// Padded Ping of exactly `size` bytes; sequence carries the size
+    fn send_mtu_probe(&self, size: usize) -> io::Result<usize> {
+        let padding = size.saturating_sub(self.mux_prefix_len() + HEADER_SIZE);
+        let mut header = PacketHeader::new(size as u64, MessageType::Ping, padding);
+        header.flags = FLAG_MTU_PROBE;
+
+        let mut packet = self.create_packet_buffer(HEADER_SIZE + padding);
+        packet.extend_from_slice(&header.to_bytes());
+        packet.resize(packet.len() + padding, 0);
+        self.socket.send_to(&packet, self.peer_addr)
+    }
+
+    /// Ask the server for our public address (answer lands in `observed_addr`)
+    pub fn request_observed_addr(&self) -> io::Result<()> {
+        self.send_nat(&NatMessage::WhoAmI, self.peer_addr)
+    }
+
+    /// Our address as seen by the server, once reported
+    pub fn observed_addr(&self) -> Option<SocketAddr> {
+        self.observed_addr
+    }
+
+    /// Rendezvous with another client holding the same `token` and try to
+    /// punch a direct path. Progress is reported by `punch_state`.
+    pub fn rendezvous(&mut self, token: u64) -> io::Result<()> {
+        self.puncher.start(token);
+        self.send_nat(&NatMessage::Rendezvous { token }, self.peer_addr)
+    }
+
+    /// Hole punch progress (`Relay` = punching failed, use the server relay)
+    pub fn punch_state(&self) -> PunchState {
+        self.puncher.state()
+    }
+
+    /// Send unreliable data to the peer: direct if punched, else via the server relay
+    pub fn send_peer(&self, data: &[u8]) -> io::Result<usize> {
+        let peer = match self.puncher.state() {
+            PunchState::Direct { peer, .. } => peer,
+            PunchState::Relay { token, .. } => return self.send_relay_to(token, data),
+            _ => return Err(io::Error::new(io::ErrorKind::NotConnected, "no peer path")),
+        };
+        let mut header = PacketHeader::new(0, MessageType::Data, data.len());
+        header.flags = 0x01; // Unreliable flag
+        self.seal(&mut header, data);
+
+        let mut packet = self.create_packet_buffer(HEADER_SIZE + data.len());
+        packet.extend_from_slice(&header.to_bytes());
+        packet.extend_from_slice(data);
+        self.socket.send_to(&packet, peer)
+    }
+
+    /// Join a server relay (server side: `MuxRudpServer::open_relay`).
+    /// `relay_token` is set once the server confirms.
+    pub fn join_relay(&mut self, token: u64) -> io::Result<()> {
+        let payload = token.to_le_bytes();
+        let mut header = PacketHeader::new(0, MessageType::Ping, payload.len());
+        header.flags = FLAG_RELAY;
+        self.seal(&mut header, &payload);
+
+        let mut packet = self.create_packet_buffer(HEADER_SIZE + payload.len());
+        packet.extend_from_slice(&header.to_bytes());
+        packet.extend_from_slice(&payload);
+        self.socket.send_to(&packet, self.peer_addr)?;
+        Ok(())
+    }
+
+    /// Relay confirmed by the server, if any
+    pub fn relay_token(&self) -> Option<u64> {
+        self.relay_token
+    }
+
+    /// Send opaque data to the other members of the joined relay
+    pub fn send_relay(&self, data: &[u8]) -> io::Result<usize> {
+        let token = self
+            .relay_token
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "no relay joined"))?;
+        self.send_relay_to(token, data)
+    }
+
+    fn send_relay_to(&self, token: u64, data: &[u8]) -> io::Result<usize> {
+        let payload_len = 8 + data.len();
+        let mut header = PacketHeader::new(0, MessageType::Data, payload_len);
+        header.flags = FLAG_RELAY | 0x01; // Unreliable
+        let mut packet = self.create_packet_buffer(HEADER_SIZE + payload_len);
+        let header_at = packet.len();
+        packet.extend_from_slice(&header.to_bytes());
+        packet.extend_from_slice(&token.to_le_bytes());
+        packet.extend_from_slice(data);
+
+        self.seal(&mut header, &packet[header_at + HEADER_SIZE..]);
+        packet[header_at..header_at + HEADER_SIZE].copy_from_slice(&header.to_bytes());
+        self.socket.send_to(&packet, self.peer_addr)
+    }
+
+    /// Send punch probes if due (also called from `receive`)
+    pub fn poll_punch(&mut self) {
+        if let Some((peer, token)) = self.puncher.poll(Instant::now()) {
+            let _ = self.send_nat(&NatMessage::Punch { token }, peer);
+        }
+    }
+
+    fn send_nat(&self, msg: &NatMessage, to: SocketAddr) -> io::Result<()> {
+        let (msg_type, payload) = msg.encode();
+        let mut header = PacketHeader::new(0, msg_type, payload.len());
+        header.flags = FLAG_NAT;
+        header.calculate_checksum(&payload);
+
+        let mut packet = self.create_packet_buffer(HEADER_SIZE + payload.len());
+        packet.extend_from_slice(&header.to_bytes());
+        packet.extend_from_slice(&payload);
+        self.socket.send_to(&packet, to)?;
+        Ok(())
+    }
+
+    fn handle_nat(&mut self, from: SocketAddr, msg: NatMessage) {
+        match msg {
+            NatMessage::ObservedAddr(addr) if from == self.peer_addr => {
+                self.observed_addr = Some(addr);
+            }
+            NatMessage::PeerInfo { token, peer } if from == self.peer_addr => {
+                self.puncher.on_peer_info(token, peer, Instant::now());
+                self.poll_punch();
+            }
+            NatMessage::Punch { token } if self.puncher.on_punch(from, token) => {
+                let _ = self.send_nat(&NatMessage::PunchAck { token }, from);
+            }
+            NatMessage::PunchAck { token } => self.puncher.on_punch_ack(from, token),
+            _ => {}
+        }
+    }
+
     /// Get the mux_key if using multiplexed mode
     pub fn mux_key(&self) -> Option<u32> {
         self.mux_key
@@ -327,11 +537,36 @@ impl ClientTransport {
         self.peer_addr
     }
 
+    /// Header checksum offered in the handshake
+    pub fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
     /// Get current sequence number
     pub fn sequence(&self) -> u64 {
         self.sequence
     }
 
+    // Helper: checksum header + payload with the negotiated algorithm
+    fn seal(&self, header: &mut PacketHeader, payload: &[u8]) {
+        if self.checksum == Checksum::None {
+            header.flags |= FLAG_NO_CRC;
+        }
+        header.checksum = 0;
+        header.checksum = self
+            .checksum
+            .builder()
+            .update(&header.to_bytes())
+            .update(payload)
+            .finalize();
+    }
+
+    fn verify(&self, header: &PacketHeader, payload: &[u8]) -> bool {
+        let mut expected = *header;
+        self.seal(&mut expected, payload);
+        self.checksum == Checksum::None || expected.checksum == header.checksum
+    }
+
     // Helper: calculate mux prefix length
     #[inline]
     fn mux_prefix_len(&self) -> usize {
@@ -378,7 +613,7 @@ impl Transport for ClientTransport {
 
         loop {
             match self.socket.recv_from(&mut self.recv_buffer) {
-                Ok((len, _addr)) => {
+                Ok((len, from)) => {
                     if len < min_len {
                         continue;
                     }
@@ -391,13 +626,55 @@ impl Transport for ClientTransport {
                         let seq = { header.sequence };
 
                         match msg_type {
+                            MessageType::Data if header.flags & FLAG_RELAY != 0 => {
+                                // Relayed peer datagram: [token][opaque], no ACK
+                                let end =
+                                    (HEADER_SIZE + header.payload_len as usize).min(data.len());
+                                let payload = &data[HEADER_SIZE..end];
+                                if payload.len() < 8 || !self.verify(&header, payload) {
+                                    continue;
+                                }
+                                handler(&payload[8..]);
+                                count += 1;
+                            }
+                            MessageType::Pong if header.flags & FLAG_RELAY != 0 => {
+                                // Join result: [token][status]
+                                let end =
+                                    (HEADER_SIZE + header.payload_len as usize).min(data.len());
+                                let body = &data[HEADER_SIZE..end];
+                                if !self.verify(&header, body) {
+                                    continue;
+                                }
+                                if body.len() >= 9 && body[8] == 0 {
+                                    let token = u64::from_le_bytes(body[..8].try_into().unwrap());
+                                    self.relay_token = Some(token);
+                                }
+                            }
                             MessageType::Data => {
-                                let payload = &data[HEADER_SIZE..];
+                                let end =
+                                    (HEADER_SIZE + header.payload_len as usize).min(data.len());
+                                let payload = &data[HEADER_SIZE..end];
+                                if !self.verify(&header, payload) {
+                                    continue;
+                                }
                                 handler(payload);
                                 count += 1;
                                 // Send ACK back
                                 self.send_ack(seq);
                             }
+                            MessageType::Pong if header.flags & FLAG_MTU_PROBE != 0 => {
+                                self.mtu.on_probe_ack(seq as usize, Instant::now());
+                            }
+                            MessageType::Ping | MessageType::Pong
+                                if header.flags & FLAG_NAT != 0 =>
+                            {
+                                let len =
+                                    (header.payload_len as usize).min(data.len() - HEADER_SIZE);
+                                let payload = &data[HEADER_SIZE..HEADER_SIZE + len];
+                                if let Some(msg) = NatMessage::decode(msg_type, payload) {
+                                    self.handle_nat(from, msg);
+                                }
+                            }
                             MessageType::Ping | MessageType::Pong => {
                                 // Heartbeat (keep-alive)
                             }
@@ -413,6 +690,8 @@ impl Transport for ClientTransport {
             }
         }
 
+        self.poll_mtu();
+        self.poll_punch();
         count
     }
 }
@@ -442,4 +721,318 @@ mod tests {
         };
         assert_eq!(config.mux_key, Some(0x12345678));
     }
+
+    #[cfg(feature = "mux")]
+    struct Nop;
+
+    #[cfg(feature = "mux")]
+    impl crate::mux::MuxHandler for Nop {
+        fn on_connect(&mut self, _client: SocketAddr) {}
+        fn on_message(&mut self, _client: SocketAddr, _data: &[u8]) {}
+        fn on_disconnect(&mut self, _client: SocketAddr) {}
+    }
+
+    #[test]
+    fn test_mtu_validated() {
+        let peer: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let config = |base_mtu| ClientTransportConfig {
+            peer_addr: peer,
+            pmtud: Some(PmtudConfig {
+                base_mtu,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let err = ClientTransport::connect_with_config(config(HEADER_SIZE)).err();
+        assert_eq!(err.map(|e| e.kind()), Some(io::ErrorKind::InvalidInput));
+
+        let mut client = ClientTransport::connect_with_config(config(pmtud::BASE_MTU)).unwrap();
+        client.set_mtu_discovery(Box::new(FixedMtu(8)));
+        assert_eq!(client.max_payload(), 0);
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_pmtud_probe_against_mux_server() {
+        use crate::mux::MuxRudpServer;
+
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(7, Box::new(Nop));
+        let mut client = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        assert_eq!(client.path_mtu(), pmtud::BASE_MTU);
+
+        // Loopback carries the full Ethernet-sized probe on the first try
+        for _ in 0..50 {
+            server.poll();
+            client.receive(|_| {});
+            if client.path_mtu() == pmtud::MAX_MTU {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(client.path_mtu(), pmtud::MAX_MTU);
+        assert_eq!(
+            client.max_payload(),
+            pmtud::MAX_MTU - MUX_KEY_SIZE - HEADER_SIZE
+        );
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_whoami_and_hole_punch_via_mux_server() {
+        use crate::mux::MuxRudpServer;
+
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(7, Box::new(Nop));
+        let mut a = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        let mut b = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+
+        let mut pump = |a: &mut ClientTransport, b: &mut ClientTransport| {
+            let mut got = Vec::new();
+            for _ in 0..20 {
+                server.poll();
+                a.receive(|_| {});
+                b.receive(|d| got.push(d.to_vec()));
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            got
+        };
+
+        a.request_observed_addr().unwrap();
+        pump(&mut a, &mut b);
+        let loopback = |c: &ClientTransport| {
+            SocketAddr::new("127.0.0.1".parse().unwrap(), c.local_addr().unwrap().port())
+        };
+        assert_eq!(a.observed_addr(), Some(loopback(&a)));
+
+        a.rendezvous(0xfeed).unwrap();
+        b.rendezvous(0xfeed).unwrap();
+        pump(&mut a, &mut b);
+        assert_eq!(
+            a.punch_state(),
+            PunchState::Direct {
+                token: 0xfeed,
+                peer: loopback(&b)
+            }
+        );
+        assert!(matches!(b.punch_state(), PunchState::Direct { .. }));
+
+        a.send_peer(b"p2p").unwrap();
+        assert!(pump(&mut a, &mut b).iter().any(|d| d == b"p2p"));
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_rendezvous_table_capped_and_mismatches_counted() {
+        use crate::mux::{MuxRudpServer, RendezvousStats};
+
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(7, Box::new(Nop));
+        server.register(8, Box::new(Nop));
+        server.set_max_pending_rendezvous(1);
+        let mut a = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        let mut b = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        let mut other_game = ClientTransport::connect_mux(server.local_addr(), 8).unwrap();
+
+        let pump = |server: &mut MuxRudpServer| {
+            for _ in 0..10 {
+                server.poll();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        };
+
+        a.rendezvous(0xfeed).unwrap();
+        pump(&mut server);
+        b.rendezvous(0xbeef).unwrap();
+        other_game.rendezvous(0xfeed).unwrap();
+        pump(&mut server);
+        assert_eq!(server.pending_rendezvous(), 1);
+        assert_eq!(
+            server.rendezvous_stats(),
+            RendezvousStats {
+                paired: 0,
+                mismatched: 1,
+                rejected: 1,
+            }
+        );
+
+        // The mismatch left a's registration for its real peer
+        b.rendezvous(0xfeed).unwrap();
+        pump(&mut server);
+        assert_eq!(server.pending_rendezvous(), 0);
+        assert_eq!(server.rendezvous_stats().paired, 1);
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_relay_forwards_between_members() {
+        use crate::mux::MuxRudpServer;
+        use crate::relay::RelayConfig;
+
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(7, Box::new(Nop));
+        server.open_relay(0xabc, RelayConfig::default());
+        let mut a = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        let mut b = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        let outsider = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+
+        let mut pump = |a: &mut ClientTransport, b: &mut ClientTransport| {
+            let mut got = Vec::new();
+            for _ in 0..10 {
+                server.poll();
+                a.receive(|_| {});
+                b.receive(|d| got.push(d.to_vec()));
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            got
+        };
+
+        assert!(a.send_relay(b"early").is_err());
+        a.join_relay(0xabc).unwrap();
+        b.join_relay(0xabc).unwrap();
+        pump(&mut a, &mut b);
+        assert_eq!(a.relay_token(), Some(0xabc));
+        assert_eq!(b.relay_token(), Some(0xabc));
+
+        a.send_relay(b"via-server").unwrap();
+        assert!(pump(&mut a, &mut b).iter().any(|d| d == b"via-server"));
+
+        // Non-members can't inject into the relay
+        outsider.send_relay_to(0xabc, b"spoof").unwrap();
+        assert!(!pump(&mut a, &mut b).iter().any(|d| d == b"spoof"));
+
+        let stats = server.relay_stats(0xabc).unwrap();
+        assert_eq!(stats.packets_forwarded, 1);
+        assert!(stats.bytes_forwarded > 0);
+    }
+
+    #[test]
+    fn test_relay_packets_checksum_verified() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        let mut client = ClientTransport::connect_mux(server.local_addr().unwrap(), 7).unwrap();
+        let mut buf = [0u8; 2048];
+        let (_, client_addr) = server.recv_from(&mut buf).unwrap();
+
+        let send = |msg_type: MessageType, payload: &[u8], corrupt: bool| {
+            let mut header = PacketHeader::new(0, msg_type, payload.len());
+            header.flags = FLAG_RELAY;
+            header.calculate_checksum(payload);
+            if corrupt {
+                header.checksum ^= 1;
+            }
+            let mut packet = 7u32.to_le_bytes().to_vec();
+            packet.extend_from_slice(&header.to_bytes());
+            packet.extend_from_slice(payload);
+            server.send_to(&packet, client_addr).unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        let mut join = 0xabcu64.to_le_bytes().to_vec();
+        join.push(0);
+        let mut relayed = 0xabcu64.to_le_bytes().to_vec();
+        relayed.extend_from_slice(b"relayed");
+
+        send(MessageType::Pong, &join, true);
+        send(MessageType::Data, &relayed, true);
+        let mut got = Vec::new();
+        client.receive(|d| got.push(d.to_vec()));
+        assert_eq!(client.relay_token(), None);
+        assert!(got.is_empty());
+
+        send(MessageType::Pong, &join, false);
+        send(MessageType::Data, &relayed, false);
+        client.receive(|d| got.push(d.to_vec()));
+        assert_eq!(client.relay_token(), Some(0xabc));
+        assert_eq!(got, vec![b"relayed".to_vec()]);
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_checksum_negotiated_in_handshake() {
+        use crate::mux::{MuxHandler, MuxRudpServer};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Collect(Rc<RefCell<Vec<Vec<u8>>>>);
+        impl MuxHandler for Collect {
+            fn on_connect(&mut self, _client: SocketAddr) {}
+            fn on_message(&mut self, _client: SocketAddr, data: &[u8]) {
+                self.0.borrow_mut().push(data.to_vec());
+            }
+            fn on_disconnect(&mut self, _client: SocketAddr) {}
+        }
+
+        let got = Rc::new(RefCell::new(Vec::new()));
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(7, Box::new(Collect(got.clone())));
+        let mut client = ClientTransport::connect_with_config(ClientTransportConfig {
+            peer_addr: server.local_addr(),
+            mux_key: Some(7),
+            pmtud: None,
+            checksum: Checksum::XxHash64,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(client.checksum(), Checksum::XxHash64);
+
+        client.send(b"hashed").unwrap();
+        let mut received = Vec::new();
+        for _ in 0..10 {
+            server.poll();
+            client.receive(|d| received.push(d.to_vec()));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let addr = SocketAddr::new([127, 0, 0, 1].into(), client.local_addr().unwrap().port());
+        assert_eq!(server.client_checksum(&addr), Some(Checksum::XxHash64));
+        assert_eq!(*got.borrow(), vec![b"hashed".to_vec()]);
+
+        // Server -> client data is sealed with the negotiated algorithm too
+        server.send(&addr, b"reply").unwrap();
+        server.broadcast_to(&[addr], b"state");
+        for _ in 0..10 {
+            client.receive(|d| received.push(d.to_vec()));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(received, vec![b"reply".to_vec(), b"state".to_vec()]);
+    }
+
+    #[cfg(feature = "mux")]
+    #[test]
+    fn test_client_quality_drives_rate_adapter() {
+        use crate::mux::MuxRudpServer;
+        use crate::rate::RateAdapter;
+        use std::time::Instant;
+
+        let mut server = MuxRudpServer::bind("127.0.0.1:0").unwrap();
+        server.register(7, Box::new(Nop));
+        let mut client = ClientTransport::connect_mux(server.local_addr(), 7).unwrap();
+        for _ in 0..5 {
+            server.poll();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let addr = SocketAddr::new([127, 0, 0, 1].into(), client.local_addr().unwrap().port());
+        assert!(server
+            .client_quality(&"127.0.0.1:1".parse().unwrap())
+            .is_none());
+
+        for _ in 0..3 {
+            server.send(&addr, b"snapshot").unwrap();
+        }
+        let before = server.client_quality(&addr).unwrap();
+        assert_eq!(before.in_flight, 3);
+        for _ in 0..10 {
+            client.receive(|_| {});
+            server.poll();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let q = server.client_quality(&addr).unwrap();
+        assert!(q.in_flight < before.in_flight);
+        assert_ne!(q.rtt_us, before.rtt_us); // sampled from the ACKs
+        assert_eq!(q.loss_events, 0);
+
+        let mut adapter = RateAdapter::default();
+        assert_eq!(adapter.update(&q, Instant::now()).hz, 60);
+    }
 }
@@ -280,6 +280,39 @@ impl BitmapWindow {
         self.ring.send_batch_naks_for_gaps(&mut send_nak);
     }
 
+    /// Received ranges above the next expected sequence (inclusive, lowest
+    /// first) for SACK. Fills `out` and returns how many were written.
+    pub fn sack_blocks(&self, out: &mut [(u64, u64)]) -> usize {
+        let end = self.bitmap_base + (self.bitmap.len() as u64) * 64;
+        let mut seq = self.ring.next_expected_seq.max(self.bitmap_base);
+        let mut run = None;
+        let mut n = 0;
+        while seq < end && n < out.len() {
+            let rel = seq - self.bitmap_base;
+            let word = self.bitmap[(rel >> 6) as usize] >> (rel & 63);
+            if run.is_none() && word == 0 {
+                // Rest of this word is empty
+                seq = self.bitmap_base + ((rel >> 6) + 1) * 64;
+                continue;
+            }
+            match (word & 1 != 0, run) {
+                (true, None) => run = Some(seq),
+                (false, Some(start)) => {
+                    out[n] = (start, seq - 1);
+                    n += 1;
+                    run = None;
+                }
+                _ => {}
+            }
+            seq += 1;
+        }
+        if let Some(start) = run {
+            out[n] = (start, end - 1);
+            n += 1;
+        }
+        n
+    }
+
     /// Returns the highest sequence number that has been delivered in-order.
     /// This can be used to send ACKs to the sender.
     pub fn last_delivered_seq(&self) -> u64 {
@@ -292,6 +325,7 @@ impl BitmapWindow {
 
     /// Advance the expected sequence number to skip non-data packets (like handshakes).
     /// Used when we receive a handshake packet and need to start expecting data packets.
+    #[cfg(feature = "mux")]
     pub fn advance_expected(&mut self, new_expected: u64) {
         if new_expected > self.ring.next_expected_seq {
             self.ring.next_expected_seq = new_expected;
@@ -440,6 +474,23 @@ mod tests {
         assert_eq!(delivered2, vec![4, 5, 6, 7]);
     }
 
+    #[test]
+    fn bitmap_sack_blocks() {
+        let mut window = BitmapWindow::new(256, 0);
+        for seq in [0, 1, 3, 4, 5, 8, 70, 71] {
+            window.insert(seq, b"x");
+        }
+        window.deliver_in_order_with(|_| {});
+
+        let mut blocks = [(0, 0); 4];
+        let n = window.sack_blocks(&mut blocks);
+        assert_eq!(&blocks[..n], &[(3, 5), (8, 8), (70, 71)]);
+
+        let mut first = [(0, 0); 1];
+        assert_eq!(window.sack_blocks(&mut first), 1);
+        assert_eq!(first[0], (3, 5));
+    }
+
     #[test]
     fn bitmap_bounded_future_packets() {
         let mut win = BitmapWindow::new(4, 0);
@@ -2,7 +2,7 @@
 //!
 //! Long-running stress tests for kaos-rudp.
 
-use kaos_test_support::stress::{print_summary, StressConfig, StressCounters, StressRunner};
+use kaos_test_support::stress::{print_summary, StressConfig, StressRunner};
 use kaos_test_support::verify::SequenceChecker;
 use std::net::UdpSocket;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -18,7 +18,7 @@ fn test_udp_baseline_stress() {
     let config = StressConfig::new(5).with_batch_size(100);
 
     let runner = StressRunner::new(config.clone());
-    let counters = runner.counters();
+    let _counters = runner.counters();
 
     let sender_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
     let receiver_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
@@ -42,7 +42,7 @@ fn test_udp_baseline_stress() {
                 }
 
                 // Rate limit to avoid overwhelming
-                if seq % 10000 == 0 {
+                if seq.is_multiple_of(10000) {
                     thread::yield_now();
                 }
             }
@@ -104,7 +104,7 @@ fn test_udp_sequence_stress() {
             let _ = sender_socket.send_to(&msg, receiver_addr);
             seq += 1;
 
-            if seq % 1000 == 0 {
+            if seq.is_multiple_of(1000) {
                 thread::yield_now();
             }
         }
@@ -14,7 +14,7 @@ use kaos::disruptor::{
     ConsumerBuilder, EventHandler, MessageRingBuffer, MessageSlot, ProducerBuilder, RingBuffer,
     RingBufferConfig, RingBufferEntry, Slot8,
 };
-use kaos::{consume_batch, publish_batch, publish_unrolled};
+use kaos::{consume_batch, publish_unrolled};
 
 const RING_SIZE: usize = 1024 * 1024;
 const BATCH_SIZE: usize = 8192;
@@ -0,0 +1,55 @@
+//! Checksum algorithm benchmarks
+//!
+//! Compares the `Checksum` strategies on packet- and archive-sized payloads:
+//! - CRC32 (crc32fast)
+//! - CRC32C (SSE4.2 / ARMv8 when available)
+//! - xxHash64
+//!
+//! Run: cargo bench --bench bench_checksum
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+use kaos::checksum::Checksum;
+
+/// Small game packet, Ethernet MTU payload, large archive frame
+const SIZES: [usize; 3] = [64, 1400, 64 * 1024];
+
+fn bench_checksums(c: &mut Criterion) {
+    let mut group = c.benchmark_group("checksum");
+    for size in SIZES {
+        let data: Vec<u8> = (0..size).map(|i| (i * 31 + 7) as u8).collect();
+        group.throughput(Throughput::Bytes(size as u64));
+        for kind in [Checksum::Crc32, Checksum::Crc32c, Checksum::XxHash64] {
+            let name = format!("{:?}{}", kind, if kind.hardware() { " (hw)" } else { "" });
+            group.bench_with_input(BenchmarkId::new(name, size), &data, |b, data| {
+                b.iter(|| black_box(kind.compute(black_box(data))))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Header + payload as two parts, the way packet headers are checksummed
+fn bench_header_and_payload(c: &mut Criterion) {
+    let header = [0u8; 24];
+    let payload = vec![0xabu8; 1400];
+    let mut group = c.benchmark_group("checksum_parts");
+    group.throughput(Throughput::Bytes((header.len() + payload.len()) as u64));
+    for kind in [Checksum::Crc32, Checksum::Crc32c, Checksum::XxHash64] {
+        group.bench_function(format!("{:?}", kind), |b| {
+            b.iter(|| {
+                black_box(
+                    kind.builder()
+                        .update(black_box(&header))
+                        .update(black_box(&payload))
+                        .finalize(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_checksums, bench_header_and_payload);
+criterion_main!(benches);
@@ -1,16 +1,12 @@
 //! Minimal test to verify criterion works
 
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use std::hint::black_box;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread;
 
-use kaos::disruptor::{
-    ConsumerBuilder, EventHandler, MessageRingBuffer, MessageSlot, ProducerBuilder, RingBuffer,
-    RingBufferConfig, RingBufferEntry, Slot8,
-};
-use kaos::{consume_batch, publish_batch, publish_unrolled};
+use kaos::disruptor::{RingBuffer, Slot8};
 
 const RING_SIZE: usize = 1024 * 1024;
 const BATCH_SIZE: usize = 8192;
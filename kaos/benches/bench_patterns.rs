@@ -260,6 +260,11 @@ fn benchmark_patterns(c: &mut Criterion) {
         b.iter(|| bench_mpsc_fast(TOTAL_EVENTS))
     });
 
+    group.throughput(Throughput::Elements(SINGLE_EVENTS));
+    group.bench_function(BenchmarkId::new("pattern", "MPMC-2P1C"), |b| {
+        b.iter(|| bench_mpmc_2p1c(SINGLE_EVENTS))
+    });
+
     group.finish();
 }
 
@@ -111,9 +111,8 @@ fn main() {
     }
 
     // Wait for producers to finish
-    let mut total_produced = 0;
     for handle in producer_threads {
-        total_produced += handle.join().unwrap();
+        handle.join().unwrap();
     }
 
     // Send sentinels (one per consumer)
@@ -132,9 +131,8 @@ fn main() {
     }
 
     // Wait for consumers to finish
-    let mut total_consumed = 0;
     for handle in consumer_threads {
-        total_consumed += handle.join().unwrap();
+        handle.join().unwrap();
     }
 
     let duration = start.elapsed();
@@ -123,9 +123,8 @@ fn main() {
 
     let sent = producer_thread.join().unwrap();
 
-    let mut total_consumed = 0;
     for handle in consumer_threads {
-        total_consumed += handle.join().unwrap();
+        handle.join().unwrap();
     }
 
     let duration = start.elapsed();
@@ -27,6 +27,7 @@ fn main() {
     let config = RingBufferConfig {
         size: RING_SIZE,
         num_consumers: 1,
+        ..Default::default()
     };
 
     let ring_buffer = Arc::new(MessageRingBuffer::new(config).unwrap());
@@ -36,7 +37,7 @@ fn main() {
     // Producer: Send numbers 1 to MAX_NUMBER using publish_unrolled! macro
     let ring_buffer_clone = ring_buffer.clone();
     let producer_thread = thread::spawn(move || {
-        let mut producer = ProducerBuilder::new()
+        let producer = ProducerBuilder::new()
             .with_ring_buffer(ring_buffer_clone)
             .build()
             .unwrap();
@@ -7,7 +7,6 @@ use kaos::disruptor::{
     ConsumerBuilder, EventHandler, MessageRingBuffer, MessageSlot, ProducerBuilder,
     RingBufferConfig,
 };
-use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
@@ -27,10 +26,10 @@ fn main() {
     let config = RingBufferConfig {
         size: RING_SIZE,
         num_consumers: 1,
+        ..Default::default()
     };
 
     let ring_buffer = Arc::new(MessageRingBuffer::new(config).unwrap());
-    let stop = Arc::new(AtomicBool::new(false));
 
     // Producer with clean API
     let mut producer = ProducerBuilder::new()
@@ -45,7 +44,6 @@ fn main() {
         .build()
         .unwrap();
 
-    let stop_clone = stop.clone();
     let start = Instant::now();
 
     // Producer thread
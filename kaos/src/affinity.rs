@@ -4,6 +4,17 @@
 //! use kaos::affinity::pin_to_core;
 //! pin_to_core(0).unwrap(); // Pin to core 0
 //! ```
+//!
+//! Co-locating a ring with its producer and consumer:
+//! ```rust,ignore
+//! use kaos::affinity::{numa_core_pair, pin_to_core};
+//! use kaos::disruptor::{RingBuffer, RingBufferConfig, Slot8};
+//!
+//! let config = RingBufferConfig::new(1 << 16)?.with_numa_node(1)?;
+//! let ring = RingBuffer::<Slot8>::from_config(config)?; // pages on node 1
+//! let (producer_core, consumer_core) = numa_core_pair(1)?;
+//! // pin_to_core(producer_core) in the producer thread, consumer_core in the consumer
+//! ```
 
 use std::io;
 
@@ -24,15 +35,76 @@ pub fn pin_to_core(core_id: usize) -> io::Result<()> {
     Ok(())
 }
 
-/// Pin current thread to all cores in a NUMA node.
+/// CPUs belonging to a NUMA node.
 #[cfg(target_os = "linux")]
-pub fn pin_to_numa_node(node: usize) -> io::Result<()> {
+pub fn numa_node_cpus(node: usize) -> io::Result<Vec<usize>> {
     // Read cpus for this node from sysfs
     let path = format!("/sys/devices/system/node/node{}/cpulist", node);
     let cpulist = std::fs::read_to_string(&path)
         .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "NUMA node not found"))?;
+    parse_cpulist(&cpulist)
+}
+
+/// Two cores on `node` for a producer/consumer pair (the same core twice
+/// if the node only has one).
+pub fn numa_core_pair(node: usize) -> io::Result<(usize, usize)> {
+    let cores = numa_node_cpus(node)?;
+    match cores.as_slice() {
+        [] => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "No CPUs in node",
+        )),
+        [only] => Ok((*only, *only)),
+        [first, second, ..] => Ok((*first, *second)),
+    }
+}
+
+/// Bind the pages covering `[addr, addr + len)` to a NUMA node.
+///
+/// Pages not yet touched are allocated on `node`; already-resident pages
+/// are migrated. `addr` is rounded down to a page boundary. Kernels built
+/// without NUMA support (ENOSYS) are treated as single-node: no-op.
+#[cfg(target_os = "linux")]
+pub(crate) fn bind_to_numa_node(addr: *const u8, len: usize, node: usize) -> io::Result<()> {
+    const MPOL_BIND: libc::c_long = 2;
+    const MPOL_MF_MOVE: libc::c_ulong = 1 << 1;
+
+    if len == 0 {
+        return Ok(());
+    }
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (addr as usize) & !(page - 1);
+    let len = (addr as usize + len) - start;
+
+    let bits = 8 * std::mem::size_of::<libc::c_ulong>();
+    let mut nodemask = vec![0 as libc::c_ulong; node / bits + 1];
+    nodemask[node / bits] |= 1 << (node % bits);
 
-    let cores = parse_cpulist(&cpulist)?;
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            start,
+            len,
+            MPOL_BIND,
+            nodemask.as_ptr(),
+            // maxnode: the kernel reads maxnode - 1 bits
+            (nodemask.len() * bits + 1) as libc::c_ulong,
+            MPOL_MF_MOVE,
+        )
+    };
+    if ret != 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ENOSYS) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Pin current thread to all cores in a NUMA node.
+#[cfg(target_os = "linux")]
+pub fn pin_to_numa_node(node: usize) -> io::Result<()> {
+    let cores = numa_node_cpus(node)?;
     if cores.is_empty() {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
@@ -138,6 +210,16 @@ pub fn pin_to_numa_node(_node: usize) -> io::Result<()> {
     Err(io::Error::new(io::ErrorKind::Unsupported, "Linux only"))
 }
 
+#[cfg(not(target_os = "linux"))]
+pub fn numa_node_cpus(_node: usize) -> io::Result<Vec<usize>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "Linux only"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn bind_to_numa_node(_addr: *const u8, _len: usize, _node: usize) -> io::Result<()> {
+    Ok(())
+}
+
 #[cfg(not(target_os = "linux"))]
 pub fn current_numa_node() -> io::Result<usize> {
     Ok(0)
@@ -172,6 +254,16 @@ mod tests {
         let _ = numa_node_count();
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_numa_core_pair() {
+        let node = current_numa_node().unwrap();
+        let (producer, consumer) = numa_core_pair(node).unwrap();
+        let cpus = numa_node_cpus(node).unwrap();
+        assert!(cpus.contains(&producer) && cpus.contains(&consumer));
+        assert!(numa_core_pair(4096).is_err());
+    }
+
     #[test]
     #[cfg(target_os = "linux")]
     fn test_current_node() {
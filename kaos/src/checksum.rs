@@ -0,0 +1,169 @@
+//! Runtime-selectable frame checksums.
+//!
+//! Every algorithm produces a `u32` so it fits the existing header and frame
+//! fields; xxHash64 is folded (`hi ^ lo`). `Checksum::None` always yields 0,
+//! which receivers already treat as "not checked".
+//!
+//! ```rust
+//! use kaos::checksum::Checksum;
+//!
+//! let kind = Checksum::from_id(Checksum::XxHash64.id()).unwrap();
+//! let crc = kind.builder().update(b"hdr").update(b"payload").finalize();
+//! assert_eq!(crc, kind.compute(b"hdrpayload"));
+//! ```
+
+use crate::crc32::{crc32c_hardware, Crc32Algorithm, Crc32Builder};
+use crate::xxhash::XxHash64;
+
+/// Checksum algorithm for packet headers and archive frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Checksum {
+    /// IEEE CRC32 (`crc32_simd`), the wire and on-disk default
+    #[default]
+    Crc32,
+    /// CRC32C, on SSE4.2 / ARMv8 CRC instructions when available
+    Crc32c,
+    /// xxHash64 folded to 32 bits (portable, no CPU features needed)
+    XxHash64,
+    /// No checksum (trust the link / filesystem)
+    None,
+}
+
+impl Checksum {
+    pub const ALL: [Checksum; 4] = [
+        Checksum::Crc32,
+        Checksum::Crc32c,
+        Checksum::XxHash64,
+        Checksum::None,
+    ];
+
+    /// Stable id for headers and handshakes (`Crc32` is 0, so zeroed fields
+    /// written before the id existed read back as CRC32)
+    pub fn id(self) -> u8 {
+        match self {
+            Checksum::Crc32 => 0,
+            Checksum::Crc32c => 1,
+            Checksum::XxHash64 => 2,
+            Checksum::None => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    /// Whether this algorithm runs on dedicated CPU instructions here
+    pub fn hardware(self) -> bool {
+        self == Checksum::Crc32c && crc32c_hardware()
+    }
+
+    pub fn compute(self, data: &[u8]) -> u32 {
+        self.builder().update(data).finalize()
+    }
+
+    pub fn builder(self) -> ChecksumBuilder {
+        let state = match self {
+            Checksum::Crc32 => State::Crc(Crc32Builder::new(Crc32Algorithm::Ieee)),
+            Checksum::Crc32c => State::Crc(Crc32Builder::new(Crc32Algorithm::Castagnoli)),
+            Checksum::XxHash64 => State::XxHash(XxHash64::new(0)),
+            Checksum::None => State::None,
+        };
+        ChecksumBuilder { state }
+    }
+}
+
+#[derive(Clone)]
+enum State {
+    Crc(Crc32Builder),
+    XxHash(XxHash64),
+    None,
+}
+
+/// Streaming checksum over scatter/gather buffers.
+#[derive(Clone)]
+pub struct ChecksumBuilder {
+    state: State,
+}
+
+impl ChecksumBuilder {
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        match &mut self.state {
+            State::Crc(builder) => {
+                builder.update(data);
+            }
+            State::XxHash(hasher) => {
+                hasher.update(data);
+            }
+            State::None => {}
+        }
+        self
+    }
+
+    /// Feed each buffer in order
+    pub fn update_all<'a, I>(&mut self, parts: I) -> &mut Self
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        for part in parts {
+            self.update(part);
+        }
+        self
+    }
+
+    /// Checksum of everything fed so far (the builder can keep going)
+    pub fn finalize(&self) -> u32 {
+        match &self.state {
+            State::Crc(builder) => builder.finalize(),
+            State::XxHash(hasher) => {
+                let h = hasher.finish();
+                (h ^ (h >> 32)) as u32
+            }
+            State::None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc32::{crc32_simd, crc32c};
+    use crate::xxhash::xxhash64;
+
+    #[test]
+    fn test_algorithms_match_primitives() {
+        let data = b"checksum strategy over a payload";
+        assert_eq!(Checksum::Crc32.compute(data), crc32_simd(data));
+        assert_eq!(Checksum::Crc32c.compute(data), crc32c(data));
+        let h = xxhash64(data, 0);
+        assert_eq!(Checksum::XxHash64.compute(data), (h ^ (h >> 32)) as u32);
+        assert_eq!(Checksum::None.compute(data), 0);
+        assert_eq!(Checksum::default(), Checksum::Crc32);
+    }
+
+    #[test]
+    fn test_ids_round_trip() {
+        for kind in Checksum::ALL {
+            assert_eq!(Checksum::from_id(kind.id()), Some(kind));
+        }
+        assert_eq!(Checksum::from_id(0), Some(Checksum::Crc32));
+        assert_eq!(Checksum::from_id(200), None);
+    }
+
+    #[test]
+    fn test_builder_over_parts() {
+        let parts: [&[u8]; 3] = [
+            b"header bytes ",
+            b"",
+            b"and a longer payload than one stripe",
+        ];
+        let whole = parts.concat();
+        for kind in Checksum::ALL {
+            assert_eq!(
+                kind.builder().update_all(parts).finalize(),
+                kind.compute(&whole),
+                "{:?}",
+                kind
+            );
+        }
+    }
+}
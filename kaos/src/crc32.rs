@@ -1,3 +1,21 @@
+//! CRC32 checksums.
+//!
+//! - `crc32_simd` - IEEE (Ethernet) polynomial via `crc32fast`
+//! - `crc32c` - Castagnoli polynomial, SSE4.2 / ARMv8 CRC instructions when
+//!   the CPU has them (detected at runtime), table-driven otherwise
+//! - `Crc32Builder` - either polynomial over several buffers, no concatenation
+//!
+//! ```rust
+//! use kaos::crc32::{crc32c, Crc32Algorithm, Crc32Builder};
+//!
+//! let (header, payload) = (b"hdr".as_slice(), b"payload".as_slice());
+//! let crc = Crc32Builder::new(Crc32Algorithm::Castagnoli)
+//!     .update(header)
+//!     .update(payload)
+//!     .finalize();
+//! assert_eq!(crc, crc32c(b"hdrpayload"));
+//! ```
+
 use crc32fast::Hasher;
 
 /// Cross-platform, hardware-accelerated CRC32 (Ethernet polynomial)
@@ -14,6 +32,174 @@ pub fn crc32_incremental(initial_crc: u32, data: &[u8]) -> u32 {
     hasher.finalize()
 }
 
+/// Castagnoli polynomial (reflected)
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32C_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC32C (Castagnoli), hardware-accelerated where available
+pub fn crc32c(data: &[u8]) -> u32 {
+    crc32c_incremental(0, data)
+}
+
+/// Incremental CRC32C (continue from previous CRC value)
+pub fn crc32c_incremental(initial_crc: u32, data: &[u8]) -> u32 {
+    !crc32c_update(!initial_crc, data)
+}
+
+/// Whether `crc32c` runs on CRC instructions on this CPU
+pub fn crc32c_hardware() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    return std::arch::is_x86_feature_detected!("sse4.2");
+    #[cfg(target_arch = "aarch64")]
+    return std::arch::is_aarch64_feature_detected!("crc");
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    false
+}
+
+/// Raw state update (no pre/post inversion)
+fn crc32c_update(state: u32, data: &[u8]) -> u32 {
+    if crc32c_hardware() {
+        // SAFETY: the required CPU feature was just detected
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        return unsafe { crc32c_hw(state, data) };
+    }
+    crc32c_sw(state, data)
+}
+
+fn crc32c_sw(mut state: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        state = CRC32C_TABLE[((state ^ byte as u32) & 0xff) as usize] ^ (state >> 8);
+    }
+    state
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+unsafe fn crc32c_hw(state: u32, data: &[u8]) -> u32 {
+    use std::arch::x86_64::{_mm_crc32_u64, _mm_crc32_u8};
+    let mut crc = state as u64;
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        crc = _mm_crc32_u64(crc, u64::from_le_bytes(word.try_into().unwrap()));
+    }
+    let mut crc = crc as u32;
+    for &byte in words.remainder() {
+        crc = _mm_crc32_u8(crc, byte);
+    }
+    crc
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "crc")]
+unsafe fn crc32c_hw(state: u32, data: &[u8]) -> u32 {
+    use std::arch::aarch64::{__crc32cb, __crc32cd};
+    let mut crc = state;
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        crc = __crc32cd(crc, u64::from_le_bytes(word.try_into().unwrap()));
+    }
+    for &byte in words.remainder() {
+        crc = __crc32cb(crc, byte);
+    }
+    crc
+}
+
+/// Polynomial for `Crc32Builder`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Crc32Algorithm {
+    /// IEEE 802.3 (`crc32_simd`)
+    #[default]
+    Ieee,
+    /// Castagnoli (`crc32c`)
+    Castagnoli,
+}
+
+#[derive(Clone)]
+enum BuilderState {
+    Ieee(Hasher),
+    Castagnoli(u32),
+}
+
+/// Streaming CRC32 over scatter/gather buffers.
+#[derive(Clone)]
+pub struct Crc32Builder {
+    state: BuilderState,
+}
+
+impl Default for Crc32Builder {
+    fn default() -> Self {
+        Self::new(Crc32Algorithm::default())
+    }
+}
+
+impl Crc32Builder {
+    pub fn new(algorithm: Crc32Algorithm) -> Self {
+        Self::with_initial(algorithm, 0)
+    }
+
+    /// Continue from a previously finalized CRC
+    pub fn with_initial(algorithm: Crc32Algorithm, initial_crc: u32) -> Self {
+        let state = match algorithm {
+            Crc32Algorithm::Ieee => BuilderState::Ieee(Hasher::new_with_initial(initial_crc)),
+            Crc32Algorithm::Castagnoli => BuilderState::Castagnoli(!initial_crc),
+        };
+        Self { state }
+    }
+
+    pub fn algorithm(&self) -> Crc32Algorithm {
+        match self.state {
+            BuilderState::Ieee(_) => Crc32Algorithm::Ieee,
+            BuilderState::Castagnoli(_) => Crc32Algorithm::Castagnoli,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        match &mut self.state {
+            BuilderState::Ieee(hasher) => hasher.update(data),
+            BuilderState::Castagnoli(state) => *state = crc32c_update(*state, data),
+        }
+        self
+    }
+
+    /// Feed each buffer in order (e.g. the `IoSlice`s of a vectored write)
+    pub fn update_all<'a, I>(&mut self, parts: I) -> &mut Self
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        for part in parts {
+            self.update(part);
+        }
+        self
+    }
+
+    /// CRC of everything fed so far (the builder can keep going)
+    pub fn finalize(&self) -> u32 {
+        match &self.state {
+            BuilderState::Ieee(hasher) => hasher.clone().finalize(),
+            BuilderState::Castagnoli(state) => !state,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +237,45 @@ mod tests {
         assert_ne!(crc, 0);
     }
 
+    #[test]
+    fn test_crc32c_check_value() {
+        // Standard check value for CRC-32C
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(&[]), 0);
+        assert_eq!(
+            crc32c_incremental(crc32c(b"1234"), b"56789"),
+            crc32c(b"123456789")
+        );
+    }
+
+    #[test]
+    fn test_crc32c_hardware_matches_table() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 + 7) as u8).collect();
+        for len in [0, 1, 7, 8, 9, 63, 64, 65, 1000] {
+            let sw = !crc32c_sw(!0, &data[..len]);
+            assert_eq!(crc32c(&data[..len]), sw, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_builder_over_parts() {
+        let parts: [&[u8]; 3] = [b"scatter ", b"", b"gather buffers"];
+        let whole = b"scatter gather buffers";
+        for (algorithm, expected) in [
+            (Crc32Algorithm::Ieee, crc32_simd(whole)),
+            (Crc32Algorithm::Castagnoli, crc32c(whole)),
+        ] {
+            let mut builder = Crc32Builder::new(algorithm);
+            assert_eq!(builder.update_all(parts).finalize(), expected);
+            assert_eq!(builder.algorithm(), algorithm);
+
+            let resumed = Crc32Builder::with_initial(algorithm, builder.finalize())
+                .update(b"!")
+                .finalize();
+            assert_eq!(resumed, builder.update(b"!").finalize());
+        }
+    }
+
     #[test]
     fn test_crc32_compatibility_with_crc32fast() {
         let data = b"Hello, World!";
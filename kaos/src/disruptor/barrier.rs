@@ -0,0 +1,282 @@
+//! Sequence barriers and multi-stage pipelines (LMAX style).
+//!
+//! A stage only sees events its upstream stages have finished with, and the
+//! producer only reuses slots once every final stage is past them:
+//!
+//! ```rust
+//! use kaos::disruptor::{DependencyGraph, RingBuffer, Slot8};
+//! use std::sync::Arc;
+//!
+//! let ring = Arc::new(RingBuffer::<Slot8>::new(1024).unwrap());
+//! let mut graph = DependencyGraph::new(ring.clone());
+//! let decode = graph.stage(&[]);
+//! let validate = graph.stage(&[decode]);
+//! let _dispatch = graph.stage(&[validate]);
+//! let stages = graph.build();
+//!
+//! ring.try_publish_with(0, |slot| slot.value = 7).unwrap();
+//! assert_eq!(stages[2].process(|_, _, _| {}), 0); // validate hasn't run yet
+//! stages[0].process(|_, _, _| {});
+//! stages[1].process(|_, _, _| {});
+//! assert_eq!(stages[2].process(|e, _, _| assert_eq!(e.value, 7)), 1);
+//! ```
+
+use crate::disruptor::{BusySpin, RingBuffer, RingBufferEntry, WaitStrategy};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Gates a consumer on the producer cursor and on upstream consumer cursors.
+///
+/// Cursors are exclusive ends: everything below the smallest one is readable.
+pub struct SequenceBarrier {
+    cursor: Arc<AtomicU64>,
+    dependencies: Vec<Arc<AtomicU64>>,
+    wait: Arc<dyn WaitStrategy>,
+}
+
+impl SequenceBarrier {
+    /// `cursor` is the producer cursor; `dependencies` are upstream consumers
+    pub fn new(cursor: Arc<AtomicU64>, dependencies: Vec<Arc<AtomicU64>>) -> Self {
+        Self {
+            cursor,
+            dependencies,
+            wait: Arc::new(BusySpin),
+        }
+    }
+
+    /// What `wait_for` does while nothing is available
+    pub fn with_wait_strategy(mut self, wait: Arc<dyn WaitStrategy>) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// Exclusive end of the sequences this consumer may read
+    #[inline]
+    pub fn available(&self) -> u64 {
+        self.dependencies
+            .iter()
+            .map(|d| d.load(Ordering::Acquire))
+            .fold(self.cursor.load(Ordering::Acquire), u64::min)
+    }
+
+    /// Wait until `sequence` is readable; returns `available()`
+    pub fn wait_for(&self, sequence: u64) -> u64 {
+        let mut idle = 0u32;
+        loop {
+            let available = self.available();
+            if available > sequence {
+                return available;
+            }
+            self.wait.idle(idle);
+            idle = idle.saturating_add(1);
+        }
+    }
+}
+
+/// Handle to a stage while building a `DependencyGraph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageId(usize);
+
+/// Builds a set of consumer stages over one `RingBuffer`.
+pub struct DependencyGraph<T: RingBufferEntry> {
+    ring: Arc<RingBuffer<T>>,
+    upstream: Vec<Vec<usize>>,
+}
+
+impl<T: RingBufferEntry> DependencyGraph<T> {
+    pub fn new(ring: Arc<RingBuffer<T>>) -> Self {
+        Self {
+            ring,
+            upstream: Vec::new(),
+        }
+    }
+
+    /// Add a stage that runs after `after` (empty: straight off the producer)
+    pub fn stage(&mut self, after: &[StageId]) -> StageId {
+        assert!(
+            after.iter().all(|s| s.0 < self.upstream.len()),
+            "stage depends on a stage from another graph"
+        );
+        self.upstream.push(after.iter().map(|s| s.0).collect());
+        StageId(self.upstream.len() - 1)
+    }
+
+    /// Stages in the order they were added. Final stages (no downstream)
+    /// together gate the producer.
+    pub fn build(self) -> Vec<Stage<T>> {
+        let start = self.ring.consumer_cursor().load(Ordering::Acquire);
+        let cursors: Vec<Arc<AtomicU64>> = self
+            .upstream
+            .iter()
+            .map(|_| Arc::new(AtomicU64::new(start)))
+            .collect();
+        let finals: Vec<Arc<AtomicU64>> = (0..cursors.len())
+            .filter(|i| !self.upstream.iter().any(|up| up.contains(i)))
+            .map(|i| cursors[i].clone())
+            .collect();
+        let wait = self.ring.wait_strategy();
+
+        self.upstream
+            .iter()
+            .enumerate()
+            .map(|(i, up)| {
+                let barrier = SequenceBarrier::new(
+                    self.ring.producer_cursor(),
+                    up.iter().map(|&u| cursors[u].clone()).collect(),
+                )
+                .with_wait_strategy(wait.clone());
+                let is_final = finals.iter().any(|f| Arc::ptr_eq(f, &cursors[i]));
+                Stage {
+                    ring: self.ring.clone(),
+                    barrier,
+                    cursor: cursors[i].clone(),
+                    finals: if is_final { finals.clone() } else { Vec::new() },
+                    wait: wait.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// One consumer stage of a `DependencyGraph` (move it to its own thread).
+pub struct Stage<T: RingBufferEntry> {
+    ring: Arc<RingBuffer<T>>,
+    barrier: SequenceBarrier,
+    cursor: Arc<AtomicU64>,
+    /// Cursors of all final stages (empty unless this stage is final)
+    finals: Vec<Arc<AtomicU64>>,
+    wait: Arc<dyn WaitStrategy>,
+}
+
+impl<T: RingBufferEntry> Stage<T> {
+    /// Next sequence this stage will process
+    pub fn cursor(&self) -> u64 {
+        self.cursor.load(Ordering::Acquire)
+    }
+
+    pub fn barrier(&self) -> &SequenceBarrier {
+        &self.barrier
+    }
+
+    /// Handle every available event with `(event, sequence, end_of_batch)`.
+    /// Returns the number handled (0 if upstream has nothing new).
+    pub fn process<F: FnMut(&T, u64, bool)>(&self, mut handler: F) -> usize {
+        let start = self.cursor.load(Ordering::Relaxed);
+        let end = self.barrier.available();
+        if end <= start {
+            return 0;
+        }
+        let mut seq = start;
+        while seq < end {
+            let batch = self.ring.get_read_batch(seq, (end - seq) as usize);
+            for event in batch {
+                handler(event, seq, seq + 1 == end);
+                seq += 1;
+            }
+        }
+        self.publish(end);
+        (end - start) as usize
+    }
+
+    /// Block (per the ring's wait strategy) until events are available, then `process`
+    pub fn wait_and_process<F: FnMut(&T, u64, bool)>(&self, handler: F) -> usize {
+        self.barrier.wait_for(self.cursor.load(Ordering::Relaxed));
+        self.process(handler)
+    }
+
+    fn publish(&self, end: u64) {
+        self.cursor.store(end, Ordering::Release);
+        if !self.finals.is_empty() {
+            // Slowest final stage frees slots for the producer
+            let min = self
+                .finals
+                .iter()
+                .map(|f| f.load(Ordering::Acquire))
+                .min()
+                .unwrap_or(end);
+            self.ring
+                .consumer_cursor()
+                .fetch_max(min, Ordering::Release);
+        }
+        // Wake downstream stages parked on this cursor
+        self.wait.signal();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disruptor::Slot8;
+
+    #[test]
+    fn test_barrier_takes_min_of_cursors() {
+        let producer = Arc::new(AtomicU64::new(10));
+        let upstream = Arc::new(AtomicU64::new(4));
+        let barrier = SequenceBarrier::new(producer.clone(), vec![upstream.clone()]);
+        assert_eq!(barrier.available(), 4);
+        upstream.store(12, Ordering::Release);
+        assert_eq!(barrier.available(), 10);
+        assert_eq!(barrier.wait_for(9), 10);
+    }
+
+    #[test]
+    fn test_diamond_gates_producer_on_slowest_final() {
+        // a -> (b, c): both b and c must pass a slot before it's reused
+        let ring = Arc::new(RingBuffer::<Slot8>::new(8).unwrap());
+        let mut graph = DependencyGraph::new(ring.clone());
+        let a = graph.stage(&[]);
+        graph.stage(&[a]);
+        graph.stage(&[a]);
+        let stages = graph.build();
+
+        for i in 0..4 {
+            ring.try_publish_with(i, |s| s.value = i).unwrap();
+        }
+        assert_eq!(stages[1].process(|_, _, _| {}), 0);
+        let mut ends = Vec::new();
+        assert_eq!(stages[0].process(|_, seq, end| ends.push((seq, end))), 4);
+        assert_eq!(ends.last(), Some(&(3, true)));
+        assert_eq!(ring.consumer_cursor().load(Ordering::Acquire), 0);
+
+        assert_eq!(stages[1].process(|_, _, _| {}), 4);
+        assert_eq!(ring.consumer_cursor().load(Ordering::Acquire), 0);
+        let mut sum = 0;
+        assert_eq!(stages[2].process(|e, _, _| sum += e.value), 4);
+        assert_eq!(sum, 6);
+        assert_eq!(ring.consumer_cursor().load(Ordering::Acquire), 4);
+    }
+
+    #[test]
+    fn test_pipeline_threads() {
+        const N: u64 = 10_000;
+        let ring = Arc::new(RingBuffer::<Slot8>::new(64).unwrap());
+        let mut graph = DependencyGraph::new(ring.clone());
+        let first = graph.stage(&[]);
+        graph.stage(&[first]);
+        let mut stages = graph.build().into_iter();
+        let (first, second) = (stages.next().unwrap(), stages.next().unwrap());
+
+        let upstream = std::thread::spawn(move || {
+            let mut seen = 0;
+            while seen < N {
+                seen += first.wait_and_process(|_, _, _| {}) as u64;
+            }
+        });
+        let downstream = std::thread::spawn(move || {
+            let (mut seen, mut sum) = (0, 0);
+            while seen < N {
+                seen += second.wait_and_process(|e, _, _| sum += e.value) as u64;
+            }
+            sum
+        });
+
+        let mut seq = 0;
+        while seq < N {
+            if ring.try_publish_with(seq, |s| s.value = seq).is_some() {
+                seq += 1;
+            }
+        }
+        upstream.join().unwrap();
+        assert_eq!(downstream.join().unwrap(), N * (N - 1) / 2);
+    }
+}
@@ -1,8 +1,15 @@
 //! Completion tracking for multi-consumer ring buffers.
+//!
+//! `completed_cursor` only moves past a slot once its claimer completes it,
+//! so one slow consumer holds back the producer. Consumers that claim with
+//! an id (`try_claim_as` / `complete_as`) are accounted per consumer, can be
+//! found via `holding_back`, and under a `StarvationPolicy` lose a claim held
+//! past the timeout to the next consumer that asks for work.
 
 use super::RingBufferEntry;
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 /// Maximum slots for completion tracking (64K = 2^16, allows efficient masking)
 const MAX_SLOTS: usize = 65536;
@@ -20,10 +27,73 @@ impl PaddedAtomicU64 {
     }
 }
 
+/// Largest batch one tagged claim can hold (count is packed into 16 bits)
+const MAX_TAGGED_BATCH: usize = u16::MAX as usize;
+
+/// `ConsumerSlot::claim` when the consumer holds nothing
+const NO_CLAIM: u64 = u64::MAX;
+
+#[inline]
+fn pack_claim(start: u64, count: usize) -> u64 {
+    (start << 16) | count as u64
+}
+
+#[inline]
+fn unpack_claim(claim: u64) -> (u64, usize) {
+    (claim >> 16, (claim & 0xffff) as usize)
+}
+
+/// Reassign a claim once its holder has sat on it for `timeout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StarvationPolicy {
+    pub timeout: Duration,
+}
+
+/// Per-consumer claim accounting (see `CompletionTracker::consumer_stats`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConsumerClaimStats {
+    /// Claims taken, including ones reassigned to this consumer
+    pub claims: u64,
+    /// Claims completed by this consumer
+    pub completed: u64,
+    /// Claims taken away from this consumer under the starvation policy
+    pub reassigned: u64,
+    /// `(start, count)` currently held
+    pub outstanding: Option<(u64, usize)>,
+    /// How long the outstanding claim has been held
+    pub held_for: Option<Duration>,
+}
+
+#[repr(align(128))]
+struct ConsumerSlot {
+    /// Packed `(start, count)` or `NO_CLAIM`
+    claim: AtomicU64,
+    /// Nanoseconds since the tracker's epoch when `claim` was taken
+    claimed_at: AtomicU64,
+    claims: AtomicU64,
+    completed: AtomicU64,
+    reassigned: AtomicU64,
+}
+
+impl ConsumerSlot {
+    fn new() -> Self {
+        Self {
+            claim: AtomicU64::new(NO_CLAIM),
+            claimed_at: AtomicU64::new(0),
+            claims: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            reassigned: AtomicU64::new(0),
+        }
+    }
+}
+
 pub struct CompletionTracker {
     claim_cursor: PaddedAtomicU64,
     completed_cursor: PaddedAtomicU64,
     slot_completed: Box<[AtomicBool; MAX_SLOTS]>,
+    consumers: Box<[ConsumerSlot]>,
+    starvation: Option<StarvationPolicy>,
+    epoch: Instant,
 }
 
 impl Default for CompletionTracker {
@@ -45,6 +115,137 @@ impl CompletionTracker {
             claim_cursor: PaddedAtomicU64::new(0),
             completed_cursor: PaddedAtomicU64::new(0),
             slot_completed,
+            consumers: Box::new([]),
+            starvation: None,
+            epoch: Instant::now(),
+        }
+    }
+
+    /// Account claims per consumer for ids `0..count` (`try_claim_as`)
+    pub fn with_consumers(mut self, count: usize) -> Self {
+        self.consumers = (0..count).map(|_| ConsumerSlot::new()).collect();
+        self
+    }
+
+    /// Reassign tagged claims held longer than `policy.timeout`
+    pub fn with_starvation_policy(mut self, policy: StarvationPolicy) -> Self {
+        self.starvation = Some(policy);
+        self
+    }
+
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        self.epoch.elapsed().as_nanos() as u64
+    }
+
+    fn record_claim(&self, consumer: usize, start: u64, count: usize) {
+        let slot = &self.consumers[consumer];
+        slot.claimed_at.store(self.now_ns(), Ordering::Relaxed);
+        slot.claim
+            .store(pack_claim(start, count), Ordering::Release);
+        slot.claims.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take over the claim blocking `completed_cursor` if it's past the policy timeout
+    fn try_reassign(&self, consumer: usize) -> Option<(u64, usize)> {
+        let timeout = self.starvation?.timeout.as_nanos() as u64;
+        let victim = self.holding_back()?;
+        if victim == consumer {
+            return None;
+        }
+        let slot = &self.consumers[victim];
+        let claim = slot.claim.load(Ordering::Acquire);
+        if claim == NO_CLAIM
+            || self
+                .now_ns()
+                .saturating_sub(slot.claimed_at.load(Ordering::Relaxed))
+                < timeout
+        {
+            return None;
+        }
+        // Whoever clears the claim first owns it: the victim's `complete_as` races us here
+        slot.claim
+            .compare_exchange(claim, NO_CLAIM, Ordering::AcqRel, Ordering::Relaxed)
+            .ok()?;
+        slot.reassigned.fetch_add(1, Ordering::Relaxed);
+        let (start, count) = unpack_claim(claim);
+        self.record_claim(consumer, start, count);
+        Some((start, count))
+    }
+
+    /// Claim up to `requested` sequences for `consumer` (an id below `with_consumers`).
+    ///
+    /// A consumer holds one claim at a time: returns `None` until the previous
+    /// one is passed to `complete_as`. Under a `StarvationPolicy` this may hand
+    /// out an older range taken from a consumer that stalled on it.
+    pub fn try_claim_as(
+        &self,
+        consumer: usize,
+        requested: usize,
+        producer_cursor: u64,
+    ) -> Option<(u64, usize)> {
+        if self.consumers[consumer].claim.load(Ordering::Acquire) != NO_CLAIM {
+            return None;
+        }
+        if let Some(claim) = self.try_reassign(consumer) {
+            return Some(claim);
+        }
+        let (start, count) =
+            self.try_claim_batch(requested.min(MAX_TAGGED_BATCH), producer_cursor)?;
+        self.record_claim(consumer, start, count);
+        Some((start, count))
+    }
+
+    /// Complete `consumer`'s claim. Returns false (and completes nothing) if the
+    /// claim was reassigned: the new holder completes it instead.
+    pub fn complete_as(&self, consumer: usize, start: u64, count: usize) -> bool {
+        let slot = &self.consumers[consumer];
+        if slot
+            .claim
+            .compare_exchange(
+                pack_claim(start, count),
+                NO_CLAIM,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            return false;
+        }
+        slot.completed.fetch_add(1, Ordering::Relaxed);
+        self.complete_batch(start, count);
+        true
+    }
+
+    /// Consumer whose tagged claim holds the slot at `completed_cursor`
+    pub fn holding_back(&self) -> Option<usize> {
+        let completed = self.completed_cursor();
+        if completed >= self.claim_cursor.0.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.consumers.iter().position(|slot| {
+            let claim = slot.claim.load(Ordering::Acquire);
+            let (start, count) = unpack_claim(claim);
+            claim != NO_CLAIM && start <= completed && completed < start + count as u64
+        })
+    }
+
+    /// Claim accounting for `consumer`
+    pub fn consumer_stats(&self, consumer: usize) -> ConsumerClaimStats {
+        let slot = &self.consumers[consumer];
+        let claim = slot.claim.load(Ordering::Acquire);
+        let outstanding = (claim != NO_CLAIM).then(|| unpack_claim(claim));
+        ConsumerClaimStats {
+            claims: slot.claims.load(Ordering::Relaxed),
+            completed: slot.completed.load(Ordering::Relaxed),
+            reassigned: slot.reassigned.load(Ordering::Relaxed),
+            outstanding,
+            held_for: outstanding.map(|_| {
+                Duration::from_nanos(
+                    self.now_ns()
+                        .saturating_sub(slot.claimed_at.load(Ordering::Relaxed)),
+                )
+            }),
         }
     }
 
@@ -239,6 +440,50 @@ mod tests {
         assert_eq!(tracker.completed_cursor(), 5);
     }
 
+    #[test]
+    fn test_holding_back_names_slow_consumer() {
+        let tracker = CompletionTracker::new().with_consumers(2);
+        assert_eq!(tracker.try_claim_as(0, 4, 10), Some((0, 4)));
+        assert_eq!(tracker.try_claim_as(0, 4, 10), None); // must complete first
+        assert_eq!(tracker.try_claim_as(1, 4, 10), Some((4, 4)));
+        assert!(tracker.complete_as(1, 4, 4));
+        assert_eq!(tracker.completed_cursor(), 0);
+        assert_eq!(tracker.holding_back(), Some(0));
+
+        let stats = tracker.consumer_stats(0);
+        assert_eq!(stats.outstanding, Some((0, 4)));
+        assert!(stats.held_for.is_some());
+        assert!(tracker.complete_as(0, 0, 4));
+        assert_eq!(tracker.completed_cursor(), 8);
+        assert_eq!(tracker.holding_back(), None);
+        assert_eq!(tracker.consumer_stats(1).completed, 1);
+    }
+
+    #[test]
+    fn test_starved_claim_is_reassigned() {
+        let tracker = CompletionTracker::new()
+            .with_consumers(2)
+            .with_starvation_policy(StarvationPolicy {
+                timeout: Duration::from_millis(1),
+            });
+        assert_eq!(tracker.try_claim_as(0, 4, 10), Some((0, 4)));
+        // Within the timeout the second consumer gets fresh work
+        assert_eq!(tracker.try_claim_as(1, 2, 10), Some((4, 2)));
+        assert!(tracker.complete_as(1, 4, 2));
+
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(tracker.try_claim_as(1, 2, 10), Some((0, 4)));
+        assert!(!tracker.complete_as(0, 0, 4)); // late: no longer the holder
+        assert_eq!(tracker.completed_cursor(), 0);
+        assert!(tracker.complete_as(1, 0, 4));
+        assert_eq!(tracker.completed_cursor(), 6);
+
+        let slow = tracker.consumer_stats(0);
+        assert_eq!((slow.claims, slow.completed, slow.reassigned), (1, 0, 1));
+        assert_eq!(slow.outstanding, None);
+        assert_eq!(tracker.consumer_stats(1).claims, 2);
+    }
+
     #[test]
     fn test_fast_path() {
         let tracker = CompletionTracker::new();
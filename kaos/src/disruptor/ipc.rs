@@ -1,13 +1,24 @@
 //! SharedRingBuffer - File-backed ring buffer for inter-process communication
 //!
 //! Uses file-backed mmap (MAP_SHARED) that can be shared between processes.
+//!
+//! - `SharedRingBuffer<T>` - one producer process, one consumer process
+//! - `SharedBroadcastRingBuffer<T>` - one producer, many subscriber processes
+//!
+//! Each side records its pid and a heartbeat (CLOCK_MONOTONIC, shared by all
+//! processes on the host) in the header. `peer_status` reports whether the
+//! other side is alive, silent past a lease, or gone without detaching; the
+//! producer can then reclaim a dead consumer's cursor instead of stalling.
 
 use crate::disruptor::RingBufferEntry;
+use crate::insights::{register_ring, RingBufferStats, RingStats};
 use std::fs::{File, OpenOptions};
 use std::io;
 use std::os::unix::io::AsRawFd;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Magic bytes "KAOS_SHR" for file format validation
 const MAGIC: u64 = 0x4b414f535f534852;
@@ -24,11 +35,180 @@ struct SharedHeader {
     slot_size: u32,
     _pad0: [u8; 44],
     producer_seq: AtomicU64,
-    _pad1: [u8; 56],
+    /// Producer process id (0 = detached)
+    producer_pid: AtomicU64,
+    /// `monotonic_ns()` at the producer's last heartbeat
+    producer_heartbeat: AtomicU64,
+    _pad1: [u8; 40],
     cached_consumer_seq: AtomicU64,
     _pad2: [u8; 56],
     consumer_seq: AtomicU64,
-    _pad3: [u8; 56],
+    consumer_pid: AtomicU64,
+    consumer_heartbeat: AtomicU64,
+    _pad3: [u8; 40],
+}
+
+/// CLOCK_MONOTONIC in nanoseconds (comparable across processes on one host)
+fn monotonic_ns() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+    }
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+fn current_pid() -> u64 {
+    std::process::id() as u64
+}
+
+fn process_alive(pid: u64) -> bool {
+    let rc = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    rc == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Liveness of the process on the other side of a shared ring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerStatus {
+    /// Nobody attached (never joined, detached cleanly, or reclaimed)
+    Detached,
+    /// Process exists and heartbeated within the lease
+    Alive,
+    /// Process exists but hasn't heartbeated within the lease (hung or not calling `heartbeat`)
+    Stale { silent_for: Duration },
+    /// Process exited without detaching
+    Dead,
+}
+
+impl PeerStatus {
+    fn read(pid: &AtomicU64, heartbeat: &AtomicU64, lease: Duration) -> Self {
+        let pid = pid.load(Ordering::Acquire);
+        if pid == 0 {
+            return PeerStatus::Detached;
+        }
+        if !process_alive(pid) {
+            return PeerStatus::Dead;
+        }
+        let silent = monotonic_ns().saturating_sub(heartbeat.load(Ordering::Acquire));
+        if silent > lease.as_nanos() as u64 {
+            PeerStatus::Stale {
+                silent_for: Duration::from_nanos(silent),
+            }
+        } else {
+            PeerStatus::Alive
+        }
+    }
+
+    /// Dead or stale: safe to reclaim from the driver's point of view
+    pub fn is_reclaimable(&self) -> bool {
+        matches!(self, PeerStatus::Dead | PeerStatus::Stale { .. })
+    }
+}
+
+/// Huge page size requested by `SharedRingBuffer::create_with_hugepages`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HugePageSize {
+    Size2M,
+    Size1G,
+}
+
+impl HugePageSize {
+    pub fn bytes(&self) -> usize {
+        match self {
+            HugePageSize::Size2M => 2 << 20,
+            HugePageSize::Size1G => 1 << 30,
+        }
+    }
+}
+
+/// What actually backs a mapping, per the kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageBacking {
+    /// Base pages (huge pages unavailable, or not requested)
+    Regular,
+    /// Transparent huge pages (tmpfs / `/dev/shm` with THP enabled)
+    Transparent,
+    /// hugetlbfs pages of `page_size` bytes
+    HugeTlb { page_size: usize },
+}
+
+#[cfg(target_os = "linux")]
+const HUGETLBFS_MAGIC: u64 = 0x958458f6;
+
+/// Page size of the hugetlbfs mount holding `file`, if it's on one
+#[cfg(target_os = "linux")]
+fn hugetlbfs_page_size(file: &File) -> Option<usize> {
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstatfs(file.as_raw_fd(), &mut stat) } != 0 {
+        return None;
+    }
+    (stat.f_type as u64 & 0xffff_ffff == HUGETLBFS_MAGIC).then_some(stat.f_bsize as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn hugetlbfs_page_size(_file: &File) -> Option<usize> {
+    None
+}
+
+/// Read the backing of the mapping starting at `addr` from `/proc/self/smaps`
+#[cfg(target_os = "linux")]
+fn page_backing_of(addr: *const u8) -> PageBacking {
+    let Ok(smaps) = std::fs::read_to_string("/proc/self/smaps") else {
+        return PageBacking::Regular;
+    };
+    let start = format!("{:x}-", addr as usize);
+    let mut lines = smaps
+        .lines()
+        .skip_while(|l| !l.starts_with(&start))
+        .skip(1)
+        // Field lines are "Name:  value"; the next mapping's header isn't
+        .take_while(|l| {
+            l.split_whitespace()
+                .next()
+                .is_some_and(|k| k.ends_with(':'))
+        });
+    let kb = |line: &str| -> usize {
+        line.split_whitespace()
+            .nth(1)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    };
+    let (mut page_kb, mut pmd_kb) = (0, 0);
+    for line in &mut lines {
+        if line.starts_with("KernelPageSize:") {
+            page_kb = kb(line);
+        } else if line.starts_with("ShmemPmdMapped:") || line.starts_with("FilePmdMapped:") {
+            pmd_kb += kb(line);
+        }
+    }
+    if page_kb > 4 {
+        PageBacking::HugeTlb {
+            page_size: page_kb * 1024,
+        }
+    } else if pmd_kb > 0 {
+        PageBacking::Transparent
+    } else {
+        PageBacking::Regular
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn page_backing_of(_addr: *const u8) -> PageBacking {
+    PageBacking::Regular
+}
+
+/// Stamp `pid` and a fresh heartbeat
+fn attach(pid: &AtomicU64, heartbeat: &AtomicU64) {
+    heartbeat.store(monotonic_ns(), Ordering::Release);
+    pid.store(current_pid(), Ordering::Release);
+}
+
+/// Clear `pid` unless someone else (a reclaim, a replacement peer) owns it now
+fn detach(pid: &AtomicU64) -> bool {
+    pid.compare_exchange(current_pid(), 0, Ordering::AcqRel, Ordering::Relaxed)
+        .is_ok()
 }
 
 pub struct SharedRingBuffer<T: RingBufferEntry> {
@@ -40,12 +220,37 @@ pub struct SharedRingBuffer<T: RingBufferEntry> {
     local_seq: u64,
     cached_remote_seq: u64,
     is_producer: bool,
+    /// This process's side only (producer: claims/stalls, consumer: lag)
+    stats: Option<Arc<RingStats>>,
     _file: File,
     _phantom: std::marker::PhantomData<T>,
 }
 
 impl<T: RingBufferEntry> SharedRingBuffer<T> {
     pub fn create<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+        Self::create_mapped(path, capacity, None)
+    }
+
+    /// Create with huge page backing to cut TLB misses on large rings (Linux).
+    ///
+    /// On a hugetlbfs mount (e.g. `/dev/hugepages/ring`) the file is sized to
+    /// whole pages of the mount's size and mapping fails if none are reserved.
+    /// Anywhere else transparent huge pages are requested (`/dev/shm` with
+    /// `shmem_enabled=advise`); if they aren't available the ring silently uses
+    /// base pages. Check `page_backing()` for what was actually obtained.
+    pub fn create_with_hugepages<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+        size: HugePageSize,
+    ) -> io::Result<Self> {
+        Self::create_mapped(path, capacity, Some(size))
+    }
+
+    fn create_mapped<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+        huge: Option<HugePageSize>,
+    ) -> io::Result<Self> {
         if capacity == 0 || (capacity & (capacity - 1)) != 0 {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -68,6 +273,13 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
             .create(true)
             .truncate(true)
             .open(&path)?;
+        let file_size = match huge {
+            Some(size) => {
+                let page = hugetlbfs_page_size(&file).unwrap_or(size.bytes());
+                file_size.div_ceil(page) * page
+            }
+            None => file_size,
+        };
         file.set_len(file_size as u64)?;
 
         let mmap_ptr = unsafe {
@@ -85,6 +297,15 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
             ptr as *mut u8
         };
 
+        // Advise before the slots are first touched (zeroed) below; a no-op
+        // on hugetlbfs and harmless where THP is unsupported
+        #[cfg(target_os = "linux")]
+        if huge.is_some() {
+            unsafe {
+                libc::madvise(mmap_ptr as *mut _, file_size, libc::MADV_HUGEPAGE);
+            }
+        }
+
         let header = unsafe { &mut *(mmap_ptr as *mut SharedHeader) };
         header.magic = MAGIC;
         header.version = VERSION;
@@ -93,6 +314,9 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
         header.producer_seq = AtomicU64::new(0);
         header.cached_consumer_seq = AtomicU64::new(0);
         header.consumer_seq = AtomicU64::new(0);
+        header.consumer_pid = AtomicU64::new(0);
+        header.consumer_heartbeat = AtomicU64::new(0);
+        attach(&header.producer_pid, &header.producer_heartbeat);
 
         unsafe {
             std::ptr::write_bytes(mmap_ptr.add(HEADER_SIZE), 0, capacity * slot_size);
@@ -109,6 +333,7 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
             cached_remote_seq: 0,
             is_producer: true,
             _file: file,
+            stats: None,
             _phantom: std::marker::PhantomData,
         })
     }
@@ -178,16 +403,21 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
             ));
         }
 
+        // Resume where the previous consumer (or a reclaim) left the cursor
+        attach(&header.consumer_pid, &header.consumer_heartbeat);
+        let local_seq = header.consumer_seq.load(Ordering::Acquire);
+
         Ok(Self {
             mmap_ptr,
             mmap_len: file_size,
             capacity: capacity as u64,
             mask: (capacity - 1) as u64,
             slot_size,
-            local_seq: 0,
+            local_seq,
             cached_remote_seq: 0,
             is_producer: false,
             _file: file,
+            stats: None,
             _phantom: std::marker::PhantomData,
         })
     }
@@ -210,16 +440,99 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
         unsafe { self.mmap_ptr.add(offset) as *mut T }
     }
 
+    /// Track this side's counters under `name` (see `kaos::insights::ring_stats`).
+    /// Latency isn't measured: producer and consumer live in different processes.
+    pub fn with_insights(mut self, name: impl Into<String>) -> Self {
+        self.stats = Some(register_ring(name, self.capacity as usize));
+        self
+    }
+
+    /// Collect `stats()` without publishing them to `kaos::insights`
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(RingStats::new("", self.capacity as usize)));
+        self
+    }
+
+    /// Counters for this side of the ring, if enabled via `with_stats`/`with_insights`
+    pub fn stats(&self) -> Option<RingBufferStats> {
+        self.stats.as_ref().map(|s| s.snapshot())
+    }
+
+    #[inline]
+    fn note_consumed(&self, producer_seq: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_consumer_lag(producer_seq.saturating_sub(self.local_seq));
+        }
+    }
+
+    /// Pages backing this process's mapping (huge pages only if the kernel granted them)
+    pub fn page_backing(&self) -> PageBacking {
+        page_backing_of(self.mmap_ptr)
+    }
+
+    /// Refresh this side's lease. Call from the poll loop, more often than the lease the peer checks.
+    pub fn heartbeat(&self) {
+        let header = self.header();
+        let beat = if self.is_producer {
+            &header.producer_heartbeat
+        } else {
+            &header.consumer_heartbeat
+        };
+        beat.store(monotonic_ns(), Ordering::Release);
+    }
+
+    /// Status of the other side (the consumer when called by the producer, and vice versa)
+    pub fn peer_status(&self, lease: Duration) -> PeerStatus {
+        let header = self.header();
+        if self.is_producer {
+            PeerStatus::read(&header.consumer_pid, &header.consumer_heartbeat, lease)
+        } else {
+            PeerStatus::read(&header.producer_pid, &header.producer_heartbeat, lease)
+        }
+    }
+
+    /// Whether this process still owns its side (false once the producer reclaimed a consumer)
+    pub fn is_attached(&self) -> bool {
+        let header = self.header();
+        let pid = if self.is_producer {
+            &header.producer_pid
+        } else {
+            &header.consumer_pid
+        };
+        pid.load(Ordering::Acquire) == current_pid()
+    }
+
+    /// Producer: drop the consumer and skip everything it hasn't read, so
+    /// publishing can continue. Returns the number of skipped messages.
+    ///
+    /// Check `peer_status` first: a live consumer that is reclaimed loses data
+    /// (it can notice via `is_attached`). A replacement consumer `open`s at the tail.
+    pub fn reclaim_consumer(&mut self) -> u64 {
+        debug_assert!(self.is_producer, "reclaim_consumer() is for producer only");
+        let header = self.header();
+        header.consumer_pid.store(0, Ordering::Release);
+        let tail = header.producer_seq.load(Ordering::Acquire);
+        let head = header.consumer_seq.swap(tail, Ordering::AcqRel);
+        self.cached_remote_seq = tail;
+        tail.saturating_sub(head)
+    }
+
     pub fn try_claim(&mut self) -> Option<u64> {
         debug_assert!(self.is_producer, "try_claim() is for producer only");
         if self.local_seq.wrapping_sub(self.cached_remote_seq) >= self.capacity {
             self.cached_remote_seq = self.header().consumer_seq.load(Ordering::Acquire);
             if self.local_seq.wrapping_sub(self.cached_remote_seq) >= self.capacity {
+                if let Some(stats) = &self.stats {
+                    stats.record_stall();
+                }
                 return None;
             }
         }
         let seq = self.local_seq;
         self.local_seq = self.local_seq.wrapping_add(1);
+        if let Some(stats) = &self.stats {
+            stats.record_claim(self.local_seq.wrapping_sub(self.cached_remote_seq));
+        }
         Some(seq)
     }
 
@@ -315,6 +628,7 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
         self.local_seq = self.local_seq.wrapping_add(1);
         let seq = self.local_seq;
         self.header_mut().consumer_seq.store(seq, Ordering::Release);
+        self.note_consumed(producer_seq);
         Some(slot)
     }
 
@@ -332,6 +646,7 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
         if count > 0 {
             let seq = self.local_seq;
             self.header_mut().consumer_seq.store(seq, Ordering::Release);
+            self.note_consumed(producer_seq);
         }
         count
     }
@@ -388,11 +703,21 @@ impl<T: RingBufferEntry> SharedRingBuffer<T> {
         self.header_mut()
             .consumer_seq
             .store(next, Ordering::Release);
+        if self.stats.is_some() {
+            let producer_seq = self.header().producer_seq.load(Ordering::Relaxed);
+            self.note_consumed(producer_seq);
+        }
     }
 }
 
 impl<T: RingBufferEntry> Drop for SharedRingBuffer<T> {
     fn drop(&mut self) {
+        let header = self.header();
+        detach(if self.is_producer {
+            &header.producer_pid
+        } else {
+            &header.consumer_pid
+        });
         unsafe {
             libc::munmap(self.mmap_ptr as *mut _, self.mmap_len);
         }
@@ -401,6 +726,460 @@ impl<T: RingBufferEntry> Drop for SharedRingBuffer<T> {
 
 unsafe impl<T: RingBufferEntry> Send for SharedRingBuffer<T> {}
 
+// ============================================================================
+// SharedBroadcastRingBuffer<T> - one producer process, many subscriber processes
+// ============================================================================
+
+/// Magic bytes "KAOS_BRD" (broadcast layout; `SharedRingBuffer::open` rejects it)
+const BROADCAST_MAGIC: u64 = 0x4b414f535f425244;
+const BROADCAST_HEADER_SIZE: usize = 128;
+const SUBSCRIBER_ENTRY_SIZE: usize = 64;
+
+/// Subscriber table entry states
+const SUB_FREE: u64 = 0;
+const SUB_JOINING: u64 = 1;
+const SUB_ACTIVE: u64 = 2;
+
+#[repr(C, align(64))]
+struct BroadcastHeader {
+    magic: u64,
+    version: u32,
+    capacity: u32,
+    slot_size: u32,
+    max_subscribers: u32,
+    _pad0: [u8; 40],
+    producer_seq: AtomicU64,
+    producer_pid: AtomicU64,
+    producer_heartbeat: AtomicU64,
+    _pad1: [u8; 40],
+}
+
+/// One per subscriber process, each on its own cache line
+#[repr(C, align(64))]
+struct SubscriberEntry {
+    state: AtomicU64,
+    /// Next sequence this subscriber will read
+    cursor: AtomicU64,
+    pid: AtomicU64,
+    heartbeat: AtomicU64,
+    _pad: [u8; 32],
+}
+
+fn map_shared(file: &File, len: usize) -> io::Result<*mut u8> {
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(ptr as *mut u8)
+}
+
+/// File-backed broadcast ring: every subscriber process sees every message.
+///
+/// Subscribers keep their cursor in a table in the shared header; the producer
+/// only reuses a slot once every active subscriber has read it. A subscriber
+/// joins at the current tail and frees its table entry on drop.
+///
+/// ```rust,no_run
+/// use kaos::disruptor::{SharedBroadcastRingBuffer, Slot8};
+///
+/// // Driver process
+/// let mut producer = SharedBroadcastRingBuffer::<Slot8>::create("/tmp/fanout", 1024, 8).unwrap();
+/// // Each application process
+/// let mut sub = SharedBroadcastRingBuffer::<Slot8>::subscribe("/tmp/fanout").unwrap();
+///
+/// producer.try_send(&7u64.to_le_bytes()).unwrap();
+/// sub.receive(|slot| println!("{}", slot.value));
+/// ```
+pub struct SharedBroadcastRingBuffer<T: RingBufferEntry> {
+    mmap_ptr: *mut u8,
+    mmap_len: usize,
+    capacity: u64,
+    mask: u64,
+    slot_size: usize,
+    data_offset: usize,
+    max_subscribers: usize,
+    /// Producer: next sequence to claim. Subscriber: next sequence to read.
+    local_seq: u64,
+    /// Producer: slowest subscriber cursor at the last scan
+    cached_gate: u64,
+    /// Table entry held by this subscriber (`None` for the producer)
+    subscriber: Option<usize>,
+    _file: File,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: RingBufferEntry> SharedBroadcastRingBuffer<T> {
+    fn data_offset(max_subscribers: usize) -> usize {
+        BROADCAST_HEADER_SIZE + max_subscribers * SUBSCRIBER_ENTRY_SIZE
+    }
+
+    /// Create the ring (producer side) with room for `max_subscribers`
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+        max_subscribers: usize,
+    ) -> io::Result<Self> {
+        if capacity == 0 || (capacity & (capacity - 1)) != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Capacity must be a power of 2",
+            ));
+        }
+        if max_subscribers == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Need at least one subscriber slot",
+            ));
+        }
+
+        let slot_size = std::mem::size_of::<T>();
+        let data_offset = Self::data_offset(max_subscribers);
+        let file_size = capacity
+            .checked_mul(slot_size)
+            .and_then(|n| n.checked_add(data_offset))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Size overflow"))?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len(file_size as u64)?;
+        let mmap_ptr = map_shared(&file, file_size)?;
+
+        // The file was just truncated, so the subscriber table starts zeroed (all free)
+        let header = unsafe { &mut *(mmap_ptr as *mut BroadcastHeader) };
+        header.version = VERSION;
+        header.capacity = capacity as u32;
+        header.slot_size = slot_size as u32;
+        header.max_subscribers = max_subscribers as u32;
+        header.producer_seq = AtomicU64::new(0);
+        attach(&header.producer_pid, &header.producer_heartbeat);
+        std::sync::atomic::fence(Ordering::Release);
+        // Magic last: subscribers racing the setup see an invalid file, not a half-written one
+        header.magic = BROADCAST_MAGIC;
+        unsafe {
+            libc::msync(mmap_ptr as *mut _, file_size, libc::MS_SYNC);
+        }
+
+        Ok(Self {
+            mmap_ptr,
+            mmap_len: file_size,
+            capacity: capacity as u64,
+            mask: (capacity - 1) as u64,
+            slot_size,
+            data_offset,
+            max_subscribers,
+            local_seq: 0,
+            cached_gate: 0,
+            subscriber: None,
+            _file: file,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Join as a subscriber. Reads start at the current tail.
+    pub fn subscribe<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        let file_size = file.metadata()?.len() as usize;
+        if file_size < BROADCAST_HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "File too small for header",
+            ));
+        }
+        let mmap_ptr = map_shared(&file, file_size)?;
+        let unmap_with = |msg: String| {
+            unsafe {
+                libc::munmap(mmap_ptr as *mut _, file_size);
+            }
+            Err(io::Error::new(io::ErrorKind::InvalidData, msg))
+        };
+
+        let header = unsafe { &*(mmap_ptr as *const BroadcastHeader) };
+        if header.magic != BROADCAST_MAGIC {
+            return unmap_with("Invalid magic (not a broadcast ring)".into());
+        }
+        if header.version != VERSION {
+            return unmap_with(format!(
+                "Version mismatch: expected {}, got {}",
+                VERSION, header.version
+            ));
+        }
+        let capacity = header.capacity as usize;
+        let slot_size = header.slot_size as usize;
+        let max_subscribers = header.max_subscribers as usize;
+        if slot_size != std::mem::size_of::<T>() {
+            return unmap_with(format!(
+                "Slot size mismatch: file has {}, expected {}",
+                slot_size,
+                std::mem::size_of::<T>()
+            ));
+        }
+        let data_offset = Self::data_offset(max_subscribers);
+        if file_size < data_offset + capacity * slot_size {
+            return unmap_with("File too small for ring".into());
+        }
+
+        let mut ring = Self {
+            mmap_ptr,
+            mmap_len: file_size,
+            capacity: capacity as u64,
+            mask: (capacity - 1) as u64,
+            slot_size,
+            data_offset,
+            max_subscribers,
+            local_seq: 0,
+            cached_gate: 0,
+            subscriber: None,
+            _file: file,
+            _phantom: std::marker::PhantomData,
+        };
+
+        let id = (0..max_subscribers)
+            .find(|&i| {
+                ring.entry(i)
+                    .state
+                    .compare_exchange(SUB_FREE, SUB_JOINING, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            })
+            .ok_or_else(|| io::Error::other("All subscriber slots in use"))?;
+        ring.subscriber = Some(id);
+
+        // Publish a cursor, go active, then move the cursor to the tail again:
+        // the producer may have advanced before it saw us
+        let entry = ring.entry(id);
+        attach(&entry.pid, &entry.heartbeat);
+        let tail = ring.header().producer_seq.load(Ordering::Acquire);
+        entry.cursor.store(tail, Ordering::Release);
+        entry.state.store(SUB_ACTIVE, Ordering::SeqCst);
+        let tail = ring.header().producer_seq.load(Ordering::Acquire);
+        entry.cursor.store(tail, Ordering::Release);
+        ring.local_seq = tail;
+        Ok(ring)
+    }
+
+    fn header(&self) -> &BroadcastHeader {
+        unsafe { &*(self.mmap_ptr as *const BroadcastHeader) }
+    }
+
+    fn entry(&self, id: usize) -> &SubscriberEntry {
+        debug_assert!(id < self.max_subscribers);
+        unsafe {
+            &*(self
+                .mmap_ptr
+                .add(BROADCAST_HEADER_SIZE + id * SUBSCRIBER_ENTRY_SIZE)
+                as *const SubscriberEntry)
+        }
+    }
+
+    fn slot_ptr(&self, seq: u64) -> *mut T {
+        let idx = (seq & self.mask) as usize;
+        unsafe { self.mmap_ptr.add(self.data_offset + idx * self.slot_size) as *mut T }
+    }
+
+    /// This process's subscriber table entry (`None` for the producer)
+    pub fn subscriber_id(&self) -> Option<usize> {
+        self.subscriber
+    }
+
+    /// Refresh this process's lease (producer or subscriber)
+    pub fn heartbeat(&self) {
+        let beat = match self.subscriber {
+            Some(id) => &self.entry(id).heartbeat,
+            None => &self.header().producer_heartbeat,
+        };
+        beat.store(monotonic_ns(), Ordering::Release);
+    }
+
+    /// Status of the producer process
+    pub fn peer_status(&self, lease: Duration) -> PeerStatus {
+        let header = self.header();
+        PeerStatus::read(&header.producer_pid, &header.producer_heartbeat, lease)
+    }
+
+    /// Status of the subscriber in table entry `id`
+    pub fn subscriber_status(&self, id: usize, lease: Duration) -> PeerStatus {
+        let entry = self.entry(id);
+        if entry.state.load(Ordering::Acquire) == SUB_FREE {
+            return PeerStatus::Detached;
+        }
+        PeerStatus::read(&entry.pid, &entry.heartbeat, lease)
+    }
+
+    /// Whether this subscriber still holds its table entry (false once reclaimed)
+    pub fn is_subscribed(&self) -> bool {
+        self.subscriber.is_some_and(|id| {
+            let entry = self.entry(id);
+            entry.state.load(Ordering::Acquire) == SUB_ACTIVE
+                && entry.pid.load(Ordering::Acquire) == current_pid()
+        })
+    }
+
+    /// Free the entries of dead subscribers and of those silent past `lease`,
+    /// so they stop gating the producer. Returns how many were freed.
+    pub fn reclaim_subscribers(&mut self, lease: Duration) -> usize {
+        let mut freed = 0;
+        for id in 0..self.max_subscribers {
+            if !self.subscriber_status(id, lease).is_reclaimable() {
+                continue;
+            }
+            let entry = self.entry(id);
+            let pid = entry.pid.load(Ordering::Acquire);
+            // Lose the race to a clean detach rather than free a re-used entry
+            if entry
+                .pid
+                .compare_exchange(pid, 0, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                entry.state.store(SUB_FREE, Ordering::Release);
+                freed += 1;
+            }
+        }
+        self.cached_gate = self.gating_sequence();
+        freed
+    }
+
+    /// Subscribers currently attached
+    pub fn active_subscribers(&self) -> usize {
+        (0..self.max_subscribers)
+            .filter(|&i| self.entry(i).state.load(Ordering::Acquire) == SUB_ACTIVE)
+            .count()
+    }
+
+    /// Slowest active subscriber cursor (`local_seq` if none is attached)
+    fn gating_sequence(&self) -> u64 {
+        (0..self.max_subscribers)
+            .map(|i| self.entry(i))
+            .filter(|e| e.state.load(Ordering::Acquire) == SUB_ACTIVE)
+            .map(|e| e.cursor.load(Ordering::Acquire))
+            .fold(self.local_seq, u64::min)
+    }
+
+    pub fn try_claim(&mut self) -> Option<u64> {
+        debug_assert!(
+            self.subscriber.is_none(),
+            "try_claim() is for producer only"
+        );
+        if self.local_seq.wrapping_sub(self.cached_gate) >= self.capacity {
+            self.cached_gate = self.gating_sequence();
+            if self.local_seq.wrapping_sub(self.cached_gate) >= self.capacity {
+                return None;
+            }
+        }
+        let seq = self.local_seq;
+        self.local_seq = self.local_seq.wrapping_add(1);
+        Some(seq)
+    }
+
+    /// Write a claimed slot
+    pub fn write_slot(&mut self, seq: u64, value: T) {
+        // SAFETY: slot_ptr masks the index into the mapped slot region
+        unsafe { std::ptr::write_volatile(self.slot_ptr(seq), value) }
+    }
+
+    pub fn publish(&mut self, seq: u64) {
+        std::sync::atomic::fence(Ordering::Release);
+        self.header()
+            .producer_seq
+            .store(seq.wrapping_add(1), Ordering::Release);
+    }
+
+    pub fn try_send(&mut self, data: &[u8]) -> io::Result<u64> {
+        let seq = self
+            .try_claim()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::WouldBlock, "Ring buffer full"))?;
+        let mut slot = T::default();
+        let slot_bytes = unsafe {
+            std::slice::from_raw_parts_mut(&mut slot as *mut T as *mut u8, std::mem::size_of::<T>())
+        };
+        let copy_len = data.len().min(slot_bytes.len());
+        slot_bytes[..copy_len].copy_from_slice(&data[..copy_len]);
+        self.write_slot(seq, slot);
+        self.publish(seq);
+        Ok(seq)
+    }
+
+    /// Messages published but not yet read by this subscriber
+    pub fn available(&self) -> u64 {
+        let producer_seq = self.header().producer_seq.load(Ordering::Acquire);
+        producer_seq.saturating_sub(self.local_seq)
+    }
+
+    fn store_cursor(&self) {
+        if let Some(id) = self.subscriber {
+            self.entry(id)
+                .cursor
+                .store(self.local_seq, Ordering::Release);
+        }
+    }
+
+    /// Try to receive a single message (non-blocking, for polling)
+    pub fn try_receive(&mut self) -> Option<T> {
+        debug_assert!(
+            self.subscriber.is_some(),
+            "try_receive() is for subscribers only"
+        );
+        let producer_seq = self.header().producer_seq.load(Ordering::Acquire);
+        if self.local_seq >= producer_seq {
+            return None;
+        }
+        let slot = unsafe { std::ptr::read_volatile(self.slot_ptr(self.local_seq)) };
+        self.local_seq += 1;
+        self.store_cursor();
+        Some(slot)
+    }
+
+    /// Receive everything available with a callback
+    pub fn receive<F: FnMut(&T)>(&mut self, mut callback: F) -> usize {
+        debug_assert!(
+            self.subscriber.is_some(),
+            "receive() is for subscribers only"
+        );
+        let producer_seq = self.header().producer_seq.load(Ordering::Acquire);
+        let start = self.local_seq;
+        while self.local_seq < producer_seq {
+            callback(unsafe { &*self.slot_ptr(self.local_seq) });
+            self.local_seq += 1;
+        }
+        if self.local_seq > start {
+            self.store_cursor();
+        }
+        (self.local_seq - start) as usize
+    }
+}
+
+impl<T: RingBufferEntry> Drop for SharedBroadcastRingBuffer<T> {
+    fn drop(&mut self) {
+        match self.subscriber {
+            Some(id) => {
+                let entry = self.entry(id);
+                if detach(&entry.pid) {
+                    entry.state.store(SUB_FREE, Ordering::Release);
+                }
+            }
+            None => {
+                detach(&self.header().producer_pid);
+            }
+        }
+        unsafe {
+            libc::munmap(self.mmap_ptr as *mut _, self.mmap_len);
+        }
+    }
+}
+
+unsafe impl<T: RingBufferEntry> Send for SharedBroadcastRingBuffer<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -425,6 +1204,190 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_stats_per_side() {
+        let path = "/tmp/kaos-shared-test-stats";
+        let _ = fs::remove_file(path);
+
+        let mut producer = SharedRingBuffer::<Slot8>::create(path, 4)
+            .unwrap()
+            .with_stats();
+        let mut consumer = SharedRingBuffer::<Slot8>::open(path).unwrap().with_stats();
+        for i in 0..5u64 {
+            let _ = producer.try_send(&i.to_le_bytes());
+        }
+        let stats = producer.stats().unwrap();
+        assert_eq!((stats.high_water, stats.stalls), (4, 1));
+
+        assert!(consumer.try_receive().is_some());
+        assert_eq!(consumer.stats().unwrap().consumer_lag, 3);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_broadcast_fan_out() {
+        let path = "/tmp/kaos-shared-test-broadcast";
+        let _ = fs::remove_file(path);
+
+        let mut producer = SharedBroadcastRingBuffer::<Slot8>::create(path, 8, 2).unwrap();
+        // Nobody attached: the producer never blocks
+        for i in 0..20u64 {
+            producer.try_send(&i.to_le_bytes()).unwrap();
+        }
+
+        let mut a = SharedBroadcastRingBuffer::<Slot8>::subscribe(path).unwrap();
+        let mut b = SharedBroadcastRingBuffer::<Slot8>::subscribe(path).unwrap();
+        assert!(SharedBroadcastRingBuffer::<Slot8>::subscribe(path).is_err());
+        assert_eq!(producer.active_subscribers(), 2);
+        assert_eq!(a.available(), 0); // joined at the tail
+
+        for i in 0..8u64 {
+            producer.try_send(&i.to_le_bytes()).unwrap();
+        }
+        let mut seen = Vec::new();
+        assert_eq!(a.receive(|s| seen.push(s.value)), 8);
+        assert_eq!(seen, (0..8).collect::<Vec<_>>());
+
+        // b hasn't read anything: the ring is full for the producer
+        assert!(producer.try_send(&[0]).is_err());
+        assert_eq!(b.try_receive().unwrap().value, 0);
+        assert!(producer.try_send(&[0]).is_ok());
+
+        // A departed subscriber stops gating the producer
+        drop(b);
+        assert_eq!(producer.active_subscribers(), 1);
+        while a.try_receive().is_some() {}
+        for i in 0..8u64 {
+            producer.try_send(&i.to_le_bytes()).unwrap();
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    /// Pid of a process that has already exited
+    fn dead_pid() -> u64 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id() as u64;
+        child.wait().unwrap();
+        pid
+    }
+
+    #[test]
+    fn test_reclaim_dead_consumer() {
+        let path = "/tmp/kaos-shared-test-reclaim";
+        let _ = fs::remove_file(path);
+        let lease = Duration::from_secs(60);
+
+        let mut producer = SharedRingBuffer::<Slot8>::create(path, 8).unwrap();
+        assert_eq!(producer.peer_status(lease), PeerStatus::Detached);
+        let mut consumer = SharedRingBuffer::<Slot8>::open(path).unwrap();
+        assert_eq!(producer.peer_status(lease), PeerStatus::Alive);
+        assert_eq!(consumer.peer_status(lease), PeerStatus::Alive);
+
+        for i in 0..8u64 {
+            producer.try_send(&i.to_le_bytes()).unwrap();
+        }
+        assert_eq!(consumer.try_receive().unwrap().value, 0);
+
+        // Consumer crashes: its pid now belongs to an exited process
+        consumer
+            .header()
+            .consumer_pid
+            .store(dead_pid(), Ordering::Release);
+        drop(consumer);
+        assert_eq!(producer.peer_status(lease), PeerStatus::Dead);
+        producer.try_send(&[0]).unwrap();
+        assert!(producer.try_send(&[0]).is_err());
+
+        assert_eq!(producer.reclaim_consumer(), 8);
+        assert_eq!(producer.peer_status(lease), PeerStatus::Detached);
+        producer.try_send(&42u64.to_le_bytes()).unwrap();
+
+        // A replacement resumes after the skipped messages
+        let mut consumer = SharedRingBuffer::<Slot8>::open(path).unwrap();
+        assert!(consumer.is_attached());
+        assert_eq!(consumer.try_receive().unwrap().value, 42);
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(matches!(
+            consumer.peer_status(Duration::from_millis(1)),
+            PeerStatus::Stale { .. }
+        ));
+        producer.heartbeat();
+        assert_eq!(consumer.peer_status(lease), PeerStatus::Alive);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_reclaim_broadcast_subscribers() {
+        let path = "/tmp/kaos-shared-test-broadcast-reclaim";
+        let _ = fs::remove_file(path);
+        let lease = Duration::from_secs(60);
+
+        let mut producer = SharedBroadcastRingBuffer::<Slot8>::create(path, 8, 4).unwrap();
+        let crashed = SharedBroadcastRingBuffer::<Slot8>::subscribe(path).unwrap();
+        let mut hung = SharedBroadcastRingBuffer::<Slot8>::subscribe(path).unwrap();
+        let mut healthy = SharedBroadcastRingBuffer::<Slot8>::subscribe(path).unwrap();
+        assert_eq!(healthy.peer_status(lease), PeerStatus::Alive);
+
+        let crashed_id = crashed.subscriber_id().unwrap();
+        crashed
+            .entry(crashed_id)
+            .pid
+            .store(dead_pid(), Ordering::Release);
+        drop(crashed);
+        assert_eq!(
+            producer.subscriber_status(crashed_id, lease),
+            PeerStatus::Dead
+        );
+        assert_eq!(producer.reclaim_subscribers(lease), 1);
+        assert_eq!(producer.active_subscribers(), 2);
+
+        for i in 0..8u64 {
+            producer.try_send(&i.to_le_bytes()).unwrap();
+        }
+        assert!(producer.try_send(&[0]).is_err());
+
+        // `hung` never reads or heartbeats; `healthy` keeps up
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(healthy.receive(|_| {}), 8);
+        healthy.heartbeat();
+        assert_eq!(producer.reclaim_subscribers(Duration::from_millis(1)), 1);
+        assert!(!hung.is_subscribed() && healthy.is_subscribed());
+        assert!(hung.try_receive().is_some()); // still mapped, no longer gating
+        producer.try_send(&[0]).unwrap();
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_create_with_hugepages_falls_back() {
+        let path = "/tmp/kaos-shared-test-hugepages";
+        let _ = fs::remove_file(path);
+
+        let mut producer =
+            SharedRingBuffer::<Slot8>::create_with_hugepages(path, 1024, HugePageSize::Size2M)
+                .unwrap();
+        // Whole huge pages, whatever backing the kernel granted
+        let len = fs::metadata(path).unwrap().len() as usize;
+        assert_eq!(len % HugePageSize::Size2M.bytes(), 0);
+        assert!(!matches!(
+            producer.page_backing(),
+            PageBacking::HugeTlb { .. }
+        ));
+
+        let mut consumer = SharedRingBuffer::<Slot8>::open(path).unwrap();
+        producer.try_send(&9u64.to_le_bytes()).unwrap();
+        assert_eq!(consumer.try_receive().unwrap().value, 9);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_broadcast_rejects_spsc_file() {
+        let path = "/tmp/kaos-shared-test-broadcast-magic";
+        let _ = fs::remove_file(path);
+        let _spsc = SharedRingBuffer::<Slot8>::create(path, 8).unwrap();
+        assert!(SharedBroadcastRingBuffer::<Slot8>::subscribe(path).is_err());
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_batch() {
         let path = "/tmp/kaos-shared-test-batch";
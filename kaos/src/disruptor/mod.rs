@@ -4,29 +4,41 @@
 //! - `BroadcastRingBuffer<T>` - SPSC, multiple consumers see ALL messages
 //! - `SpmcRingBuffer<T>` - Fan-out work distribution (each msg to ONE consumer)
 //! - `MpscRingBuffer<T>` - Multiple producers, single consumer
-//! - `MpmcRingBuffer<T>` - Full flexibility (slowest)
+//! - `MpmcRingBuffer<T>` - Full flexibility, optional consumer groups (slowest)
 
+mod barrier;
 mod completion;
 mod ipc;
 pub mod macros;
 mod multi;
+mod overflow;
+mod pipeline;
 mod single;
 mod slots;
+mod wait;
 
 // Re-exports
-pub use completion::{BatchReadGuard, CompletionTracker, ReadGuard, ReadableRing};
-pub use ipc::SharedRingBuffer;
+pub use barrier::{DependencyGraph, SequenceBarrier, Stage, StageId};
+pub use completion::{
+    BatchReadGuard, CompletionTracker, ConsumerClaimStats, ReadGuard, ReadableRing,
+    StarvationPolicy,
+};
+pub use ipc::{HugePageSize, PageBacking, PeerStatus, SharedBroadcastRingBuffer, SharedRingBuffer};
 pub use multi::{
     CachedMpmcProducer, CachedMpscProducer, MpmcRingBuffer, MpscConsumer, MpscConsumerBuilder,
     MpscEventHandler, MpscProducer, MpscProducerBuilder, MpscRingBuffer, SpmcRingBuffer,
 };
+pub use overflow::{OverflowRingBuffer, Placement};
+pub use pipeline::{Pipeline, PipelineBuilder, PipelineProducer};
 pub use single::{
-    BroadcastRingBuffer, CachedProducer, Consumer, ConsumerBuilder, EventHandler,
+    BroadcastRingBuffer, CachedProducer, Consumer, ConsumerBuilder, EventHandler, EventPoller,
     MessageRingBuffer, Producer, ProducerBuilder, RingBuffer,
 };
 pub use slots::{MessageSlot, Slot16, Slot32, Slot64, Slot8};
+pub use wait::{Blocking, BusySpin, Sleeping, WaitStrategy, Yielding};
 
 use crate::error::{KaosError, Result};
+use std::sync::Arc;
 
 /// Default ring buffer size (must be power of 2)
 const DEFAULT_RING_BUFFER_SIZE: usize = 64 * 1024; // 64K slots
@@ -53,6 +65,10 @@ pub struct RingBufferConfig {
     pub size: usize,
     /// Number of consumers
     pub num_consumers: usize,
+    /// What idle consumers do between polls (default: `BusySpin`)
+    pub wait_strategy: Arc<dyn WaitStrategy>,
+    /// NUMA node to allocate the buffer on (Linux; default: first-touch)
+    pub numa_node: Option<usize>,
 }
 
 impl Default for RingBufferConfig {
@@ -60,6 +76,8 @@ impl Default for RingBufferConfig {
         Self {
             size: DEFAULT_RING_BUFFER_SIZE,
             num_consumers: 1,
+            wait_strategy: Arc::new(BusySpin),
+            numa_node: None,
         }
     }
 }
@@ -96,6 +114,22 @@ impl RingBufferConfig {
         self.num_consumers = num_consumers;
         Ok(self)
     }
+
+    /// Set the consumer wait strategy
+    pub fn with_wait_strategy(mut self, strategy: impl WaitStrategy + 'static) -> Self {
+        self.wait_strategy = Arc::new(strategy);
+        self
+    }
+
+    /// Allocate the buffer on a NUMA node (see `kaos::affinity::numa_core_pair`
+    /// to pin the producer and consumer next to it)
+    pub fn with_numa_node(mut self, node: usize) -> Result<Self> {
+        if node >= crate::affinity::numa_node_count() {
+            return Err(KaosError::config(format!("NUMA node {} not found", node)));
+        }
+        self.numa_node = Some(node);
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -134,4 +168,17 @@ mod tests {
         let result = RingBufferConfig::new(1024).unwrap().with_consumers(2000);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_ring_buffer_config_numa_node() {
+        let config = RingBufferConfig::new(1024)
+            .unwrap()
+            .with_numa_node(0)
+            .unwrap();
+        assert_eq!(config.numa_node, Some(0));
+        assert!(RingBufferConfig::new(1024)
+            .unwrap()
+            .with_numa_node(4096)
+            .is_err());
+    }
 }
@@ -8,9 +8,13 @@ use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use crate::disruptor::completion::{BatchReadGuard, CompletionTracker, ReadGuard, ReadableRing};
+use crate::disruptor::completion::{
+    BatchReadGuard, CompletionTracker, ConsumerClaimStats, ReadGuard, ReadableRing,
+    StarvationPolicy,
+};
 use crate::disruptor::RingBufferEntry;
 use crate::error::{KaosError, Result};
+use crate::insights::{register_ring, RingBufferStats, RingStats};
 
 // ============================================================================
 // MPSC - Multi-Producer Single Consumer
@@ -24,6 +28,7 @@ pub struct MpscRingBuffer<T: RingBufferEntry> {
     available: Box<[AtomicU64]>,
     index_mask: usize,
     index_shift: usize,
+    stats: Option<Arc<RingStats>>,
 }
 
 impl<T: RingBufferEntry> MpscRingBuffer<T> {
@@ -55,9 +60,32 @@ impl<T: RingBufferEntry> MpscRingBuffer<T> {
             available,
             index_mask: size - 1,
             index_shift: Self::log2(size),
+            stats: None,
         })
     }
 
+    /// Track occupancy, stalls and consumer lag under `name` (see `kaos::insights::ring_stats`)
+    pub fn with_insights(mut self, name: impl Into<String>) -> Self {
+        self.stats = Some(register_ring(name, self.buffer.len()));
+        self
+    }
+
+    /// Collect `stats()` without publishing them to `kaos::insights`
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(RingStats::new("", self.buffer.len())));
+        self
+    }
+
+    /// Diagnostics handle, if enabled via `with_insights`
+    pub fn insights(&self) -> Option<&Arc<RingStats>> {
+        self.stats.as_ref()
+    }
+
+    /// Counters and latency histogram, if enabled via `with_stats`/`with_insights`
+    pub fn stats(&self) -> Option<RingBufferStats> {
+        self.stats.as_ref().map(|s| s.snapshot())
+    }
+
     fn log2(i: usize) -> usize {
         std::mem::size_of::<usize>() * 8 - (i.leading_zeros() as usize) - 1
     }
@@ -67,7 +95,11 @@ impl<T: RingBufferEntry> MpscRingBuffer<T> {
             let current = self.claim_cursor.load(Ordering::Relaxed);
             let next = current + (count as u64);
             let consumer_seq = self.consumer_cursor.load(Ordering::Acquire);
-            if (self.buffer.len() as u64) <= next - consumer_seq {
+            // `current` may be stale (consumer already past it): the CAS below retries
+            if (self.buffer.len() as u64) <= next.saturating_sub(consumer_seq) {
+                if let Some(stats) = &self.stats {
+                    stats.record_stall();
+                }
                 return None;
             }
 
@@ -78,6 +110,9 @@ impl<T: RingBufferEntry> MpscRingBuffer<T> {
                 Ordering::Relaxed,
             ) {
                 Ok(_) => {
+                    if let Some(stats) = &self.stats {
+                        stats.record_claim(next - consumer_seq);
+                    }
                     return Some(current);
                 }
                 Err(_) => std::hint::spin_loop(),
@@ -148,6 +183,9 @@ impl<T: RingBufferEntry> MpscRingBuffer<T> {
 
     /// Publish a single slot
     pub fn publish(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(sequence, sequence + 1);
+        }
         let (avail_idx, bit_idx) = self.calculate_indices(sequence);
         self.available[avail_idx].fetch_xor(1u64 << bit_idx, Ordering::Release);
     }
@@ -159,6 +197,9 @@ impl<T: RingBufferEntry> MpscRingBuffer<T> {
         if count == 0 {
             return;
         }
+        if let Some(stats) = &self.stats {
+            stats.record_publish(start, start + count as u64);
+        }
 
         let (mut avail_idx, mut bit_idx) = self.calculate_indices(start);
         let mut flip_mask = 0u64;
@@ -232,7 +273,14 @@ impl<T: RingBufferEntry> MpscRingBuffer<T> {
     }
 
     pub fn update_consumer(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_consume(self.consumer_cursor.load(Ordering::Relaxed), sequence);
+        }
         self.consumer_cursor.store(sequence, Ordering::Release);
+        if let Some(stats) = &self.stats {
+            let claimed = self.claim_cursor.load(Ordering::Relaxed);
+            stats.record_consumer_lag(claimed.saturating_sub(sequence));
+        }
     }
 
     /// Get highest contiguous published sequence (optimized with trailing_zeros)
@@ -451,6 +499,7 @@ pub struct SpmcRingBuffer<T: RingBufferEntry> {
     mask: usize,
     producer_cursor: Arc<AtomicU64>,
     completion_tracker: CompletionTracker,
+    stats: Option<Arc<RingStats>>,
 }
 
 impl<T: RingBufferEntry> SpmcRingBuffer<T> {
@@ -467,15 +516,85 @@ impl<T: RingBufferEntry> SpmcRingBuffer<T> {
             mask: size - 1,
             producer_cursor: Arc::new(AtomicU64::new(0)),
             completion_tracker: CompletionTracker::new(),
+            stats: None,
         })
     }
 
+    /// Track occupancy, stalls and latency under `name` (see `kaos::insights::ring_stats`)
+    pub fn with_insights(mut self, name: impl Into<String>) -> Self {
+        self.stats = Some(register_ring(name, self.buffer.len()));
+        self
+    }
+
+    /// Collect `stats()` without publishing them to `kaos::insights`
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(RingStats::new("", self.buffer.len())));
+        self
+    }
+
+    /// Diagnostics handle, if enabled via `with_insights`
+    pub fn insights(&self) -> Option<&Arc<RingStats>> {
+        self.stats.as_ref()
+    }
+
+    /// Counters and latency histogram, if enabled via `with_stats`/`with_insights`
+    pub fn stats(&self) -> Option<RingBufferStats> {
+        self.stats.as_ref().map(|s| s.snapshot())
+    }
+
+    /// Account claims per consumer for ids `0..count` (`try_read_batch_as`)
+    pub fn with_consumers(mut self, count: usize) -> Self {
+        self.completion_tracker = self.completion_tracker.with_consumers(count);
+        self
+    }
+
+    /// Hand claims stalled past `policy.timeout` to the next consumer asking for work
+    pub fn with_starvation_policy(mut self, policy: StarvationPolicy) -> Self {
+        self.completion_tracker = self.completion_tracker.with_starvation_policy(policy);
+        self
+    }
+
+    /// Claim up to `max_count` slots for `consumer`; read them with `read_slot`
+    /// and finish with `complete_read_as`. See `CompletionTracker::try_claim_as`.
+    pub fn try_read_batch_as(&self, consumer: usize, max_count: usize) -> Option<(u64, usize)> {
+        let producer_seq = self.producer_cursor.load(Ordering::Relaxed);
+        let claim = self
+            .completion_tracker
+            .try_claim_as(consumer, max_count, producer_seq)?;
+        std::sync::atomic::fence(Ordering::Acquire);
+        Some(claim)
+    }
+
+    /// Complete `consumer`'s claim; false if it was reassigned to another consumer
+    pub fn complete_read_as(&self, consumer: usize, start: u64, count: usize) -> bool {
+        let done = self.completion_tracker.complete_as(consumer, start, count);
+        if done {
+            self.note_consume(start, count);
+        }
+        done
+    }
+
+    /// Consumer whose claim is holding back `completed_cursor`
+    pub fn holding_back(&self) -> Option<usize> {
+        self.completion_tracker.holding_back()
+    }
+
+    pub fn consumer_stats(&self, consumer: usize) -> ConsumerClaimStats {
+        self.completion_tracker.consumer_stats(consumer)
+    }
+
     pub fn try_claim(&self, count: usize, current_cursor: u64) -> Option<u64> {
         let next = current_cursor + (count as u64);
         let consumer_seq = self.completion_tracker.completed_cursor();
         if (self.buffer.len() as u64) - (next - consumer_seq) > 0 {
+            if let Some(stats) = &self.stats {
+                stats.record_claim(next - consumer_seq);
+            }
             Some(next)
         } else {
+            if let Some(stats) = &self.stats {
+                stats.record_stall();
+            }
             None
         }
     }
@@ -531,6 +650,9 @@ impl<T: RingBufferEntry> SpmcRingBuffer<T> {
     }
 
     pub fn publish(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(self.producer_cursor.load(Ordering::Relaxed), sequence);
+        }
         std::sync::atomic::fence(Ordering::Release);
         self.producer_cursor.store(sequence, Ordering::Relaxed);
     }
@@ -616,9 +738,17 @@ impl<T: RingBufferEntry> SpmcRingBuffer<T> {
     }
 
     pub fn complete_read(&self, sequence: u64) {
+        self.note_consume(sequence, 1);
         self.completion_tracker.complete(sequence);
     }
 
+    #[inline]
+    fn note_consume(&self, start: u64, count: usize) {
+        if let Some(stats) = &self.stats {
+            stats.record_consume(start, start + count as u64);
+        }
+    }
+
     pub fn get_read_batch_fast(&self, consumer_cursor: u64, max_count: usize) -> &[T] {
         let producer_seq = self.producer_cursor.load(Ordering::Acquire);
         let available = producer_seq.saturating_sub(consumer_cursor) as usize;
@@ -638,6 +768,9 @@ impl<T: RingBufferEntry> SpmcRingBuffer<T> {
     }
 
     pub fn update_consumer_fast(&self, cursor: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_consume(self.completion_tracker.completed_cursor(), cursor);
+        }
         self.completion_tracker.set_completed_cursor(cursor);
     }
     pub fn producer_cursor(&self) -> Arc<AtomicU64> {
@@ -653,9 +786,11 @@ impl<T: RingBufferEntry> ReadableRing<T> for SpmcRingBuffer<T> {
         &self.buffer[(sequence as usize) & self.mask]
     }
     fn complete_read(&self, sequence: u64) {
+        self.note_consume(sequence, 1);
         self.completion_tracker.complete(sequence);
     }
     fn complete_read_batch(&self, start: u64, count: usize) {
+        self.note_consume(start, count);
         self.completion_tracker.complete_batch(start, count);
     }
 }
@@ -667,16 +802,26 @@ unsafe impl<T: RingBufferEntry> Sync for SpmcRingBuffer<T> {}
 // MPMC - Multi-Producer Multi-Consumer (with availability bitmap)
 // ============================================================================
 
+/// Read cursor of one consumer group, on its own cache line
+#[repr(align(128))]
+struct GroupCursor(AtomicU64);
+
+/// Consumers compete for each message by default. With `with_consumer_groups`
+/// every group sees every message and members of a group compete within it
+/// (e.g. a metrics sampler in one group, a worker pool in another).
 pub struct MpmcRingBuffer<T: RingBufferEntry> {
     buffer: Box<[T]>,
     size: usize,
     mask: usize,
     claim_cursor: AtomicU64,
+    /// Release cursor for producers (with groups: the slowest group's cursor)
     consumer_cursor: AtomicU64,
     // Availability bitmap: 1 bit per slot, XOR to flip on publish
     available: Box<[AtomicU64]>,
     index_mask: usize,
     index_shift: usize,
+    groups: Box<[GroupCursor]>,
+    stats: Option<Arc<RingStats>>,
 }
 
 impl<T: RingBufferEntry> MpmcRingBuffer<T> {
@@ -711,9 +856,55 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
             available,
             index_mask: size - 1,
             index_shift: Self::log2(size),
+            groups: Box::new([]),
+            stats: None,
         })
     }
 
+    /// Split consumers into `count` groups that each see every message.
+    /// Read with `try_read_group`/`try_read_batch_group` instead of `try_read`.
+    pub fn with_consumer_groups(mut self, count: usize) -> Result<Self> {
+        if count == 0 {
+            return Err(KaosError::config("Need at least one consumer group"));
+        }
+        let start = self.consumer_cursor.load(Ordering::Relaxed);
+        self.groups = (0..count)
+            .map(|_| GroupCursor(AtomicU64::new(start)))
+            .collect();
+        Ok(self)
+    }
+
+    pub fn consumer_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Next sequence `group` will read
+    pub fn group_cursor(&self, group: usize) -> u64 {
+        self.groups[group].0.load(Ordering::Acquire)
+    }
+
+    /// Track occupancy, stalls and latency under `name` (see `kaos::insights::ring_stats`)
+    pub fn with_insights(mut self, name: impl Into<String>) -> Self {
+        self.stats = Some(register_ring(name, self.size));
+        self
+    }
+
+    /// Collect `stats()` without publishing them to `kaos::insights`
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(RingStats::new("", self.size)));
+        self
+    }
+
+    /// Diagnostics handle, if enabled via `with_insights`
+    pub fn insights(&self) -> Option<&Arc<RingStats>> {
+        self.stats.as_ref()
+    }
+
+    /// Counters and latency histogram, if enabled via `with_stats`/`with_insights`
+    pub fn stats(&self) -> Option<RingBufferStats> {
+        self.stats.as_ref().map(|s| s.snapshot())
+    }
+
     fn log2(i: usize) -> usize {
         std::mem::size_of::<usize>() * 8 - (i.leading_zeros() as usize) - 1
     }
@@ -735,6 +926,9 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
             let consumer_seq = self.consumer_cursor.load(Ordering::Acquire);
 
             if next.wrapping_sub(consumer_seq) > (self.size as u64) {
+                if let Some(stats) = &self.stats {
+                    stats.record_stall();
+                }
                 return None;
             }
 
@@ -745,6 +939,9 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
                 Ordering::Relaxed,
             ) {
                 Ok(_) => {
+                    if let Some(stats) = &self.stats {
+                        stats.record_claim(next.wrapping_sub(consumer_seq));
+                    }
                     return Some(current);
                 }
                 Err(_) => std::hint::spin_loop(),
@@ -800,6 +997,9 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
     /// Publish single slot (XOR flip bit)
     #[inline]
     pub fn publish(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(sequence, sequence + 1);
+        }
         let (avail_idx, bit_idx) = self.calculate_indices(sequence);
         self.available[avail_idx].fetch_xor(1u64 << bit_idx, Ordering::Release);
     }
@@ -810,6 +1010,9 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
         if count == 0 {
             return;
         }
+        if let Some(stats) = &self.stats {
+            stats.record_publish(start, start + count as u64);
+        }
 
         let (mut avail_idx, mut bit_idx) = self.calculate_indices(start);
         let mut flip_mask = 0u64;
@@ -837,11 +1040,17 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
     /// Uses trailing_zeros() for O(1) per 64 slots instead of O(64)
     #[inline]
     pub fn get_published_sequence(&self) -> u64 {
+        self.published_end().saturating_sub(1)
+    }
+
+    /// First sequence not yet published (exclusive end of readable data)
+    #[inline]
+    fn published_end(&self) -> u64 {
         let consumer = self.consumer_cursor.load(Ordering::Relaxed);
         let claimed = self.claim_cursor.load(Ordering::Acquire);
 
         if consumer >= claimed {
-            return consumer.saturating_sub(1);
+            return consumer;
         }
 
         let mut seq = consumer;
@@ -878,7 +1087,7 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
             }
         }
 
-        seq.min(claimed).saturating_sub(1)
+        seq.min(claimed)
     }
 
     /// Read a value from a slot (safe, with bounds checking).
@@ -924,10 +1133,63 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
     }
 
     pub fn update_consumer(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_consume(self.consumer_cursor.load(Ordering::Relaxed), sequence);
+        }
         self.consumer_cursor.store(sequence, Ordering::Release);
     }
 
+    /// Claim the next message for `group` (competing with the group's other members)
+    pub fn try_read_group(&self, group: usize) -> Option<(u64, &T)> {
+        let (seq, batch) = self.try_read_batch_group(group, 1)?;
+        Some((seq, &batch[0]))
+    }
+
+    /// Claim up to `max_count` messages for `group`. Stops at the buffer end on wrap.
+    pub fn try_read_batch_group(&self, group: usize, max_count: usize) -> Option<(u64, &[T])> {
+        let cursor = &self.groups[group].0;
+        let end = self.published_end();
+        let start = cursor.load(Ordering::Relaxed);
+        if start >= end || max_count == 0 {
+            return None;
+        }
+        let start_idx = (start as usize) & self.mask;
+        let count = ((end - start) as usize)
+            .min(max_count)
+            .min(self.size - start_idx);
+        cursor
+            .compare_exchange_weak(
+                start,
+                start + count as u64,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .ok()?;
+        self.release_groups();
+        Some((start, &self.buffer[start_idx..start_idx + count]))
+    }
+
+    /// Let producers reuse everything the slowest group has read
+    fn release_groups(&self) {
+        let slowest = self
+            .groups
+            .iter()
+            .map(|g| g.0.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(0);
+        let prev = self.consumer_cursor.fetch_max(slowest, Ordering::Release);
+        if slowest > prev {
+            if let Some(stats) = &self.stats {
+                stats.record_consume(prev, slowest);
+            }
+        }
+    }
+
     pub fn try_read(&self) -> Option<(u64, &T)> {
+        debug_assert!(
+            self.groups.is_empty(),
+            "use try_read_group with consumer groups"
+        );
         let published = self.get_published_sequence();
         let consumer = self.consumer_cursor.load(Ordering::Relaxed);
 
@@ -945,6 +1207,9 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
             Ordering::Relaxed,
         ) {
             Ok(_) => {
+                if let Some(stats) = &self.stats {
+                    stats.record_consume(consumer, consumer + 1);
+                }
                 let idx = (consumer as usize) & self.mask;
                 Some((consumer, &self.buffer[idx]))
             }
@@ -953,6 +1218,10 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
     }
 
     pub fn try_read_batch(&self, max_count: usize) -> Option<(u64, &[T])> {
+        debug_assert!(
+            self.groups.is_empty(),
+            "use try_read_batch_group with consumer groups"
+        );
         let published = self.get_published_sequence();
         let consumer = self.consumer_cursor.load(Ordering::Relaxed);
 
@@ -972,6 +1241,9 @@ impl<T: RingBufferEntry> MpmcRingBuffer<T> {
             Ordering::Relaxed,
         ) {
             Ok(_) => {
+                if let Some(stats) = &self.stats {
+                    stats.record_consume(consumer, consumer + count as u64);
+                }
                 let start_idx = (consumer as usize) & self.mask;
                 let end_idx = start_idx + count;
 
@@ -1235,6 +1507,37 @@ mod tests {
         assert_eq!(ring.completed_cursor(), 1);
     }
 
+    #[test]
+    fn test_spmc_starved_consumer_reassigned() {
+        let ring = SpmcRingBuffer::<Slot8>::new(8)
+            .unwrap()
+            .with_consumers(2)
+            .with_starvation_policy(StarvationPolicy {
+                timeout: std::time::Duration::from_millis(1),
+            });
+        for i in 0..7 {
+            ring.write_slot(i, Slot8 { value: i }).unwrap();
+        }
+        ring.publish(7);
+
+        let (start, count) = ring.try_read_batch_as(0, 4).unwrap();
+        assert_eq!((start, count), (0, 4));
+        let (start, count) = ring.try_read_batch_as(1, 4).unwrap();
+        assert!(ring.complete_read_as(1, start, count));
+        // Consumer 0 stalls: the producer can't reuse its slots
+        assert_eq!(ring.holding_back(), Some(0));
+        assert!(ring.try_claim(1, 7).is_none());
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let (start, count) = ring.try_read_batch_as(1, 4).unwrap();
+        assert_eq!((start, count), (0, 4));
+        assert_eq!(ring.read_slot(start).unwrap().value, 0);
+        assert!(ring.complete_read_as(1, start, count));
+        assert_eq!(ring.completed_cursor(), 7);
+        assert!(ring.try_claim(1, 7).is_some());
+        assert_eq!(ring.consumer_stats(0).reassigned, 1);
+    }
+
     #[test]
     fn test_spmc_batch() {
         let ring = SpmcRingBuffer::<Slot8>::new(1024).unwrap();
@@ -1281,6 +1584,62 @@ mod tests {
         assert!(published >= seq + 63);
     }
 
+    #[test]
+    fn test_mpmc_groups_each_see_all() {
+        // Group 0: one sampler. Group 1: two workers sharing the load.
+        let ring = MpmcRingBuffer::<Slot8>::new(64)
+            .unwrap()
+            .with_consumer_groups(2)
+            .unwrap();
+        assert!(ring.try_read_group(0).is_none());
+        let seq = ring.try_claim(64).unwrap();
+        for i in 0..64 {
+            ring.write_slot(seq + i, Slot8 { value: i }).unwrap();
+        }
+        ring.publish_batch(seq, 64);
+
+        let (mut worker_a, mut worker_b) = (0, 0);
+        while let Some((_, batch)) = ring.try_read_batch_group(1, 10) {
+            worker_a += batch.len();
+            if let Some((_, batch)) = ring.try_read_batch_group(1, 10) {
+                worker_b += batch.len();
+            }
+        }
+        assert_eq!(worker_a + worker_b, 64);
+        assert!(worker_b > 0);
+
+        // The sampler hasn't read anything: no slot can be reused yet
+        assert!(ring.try_claim(1).is_none());
+        let (first, slot) = ring.try_read_group(0).unwrap();
+        assert_eq!((first, slot.value), (0, 0));
+        assert_eq!(ring.consumer_cursor(), 1);
+        let (_, rest) = ring.try_read_batch_group(0, 64).unwrap();
+        assert_eq!(rest.len(), 63);
+        assert_eq!(ring.consumer_cursor(), 64);
+
+        let seq = ring.try_claim(1).unwrap();
+        ring.write_slot(seq, Slot8 { value: 99 }).unwrap();
+        ring.publish(seq);
+        assert_eq!(ring.try_read_group(0).unwrap().1.value, 99);
+        assert_eq!(ring.try_read_group(1).unwrap().1.value, 99);
+        assert!(ring.try_read_group(1).is_none());
+    }
+
+    #[test]
+    fn test_mpmc_stats() {
+        let ring = MpmcRingBuffer::<Slot8>::new(64).unwrap().with_stats();
+        let seq = ring.try_claim(64).unwrap();
+        assert!(ring.try_claim(1).is_none());
+        ring.publish_batch(seq, 64);
+        assert_eq!(ring.try_read_batch(64).unwrap().1.len(), 64);
+
+        let stats = ring.stats().unwrap();
+        assert_eq!((stats.high_water, stats.stalls), (64, 1));
+        assert_eq!(stats.latency.count, 1);
+        assert_eq!(stats.latency.buckets.iter().sum::<u64>(), 1);
+        assert!(MpmcRingBuffer::<Slot8>::new(64).unwrap().stats().is_none());
+    }
+
     #[test]
     fn test_write_slot_unchecked() {
         let ring = MpmcRingBuffer::<Slot8>::new(1024).unwrap();
@@ -0,0 +1,222 @@
+//! Overflow mode: a bounded secondary queue chained behind a ring.
+//!
+//! When the ring is full, events spill into the overflow queue instead of
+//! failing; `drain` moves them into the ring (in order) once consumers free
+//! slots. Sequences are assigned up front, so a spilled event keeps the
+//! sequence it was given at `publish_with`.
+//!
+//! ```rust
+//! use kaos::disruptor::{OverflowRingBuffer, Placement, RingBufferConfig, Slot16};
+//!
+//! let config = RingBufferConfig::new(2).unwrap();
+//! let mut ring = OverflowRingBuffer::<Slot16>::new(config, 16).unwrap();
+//! for value in 0..3 {
+//!     ring.publish_with(|s| s.value2 = value).unwrap();
+//! }
+//! assert_eq!(ring.queued(), 1); // third event spilled
+//!
+//! ring.ring().advance_consumer(0, 1); // consumer frees the ring
+//! assert_eq!(ring.drain(usize::MAX, |_| {}), 1);
+//! assert_eq!(ring.publish_with(|s| s.value2 = 3), Some(Placement::Ring(3)));
+//! ```
+
+use crate::disruptor::{BroadcastRingBuffer, RingBufferConfig, RingBufferEntry};
+use crate::error::Result;
+use std::collections::VecDeque;
+
+/// Where `publish_with` put an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Published to the ring under this sequence
+    Ring(u64),
+    /// Spilled; will get this sequence when drained
+    Overflow(u64),
+}
+
+impl Placement {
+    pub fn sequence(&self) -> u64 {
+        match *self {
+            Placement::Ring(seq) | Placement::Overflow(seq) => seq,
+        }
+    }
+}
+
+/// `BroadcastRingBuffer` with a bounded overflow queue for bursts.
+pub struct OverflowRingBuffer<T: RingBufferEntry> {
+    ring: BroadcastRingBuffer<T>,
+    overflow: VecDeque<T>,
+    overflow_capacity: usize,
+    high_water: usize,
+}
+
+impl<T: RingBufferEntry> OverflowRingBuffer<T> {
+    /// `overflow_capacity` of 0 disables spilling (plain ring behaviour)
+    pub fn new(config: RingBufferConfig, overflow_capacity: usize) -> Result<Self> {
+        Ok(Self {
+            ring: BroadcastRingBuffer::new(config)?,
+            overflow: VecDeque::with_capacity(overflow_capacity),
+            overflow_capacity,
+            high_water: 0,
+        })
+    }
+
+    /// The ring itself, for consumers (`try_consume_batch`, `advance_consumer`, ...)
+    pub fn ring(&self) -> &BroadcastRingBuffer<T> {
+        &self.ring
+    }
+
+    /// Claiming from the ring directly while events are queued reorders them;
+    /// check `queued() == 0` first.
+    pub fn ring_mut(&mut self) -> &mut BroadcastRingBuffer<T> {
+        &mut self.ring
+    }
+
+    pub fn overflow_capacity(&self) -> usize {
+        self.overflow_capacity
+    }
+
+    /// Resize the overflow queue (never below what is currently queued)
+    pub fn set_overflow_capacity(&mut self, capacity: usize) {
+        self.overflow_capacity = capacity.max(self.overflow.len());
+        self.overflow
+            .reserve(self.overflow_capacity - self.overflow.len());
+    }
+
+    /// Events waiting in the overflow queue
+    pub fn queued(&self) -> usize {
+        self.overflow.len()
+    }
+
+    /// Most events ever queued at once
+    pub fn overflow_high_water(&self) -> usize {
+        self.high_water
+    }
+
+    /// Sequence the next published event will get
+    pub fn next_sequence(&self) -> u64 {
+        self.ring.next_sequence() + self.overflow.len() as u64
+    }
+
+    /// Publish to the ring, or spill if it's full (or older events are still
+    /// queued). `None` if both are full.
+    pub fn publish_with<F: FnOnce(&mut T)>(&mut self, fill: F) -> Option<Placement> {
+        if self.overflow.is_empty() {
+            if let Some((seq, slots)) = self.ring.try_claim_slots(1) {
+                fill(&mut slots[0]);
+                slots[0].set_sequence(seq);
+                self.ring.publish_batch(seq, 1);
+                return Some(Placement::Ring(seq));
+            }
+        }
+        self.defer_with(fill).map(Placement::Overflow)
+    }
+
+    /// Queue without trying the ring (e.g. the caller is rate limited).
+    /// Returns the sequence the event will get, `None` if the queue is full.
+    pub fn defer_with<F: FnOnce(&mut T)>(&mut self, fill: F) -> Option<u64> {
+        if self.overflow.len() >= self.overflow_capacity {
+            return None;
+        }
+        let seq = self.next_sequence();
+        let mut event = T::default();
+        fill(&mut event);
+        event.set_sequence(seq);
+        self.overflow.push_back(event);
+        self.high_water = self.high_water.max(self.overflow.len());
+        Some(seq)
+    }
+
+    /// Move up to `max` queued events into the ring, calling `on_publish`
+    /// for each. Stops when the ring is full. Returns the number moved.
+    pub fn drain<F: FnMut(&T)>(&mut self, max: usize, mut on_publish: F) -> usize {
+        let mut moved = 0;
+        while moved < max && !self.overflow.is_empty() {
+            let Some((seq, slots)) = self.ring.try_claim_slots(1) else {
+                break;
+            };
+            let event = self.overflow.pop_front().expect("checked non-empty");
+            debug_assert_eq!(event.sequence(), seq);
+            slots[0] = event;
+            on_publish(&slots[0]);
+            self.ring.publish_batch(seq, 1);
+            moved += 1;
+        }
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disruptor::Slot16;
+
+    fn ring(size: usize, overflow: usize) -> OverflowRingBuffer<Slot16> {
+        let config = RingBufferConfig::new(size).unwrap();
+        OverflowRingBuffer::new(config, overflow).unwrap()
+    }
+
+    #[test]
+    fn test_burst_spills_and_drains_in_order() {
+        let mut ring = ring(4, 8);
+        let placements: Vec<_> = (0..10)
+            .map(|v| ring.publish_with(|s| s.value2 = v * 10))
+            .collect();
+        assert_eq!(placements[3], Some(Placement::Ring(3)));
+        assert_eq!(placements[4], Some(Placement::Overflow(4)));
+        assert_eq!(placements[9], Some(Placement::Overflow(9)));
+        assert_eq!(ring.queued(), 6);
+        assert_eq!(ring.next_sequence(), 10);
+
+        let mut seen: Vec<u64> = ring
+            .ring()
+            .try_consume_batch(0, 16)
+            .iter()
+            .map(|s| s.value2)
+            .collect();
+        ring.ring().advance_consumer(0, 3);
+        while ring.queued() > 0 {
+            ring.drain(usize::MAX, |_| {});
+            let batch: Vec<u64> = ring
+                .ring()
+                .try_consume_batch(0, 16)
+                .iter()
+                .map(|s| s.value2)
+                .collect();
+            ring.ring()
+                .advance_consumer(0, ring.ring().next_sequence() - 1);
+            seen.extend(batch);
+        }
+        assert_eq!(seen, (0..10).map(|v| v * 10).collect::<Vec<_>>());
+        assert_eq!(ring.overflow_high_water(), 6);
+    }
+
+    #[test]
+    fn test_queued_events_keep_ring_order() {
+        let mut ring = ring(2, 4);
+        ring.publish_with(|_| {});
+        ring.publish_with(|_| {});
+        assert_eq!(ring.publish_with(|_| {}), Some(Placement::Overflow(2)));
+
+        // Ring has room again, but seq 2 is still queued: 3 must queue behind it
+        ring.ring().advance_consumer(0, 1);
+        assert_eq!(ring.publish_with(|_| {}), Some(Placement::Overflow(3)));
+        let mut drained = Vec::new();
+        assert_eq!(ring.drain(usize::MAX, |s| drained.push(s.sequence())), 2);
+        assert_eq!(drained, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_full_overflow_rejects() {
+        let mut ring = ring(2, 1);
+        ring.publish_with(|_| {});
+        ring.publish_with(|_| {});
+        assert!(ring.publish_with(|_| {}).is_some());
+        assert!(ring.publish_with(|_| {}).is_none());
+        assert!(ring.defer_with(|_| {}).is_none());
+
+        let mut plain = self::ring(2, 0);
+        plain.publish_with(|_| {});
+        plain.publish_with(|_| {});
+        assert!(plain.publish_with(|_| {}).is_none());
+    }
+}
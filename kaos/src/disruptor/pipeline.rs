@@ -0,0 +1,339 @@
+//! Managed threads around an `MpscRingBuffer`.
+//!
+//! `Pipeline` owns the consumer loop (and optionally producer loops): it
+//! spawns the threads, pins them to cores, idles with the chosen wait
+//! strategy and on `shutdown` stops producers, drains the ring and hands the
+//! handler back.
+//!
+//! ```rust
+//! use kaos::disruptor::{MpscEventHandler, Pipeline, Slot8, Yielding};
+//!
+//! struct Sum(u64);
+//! impl MpscEventHandler<Slot8> for Sum {
+//!     fn on_event(&mut self, event: &Slot8, _seq: u64, _end_of_batch: bool) {
+//!         self.0 += event.value;
+//!     }
+//! }
+//!
+//! let mut next = 0;
+//! let pipeline = Pipeline::<Slot8>::builder(1024)
+//!     .with_wait_strategy(Yielding)
+//!     .spawn_producer(None, move |producer| {
+//!         if producer.publish(|slot| slot.value = next).is_ok() {
+//!             next += 1;
+//!         }
+//!         next < 100 // stop after 100 events
+//!     })
+//!     .start(Sum(0))
+//!     .unwrap();
+//! while !pipeline.producers_finished() {
+//!     std::thread::yield_now();
+//! }
+//! assert_eq!(pipeline.shutdown().unwrap().0, 4950);
+//! ```
+
+use crate::affinity::pin_to_core;
+use crate::disruptor::{
+    BusySpin, MpscConsumer, MpscEventHandler, MpscProducer, MpscRingBuffer, RingBufferEntry,
+    WaitStrategy,
+};
+use crate::error::{KaosError, Result};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+
+type ProducerLoop<T> = Box<dyn FnMut(&PipelineProducer<T>) -> bool + Send>;
+
+/// `MpscProducer` that wakes the pipeline's consumer after publishing
+pub struct PipelineProducer<T: RingBufferEntry> {
+    inner: MpscProducer<T>,
+    wait: Arc<dyn WaitStrategy>,
+}
+
+impl<T: RingBufferEntry> PipelineProducer<T> {
+    pub fn publish<F: FnOnce(&mut T)>(&self, writer: F) -> std::result::Result<(), &'static str> {
+        self.inner.publish(writer)?;
+        self.wait.signal();
+        Ok(())
+    }
+
+    pub fn publish_batch<F: FnMut(usize, &mut T)>(
+        &self,
+        count: usize,
+        writer: F,
+    ) -> std::result::Result<usize, &'static str> {
+        let published = self.inner.publish_batch(count, writer)?;
+        self.wait.signal();
+        Ok(published)
+    }
+}
+
+pub struct PipelineBuilder<T: RingBufferEntry> {
+    size: usize,
+    batch_size: usize,
+    wait: Arc<dyn WaitStrategy>,
+    name: String,
+    consumer_core: Option<usize>,
+    producers: Vec<(Option<usize>, ProducerLoop<T>)>,
+}
+
+impl<T: RingBufferEntry> PipelineBuilder<T> {
+    /// Events handed to the handler per poll (default 2048)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// What the consumer does when the ring is empty (default: `BusySpin`)
+    pub fn with_wait_strategy(mut self, strategy: impl WaitStrategy + 'static) -> Self {
+        self.wait = Arc::new(strategy);
+        self
+    }
+
+    /// Thread name prefix (default "kaos-pipeline")
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Pin the consumer thread to `core`
+    pub fn with_consumer_core(mut self, core: usize) -> Self {
+        self.consumer_core = Some(core);
+        self
+    }
+
+    /// Run `step` on its own thread (pinned to `core` if given) until it
+    /// returns false or the pipeline shuts down
+    pub fn spawn_producer<F>(mut self, core: Option<usize>, step: F) -> Self
+    where
+        F: FnMut(&PipelineProducer<T>) -> bool + Send + 'static,
+    {
+        self.producers.push((core, Box::new(step)));
+        self
+    }
+
+    /// Spawn the threads. Fails (with nothing left running) if the ring
+    /// can't be created or a thread can't be pinned.
+    pub fn start<H>(self, handler: H) -> Result<Pipeline<T, H>>
+    where
+        H: MpscEventHandler<T> + Send + 'static,
+    {
+        let ring = Arc::new(MpscRingBuffer::new(self.size)?);
+        let mut pipeline = Pipeline {
+            ring: ring.clone(),
+            wait: self.wait.clone(),
+            running: Arc::new(AtomicBool::new(true)),
+            draining: Arc::new(AtomicBool::new(false)),
+            producers: Vec::new(),
+            consumer: None,
+        };
+        let (pinned_tx, pinned_rx) = mpsc::channel();
+        let mut pinned = 0;
+
+        let mut consumer = MpscConsumer::new(ring.clone(), self.batch_size);
+        let (wait, draining) = (self.wait.clone(), pipeline.draining.clone());
+        let mut handler = handler;
+        let consumer_core = self.consumer_core;
+        pinned += consumer_core.is_some() as usize;
+        let tx = pinned_tx.clone();
+        pipeline.consumer = Some(
+            thread::Builder::new()
+                .name(format!("{}-consumer", self.name))
+                .spawn(move || {
+                    if let Some(core) = consumer_core {
+                        let _ = tx.send(pin_to_core(core));
+                    }
+                    let mut idle = 0u32;
+                    loop {
+                        if consumer.process_events(&mut handler) > 0 {
+                            idle = 0;
+                            continue;
+                        }
+                        if draining.load(Ordering::Acquire) {
+                            // Producers are joined: whatever is left was published before the flag
+                            while consumer.process_events(&mut handler) > 0 {}
+                            return handler;
+                        }
+                        wait.idle(idle);
+                        idle = idle.saturating_add(1);
+                    }
+                })?,
+        );
+
+        for (i, (core, mut step)) in self.producers.into_iter().enumerate() {
+            let producer = pipeline.producer();
+            let running = pipeline.running.clone();
+            pinned += core.is_some() as usize;
+            let tx = pinned_tx.clone();
+            pipeline.producers.push(
+                thread::Builder::new()
+                    .name(format!("{}-producer-{}", self.name, i))
+                    .spawn(move || {
+                        if let Some(core) = core {
+                            let _ = tx.send(pin_to_core(core));
+                        }
+                        while running.load(Ordering::Relaxed) && step(&producer) {}
+                    })?,
+            );
+        }
+
+        drop(pinned_tx);
+        for _ in 0..pinned {
+            let pinned = pinned_rx
+                .recv()
+                .map_err(|_| io::Error::other("pipeline thread exited before pinning"))
+                .and_then(|r| r);
+            if let Err(e) = pinned {
+                // Dropping the pipeline stops and joins whatever started
+                return Err(KaosError::Io(e));
+            }
+        }
+        Ok(pipeline)
+    }
+}
+
+/// Running consumer (and producer) threads over one `MpscRingBuffer`.
+/// Dropping it without `shutdown` also stops and joins the threads.
+pub struct Pipeline<T: RingBufferEntry, H = ()> {
+    ring: Arc<MpscRingBuffer<T>>,
+    wait: Arc<dyn WaitStrategy>,
+    /// Cleared to stop producer loops
+    running: Arc<AtomicBool>,
+    /// Set once producers are joined: the consumer drains and exits
+    draining: Arc<AtomicBool>,
+    producers: Vec<JoinHandle<()>>,
+    consumer: Option<JoinHandle<H>>,
+}
+
+impl<T: RingBufferEntry> Pipeline<T, ()> {
+    /// Ring of `size` slots (power of 2)
+    pub fn builder(size: usize) -> PipelineBuilder<T> {
+        PipelineBuilder {
+            size,
+            batch_size: 2048,
+            wait: Arc::new(BusySpin),
+            name: "kaos-pipeline".to_string(),
+            consumer_core: None,
+            producers: Vec::new(),
+        }
+    }
+}
+
+impl<T: RingBufferEntry, H> Pipeline<T, H> {
+    /// Producer handle for threads the application runs itself
+    pub fn producer(&self) -> PipelineProducer<T> {
+        PipelineProducer {
+            inner: MpscProducer::new(self.ring.clone()),
+            wait: self.wait.clone(),
+        }
+    }
+
+    pub fn ring(&self) -> &Arc<MpscRingBuffer<T>> {
+        &self.ring
+    }
+
+    /// Every spawned producer loop has returned false (or panicked)
+    pub fn producers_finished(&self) -> bool {
+        self.producers.iter().all(|p| p.is_finished())
+    }
+
+    /// Stop producer loops, drain the ring, join everything and return the handler
+    pub fn shutdown(mut self) -> Result<H> {
+        self.stop()
+            .ok_or_else(|| KaosError::Io(io::Error::other("pipeline thread panicked")))
+    }
+
+    fn stop(&mut self) -> Option<H> {
+        self.running.store(false, Ordering::Release);
+        // Join every producer, even after one that panicked
+        let joined: Vec<bool> = self.producers.drain(..).map(|p| p.join().is_ok()).collect();
+        let producers_ok = joined.iter().all(|&ok| ok);
+        self.draining.store(true, Ordering::Release);
+        self.wait.signal();
+        let handler = self.consumer.take()?.join().ok();
+        handler.filter(|_| producers_ok)
+    }
+}
+
+impl<T: RingBufferEntry, H> Drop for Pipeline<T, H> {
+    fn drop(&mut self) {
+        if self.consumer.is_some() {
+            self.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disruptor::{Blocking, Slot8};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Collect {
+        count: u64,
+        sum: u64,
+    }
+
+    impl MpscEventHandler<Slot8> for Collect {
+        fn on_event(&mut self, event: &Slot8, _seq: u64, _end_of_batch: bool) {
+            self.count += 1;
+            self.sum += event.value;
+        }
+    }
+
+    #[test]
+    fn test_shutdown_drains_all_producers() {
+        const N: u64 = 10_000;
+        let mut builder = Pipeline::<Slot8>::builder(256)
+            .with_batch_size(64)
+            .with_wait_strategy(Blocking::new(Duration::from_millis(5)));
+        for _ in 0..2 {
+            let mut next = 0;
+            builder = builder.spawn_producer(None, move |producer| {
+                if producer.publish(|slot| slot.value = next).is_ok() {
+                    next += 1;
+                }
+                next < N
+            });
+        }
+        let pipeline = builder.start(Collect::default()).unwrap();
+
+        // Plus a producer the application drives itself
+        let producer = pipeline.producer();
+        while producer.publish(|slot| slot.value = 1).is_err() {}
+
+        // Let the spawned producers finish on their own before stopping
+        while !pipeline.producers_finished() {
+            thread::yield_now();
+        }
+        let collected = pipeline.shutdown().unwrap();
+        assert_eq!(collected.count, 2 * N + 1);
+        assert_eq!(collected.sum, N * (N - 1) + 1);
+    }
+
+    #[test]
+    fn test_shutdown_stops_endless_producer() {
+        let pipeline = Pipeline::<Slot8>::builder(64)
+            .spawn_producer(None, |producer| {
+                let _ = producer.publish(|slot| slot.value = 1);
+                true
+            })
+            .start(Collect::default())
+            .unwrap();
+        thread::sleep(Duration::from_millis(5));
+        let collected = pipeline.shutdown().unwrap();
+        assert!(collected.count > 0);
+        assert_eq!(collected.sum, collected.count);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pin_failure_fails_start() {
+        let result = Pipeline::<Slot8>::builder(64)
+            .with_consumer_core(1023)
+            .start(Collect::default());
+        assert!(result.is_err());
+    }
+}
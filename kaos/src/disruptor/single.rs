@@ -8,8 +8,10 @@
 //! - `new_mapped()` - Memory-mapped with mlock (faster, no page faults)
 //! - `new_broadcast()` - Multiple consumer broadcast pattern
 
-use crate::disruptor::{RingBufferConfig, RingBufferEntry};
+use crate::affinity::bind_to_numa_node;
+use crate::disruptor::{BusySpin, RingBufferConfig, RingBufferEntry, WaitStrategy};
 use crate::error::{KaosError, Result};
+use crate::insights::{register_ring, RingBufferStats, RingStats};
 use std::marker::PhantomData;
 use std::ptr;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -50,6 +52,80 @@ impl PaddedAtomicU64 {
     }
 }
 
+// ============================================================================
+// Shared: Mapped slot storage
+// ============================================================================
+
+/// Anonymous mapping for `size` zeroed slots (mlocked). With `numa_node` the
+/// mapping is bound to that node before its pages are faulted in.
+fn map_slots<T>(size: usize, numa_node: Option<usize>) -> Result<*mut T> {
+    let buffer_size = size
+        .checked_mul(std::mem::size_of::<T>())
+        .ok_or_else(|| KaosError::config("Buffer size overflow"))?;
+    unsafe {
+        let p = libc::mmap(
+            ptr::null_mut(),
+            buffer_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if p == libc::MAP_FAILED {
+            return Err(KaosError::config("mmap failed"));
+        }
+        // Bind before mlock/zeroing fault the pages in
+        if let Some(node) = numa_node {
+            if let Err(e) = bind_to_numa_node(p as *const u8, buffer_size, node) {
+                libc::munmap(p, buffer_size);
+                return Err(e.into());
+            }
+        }
+        let _ = libc::mlock(p, buffer_size);
+        std::ptr::write_bytes(p as *mut u8, 0, buffer_size);
+        Ok(p as *mut T)
+    }
+}
+
+/// Broadcast slots: heap-allocated, or mapped (`map_slots`) when bound to a
+/// NUMA node so the policy covers only the ring's own pages
+enum Slots<T> {
+    Heap(Box<[T]>),
+    Mapped { ptr: *mut T, len: usize },
+}
+
+impl<T> std::ops::Deref for Slots<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Slots::Heap(slots) => slots,
+            // Safety: the mapping holds `len` zero-initialized slots until drop
+            Slots::Mapped { ptr, len } => unsafe { std::slice::from_raw_parts(*ptr, *len) },
+        }
+    }
+}
+
+impl<T> std::ops::DerefMut for Slots<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Slots::Heap(slots) => slots,
+            // Safety: as in `deref`, and `&mut self` is exclusive
+            Slots::Mapped { ptr, len } => unsafe { std::slice::from_raw_parts_mut(*ptr, *len) },
+        }
+    }
+}
+
+impl<T> Drop for Slots<T> {
+    fn drop(&mut self) {
+        if let Slots::Mapped { ptr, len } = *self {
+            unsafe {
+                libc::munmap(ptr as *mut libc::c_void, len * std::mem::size_of::<T>());
+            }
+        }
+    }
+}
+
 // ============================================================================
 // RingBuffer<T> - Simple SPSC (Single Producer, Single Consumer)
 // ============================================================================
@@ -62,6 +138,8 @@ pub struct RingBuffer<T: RingBufferEntry> {
     consumer_cursor: Arc<AtomicU64>,
     _heap: Option<Box<[T]>>,
     is_mapped: bool,
+    stats: Option<Arc<RingStats>>,
+    wait: Option<Arc<dyn WaitStrategy>>,
 }
 
 impl<T: RingBufferEntry> RingBuffer<T> {
@@ -85,34 +163,34 @@ impl<T: RingBufferEntry> RingBuffer<T> {
             consumer_cursor: Arc::new(AtomicU64::new(0)),
             _heap: Some(buffer),
             is_mapped: false,
+            stats: None,
+            wait: None,
         })
     }
 
     /// Create with memory-mapped allocation (mmap + mlock)
     pub fn new_mapped(size: usize) -> Result<Self> {
+        Self::map(size, None)
+    }
+
+    /// Create from a config. With `numa_node` set the buffer is mapped and
+    /// bound to that node; otherwise heap-allocated. `wait_for` uses the
+    /// config's wait strategy.
+    pub fn from_config(config: RingBufferConfig) -> Result<Self> {
+        let mut ring = match config.numa_node {
+            Some(node) => Self::map(config.size, Some(node))?,
+            None => Self::new(config.size)?,
+        };
+        ring.wait = Some(config.wait_strategy);
+        Ok(ring)
+    }
+
+    fn map(size: usize, numa_node: Option<usize>) -> Result<Self> {
         if !size.is_power_of_two() {
             return Err(KaosError::config("Size must be power of 2"));
         }
 
-        let buffer_size = size
-            .checked_mul(std::mem::size_of::<T>())
-            .ok_or_else(|| KaosError::config("Buffer size overflow"))?;
-        let ptr = unsafe {
-            let p = libc::mmap(
-                ptr::null_mut(),
-                buffer_size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
-                -1,
-                0,
-            );
-            if p == libc::MAP_FAILED {
-                return Err(KaosError::config("mmap failed"));
-            }
-            let _ = libc::mlock(p, buffer_size);
-            std::ptr::write_bytes(p as *mut u8, 0, buffer_size);
-            p as *mut T
-        };
+        let ptr = map_slots::<T>(size, numa_node)?;
 
         Ok(Self {
             buffer: ptr,
@@ -122,6 +200,8 @@ impl<T: RingBufferEntry> RingBuffer<T> {
             consumer_cursor: Arc::new(AtomicU64::new(0)),
             _heap: None,
             is_mapped: true,
+            stats: None,
+            wait: None,
         })
     }
 
@@ -130,6 +210,85 @@ impl<T: RingBufferEntry> RingBuffer<T> {
         BroadcastRingBuffer::new(config)
     }
 
+    /// Track occupancy, stalls and consumer lag under `name` (see `kaos::insights::ring_stats`)
+    pub fn with_insights(mut self, name: impl Into<String>) -> Self {
+        self.stats = Some(register_ring(name, self.size));
+        self
+    }
+
+    /// Collect `stats()` without publishing them to `kaos::insights`
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(RingStats::new("", self.size)));
+        self
+    }
+
+    /// Diagnostics handle, if enabled via `with_insights`
+    pub fn insights(&self) -> Option<&Arc<RingStats>> {
+        self.stats.as_ref()
+    }
+
+    /// Counters and latency histogram, if enabled via `with_stats`/`with_insights`
+    pub fn stats(&self) -> Option<RingBufferStats> {
+        self.stats.as_ref().map(|s| s.snapshot())
+    }
+
+    /// Set what `wait_for` does while the ring is empty (default: busy-spin)
+    pub fn with_wait_strategy(mut self, strategy: impl WaitStrategy + 'static) -> Self {
+        self.wait = Some(Arc::new(strategy));
+        self
+    }
+
+    /// Wait until the producer has published past `sequence`.
+    /// Returns the producer cursor (exclusive end of readable sequences).
+    pub fn wait_for(&self, sequence: u64) -> u64 {
+        let wait: &dyn WaitStrategy = self.wait.as_deref().unwrap_or(&BusySpin);
+        let mut idle = 0u32;
+        loop {
+            let produced = self.producer_cursor.load(Ordering::Acquire);
+            if produced > sequence {
+                return produced;
+            }
+            wait.idle(idle);
+            idle = idle.saturating_add(1);
+        }
+    }
+
+    /// Strategy for consumers gating on this ring (busy-spin if unset)
+    pub(crate) fn wait_strategy(&self) -> Arc<dyn WaitStrategy> {
+        self.wait.clone().unwrap_or_else(|| Arc::new(BusySpin))
+    }
+
+    #[inline]
+    fn signal(&self) {
+        if let Some(wait) = &self.wait {
+            wait.signal();
+        }
+    }
+
+    #[inline]
+    fn note_claim(&self, next: u64, consumer_seq: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_claim(next.wrapping_sub(consumer_seq));
+        }
+    }
+
+    #[inline]
+    fn note_stall(&self) {
+        if let Some(stats) = &self.stats {
+            stats.record_stall();
+        }
+    }
+
+    /// Move the producer cursor from `from` to `to` and wake waiters
+    #[inline]
+    fn store_producer(&self, from: u64, to: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(from, to);
+        }
+        self.producer_cursor.store(to, Ordering::Release);
+        self.signal();
+    }
+
     pub fn consumer_cursor(&self) -> Arc<AtomicU64> {
         self.consumer_cursor.clone()
     }
@@ -141,8 +300,10 @@ impl<T: RingBufferEntry> RingBuffer<T> {
         let next = local_cursor + (count as u64);
         let consumer_seq = self.consumer_cursor.load(Ordering::Relaxed);
         if next.wrapping_sub(consumer_seq) < (self.size as u64) {
+            self.note_claim(next, consumer_seq);
             Some(next)
         } else {
+            self.note_stall();
             None
         }
     }
@@ -199,8 +360,10 @@ impl<T: RingBufferEntry> RingBuffer<T> {
                 );
                 std::slice::from_raw_parts_mut(self.buffer.add(start_idx), len)
             };
+            self.note_claim(local_cursor + slots.len() as u64, consumer_seq);
             Some((local_cursor, slots))
         } else {
+            self.note_stall();
             None
         }
     }
@@ -219,8 +382,10 @@ impl<T: RingBufferEntry> RingBuffer<T> {
             let slot = unsafe { &mut *self.buffer.add(idx) };
             update(slot);
             self.publish(next);
+            self.note_claim(next, consumer_seq);
             Some(local_cursor)
         } else {
+            self.note_stall();
             None
         }
     }
@@ -257,8 +422,10 @@ impl<T: RingBufferEntry> RingBuffer<T> {
             }
 
             self.publish(local_cursor + (available as u64));
+            self.note_claim(local_cursor + (available as u64), consumer_seq);
             Some((local_cursor, available))
         } else {
+            self.note_stall();
             None
         }
     }
@@ -338,8 +505,12 @@ impl<T: RingBufferEntry> RingBuffer<T> {
     }
 
     pub fn publish(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(self.producer_cursor.load(Ordering::Relaxed), sequence);
+        }
         std::sync::atomic::fence(Ordering::Release);
         self.producer_cursor.store(sequence, Ordering::Relaxed);
+        self.signal();
     }
 
     /// Read a value from a slot (safe, with bounds checking).
@@ -390,7 +561,37 @@ impl<T: RingBufferEntry> RingBuffer<T> {
     }
 
     pub fn update_consumer(&self, sequence: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_consume(self.consumer_cursor.load(Ordering::Relaxed), sequence);
+        }
         self.consumer_cursor.store(sequence, Ordering::Relaxed);
+        if let Some(stats) = &self.stats {
+            let produced = self.producer_cursor.load(Ordering::Relaxed);
+            stats.record_consumer_lag(produced.saturating_sub(sequence));
+        }
+    }
+
+    /// Hand up to `max` published events from `cursor` to `handler` as
+    /// `(event, sequence, end_of_batch)`, then release them to the producer.
+    /// Returns the new cursor.
+    pub fn poll<F>(&self, cursor: u64, max: usize, mut handler: F) -> u64
+    where
+        F: FnMut(&T, u64, bool),
+    {
+        let produced = self.producer_cursor.load(Ordering::Acquire);
+        let end = produced.min(cursor + (max as u64));
+        if end <= cursor {
+            return cursor;
+        }
+        let mut seq = cursor;
+        while seq < end {
+            for event in self.get_read_batch(seq, (end - seq) as usize) {
+                handler(event, seq, seq + 1 == end);
+                seq += 1;
+            }
+        }
+        self.update_consumer(end);
+        end
     }
 }
 
@@ -449,10 +650,12 @@ impl<T: RingBufferEntry> CachedProducer<T> {
             let free_slots = (self.ring.size as u64).wrapping_sub(seq.wrapping_sub(consumer_seq));
 
             if free_slots == 0 {
+                self.ring.note_stall();
                 return None;
             }
 
             self.sequence_clear_of_consumers = consumer_seq + (self.ring.size as u64) - 1;
+            self.ring.note_claim(seq + 1, consumer_seq);
         }
 
         let idx = (seq as usize) & self.ring.mask;
@@ -460,9 +663,7 @@ impl<T: RingBufferEntry> CachedProducer<T> {
         update(slot);
 
         self.sequence += 1;
-        self.ring
-            .producer_cursor
-            .store(self.sequence, Ordering::Release);
+        self.ring.store_producer(seq, self.sequence);
 
         Some(seq)
     }
@@ -473,6 +674,7 @@ impl<T: RingBufferEntry> CachedProducer<T> {
     where
         F: FnMut(&mut T),
     {
+        let mut stalled = false;
         loop {
             let seq = self.sequence;
 
@@ -481,9 +683,7 @@ impl<T: RingBufferEntry> CachedProducer<T> {
                 let slot = unsafe { &mut *self.ring.buffer.add(idx) };
                 update(slot);
                 self.sequence += 1;
-                self.ring
-                    .producer_cursor
-                    .store(self.sequence, Ordering::Release);
+                self.ring.store_producer(seq, self.sequence);
                 return;
             }
 
@@ -492,16 +692,20 @@ impl<T: RingBufferEntry> CachedProducer<T> {
 
             if free_slots > 0 {
                 self.sequence_clear_of_consumers = consumer_seq + (self.ring.size as u64) - 1;
+                self.ring.note_claim(seq + 1, consumer_seq);
                 let idx = (seq as usize) & self.ring.mask;
                 let slot = unsafe { &mut *self.ring.buffer.add(idx) };
                 update(slot);
                 self.sequence += 1;
-                self.ring
-                    .producer_cursor
-                    .store(self.sequence, Ordering::Release);
+                self.ring.store_producer(seq, self.sequence);
                 return;
             }
 
+            // Count one stall per publish, not per spin
+            if !stalled {
+                stalled = true;
+                self.ring.note_stall();
+            }
             std::hint::spin_loop();
         }
     }
@@ -526,10 +730,12 @@ impl<T: RingBufferEntry> CachedProducer<T> {
                 (self.ring.size as u64).wrapping_sub(start_seq.wrapping_sub(consumer_seq));
 
             if free_slots < (count as u64) {
+                self.ring.note_stall();
                 return None;
             }
 
             self.sequence_clear_of_consumers = consumer_seq + (self.ring.size as u64) - 1;
+            self.ring.note_claim(end_seq + 1, consumer_seq);
         }
 
         // Write all slots
@@ -544,9 +750,7 @@ impl<T: RingBufferEntry> CachedProducer<T> {
 
         // Publish
         self.sequence = start_seq + (actual as u64);
-        self.ring
-            .producer_cursor
-            .store(self.sequence, Ordering::Release);
+        self.ring.store_producer(start_seq, self.sequence);
 
         Some((start_seq, actual))
     }
@@ -558,11 +762,12 @@ impl<T: RingBufferEntry> CachedProducer<T> {
 
 pub struct BroadcastRingBuffer<T: RingBufferEntry> {
     config: RingBufferConfig,
-    buffer: Box<[T]>,
+    buffer: Slots<T>,
     mask: usize,
     producer_sequence: PaddedAtomicU64,
     consumer_sequences: Vec<PaddedAtomicU64>,
     gating_sequence: PaddedAtomicU64,
+    stats: Option<Arc<RingStats>>,
 }
 
 unsafe impl<T: RingBufferEntry> Send for BroadcastRingBuffer<T> {}
@@ -575,10 +780,18 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
         }
 
         let mask = config.size - 1;
-        let buffer = (0..config.size)
-            .map(|_| T::default())
-            .collect::<Vec<_>>()
-            .into_boxed_slice();
+        let buffer = match config.numa_node {
+            Some(node) => Slots::Mapped {
+                ptr: map_slots::<T>(config.size, Some(node))?,
+                len: config.size,
+            },
+            None => Slots::Heap(
+                (0..config.size)
+                    .map(|_| T::default())
+                    .collect::<Vec<_>>()
+                    .into_boxed_slice(),
+            ),
+        };
         let consumer_sequences = (0..config.num_consumers)
             .map(|_| PaddedAtomicU64::new(u64::MAX))
             .collect();
@@ -590,9 +803,62 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
             producer_sequence: PaddedAtomicU64::new(u64::MAX),
             consumer_sequences,
             gating_sequence: PaddedAtomicU64::new(u64::MAX),
+            stats: None,
         })
     }
 
+    /// Track occupancy, stalls and slowest-consumer lag under `name`
+    pub fn with_insights(mut self, name: impl Into<String>) -> Self {
+        self.stats = Some(register_ring(name, self.config.size));
+        self
+    }
+
+    /// Collect `stats()` without publishing them to `kaos::insights`
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(RingStats::new("", self.config.size)));
+        self
+    }
+
+    /// Diagnostics handle, if enabled via `with_insights`
+    pub fn insights(&self) -> Option<&Arc<RingStats>> {
+        self.stats.as_ref()
+    }
+
+    /// Counters and latency histogram (per consumer delivery), if enabled
+    /// via `with_stats`/`with_insights`
+    pub fn stats(&self) -> Option<RingBufferStats> {
+        self.stats.as_ref().map(|s| s.snapshot())
+    }
+
+    /// `next` is the last claimed sequence, `min_consumer` the gating sequence
+    #[inline]
+    fn note_claim(&self, next: u64, min_consumer: u64) {
+        if let Some(stats) = &self.stats {
+            let occupancy = if min_consumer == u64::MAX {
+                next.wrapping_add(1)
+            } else {
+                next.wrapping_sub(min_consumer)
+            };
+            stats.record_claim(occupancy);
+        }
+    }
+
+    #[inline]
+    fn note_stall(&self) {
+        if let Some(stats) = &self.stats {
+            stats.record_stall();
+        }
+    }
+
+    /// A consumer took `[first, last]`
+    #[inline]
+    fn note_consumed(&self, producer: u64, first: u64, last: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_consume(first, last + 1);
+            stats.record_consumer_lag(producer.saturating_sub(last));
+        }
+    }
+
     pub fn try_claim_slots(&mut self, count: usize) -> Option<(u64, &mut [T])> {
         if count == 0 {
             return None;
@@ -608,9 +874,11 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
         let min_consumer = self.gating_sequence.load(Ordering::Relaxed);
         if min_consumer == u64::MAX {
             if next > (self.config.size as u64) - 1 {
+                self.note_stall();
                 return None;
             }
         } else if next > min_consumer + (self.config.size as u64) {
+            self.note_stall();
             return None;
         }
 
@@ -621,6 +889,7 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
             Ordering::Relaxed,
         ) {
             Ok(_) => {
+                self.note_claim(next, min_consumer);
                 let start = (slot_seq as usize) & self.mask;
                 let end = ((slot_seq + (count as u64)) as usize) & self.mask;
                 let slots = if start < end {
@@ -658,6 +927,7 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
         let mut min_consumer = self.gating_sequence.load(Ordering::Relaxed);
         if min_consumer == u64::MAX {
             if next > (self.config.size as u64) {
+                self.note_stall();
                 return None;
             }
         } else if next.wrapping_sub(min_consumer) > (self.config.size as u64) {
@@ -666,9 +936,11 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
             std::sync::atomic::fence(Ordering::Acquire);
             min_consumer = self.gating_sequence.load(Ordering::Relaxed);
             if next.wrapping_sub(min_consumer) > (self.config.size as u64) {
+                self.note_stall();
                 return None;
             }
         }
+        self.note_claim(next, min_consumer);
 
         let start_seq = current.wrapping_add(1);
         let start_idx = (start_seq as usize) & self.mask;
@@ -678,8 +950,19 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
         Some((start_seq, slots))
     }
 
-    pub fn publish_batch_relaxed(&self, _start: u64, end: u64) {
+    /// Sequence the next claimed slot will get
+    pub fn next_sequence(&self) -> u64 {
+        self.producer_sequence
+            .load(Ordering::Acquire)
+            .wrapping_add(1)
+    }
+
+    pub fn publish_batch_relaxed(&self, start: u64, end: u64) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(start, end + 1);
+        }
         self.producer_sequence.store(end, Ordering::Release);
+        self.config.wait_strategy.signal();
     }
 
     pub fn try_consume_batch(&self, consumer_id: usize, max_count: usize) -> Vec<&T> {
@@ -725,6 +1008,7 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
             if new_seq % 1000 < (messages.len() as u64) {
                 self.update_gating_sequence();
             }
+            self.note_consumed(producer, first, new_seq);
         }
         messages
     }
@@ -759,16 +1043,60 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
             return &[];
         }
 
-        let new_seq = start_seq + (actual as u64) - 1;
-        self.consumer_sequences[consumer_id].store(new_seq, Ordering::Relaxed);
-        if new_seq % 1000 < (actual as u64) {
+        self.release(consumer_id, producer, start_seq, actual);
+        &self.buffer[start_idx..start_idx + actual]
+    }
+
+    /// Mark `count` events from `first` consumed by `consumer_id`, freeing
+    /// their slots for the producer
+    #[inline]
+    fn release(&self, consumer_id: usize, producer: u64, first: u64, count: usize) {
+        let new_seq = first + (count as u64) - 1;
+        self.consumer_sequences[consumer_id].store(new_seq, Ordering::Release);
+        if new_seq % 1000 < (count as u64) {
             self.update_gating_sequence();
         }
-        &self.buffer[start_idx..start_idx + actual]
+        self.note_consumed(producer, first, new_seq);
     }
 
-    pub fn publish_batch(&self, _start: u64, _count: usize) {
+    /// Consume up to `max` events for `consumer_id`, handing each to `handler`
+    /// as `(event, sequence, end_of_batch)`. Returns the number handled.
+    pub fn poll<F>(&self, consumer_id: usize, max: usize, mut handler: F) -> usize
+    where
+        F: FnMut(&T, u64, bool),
+    {
+        let producer = self.producer_sequence.load(Ordering::Acquire);
+        // u64::MAX (nothing consumed yet / published yet) wraps to 0
+        let start = self.consumer_sequences[consumer_id]
+            .load(Ordering::Relaxed)
+            .wrapping_add(1);
+        let published = producer.wrapping_add(1);
+        if published <= start {
+            return 0;
+        }
+        let count = (published - start).min(max as u64) as usize;
+        if count == 0 {
+            return 0;
+        }
+        // Batch runs past the end of the buffer: the wrapped part is at the front
+        let start_idx = (start as usize) & self.mask;
+        let head_len = count.min(self.config.size - start_idx);
+        let head = &self.buffer[start_idx..start_idx + head_len];
+        let tail = &self.buffer[..count - head_len];
+        for (i, event) in head.iter().chain(tail).enumerate() {
+            handler(event, start + (i as u64), i == count - 1);
+        }
+        // Only now may the producer reuse the slots
+        self.release(consumer_id, producer, start, count);
+        count
+    }
+
+    pub fn publish_batch(&self, start: u64, count: usize) {
+        if let Some(stats) = &self.stats {
+            stats.record_publish(start, start + count as u64);
+        }
         std::sync::atomic::fence(Ordering::Release);
+        self.config.wait_strategy.signal();
     }
 
     pub fn peek_batch(&self, consumer_id: usize, max_count: usize) -> Vec<&T> {
@@ -818,6 +1146,10 @@ impl<T: RingBufferEntry> BroadcastRingBuffer<T> {
 
     pub fn advance_consumer(&self, consumer_id: usize, seq: u64) {
         if consumer_id < self.consumer_sequences.len() {
+            if let Some(stats) = &self.stats {
+                let last = self.consumer_sequences[consumer_id].load(Ordering::Relaxed);
+                stats.record_consume(last.wrapping_add(1), seq.wrapping_add(1));
+            }
             self.consumer_sequences[consumer_id].store(seq, Ordering::Release);
             self.update_gating_sequence();
         }
@@ -942,15 +1274,104 @@ impl<T: RingBufferEntry> Consumer<T> {
         handler: &mut H,
         stop_flag: &std::sync::atomic::AtomicBool,
     ) {
+        let wait = &self.ring_buffer.config.wait_strategy;
+        let mut idle = 0u32;
         while !stop_flag.load(Ordering::Relaxed) {
             if self.process_events(handler) == 0 {
-                std::hint::spin_loop();
+                wait.idle(idle);
+                idle = idle.saturating_add(1);
+            } else {
+                idle = 0;
             }
         }
         while self.process_events(handler) > 0 {}
     }
 }
 
+/// Pull-style consumer with end-of-batch hints, for `RingBuffer` and
+/// `BroadcastRingBuffer` alike (the `MpscConsumer` model).
+///
+/// ```rust
+/// use kaos::disruptor::{EventPoller, RingBuffer, Slot8};
+/// use std::sync::Arc;
+///
+/// let ring = Arc::new(RingBuffer::<Slot8>::new(64).unwrap());
+/// let mut poller = EventPoller::new(ring.clone());
+/// ring.try_publish_with(0, |s| s.value = 1).unwrap();
+/// ring.try_publish_with(1, |s| s.value = 2).unwrap();
+///
+/// let mut flushed = 0;
+/// poller.poll(|_event, _seq, end_of_batch| {
+///     if end_of_batch {
+///         flushed += 1; // flush downstream work once per batch
+///     }
+/// });
+/// assert_eq!(flushed, 1);
+/// ```
+pub struct EventPoller<T: RingBufferEntry> {
+    source: PollSource<T>,
+    batch_size: usize,
+}
+
+enum PollSource<T: RingBufferEntry> {
+    Single {
+        ring: Arc<RingBuffer<T>>,
+        cursor: u64,
+    },
+    Broadcast {
+        ring: Arc<BroadcastRingBuffer<T>>,
+        consumer_id: usize,
+    },
+}
+
+impl<T: RingBufferEntry> EventPoller<T> {
+    /// Poll a `RingBuffer` from its current consumer cursor
+    pub fn new(ring: Arc<RingBuffer<T>>) -> Self {
+        let cursor = ring.consumer_cursor.load(Ordering::Acquire);
+        Self {
+            source: PollSource::Single { ring, cursor },
+            batch_size: 2048,
+        }
+    }
+
+    /// Poll a `BroadcastRingBuffer` as `consumer_id`
+    pub fn broadcast(ring: Arc<BroadcastRingBuffer<T>>, consumer_id: usize) -> Self {
+        assert!(
+            consumer_id < ring.consumer_sequences.len(),
+            "consumer_id out of range"
+        );
+        Self {
+            source: PollSource::Broadcast { ring, consumer_id },
+            batch_size: 2048,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Hand available events (up to the batch size) to `handler` as
+    /// `(event, sequence, end_of_batch)`. Returns the number handled.
+    pub fn poll<F: FnMut(&T, u64, bool)>(&mut self, handler: F) -> usize {
+        match &mut self.source {
+            PollSource::Single { ring, cursor } => {
+                let start = *cursor;
+                *cursor = ring.poll(start, self.batch_size, handler);
+                (*cursor - start) as usize
+            }
+            PollSource::Broadcast { ring, consumer_id } => {
+                ring.poll(*consumer_id, self.batch_size, handler)
+            }
+        }
+    }
+
+    /// `poll` with an `EventHandler`
+    pub fn poll_handler<H: EventHandler<T>>(&mut self, handler: &mut H) -> usize {
+        self.poll(|event, seq, end_of_batch| handler.on_event(event, seq, end_of_batch))
+    }
+}
+
 pub struct ConsumerBuilder<T: RingBufferEntry> {
     ring_buffer: Option<Arc<BroadcastRingBuffer<T>>>,
     consumer_id: usize,
@@ -1007,6 +1428,144 @@ mod tests {
         assert_eq!(ring.producer_cursor().load(Ordering::Relaxed), 0);
     }
 
+    #[test]
+    fn test_spsc_insights() {
+        let ring = RingBuffer::<Slot8>::new(8)
+            .unwrap()
+            .with_insights("spsc-insights");
+        let mut cursor = 0;
+        while let Some(seq) = ring.try_publish_with(cursor, |s| s.value = cursor) {
+            cursor = seq + 1;
+        }
+        assert!(ring.try_publish_with(cursor, |_| {}).is_none());
+        ring.update_consumer(3);
+
+        let snap = ring.insights().unwrap().snapshot();
+        assert_eq!(snap.high_water, 7);
+        assert_eq!(snap.stalls, 2);
+        assert_eq!(snap.consumer_lag, 4);
+        assert!(crate::insights::ring_stats()
+            .iter()
+            .any(|r| r.name == "spsc-insights"));
+    }
+
+    #[test]
+    fn test_spsc_stats_latency() {
+        let ring = Arc::new(RingBuffer::<Slot8>::new(256).unwrap().with_stats());
+        for seq in 0..128 {
+            ring.try_publish_with(seq, |s| s.value = seq).unwrap();
+        }
+        assert_eq!(EventPoller::new(ring.clone()).poll(|_, _, _| {}), 128);
+
+        // Sequences 0 and 64 are sampled
+        let stats = ring.stats().unwrap();
+        assert_eq!(stats.high_water, 128);
+        assert_eq!(stats.latency.count, 2);
+        assert_eq!(stats.latency.buckets.iter().sum::<u64>(), 2);
+        // `with_stats` rings stay out of the insights registry
+        assert!(crate::insights::ring_stats()
+            .iter()
+            .all(|r| !r.name.is_empty()));
+    }
+
+    #[test]
+    fn test_spsc_wait_for_blocking() {
+        use crate::disruptor::Blocking;
+        use std::time::Duration;
+
+        let ring = Arc::new(
+            RingBuffer::<Slot8>::new(64)
+                .unwrap()
+                .with_wait_strategy(Blocking::new(Duration::from_secs(10))),
+        );
+        let producer = ring.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            producer.try_publish_with(0, |s| s.value = 42).unwrap();
+        });
+
+        // Parks until the publish signals, well before the 10s timeout
+        assert_eq!(ring.wait_for(0), 1);
+        assert_eq!(ring.read_slot(0).unwrap().value, 42);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_spsc_numa_node() {
+        let node = crate::affinity::current_numa_node().unwrap();
+        let config = RingBufferConfig::new(1024)
+            .unwrap()
+            .with_numa_node(node)
+            .unwrap();
+        let ring = RingBuffer::<Slot8>::from_config(config.clone()).unwrap();
+        assert!(ring.try_publish_with(0, |s| s.value = 7).is_some());
+        assert_eq!(ring.read_slot(0).unwrap().value, 7);
+
+        // Mapped and bound like the SPSC ring, slots start zeroed
+        let mut broadcast = BroadcastRingBuffer::<Slot8>::new(config).unwrap();
+        assert!(broadcast.insights().is_none());
+        let (seq, slots) = broadcast.try_claim_slots_relaxed(1).unwrap();
+        assert_eq!(slots[0].value, 0);
+        slots[0].value = 9;
+        broadcast.publish_batch_relaxed(seq, seq);
+        let mut values = Vec::new();
+        broadcast.poll(0, 8, |e, _, _| values.push(e.value));
+        assert_eq!(values, [9]);
+    }
+
+    #[test]
+    fn test_spsc_poll_wraps_with_single_end_of_batch() {
+        let ring = Arc::new(RingBuffer::<Slot8>::new(8).unwrap());
+        let mut poller = EventPoller::new(ring.clone());
+        let mut events = Vec::new();
+        for round in 0..2u64 {
+            for seq in round * 6..round * 6 + 6 {
+                ring.try_publish_with(seq, |s| s.value = seq * 10).unwrap();
+            }
+            events.clear();
+            assert_eq!(
+                poller.poll(|e, seq, end| events.push((e.value, seq, end))),
+                6
+            );
+        }
+        // Second round spans slots 6, 7, 0..3 but is one batch
+        let expected: Vec<_> = (6..12).map(|seq| (seq * 10, seq, seq == 11)).collect();
+        assert_eq!(events, expected);
+        assert_eq!(ring.consumer_cursor().load(Ordering::Relaxed), 12);
+        assert_eq!(poller.poll(|_, _, _| panic!("nothing published")), 0);
+    }
+
+    #[test]
+    fn test_broadcast_poll_wraps_with_single_end_of_batch() {
+        let config = RingBufferConfig::new(8).unwrap().with_consumers(2).unwrap();
+        let mut ring = BroadcastRingBuffer::<Slot8>::new(config).unwrap();
+        let publish = |ring: &mut BroadcastRingBuffer<Slot8>, n: u64| {
+            for _ in 0..n {
+                let (seq, slots) = ring.try_claim_slots_relaxed(1).unwrap();
+                slots[0].value = seq;
+                ring.publish_batch_relaxed(seq, seq);
+            }
+        };
+        publish(&mut ring, 6);
+        let ring = Arc::new(ring);
+        let mut first = EventPoller::broadcast(ring.clone(), 0);
+        let mut second = EventPoller::broadcast(ring.clone(), 1).with_batch_size(4);
+        assert_eq!(first.poll(|_, _, _| {}), 6);
+        assert_eq!(second.poll(|_, _, _| {}), 4);
+        assert_eq!(second.poll(|_, _, _| {}), 2);
+        drop((first, second));
+
+        let mut ring = Arc::try_unwrap(ring).ok().unwrap();
+        publish(&mut ring, 6);
+        let mut events = Vec::new();
+        assert_eq!(
+            ring.poll(0, 100, |e, seq, end| events.push((e.value, seq, end))),
+            6
+        );
+        let expected: Vec<_> = (6..12).map(|seq| (seq, seq, seq == 11)).collect();
+        assert_eq!(events, expected);
+    }
+
     #[test]
     fn test_spsc_mapped() {
         let ring = RingBuffer::<Slot8>::new_mapped(1024).unwrap();
@@ -1049,6 +1608,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_broadcast_poll_releases_after_handler() {
+        let config = RingBufferConfig::new(8).unwrap().with_consumers(1).unwrap();
+        let mut ring = BroadcastRingBuffer::<Slot8>::new(config).unwrap();
+        // Wrap the batch: consume 6, then publish 6 more across the end
+        for round in 0..2 {
+            for _ in 0..6 {
+                let (seq, slots) = ring.try_claim_slots_relaxed(1).unwrap();
+                slots[0].value = seq;
+                ring.publish_batch_relaxed(seq, seq);
+            }
+            let before = ring.consumer_sequences[0].load(Ordering::Acquire);
+            let mut seen = Vec::new();
+            let handled = ring.poll(0, 8, |e, seq, _| {
+                // The producer can't reclaim any slot of the batch yet
+                assert_eq!(ring.consumer_sequences[0].load(Ordering::Acquire), before);
+                seen.push((e.value, seq));
+            });
+            assert_eq!(handled, 6);
+            let first = round * 6;
+            let expected: Vec<_> = (first..first + 6).map(|s| (s, s)).collect();
+            assert_eq!(seen, expected);
+            assert_eq!(
+                ring.consumer_sequences[0].load(Ordering::Acquire),
+                first + 5
+            );
+        }
+    }
+
     #[test]
     fn test_broadcast_creation() {
         let config = RingBufferConfig::new(1024)
@@ -257,7 +257,7 @@ mod tests {
     #[test]
     fn test_message_slot_alignment() {
         assert_eq!(std::mem::align_of::<MessageSlot>(), 128);
-        assert!(std::mem::size_of::<MessageSlot>() % 128 == 0);
+        assert!(std::mem::size_of::<MessageSlot>().is_multiple_of(128));
     }
 
     #[test]
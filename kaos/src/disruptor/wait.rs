@@ -0,0 +1,179 @@
+//! Consumer wait strategies (what an idle consumer does between polls).
+//!
+//! - `BusySpin` - `spin_loop()` only, lowest latency, burns a core (default)
+//! - `Yielding` - spin, then `thread::yield_now()`
+//! - `Sleeping` - spin, yield, then short sleeps
+//! - `Blocking` - spin, then park until the producer signals (or a timeout)
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Empty polls spent spinning before backing off
+const SPIN_TRIES: u32 = 100;
+
+/// What a consumer does when a poll finds nothing.
+pub trait WaitStrategy: Send + Sync + fmt::Debug {
+    /// Called after an empty poll; `idle` counts consecutive empty polls (from 0)
+    fn idle(&self, idle: u32);
+
+    /// Called by the producer after publishing. Only blocking strategies need it.
+    #[inline]
+    fn signal(&self) {}
+}
+
+/// Spin on the CPU. Lowest latency, 100% of a core while idle.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BusySpin;
+
+impl WaitStrategy for BusySpin {
+    #[inline]
+    fn idle(&self, _idle: u32) {
+        std::hint::spin_loop();
+    }
+}
+
+/// Spin briefly, then yield the CPU to other threads.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Yielding;
+
+impl WaitStrategy for Yielding {
+    #[inline]
+    fn idle(&self, idle: u32) {
+        if idle < SPIN_TRIES {
+            std::hint::spin_loop();
+        } else {
+            thread::yield_now();
+        }
+    }
+}
+
+/// Spin, yield, then sleep for `sleep` between polls.
+#[derive(Debug, Clone, Copy)]
+pub struct Sleeping {
+    sleep: Duration,
+}
+
+impl Sleeping {
+    pub fn new(sleep: Duration) -> Self {
+        Self { sleep }
+    }
+}
+
+impl Default for Sleeping {
+    fn default() -> Self {
+        Self::new(Duration::from_micros(100))
+    }
+}
+
+impl WaitStrategy for Sleeping {
+    #[inline]
+    fn idle(&self, idle: u32) {
+        if idle < SPIN_TRIES {
+            std::hint::spin_loop();
+        } else if idle < SPIN_TRIES * 2 {
+            thread::yield_now();
+        } else {
+            thread::sleep(self.sleep);
+        }
+    }
+}
+
+/// Spin, then park until the producer calls `signal()`.
+///
+/// A wakeup racing the consumer's last poll can be missed, so parking is
+/// bounded by `timeout`; that bounds the worst-case added latency.
+/// `signal()` only takes the lock when a consumer is actually parked.
+#[derive(Debug)]
+pub struct Blocking {
+    generation: AtomicU64,
+    waiters: AtomicUsize,
+    lock: Mutex<()>,
+    cond: Condvar,
+    timeout: Duration,
+}
+
+impl Blocking {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            generation: AtomicU64::new(0),
+            waiters: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            cond: Condvar::new(),
+            timeout,
+        }
+    }
+}
+
+impl Default for Blocking {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(1))
+    }
+}
+
+impl WaitStrategy for Blocking {
+    fn idle(&self, idle: u32) {
+        if idle < SPIN_TRIES {
+            std::hint::spin_loop();
+            return;
+        }
+        let seen = self.generation.load(Ordering::Acquire);
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if self.generation.load(Ordering::Acquire) == seen {
+            let _ = self.cond.wait_timeout(guard, self.timeout);
+        }
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn signal(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.cond.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    #[test]
+    fn test_sleeping_backs_off() {
+        let wait = Sleeping::new(Duration::from_millis(5));
+        let start = Instant::now();
+        wait.idle(0);
+        assert!(start.elapsed() < Duration::from_millis(5));
+        wait.idle(SPIN_TRIES * 2);
+        assert!(start.elapsed() >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_blocking_times_out() {
+        let wait = Blocking::new(Duration::from_millis(10));
+        let start = Instant::now();
+        wait.idle(SPIN_TRIES);
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_blocking_woken_by_signal() {
+        let wait = Arc::new(Blocking::new(Duration::from_secs(10)));
+        let parked = wait.clone();
+        let start = Instant::now();
+        let consumer = thread::spawn(move || parked.idle(SPIN_TRIES));
+
+        while wait.waiters.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        }
+        wait.signal();
+        consumer.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
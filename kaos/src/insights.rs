@@ -20,6 +20,24 @@
 //! kaos::init_tracy();
 //! ```
 //! Then run Tracy profiler: https://github.com/wolfpld/tracy
+//!
+//! ## Ring buffer diagnostics (always available)
+//! ```rust
+//! use kaos::disruptor::{RingBuffer, Slot8};
+//!
+//! let ring = RingBuffer::<Slot8>::new(1024).unwrap().with_insights("orders");
+//! for r in kaos::insights::ring_stats() {
+//!     println!("{}", r);
+//! }
+//! ```
+//! Tracks occupancy high-water marks, producer stalls (failed claims),
+//! consumer lag and publish-to-consume latency per ring, so backpressure
+//! sources show up without Tracy. `with_stats()` collects the same counters
+//! (read via the ring's `stats()`) without listing the ring here.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
 
 /// Initialize Tracy profiler (call once at startup)
 #[cfg(feature = "tracy")]
@@ -78,6 +96,360 @@ pub fn record_retransmit() {
 #[inline(always)]
 pub fn record_retransmit() {}
 
+// ============================================================================
+// Ring buffer diagnostics
+// ============================================================================
+
+/// Cheap timestamps: the TSC on x86_64 (calibrated against `Instant` on the
+/// first stats read), nanoseconds since first use elsewhere
+mod tsc {
+    use super::*;
+
+    #[cfg(target_arch = "x86_64")]
+    #[inline(always)]
+    pub fn ticks() -> u64 {
+        // SAFETY: rdtsc has no preconditions on x86_64
+        unsafe { core::arch::x86_64::_rdtsc() }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    #[inline(always)]
+    pub fn ticks() -> u64 {
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+
+    /// Tick count and clock at first use; `ns_per_tick` measures from here
+    pub fn anchor() -> (u64, Instant) {
+        static ANCHOR: OnceLock<(u64, Instant)> = OnceLock::new();
+        *ANCHOR.get_or_init(|| (ticks(), Instant::now()))
+    }
+
+    /// Nanoseconds per tick, measured once over at least 2ms since `anchor`
+    /// (only spins if the first read comes sooner than that)
+    pub fn ns_per_tick() -> f64 {
+        static CALIBRATION: OnceLock<f64> = OnceLock::new();
+        *CALIBRATION.get_or_init(|| {
+            if !cfg!(target_arch = "x86_64") {
+                return 1.0;
+            }
+            let (t0, start) = anchor();
+            while start.elapsed() < Duration::from_millis(2) {
+                std::hint::spin_loop();
+            }
+            let (t1, elapsed) = (ticks(), start.elapsed());
+            elapsed.as_nanos() as f64 / t1.wrapping_sub(t0).max(1) as f64
+        })
+    }
+}
+
+/// Power-of-two latency buckets: bucket `i` holds `[2^(i-1), 2^i)` ns
+pub const LATENCY_BUCKETS: usize = 40;
+
+/// Lock-free latency histogram (log2 buckets, nanoseconds)
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS],
+    count: AtomicU64,
+    sum_ns: AtomicU64,
+    max_ns: AtomicU64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_ns: AtomicU64::new(0),
+            max_ns: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    #[inline]
+    pub fn record(&self, ns: u64) {
+        let bucket = (64 - ns.leading_zeros() as usize).min(LATENCY_BUCKETS - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_ns.fetch_add(ns, Ordering::Relaxed);
+        if ns > self.max_ns.load(Ordering::Relaxed) {
+            self.max_ns.fetch_max(ns, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+            count: self.count.load(Ordering::Relaxed),
+            sum_ns: self.sum_ns.load(Ordering::Relaxed),
+            max_ns: self.max_ns.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time copy of a `LatencyHistogram`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencySnapshot {
+    pub buckets: [u64; LATENCY_BUCKETS],
+    pub count: u64,
+    pub sum_ns: u64,
+    pub max_ns: u64,
+}
+
+impl LatencySnapshot {
+    pub fn mean_ns(&self) -> u64 {
+        self.sum_ns / self.count.max(1)
+    }
+
+    /// Upper bound of the bucket holding the `p`-th percentile (0.0..=1.0)
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((self.count as f64) * p.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return ((1u64 << i) - 1).min(self.max_ns);
+            }
+        }
+        self.max_ns
+    }
+
+    /// Convert a histogram recorded in other units (e.g. TSC ticks) to
+    /// nanoseconds; each bucket moves to the bucket of its scaled upper bound
+    pub fn scaled(&self, ns_per_unit: f64) -> LatencySnapshot {
+        let scale = |v: u64| (v as f64 * ns_per_unit) as u64;
+        let mut buckets = [0; LATENCY_BUCKETS];
+        for (i, &n) in self.buckets.iter().enumerate() {
+            let upper = scale((1u64 << i) - 1);
+            buckets[(64 - upper.leading_zeros() as usize).min(LATENCY_BUCKETS - 1)] += n;
+        }
+        LatencySnapshot {
+            buckets,
+            count: self.count,
+            sum_ns: scale(self.sum_ns),
+            max_ns: scale(self.max_ns),
+        }
+    }
+}
+
+/// One publish timestamp is kept per this many sequences
+const STAMP_EVERY: u64 = 64;
+/// Sampled timestamps in flight (covers 64 * 64 = 4096 sequences)
+const STAMP_SLOTS: usize = 64;
+
+#[derive(Debug)]
+struct Stamp {
+    seq: AtomicU64,
+    ticks: AtomicU64,
+}
+
+/// Live counters for one ring. Rings update these only when created with
+/// `with_stats()` or `with_insights(name)`; otherwise the hot path is untouched.
+#[derive(Debug)]
+pub struct RingStats {
+    name: String,
+    capacity: u64,
+    occupancy: AtomicU64,
+    high_water: AtomicU64,
+    stalls: AtomicU64,
+    consumer_lag: AtomicU64,
+    max_consumer_lag: AtomicU64,
+    /// In ticks; converted to nanoseconds by `snapshot`
+    latency: LatencyHistogram,
+    stamps: Box<[Stamp]>,
+}
+
+impl RingStats {
+    /// Standalone counters; only `register_ring` lists them in `ring_stats()`
+    pub fn new(name: impl Into<String>, capacity: usize) -> Self {
+        tsc::anchor();
+        Self {
+            name: name.into(),
+            capacity: capacity as u64,
+            occupancy: AtomicU64::new(0),
+            high_water: AtomicU64::new(0),
+            stalls: AtomicU64::new(0),
+            consumer_lag: AtomicU64::new(0),
+            max_consumer_lag: AtomicU64::new(0),
+            latency: LatencyHistogram::default(),
+            stamps: (0..STAMP_SLOTS)
+                .map(|_| Stamp {
+                    seq: AtomicU64::new(u64::MAX),
+                    ticks: AtomicU64::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    /// First sampled sequence at or after `seq`
+    #[inline]
+    fn first_sample(seq: u64) -> u64 {
+        seq.wrapping_add(STAMP_EVERY - 1) & !(STAMP_EVERY - 1)
+    }
+
+    #[inline]
+    fn stamp(&self, seq: u64) -> &Stamp {
+        &self.stamps[((seq / STAMP_EVERY) as usize) % STAMP_SLOTS]
+    }
+
+    /// Sequences `[start, end)` were published (timestamps a sample of them)
+    #[inline]
+    pub fn record_publish(&self, start: u64, end: u64) {
+        let mut seq = Self::first_sample(start);
+        if seq >= end {
+            return;
+        }
+        let now = tsc::ticks();
+        while seq < end {
+            let stamp = self.stamp(seq);
+            stamp.ticks.store(now, Ordering::Relaxed);
+            stamp.seq.store(seq, Ordering::Release);
+            seq += STAMP_EVERY;
+        }
+    }
+
+    /// Sequences `[start, end)` were consumed (records latency of sampled ones)
+    #[inline]
+    pub fn record_consume(&self, start: u64, end: u64) {
+        let mut seq = Self::first_sample(start);
+        if seq >= end {
+            return;
+        }
+        let now = tsc::ticks();
+        while seq < end {
+            let stamp = self.stamp(seq);
+            // Skip samples overwritten by a later lap (ring larger than the stamp window)
+            if stamp.seq.load(Ordering::Acquire) == seq {
+                let ticks = now.saturating_sub(stamp.ticks.load(Ordering::Relaxed));
+                self.latency.record(ticks);
+            }
+            seq += STAMP_EVERY;
+        }
+    }
+
+    /// Slots in use after a successful claim
+    #[inline]
+    pub fn record_claim(&self, occupancy: u64) {
+        self.occupancy.store(occupancy, Ordering::Relaxed);
+        if occupancy > self.high_water.load(Ordering::Relaxed) {
+            self.high_water.fetch_max(occupancy, Ordering::Relaxed);
+        }
+    }
+
+    /// Producer found the ring full
+    #[inline]
+    pub fn record_stall(&self) {
+        self.stalls.fetch_add(1, Ordering::Relaxed);
+        record_backpressure();
+    }
+
+    /// Published-but-unconsumed slots left after a consume
+    #[inline]
+    pub fn record_consumer_lag(&self, lag: u64) {
+        self.consumer_lag.store(lag, Ordering::Relaxed);
+        if lag > self.max_consumer_lag.load(Ordering::Relaxed) {
+            self.max_consumer_lag.fetch_max(lag, Ordering::Relaxed);
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn snapshot(&self) -> RingBufferStats {
+        RingBufferStats {
+            name: self.name.clone(),
+            capacity: self.capacity,
+            occupancy: self.occupancy.load(Ordering::Relaxed),
+            high_water: self.high_water.load(Ordering::Relaxed),
+            stalls: self.stalls.load(Ordering::Relaxed),
+            consumer_lag: self.consumer_lag.load(Ordering::Relaxed),
+            max_consumer_lag: self.max_consumer_lag.load(Ordering::Relaxed),
+            latency: self.latency.snapshot().scaled(tsc::ns_per_tick()),
+        }
+    }
+}
+
+/// Point-in-time copy of a ring's counters (returned by each ring's `stats()`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingBufferStats {
+    pub name: String,
+    pub capacity: u64,
+    /// Slots in use at the last claim
+    pub occupancy: u64,
+    /// Highest occupancy seen
+    pub high_water: u64,
+    /// Failed claims (ring full)
+    pub stalls: u64,
+    /// Unconsumed slots at the last consume
+    pub consumer_lag: u64,
+    /// Highest consumer lag seen
+    pub max_consumer_lag: u64,
+    /// Publish-to-consume latency (sampled every 64th sequence)
+    pub latency: LatencySnapshot,
+}
+
+impl RingBufferStats {
+    /// High-water mark as a fraction of capacity (1.0 = ring filled up)
+    pub fn peak_fill(&self) -> f64 {
+        self.high_water as f64 / self.capacity.max(1) as f64
+    }
+}
+
+impl std::fmt::Display for RingBufferStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}/{} slots (peak {}, {:.0}%), {} stalls, lag {} (max {})",
+            self.name,
+            self.occupancy,
+            self.capacity,
+            self.high_water,
+            self.peak_fill() * 100.0,
+            self.stalls,
+            self.consumer_lag,
+            self.max_consumer_lag
+        )?;
+        if self.latency.count > 0 {
+            write!(
+                f,
+                ", latency p50 <{}ns p99 <{}ns max {}ns",
+                self.latency.percentile(0.5),
+                self.latency.percentile(0.99),
+                self.latency.max_ns
+            )?;
+        }
+        Ok(())
+    }
+}
+
+static RINGS: Mutex<Vec<Weak<RingStats>>> = Mutex::new(Vec::new());
+
+/// Register a ring for `ring_stats()`. The entry goes away when the
+/// returned handle (held by the ring) is dropped.
+pub fn register_ring(name: impl Into<String>, capacity: usize) -> Arc<RingStats> {
+    let stats = Arc::new(RingStats::new(name, capacity));
+    let mut rings = RINGS.lock().unwrap_or_else(|e| e.into_inner());
+    rings.retain(|r| r.strong_count() > 0);
+    rings.push(Arc::downgrade(&stats));
+    stats
+}
+
+/// Snapshot every live registered ring (for metrics scrapes)
+pub fn ring_stats() -> Vec<RingBufferStats> {
+    let mut rings = RINGS.lock().unwrap_or_else(|e| e.into_inner());
+    rings.retain(|r| r.strong_count() > 0);
+    rings
+        .iter()
+        .filter_map(Weak::upgrade)
+        .map(|r| r.snapshot())
+        .collect()
+}
+
 /// Create a span for a connection
 #[cfg(feature = "tracing")]
 #[macro_export]
@@ -122,4 +494,75 @@ mod tests {
         record_backpressure();
         record_retransmit();
     }
+
+    #[test]
+    fn test_ring_stats_registry() {
+        let stats = register_ring("test-registry", 64);
+        stats.record_claim(10);
+        stats.record_claim(40);
+        stats.record_claim(5);
+        stats.record_stall();
+        stats.record_consumer_lag(7);
+        stats.record_consumer_lag(2);
+
+        let snap = ring_stats()
+            .into_iter()
+            .find(|r| r.name == "test-registry")
+            .unwrap();
+        assert_eq!(snap.occupancy, 5);
+        assert_eq!(snap.high_water, 40);
+        assert_eq!(snap.stalls, 1);
+        assert_eq!((snap.consumer_lag, snap.max_consumer_lag), (2, 7));
+        assert!((snap.peak_fill() - 0.625).abs() < 1e-9);
+
+        drop(stats);
+        assert!(ring_stats().iter().all(|r| r.name != "test-registry"));
+    }
+
+    #[test]
+    fn test_latency_sampling() {
+        let stats = RingStats::new("latency", 1024);
+        stats.record_publish(0, 200); // samples 0, 64, 128, 192
+        stats.record_consume(0, 100);
+        assert_eq!(stats.snapshot().latency.count, 2);
+        stats.record_consume(100, 200);
+        assert_eq!(stats.snapshot().latency.count, 4);
+
+        // Unsampled ranges record nothing
+        stats.record_publish(1, 60);
+        stats.record_consume(1, 60);
+        assert_eq!(stats.snapshot().latency.count, 4);
+
+        // A later lap overwrote the stamp for 0: no bogus sample
+        stats.record_publish(4096, 4097);
+        stats.record_consume(0, 1);
+        assert_eq!(stats.snapshot().latency.count, 4);
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let histogram = LatencyHistogram::default();
+        for ns in [100, 100, 100, 5_000] {
+            histogram.record(ns);
+        }
+        let snap = histogram.snapshot();
+        assert_eq!(snap.percentile(0.5), 127);
+        assert_eq!(snap.percentile(1.0), 5_000);
+        assert_eq!(snap.mean_ns(), 1_325);
+    }
+
+    #[test]
+    fn test_latency_scaled() {
+        let histogram = LatencyHistogram::default();
+        for ticks in [100, 100, 100, 5_000] {
+            histogram.record(ticks);
+        }
+        let snap = histogram.snapshot().scaled(2.0);
+        assert_eq!(snap.count, 4);
+        assert_eq!((snap.sum_ns, snap.max_ns), (10_600, 10_000));
+        // 100 ticks = 200ns, reported against the bucket of the scaled bound
+        assert_eq!(snap.percentile(0.5), 255);
+        assert_eq!(snap.percentile(1.0), 10_000);
+        assert_eq!(histogram.snapshot().scaled(1.0), histogram.snapshot());
+    }
 }
@@ -1,16 +1,19 @@
 //! Kaos - Lock-free ring buffers
 
 pub mod affinity;
+pub mod checksum;
 pub mod crc32;
 pub mod disruptor;
 pub mod error;
 pub mod insights;
+pub mod xxhash;
 
 // Re-export main components
 pub use disruptor::{MessageRingBuffer, MessageSlot, RingBuffer, RingBufferConfig};
 pub use error::{KaosError, Result};
 pub use insights::{
-    init_tracy, record_backpressure, record_receive, record_retransmit, record_send,
+    init_tracy, record_backpressure, record_receive, record_retransmit, record_send, register_ring,
+    ring_stats, LatencySnapshot, RingBufferStats, RingStats,
 };
 
 #[cfg(test)]